@@ -355,6 +355,20 @@ impl SceneTable {
         self.labels.iter()
     }
 
+    /// Select an index from `0..len` with this table's random selector.
+    ///
+    /// Lets callers that apply their own matching rules pick among equally
+    /// ranked scenes with the same (seedable/mockable) selector.
+    pub fn select_index(&mut self, len: usize) -> Option<usize> {
+        self.random_selector.select_index(len)
+    }
+
+    /// Replace the random selector, keeping the registered scenes.
+    pub fn set_random_selector(&mut self, random_selector: Box<dyn RandomSelector>) {
+        self.random_selector = random_selector;
+        self.cache.clear();
+    }
+
     /// Set shuffle enabled flag (for testing/debugging).
     pub fn set_shuffle_enabled(&mut self, enabled: bool) {
         self.shuffle_enabled = enabled;
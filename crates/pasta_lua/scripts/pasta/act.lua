@@ -30,7 +30,8 @@ local function group_by_actor(tokens)
         -- アクター属性設定トークン: 独立して出力
         if t == "spot" or t == "clear_spot" then
             table.insert(result, token)
-        elseif t == "talk" then
+        elseif t == "talk" or (t == "surface" and token.actor) then
+            -- アクター付きsurface（感情変更）もそのアクターのグループに入れる
             local talk_actor = token.actor
             -- アクター変更検出（最初のtalkまたはアクター変更時）
             if current_actor_token == nil or talk_actor ~= current_actor then
@@ -112,6 +113,28 @@ local function merge_consecutive_talks(grouped)
     return result
 end
 
+--- トーク終了時の感情リセット（emotion_reset = "talk"）
+--- 発言したアクターのうち感情が中立でないものについて、中立のサーフェスへ戻す
+--- アクター付きsurfaceトークンを末尾に追加する。
+--- @param tokens table[] フラットなトークン配列（末尾に追加される）
+--- @param now integer|nil 現在時刻（Unix秒）
+local function reset_emotions_on_talk_end(tokens, now)
+    local seen = {}
+    local count = #tokens
+    for i = 1, count do
+        local actor = tokens[i].actor
+        if tokens[i].type == "talk" and actor and not seen[actor] then
+            seen[actor] = true
+            if actor.emotion_reset == "talk" and actor.is_emotional and actor:is_emotional() then
+                local surface = actor:reset_emotion(now)
+                if surface ~= nil then
+                    table.insert(tokens, { type = "surface", id = surface, actor = actor })
+                end
+            end
+        end
+    end
+end
+
 -- ============================================================================
 -- Actクラス定義
 -- ============================================================================
@@ -193,9 +216,10 @@ end
 --- surfaceトークン蓄積
 --- @param self Act アクションオブジェクト
 --- @param id number|string サーフェスID
+--- @param actor Actor|nil サーフェスを変えるアクター（省略時は直前の話者）
 --- @return Act self メソッドチェーン用
-function ACT_IMPL.surface(self, id)
-    table.insert(self.token, { type = "surface", id = id })
+function ACT_IMPL.surface(self, id, actor)
+    table.insert(self.token, { type = "surface", id = id, actor = actor })
     return self
 end
 
@@ -288,6 +312,11 @@ function ACT_IMPL.build(self)
         return nil
     end
 
+    -- Phase 0: emotion_reset = "talk" のアクターを中立の感情へ戻す
+    local req = rawget(self, "req")
+    local date = req and req.date
+    reset_emotions_on_talk_end(tokens, date and date.unix)
+
     -- Phase 1: アクター切り替え境界でグループ化
     local grouped = group_by_actor(tokens)
 
@@ -355,6 +384,28 @@ function ACT_IMPL.call(self, global_scene_name, key, attrs, ...)
     return nil
 end
 
+--- 関数呼び出し（DSL の `＠関数名（引数）`）
+---
+--- トランスパイラ出力から呼び出される。検索順序:
+--- 1. シーンテーブルの関数 (scene[name])（`＠＊関数名` では scene = nil で省略）
+--- 2. GLOBAL完全一致 (GLOBAL[name])
+---
+--- @param self Act アクションオブジェクト
+--- @param scene SceneTable|nil シーンテーブル
+--- @param name string 関数名
+--- @param ... any 引数（関数には (act, ...) で渡す）
+--- @return any 関数の戻り値
+function ACT_IMPL.call_fn(self, scene, name, ...)
+    local fn = scene and scene[name]
+    if type(fn) ~= "function" then
+        fn = GLOBAL[name]
+    end
+    if type(fn) ~= "function" then
+        error("function not found: " .. tostring(name), 2)
+    end
+    return fn(self, ...)
+end
+
 --- スポット設定トークン生成（状態レス化）
 --- @param self Act アクションオブジェクト
 --- @param name string アクター名
@@ -10,7 +10,18 @@ local GLOBAL = require("pasta.global")
 
 --- @class Actor アクターオブジェクト
 --- @field name string アクター名
---- @field spot integer|nil 立ち位置（0以上）
+--- @field spot integer|string|nil 既定の立ち位置（0以上、または "sakura"/"kero"/"charN"）
+--- @field balloon integer|nil バルーンID（話者切り替え時に \b[ID] を出力）
+--- @field spot_newlines number|nil このアクターのスポットへ切り替える時の改行量（[ghost] spot_newlines を上書き）
+--- @field wait_profile string|nil ウェイトプロファイル名（[talk.profiles.<名前>]）
+--- @field emotions table<string, integer|string>|nil 感情名 → サーフェスID
+--- @field shells table<string, table<string, integer|string>>|nil シェル名 → {感情名またはサーフェスID → サーフェスID}
+--- @field neutral string|nil 中立の感情名（デフォルト "通常"）
+--- @field emotion_reset string|nil "talk" でトーク終了時に中立の感情へ戻す
+--- @field emotion_reset_secs number|nil 感情を変えてから指定秒数後に中立の感情へ戻す（OnSecondChange）
+--- @field emotion string|nil 現在の感情（ウェイトルールの emotion 条件で参照）
+--- @field surface integer|string|nil 現在のサーフェスID（感情から解決したもの）
+--- @field emotion_at integer|nil 感情を変えた時刻（Unix秒）
 local ACTOR = {}
 
 --- アクター実装メタテーブル
@@ -64,6 +75,188 @@ function ACTOR_IMPL.create_word(self, key)
     return setmetatable(builder, ACTOR_WORD_BUILDER_IMPL)
 end
 
+-------------------------------------------
+-- 感情・サーフェス管理
+-------------------------------------------
+
+--- 既定の中立の感情名
+local DEFAULT_NEUTRAL = "通常"
+
+--- 中立の感情名を取得
+--- @param actor Actor アクターオブジェクト
+--- @return string 中立の感情名
+local function neutral_of(actor)
+    return actor.neutral or DEFAULT_NEUTRAL
+end
+
+--- 感情を定義（DSL の `＆emotions：通常=0,笑顔=5` からも呼び出し）
+--- pasta.toml の [actor.*.emotions] で定義済みの感情は上書きしない。
+--- @param self Actor アクターオブジェクト
+--- @param name string 感情名
+--- @param surface integer|string サーフェスID
+--- @return Actor self メソッドチェーン用
+function ACTOR_IMPL.define_emotion(self, name, surface)
+    local emotions = rawget(self, "emotions")
+    if emotions == nil then
+        emotions = {}
+        self.emotions = emotions
+    end
+    if emotions[name] == nil then
+        emotions[name] = surface
+    end
+    return self
+end
+
+--- 感情が定義されているか
+--- @param self Actor アクターオブジェクト
+--- @param name string 感情名
+--- @return boolean
+function ACTOR_IMPL.has_emotion(self, name)
+    local emotions = self.emotions
+    return type(emotions) == "table" and emotions[name] ~= nil
+end
+
+--- 感情名からサーフェスIDを解決
+--- 現在のシェル（pasta.areka.env のシェル名）に shells.<シェル名> の別名があれば、
+--- 感情名 → 既定のサーフェスID の順に引き直す。
+--- @param self Actor アクターオブジェクト
+--- @param name string 感情名
+--- @return integer|string|nil サーフェスID（未定義の感情はnil）
+function ACTOR_IMPL.surface_of(self, name)
+    if not self:has_emotion(name) then
+        return nil
+    end
+    local surface = self.emotions[name]
+
+    -- ENV.get() は環境テーブルを作成してしまう（ENV.captured() が真になる）ため直接読む
+    local shell_name = STORE.env and STORE.env.ghost.shell_name
+    local aliases = type(self.shells) == "table" and shell_name and self.shells[shell_name]
+    if type(aliases) == "table" then
+        local alias = aliases[name]
+        if alias == nil then
+            alias = aliases[tostring(surface)]
+        end
+        if alias ~= nil then
+            surface = alias
+        end
+    end
+    return surface
+end
+
+--- 感情を変更
+--- @param self Actor アクターオブジェクト
+--- @param name string 感情名
+--- @param now integer|nil 現在時刻（Unix秒、省略時は os.time()）
+--- @return integer|string サーフェスID
+function ACTOR_IMPL.set_emotion(self, name, now)
+    local surface = self:surface_of(name)
+    if surface == nil then
+        error(string.format("actor '%s': unknown emotion '%s'", tostring(self.name), tostring(name)), 2)
+    end
+    self.emotion = name
+    self.surface = surface
+    self.emotion_at = now or os.time()
+    return surface
+end
+
+--- 中立の感情へ戻す必要があるか
+--- @param self Actor アクターオブジェクト
+--- @return boolean
+function ACTOR_IMPL.is_emotional(self)
+    return self.emotion ~= nil and self.emotion ~= neutral_of(self)
+end
+
+--- 中立の感情へ戻す
+--- 中立の感情が定義されていない場合は感情の状態だけを消す。
+--- @param self Actor アクターオブジェクト
+--- @param now integer|nil 現在時刻（Unix秒、省略時は os.time()）
+--- @return integer|string|nil 中立のサーフェスID（未定義ならnil）
+function ACTOR_IMPL.reset_emotion(self, now)
+    local neutral = neutral_of(self)
+    if self:has_emotion(neutral) then
+        return self:set_emotion(neutral, now)
+    end
+    self.emotion = nil
+    self.surface = nil
+    self.emotion_at = nil
+    return nil
+end
+
+--- 感情の保持時間（emotion_reset_secs）を過ぎたか
+--- @param self Actor アクターオブジェクト
+--- @param now integer 現在時刻（Unix秒）
+--- @return boolean
+function ACTOR_IMPL.is_emotion_expired(self, now)
+    local secs = self.emotion_reset_secs
+    return type(secs) == "number" and self:is_emotional()
+        and self.emotion_at ~= nil and now - self.emotion_at >= secs
+end
+
+--- 保持時間を過ぎた感情を中立へ戻し、サーフェス変更を act に蓄積する
+--- OnSecondChange の EVENT.fire から（REG.OnSecondChange の前に）呼び出される。
+--- @param act Act アクションオブジェクト
+--- @param now integer 現在時刻（Unix秒）
+--- @return integer 中立へ戻したアクター数
+function ACTOR.reset_expired_emotions(act, now)
+    local count = 0
+    for _, actor in pairs(STORE.actors) do
+        if type(actor) == "table" and getmetatable(actor) == ACTOR_IMPL and actor:is_emotion_expired(now) then
+            local surface = actor:reset_emotion(now)
+            if surface ~= nil then
+                act:surface(surface, actor)
+            end
+            count = count + 1
+        end
+    end
+    return count
+end
+
+--- `＆キー：値` で設定できるアクター属性
+--- それ以外のキーはメソッドや単語を隠さないよう設定しない。
+ACTOR.ATTR_KEYS = {
+    spot = true,
+    balloon = true,
+    spot_newlines = true,
+    wait_profile = true,
+    emotions = true,
+    neutral = true,
+    emotion_reset = true,
+    emotion_reset_secs = true,
+}
+
+--- アクター属性を設定（DSL の ％ アクタースコープの `＆キー：値` から呼び出し）
+--- pasta.toml の [actor.*] で設定済みのフィールドは上書きしない。
+--- ACTOR.ATTR_KEYS にないキーは警告ログを出して無視する。
+--- @param self Actor アクターオブジェクト
+--- @param key string 属性名
+--- @param value any 属性値
+--- @return Actor self メソッドチェーン用
+function ACTOR_IMPL.set_attr(self, key, value)
+    if not ACTOR.ATTR_KEYS[key] then
+        local LOG = package.loaded["@pasta_log"]
+        if LOG then
+            LOG.warn(string.format("actor %s: unknown attribute '%s' ignored", tostring(self.name), tostring(key)))
+        end
+        return self
+    end
+    -- `＆emotions：通常=0,笑顔=5` は感情の定義
+    if key == "emotions" and type(value) == "string" then
+        -- 全角の区切りはマルチバイトのため半角に揃えてから分割する
+        local text = value:gsub("，", ","):gsub("、", ","):gsub("＝", "=")
+        for pair in text:gmatch("[^,]+") do
+            local name, surface = pair:match("^%s*(.-)%s*=%s*(.-)%s*$")
+            if name and name ~= "" and surface ~= "" then
+                self:define_emotion(name, math.tointeger(tonumber(surface)) or surface)
+            end
+        end
+        return self
+    end
+    if rawget(self, key) == nil then
+        self[key] = value
+    end
+    return self
+end
+
 --- アクターを取得または新規作成
 --- @param name string アクター名
 --- @return Actor アクターオブジェクト
@@ -109,6 +302,17 @@ function PROXY_IMPL.talk(self, text)
     self.act:talk(self.actor, text)
 end
 
+--- 感情を変更し、サーフェス切り替えをact経由で蓄積
+--- @param self ActorProxy プロキシオブジェクト
+--- @param name string 感情名
+--- @return nil
+function PROXY_IMPL.emotion(self, name)
+    local req = rawget(self.act, "req")
+    local date = req and req.date
+    local surface = self.actor:set_emotion(name, date and date.unix)
+    self.act:surface(surface, self.actor)
+end
+
 -------------------------------------------
 -- PROXY_IMPL:word 3レベルフォールバック検索
 -------------------------------------------
@@ -118,6 +322,8 @@ end
 --- 1. アクター完全一致 (actor[name])
 --- 2. アクター辞書前方一致 (SEARCH:search_word(name, __actor_xxx__))
 --- 3. act:word() に委譲（シーン→グローバル検索）
+--- 4. 感情名 (actor.emotions[name]) → 感情を変更して空文字列を返す
+--- 既存の単語を感情名で隠さないよう、感情は単語が見つからない場合だけ適用する。
 --- @param self ActorProxy プロキシオブジェクト
 --- @param name string 単語名（＠なし）
 --- @return string|nil 見つかった単語、またはnil
@@ -143,7 +349,18 @@ function PROXY_IMPL.word(self, name)
     end
 
     -- 3. act:word() に委譲（シーン→グローバル検索）
-    return self.act:word(name)
+    local word = self.act:word(name)
+    if word ~= nil then
+        return word
+    end
+
+    -- 4. 感情名（＠笑顔）: 感情を変更してサーフェス切り替えを蓄積
+    if self.actor.has_emotion and self.actor:has_emotion(name) then
+        self:emotion(name)
+        return ""
+    end
+
+    return nil
 end
 
 -- CONFIG 由来アクターへのメタテーブル設定
@@ -0,0 +1,290 @@
+--- @module pasta.areka.env
+--- ベースウェア環境情報キャッシュ
+---
+--- ベースウェアが起動時などに送る通知イベント（basewareversion, OnNotifyOSInfo,
+--- ownerghostname, OnNotifyUserInfo, installedghostname, otherghostname 等）を
+--- 捕捉し、型付きの環境テーブル（STORE.env）に保存する。
+---
+--- 捕捉は EVENT.fire がハンドラ呼び出し前に ENV.capture(req) で行うため、
+--- REG にハンドラを登録しても（シーン関数フォールバックでも）キャッシュは更新される。
+---
+--- 使用例:
+--- ```lua
+--- local ENV = require("pasta.areka.env")
+--- print(ENV.ghost_name(), ENV.user_name())
+--- if ENV.is_installed("さくら") then ... end
+--- local env = ENV.get()  -- 環境テーブル全体
+--- ```
+---
+--- シーンからは act.var の変数（ゴースト名、ユーザー名 等）として参照できる
+--- （SHIORI_ACT:transfer_env_to_var() 参照）。
+
+local STORE = require("pasta.store")
+
+--- @class AREKA_ENV_Baseware
+--- @field name string|nil ベースウェア名（例: "SSP"）
+--- @field version string|nil バージョン
+--- @field build string|nil ビルド番号
+
+--- @class AREKA_ENV_OS
+--- @field name string|nil OS 名
+--- @field version string|nil OS バージョン
+--- @field edition string|nil エディション等の詳細
+--- @field references string[] OnNotifyOSInfo の全 Reference（CPU・メモリ情報等を含む）
+
+--- @class AREKA_ENV_Ghost
+--- @field name string|nil ゴースト名（ownerghostname / OnNotifySelfInfo）
+--- @field sakura_name string|nil 本体側の名前
+--- @field kero_name string|nil 相方側の名前
+--- @field shell_name string|nil シェル名
+--- @field shell_path string|nil シェルのパス
+--- @field balloon_name string|nil バルーン名
+--- @field balloon_path string|nil バルーンのパス
+--- @field surfaces table<integer, integer> スコープ → サーフェス番号（OnSurfaceChange）
+
+--- @class AREKA_ENV_User
+--- @field nickname string|nil ユーザーの呼び名
+--- @field name string|nil ユーザーの本名
+--- @field birthday string|nil 誕生日（"yyyy,mm,dd"）
+--- @field sex string|nil 性別
+
+--- @class AREKA_ENV_OtherGhost
+--- @field name string ゴースト名
+--- @field surfaces integer[] サーフェス番号（0: 本体, 1: 相方）
+
+--- @class AREKA_ENV
+--- @field baseware AREKA_ENV_Baseware
+--- @field os AREKA_ENV_OS
+--- @field ghost AREKA_ENV_Ghost
+--- @field user AREKA_ENV_User
+--- @field installed table<string, string[]> 種別（ghost, sakura, kero, shell, balloon, headline, plugin）→ 名前リスト
+--- @field other_ghosts AREKA_ENV_OtherGhost[] 起動中の他ゴースト
+
+local ENV = {}
+
+--- 空の環境テーブルを作成
+--- @return AREKA_ENV
+function ENV.new()
+    return {
+        baseware = {},
+        os = { references = {} },
+        ghost = { surfaces = {} },
+        user = {},
+        installed = {},
+        other_ghosts = {},
+    }
+end
+
+--- 環境テーブルを取得（未作成なら作成）
+--- @return AREKA_ENV
+function ENV.get()
+    if not STORE.env then
+        STORE.env = ENV.new()
+    end
+    return STORE.env
+end
+
+--- 通知を1件以上捕捉済みか
+--- @return boolean
+function ENV.captured()
+    return STORE.env ~= nil
+end
+
+--- 環境テーブルを破棄（次の捕捉・取得時に再作成）
+function ENV.reset()
+    STORE.env = nil
+end
+
+--- Reference を 0 始まりの配列から 1 始まりのリストへ変換
+--- 値が1つで \1 区切りを含む場合は分割する。
+--- @param ref table|nil req.reference
+--- @return string[]
+local function reference_list(ref)
+    local list = {}
+    if not ref then
+        return list
+    end
+    local i = 0
+    while ref[i] ~= nil do
+        table.insert(list, ref[i])
+        i = i + 1
+    end
+    if #list == 1 and list[1]:find("\1", 1, true) then
+        local values = {}
+        for value in (list[1] .. "\1"):gmatch("([^\1]*)\1") do
+            table.insert(values, value)
+        end
+        return values
+    end
+    -- 空の Reference0 のみはリストなし
+    if #list == 1 and list[1] == "" then
+        return {}
+    end
+    return list
+end
+
+--- installed* 通知 → 種別名
+local INSTALLED = {
+    installedghostname = "ghost",
+    installedsakuraname = "sakura",
+    installedkeroname = "kero",
+    installedshellname = "shell",
+    installedballoonname = "balloon",
+    installedheadlinename = "headline",
+    installedplugin = "plugin",
+}
+
+--- 通知イベント別の捕捉関数
+--- ゴースト側で ENV.handlers[id] = function(env, ref) ... end として追加・上書きできる。
+--- @type table<string, fun(env: AREKA_ENV, ref: table)>
+ENV.handlers = {}
+
+ENV.handlers.basewareversion = function(env, ref)
+    env.baseware.name = ref[0]
+    env.baseware.version = ref[1]
+    env.baseware.build = ref[2]
+end
+
+ENV.handlers.OnNotifyOSInfo = function(env, ref)
+    env.os.name = ref[0]
+    env.os.version = ref[1]
+    env.os.edition = ref[2]
+    env.os.references = reference_list(ref)
+end
+
+ENV.handlers.ownerghostname = function(env, ref)
+    env.ghost.name = ref[0]
+end
+
+ENV.handlers.OnNotifySelfInfo = function(env, ref)
+    env.ghost.name = ref[0] or env.ghost.name
+    env.ghost.sakura_name = ref[1]
+    env.ghost.kero_name = ref[2]
+    env.ghost.shell_name = ref[3]
+    env.ghost.shell_path = ref[4]
+    env.ghost.balloon_name = ref[5]
+    env.ghost.balloon_path = ref[6]
+end
+
+ENV.handlers.OnNotifyUserInfo = function(env, ref)
+    env.user.nickname = ref[0]
+    env.user.name = ref[1]
+    env.user.birthday = ref[2]
+    env.user.sex = ref[3]
+end
+
+ENV.handlers.otherghostname = function(env, ref)
+    local ghosts = {}
+    local i = 0
+    while ref[i] ~= nil do
+        -- "ゴースト名\1本体サーフェス\1相方サーフェス"
+        local fields = {}
+        for field in (ref[i] .. "\1"):gmatch("([^\1]*)\1") do
+            table.insert(fields, field)
+        end
+        if fields[1] and fields[1] ~= "" then
+            local surfaces = {}
+            for j = 2, #fields do
+                table.insert(surfaces, tonumber(fields[j]))
+            end
+            table.insert(ghosts, { name = fields[1], surfaces = surfaces })
+        end
+        i = i + 1
+    end
+    env.other_ghosts = ghosts
+end
+
+ENV.handlers.OnSurfaceChange = function(env, ref)
+    env.ghost.surfaces[0] = tonumber(ref[0]) or env.ghost.surfaces[0]
+    env.ghost.surfaces[1] = tonumber(ref[1]) or env.ghost.surfaces[1]
+end
+
+ENV.handlers.OnShellChanged = function(env, ref)
+    env.ghost.shell_name = ref[0]
+    env.ghost.shell_path = ref[2]
+end
+
+ENV.handlers.OnBalloonChange = function(env, ref)
+    env.ghost.balloon_name = ref[0]
+    env.ghost.balloon_path = ref[1]
+end
+
+for id, kind in pairs(INSTALLED) do
+    ENV.handlers[id] = function(env, ref)
+        env.installed[kind] = reference_list(ref)
+    end
+end
+
+--- リクエストが通知イベントなら環境テーブルに捕捉する
+--- @param req table SHIORIリクエストテーブル
+--- @return boolean 捕捉した場合 true
+function ENV.capture(req)
+    local handler = req and ENV.handlers[req.id]
+    if not handler then
+        return false
+    end
+    handler(ENV.get(), req.reference or {})
+    return true
+end
+
+-- ============================================================================
+-- 問い合わせ関数
+-- ============================================================================
+
+--- ゴースト名
+--- @return string|nil
+function ENV.ghost_name()
+    return ENV.get().ghost.name
+end
+
+--- ユーザー名（呼び名を優先し、なければ本名）
+--- @return string|nil
+function ENV.user_name()
+    local user = ENV.get().user
+    if user.nickname and user.nickname ~= "" then
+        return user.nickname
+    end
+    return user.name
+end
+
+--- ベースウェア名とバージョン
+--- @return string|nil name
+--- @return string|nil version
+function ENV.baseware()
+    local baseware = ENV.get().baseware
+    return baseware.name, baseware.version
+end
+
+--- インストール済みの名前リスト
+--- @param kind string|nil 種別（デフォルト "ghost"）
+--- @return string[]
+function ENV.installed(kind)
+    return ENV.get().installed[kind or "ghost"] or {}
+end
+
+--- 指定した名前がインストール済みか
+--- @param name string 名前
+--- @param kind string|nil 種別（デフォルト "ghost"）
+--- @return boolean
+function ENV.is_installed(name, kind)
+    for _, value in ipairs(ENV.installed(kind)) do
+        if value == name then
+            return true
+        end
+    end
+    return false
+end
+
+--- 指定したゴーストが起動中か（otherghostname）
+--- @param name string ゴースト名
+--- @return boolean
+function ENV.is_running(name)
+    for _, ghost in ipairs(ENV.get().other_ghosts) do
+        if ghost.name == name then
+            return true
+        end
+    end
+    return false
+end
+
+return ENV
@@ -2,13 +2,29 @@
 --- AREKA拡張モジュール
 ---
 --- 伺かのSHIORIプロトコルを補助するためのAREKA固有機能を提供する。
---- 将来の拡張ポイント: ゴースト名取得、ユーザー情報、時刻イベント等
+--- 将来の拡張ポイント: 時刻イベント等
 
 local AREKA = {}
 
+--- SAORI 呼び出し（pasta.areka.saori）
+AREKA.saori = require("pasta.areka.saori")
+
+--- ベースウェア環境情報（pasta.areka.env）
+AREKA.env = require("pasta.areka.env")
+
+--- ゴースト名取得（ownerghostname / OnNotifySelfInfo）
+--- @return string|nil
+function AREKA.ghost_name()
+    return AREKA.env.ghost_name()
+end
+
+--- ユーザー名取得（OnNotifyUserInfo の呼び名、なければ本名）
+--- @return string|nil
+function AREKA.user_name()
+    return AREKA.env.user_name()
+end
+
 -- TODO: 将来の拡張ポイント
--- - ghost_name(): ゴースト名取得
--- - user_name(): ユーザー名取得
 -- - current_time(): 現在時刻取得
 -- - random(min, max): 乱数生成
 
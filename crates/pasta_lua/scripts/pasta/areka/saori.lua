@@ -0,0 +1,74 @@
+--- @module pasta.areka.saori
+--- SAORI 呼び出しモジュール
+---
+--- 外部 SAORI ヘルパー（SAORI/1.0、SAORI-basic 方式の実行ファイル）を
+--- Lua から呼び出し、DSL の `＠関数名（引数）` から使える関数として登録する。
+---
+--- 使用例（main.lua 等）:
+---   local SAORI = require("pasta.areka.saori")
+---   SAORI.register("計算", "saori/calc", { timeout = 3 })
+---
+--- DSL から:
+---   さくら：答えは＠計算（１、＋、２）です。
+
+local GLOBAL = require("pasta.global")
+
+--- @class SaoriModule
+local SAORI = {}
+
+--- ロード済みヘルパー（パス → ヘルパー）
+--- @type table<string, userdata>
+SAORI.helpers = {}
+
+--- ヘルパーをロード（同じパスは再利用）
+--- @param path string ヘルパー実行ファイルのパス（ゴーストディレクトリからの相対パス可）
+--- @param opts table|nil { timeout = 秒, sender = 送信者名 }
+--- @return userdata ヘルパー（execute / version メソッドを持つ）
+function SAORI.load(path, opts)
+    local helper = SAORI.helpers[path]
+    if not helper then
+        local RAW = require("@pasta_saori")
+        helper = RAW.load(path, opts)
+        SAORI.helpers[path] = helper
+    end
+    return helper
+end
+
+--- EXECUTE を送信して結果テーブルを返す
+--- @param path string ヘルパーのパス
+--- @param ... any 引数（Argument0..n）
+--- @return table|nil 結果テーブル { status, status_text, result, values, headers }
+--- @return string|nil エラーメッセージ
+function SAORI.execute(path, ...)
+    return SAORI.load(path):execute(...)
+end
+
+--- ヘルパーを DSL 関数として登録する
+---
+--- `GLOBAL[name](act, ...)` として登録し、Result ヘッダーの値を返す。
+--- 失敗時（起動失敗・タイムアウト・200 以外）は空文字列を返し、警告ログを出力する。
+--- 結果テーブル全体は `act.var.saori` に保存する。
+--- @param name string 関数名
+--- @param path string ヘルパーのパス
+--- @param opts table|nil SAORI.load と同じオプション
+--- @return userdata ヘルパー
+function SAORI.register(name, path, opts)
+    local helper = SAORI.load(path, opts)
+    GLOBAL[name] = function(act, ...)
+        local res, err = helper:execute(...)
+        if act and act.var then
+            act.var.saori = res
+        end
+        if not res then
+            require("@pasta_log").warn("SAORI " .. name .. " failed: " .. tostring(err))
+            return ""
+        end
+        if res.status ~= 200 then
+            return ""
+        end
+        return res.result or ""
+    end
+    return helper
+end
+
+return SAORI
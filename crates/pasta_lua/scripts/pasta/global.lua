@@ -24,6 +24,20 @@ end
 
 GLOBAL.チェイントーク = GLOBAL.yield
 
+--- 入力ボックス関数
+--- 入力ボックスを開き、入力値が届くまでシーンを中断する（act:input と同一動作）。
+--- Pasta DSL の `＄名前＝＠入力（"name"）` で呼び出される。キャンセル時は空文字列を返す。
+--- @param act ShioriAct ACT オブジェクト
+--- @param id string 入力ID
+--- @param default string|nil 初期値
+--- @return string 入力値
+function GLOBAL.input(act, id, default)
+    local value = act:input(id, { default = default })
+    return value or ""
+end
+
+GLOBAL.入力 = GLOBAL.input
+
 
 
 return GLOBAL
@@ -3,6 +3,8 @@
 ---
 --- ランタイム起動時に自動ロードされ、セッションを跨いで保持される。
 --- ctx.saveから参照可能。Drop時に自動保存される。
+--- [persistence] autosave_interval 設定時は OnSecondChange で定期保存され、
+--- @pasta_persistence の flush() で即時保存できる（変更がなければ書き込まない）。
 
 local persistence = require("@pasta_persistence")
 
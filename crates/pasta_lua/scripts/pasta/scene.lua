@@ -14,6 +14,7 @@ local SCENE = {}
 --- シーンテーブル実装メタテーブル（create_word メソッドを提供）
 --- @class SceneTable
 --- @field __global_name__ string グローバルシーン名
+--- @field __base_name__ string|nil ベース名（DSL 上のシーン名、create_scene で設定）
 local SCENE_TABLE_IMPL = {}
 
 --- ローカル単語ビルダーを作成（scene:create_word("key") 形式）
@@ -103,6 +104,14 @@ function SCENE.get_global_name(scene_table)
     return scene_table.__global_name__
 end
 
+--- ベース名（DSL 上のシーン名）を取得
+--- @param global_name string グローバルシーン名（例: "メイン1"）
+--- @return string|nil ベース名（例: "メイン"）、またはnil
+function SCENE.get_base_name(global_name)
+    local global_table = STORE.scenes[global_name]
+    return global_table and global_table.__base_name__
+end
+
 --- エントリーポイント（__start__）を取得
 --- @param global_name string グローバルシーン名
 --- @return function|nil __start__シーン関数、またはnil
@@ -133,7 +142,9 @@ function SCENE.create_scene(base_name, local_name, scene_func)
     if scene_func and local_name then
         SCENE.register(global_name, local_name, scene_func)
     end
-    return SCENE.get_global_table(global_name) or SCENE.create_global_table(global_name)
+    local scene_table = SCENE.get_global_table(global_name) or SCENE.create_global_table(global_name)
+    scene_table.__base_name__ = base_name
+    return scene_table
 end
 
 --- シーンを名前で検索（プレフィックス検索）
@@ -179,6 +190,27 @@ function SCENE.search(name, global_scene_name, attrs)
     }, scene_result_mt)
 end
 
+--- シーン関数を「実行後に必ず build する」コルーチンでラップする
+--- @param fn any シーン関数
+--- @return thread|nil シーンコルーチン、または有効な関数でない場合nil
+local function wrap_scene_fn(fn)
+    -- シーン関数が有効な関数であることを確認
+    if type(fn) ~= "function" then
+        return nil
+    end
+
+    -- 必ず最後にbuildを呼び出す関数
+    local function wrapped_fn(act, ...)
+        fn(act, ...)
+        local result = act:build()
+        if result ~= nil then
+            return result
+        end
+    end
+
+    return coroutine.create(wrapped_fn)
+end
+
 --- シーン実行コルーチンを作成してコルーチンを返す。
 ---
 --- シーンを検索し、見つかった場合はシーン関数をコルーチンでラップして返す。
@@ -188,27 +220,28 @@ end
 --- @param global_scene_name string|nil ローカル検索の場合のグローバルシーン名
 --- @param attrs table|nil 属性テーブル（将来拡張用、現在は未使用）
 --- @return thread|nil シーンコルーチン、またはnil
+--- @return SceneSearchResult|nil 検索結果（グローバル名・ローカル名の参照用）
 function SCENE.co_exec(name, global_scene_name, attrs)
     local scene_result = SCENE.search(name, global_scene_name, attrs)
     if not scene_result then
         return nil
     end
-    local fn = scene_result.func
-    -- シーン関数が有効な関数であることを確認
-    if type(fn) ~= "function" then
+    local co = wrap_scene_fn(scene_result.func)
+    if not co then
         return nil
     end
+    return co, scene_result
+end
 
-    -- 必ず最後にbuildを呼び出す関数
-    local function wrapped_fn(act, ...)
-        fn(act, ...)
-        local result = act:build()
-        if result ~= nil then
-            return result
-        end
-    end
-
-    return coroutine.create(wrapped_fn)
+--- グローバルシーン名を指定してシーン実行コルーチンを作成する。
+---
+--- 検索を経由せず、指定したグローバルシーン（例: "メイン1"）の __start__ を実行する。
+--- 見つからない場合はnilを返す。
+---
+--- @param global_name string グローバルシーン名（トランスパイラー出力形式）
+--- @return thread|nil シーンコルーチン、またはnil
+function SCENE.co_start(global_name)
+    return wrap_scene_fn(SCENE.get_start(global_name))
 end
 
 return SCENE
@@ -2,16 +2,21 @@
 --- SHIORI専用アクションオブジェクトモジュール
 ---
 --- pasta.actを継承し、build()をオーバーライドしてさくらスクリプト文字列を生成する。
+--- 出力形式 "json" のときは、さくらスクリプトの代わりに構造化イベントのJSONを生成する。
 --- サーフェス切り替え、待機、改行、クリア等は親クラスから継承。
 
 local ACT = require("pasta.act")
 local BUILDER = require("pasta.shiori.sakura_builder")
+local EVENT_BUILDER = require("pasta.shiori.event_builder")
 local CONFIG = require("pasta.config")
 local STORE = require("pasta.store")
+local ENV = require("pasta.areka.env")
 
 --- @class ShioriAct : Act SHIORI専用アクションオブジェクト
 --- @field _spot_newlines number スポット切り替え時の改行数（デフォルト1.5）
+--- @field _output string 出力形式（"sakura" | "json"、デフォルトは [talk] output）
 --- @field req ShioriRequest|nil SHIORIリクエストオブジェクト（読み取り専用として扱うこと）
+--- @field _response table レスポンスヘッダー蓄積（set_reference 等で設定、response_headers で取得）
 local SHIORI_ACT = {}
 
 --- SHIORI_ACT実装メタテーブル
@@ -45,15 +50,35 @@ function SHIORI_ACT.new(actors, req)
     local base = ACT.new(actors)
     -- pasta.tomlの[ghost]セクションからspot_newlinesを読み込み（デフォルト1.5）
     base._spot_newlines = CONFIG.get("ghost", "spot_newlines", 1.5)
+    -- pasta.tomlの[talk]セクションから出力形式を読み込み（デフォルト"sakura"）
+    base._output = CONFIG.get("talk", "output", "sakura")
     -- SHIORIリクエストオブジェクトを設定（任意）
     base.req = req
+    -- レスポンスヘッダー蓄積（shiori-response-headers）
+    base._response = { headers = {}, errors = {}, balloon_offsets = {} }
     return setmetatable(base, SHIORI_ACT_IMPL)
 end
 
+--- 出力形式
+local OUTPUTS = { sakura = true, json = true }
+
+--- このリクエストの出力形式を設定
+--- @param self ShioriAct アクションオブジェクト
+--- @param output string "sakura"（さくらスクリプト）| "json"（構造化イベントのJSON）
+--- @return ShioriAct self メソッドチェーン用
+function SHIORI_ACT_IMPL.set_output(self, output)
+    if not OUTPUTS[output] then
+        error("unknown output format: " .. tostring(output), 2)
+    end
+    self._output = output
+    return self
+end
+
 --- build()オーバーライド: さくらスクリプト生成
 --- 親のbuild()でトークン取得＆リセット後、sakura_builderで変換
+--- 出力形式 "json" のときは event_builder で変換し、@pasta_script_event でJSON化する
 --- @param self ShioriAct アクションオブジェクト
---- @return string|nil さくらスクリプト文字列、またはnil（トークン0件時）
+--- @return string|nil さくらスクリプト文字列（またはJSON文字列）、またはnil（トークン0件時）
 function SHIORI_ACT_IMPL.build(self)
     -- 親のbuild()でトークン取得＆リセット
     local token = ACT.IMPL.build(self)
@@ -66,9 +91,20 @@ function SHIORI_ACT_IMPL.build(self)
     -- STORE.actor_spotsを読み取り、BUILDER.build()に渡す（persist-spot-position）
     local current_spots = STORE.actor_spots
 
+    if self._output == "json" then
+        local events, updated_spots = EVENT_BUILDER.build(token, current_spots)
+        STORE.actor_spots = updated_spots
+        local json, err = require("@pasta_script_event").encode(events)
+        if not json then
+            error(err)
+        end
+        return json
+    end
+
     -- sakura_builderで変換（新プロパティ名spot_newlinesを使用）
     local script, updated_spots = BUILDER.build(token, {
-        spot_newlines = self._spot_newlines
+        spot_newlines = self._spot_newlines,
+        wait_profiles = CONFIG.get("talk", "profiles"),
     }, current_spots)
 
     -- 更新されたスポット状態をSTOREに書き戻し
@@ -76,9 +112,34 @@ function SHIORI_ACT_IMPL.build(self)
         STORE.actor_spots = updated_spots
     end
 
+    -- [talk] optimize_script = true の場合、表示タイミングを変えずにスクリプトを縮小
+    -- （字句エラーで最適化できない場合は元のスクリプトを使う）
+    if CONFIG.get("talk", "optimize_script", false) then
+        script = require("@pasta_sakura_script").optimize(script) or script
+    end
+
+    -- [talk] validate_script = true の場合、生成したスクリプトを検証してログに警告を出力
+    if CONFIG.get("talk", "validate_script", false) then
+        SHIORI_ACT.validate_script(script)
+    end
+
     return script
 end
 
+--- さくらスクリプトを検証し、問題をログに警告として出力する
+--- @param script string さくらスクリプト
+--- @return table[] 診断結果の配列（{ kind, pos, raw, message }）
+function SHIORI_ACT.validate_script(script)
+    local diagnostics = require("@pasta_sakura_script").validate(script)
+    if #diagnostics > 0 then
+        local LOG = require("@pasta_log")
+        for _, d in ipairs(diagnostics) do
+            LOG.warn(string.format("sakura script %s at %d (%s): %s", d.kind, d.pos, d.raw, d.message))
+        end
+    end
+    return diagnostics
+end
+
 -- ============================================================================
 -- 日時転記機能 (onhour-date-var-transfer)
 -- ============================================================================
@@ -189,4 +250,269 @@ function SHIORI_ACT_IMPL.transfer_req_to_var(self)
     return self
 end
 
+-- ============================================================================
+-- 環境情報転記機能
+-- ============================================================================
+
+--- 環境情報変数の対応表（{ 英語キー, 日本語キー, 取得関数 }）
+local ENV_VARS = {
+    { "ghost_name", "ゴースト名", ENV.ghost_name },
+    { "user_name", "ユーザー名", ENV.user_name },
+    { "baseware_name", "ベースウェア名", function() return ENV.get().baseware.name end },
+    { "baseware_version", "ベースウェアバージョン", function() return ENV.get().baseware.version end },
+    { "os_name", "OS名", function() return ENV.get().os.name end },
+    { "shell_name", "シェル名", function() return ENV.get().ghost.shell_name end },
+    { "balloon_name", "バルーン名", function() return ENV.get().ghost.balloon_name end },
+}
+
+--- ベースウェア環境情報（pasta.areka.env）を var へ転記
+--- 未取得の項目は転記しない。
+--- @param self ShioriAct アクションオブジェクト
+--- @return ShioriAct self メソッドチェーン用
+function SHIORI_ACT_IMPL.transfer_env_to_var(self)
+    -- 通知を1件も受け取っていない場合は何もしない
+    if not ENV.captured() then
+        return self
+    end
+
+    for _, entry in ipairs(ENV_VARS) do
+        local value = entry[3]()
+        if value ~= nil then
+            self.var[entry[1]] = value
+            self.var[entry[2]] = value
+        end
+    end
+
+    return self
+end
+
+-- ============================================================================
+-- レスポンスヘッダー設定 (shiori-response-headers)
+-- ============================================================================
+
+--- ErrorLevel に指定可能な値
+local ERROR_LEVELS = { info = true, notice = true, warning = true, error = true, critical = true }
+
+--- ヘッダー値を検証して文字列化（CR/LF はヘッダーを壊すため拒否）
+--- @param name string ヘッダー名（エラーメッセージ用）
+--- @param value string|number ヘッダー値
+--- @return string 文字列化したヘッダー値
+local function header_value(name, value)
+    local t = type(value)
+    if t ~= "string" and t ~= "number" then
+        error(string.format("%s: string expected, got %s", name, t), 3)
+    end
+    local text = tostring(value)
+    if text:find("[\r\n]") then
+        error(string.format("%s: value must not contain CR/LF", name), 3)
+    end
+    return text
+end
+
+--- 非負整数を検証
+--- @param name string 引数名（エラーメッセージ用）
+--- @param value any 検証対象
+--- @return integer 検証済みの値
+local function non_negative_integer(name, value)
+    if math.type(value) ~= "integer" or value < 0 then
+        error(string.format("%s: non-negative integer expected, got %s", name, tostring(value)), 3)
+    end
+    return value
+end
+
+--- Reference ヘッダーを設定（OnCommunicate の返信先指定など）
+--- @param self ShioriAct アクションオブジェクト
+--- @param index integer Reference番号（0以上）
+--- @param value string|number 値
+--- @return ShioriAct self メソッドチェーン用
+function SHIORI_ACT_IMPL.set_reference(self, index, value)
+    index = non_negative_integer("index", index)
+    self._response.headers["Reference" .. index] = header_value("Reference" .. index, value)
+    return self
+end
+
+--- 話しかける相手（OnCommunicate の送信先）ゴーストを設定（Reference0）
+--- OnCommunicate 以外のイベントで呼んだ場合は、自分から話しかけたものとして往復回数を数え直す。
+--- @param self ShioriAct アクションオブジェクト
+--- @param ghost_name string ゴースト名
+--- @return ShioriAct self メソッドチェーン用
+function SHIORI_ACT_IMPL.communicate_to(self, ghost_name)
+    self:set_reference(0, ghost_name)
+    if not (self.req and self.req.id == "OnCommunicate") then
+        STORE.communicate = nil
+    end
+    return self
+end
+
+--- Marker ヘッダーを設定（ベースウェアのステータス表示）
+--- @param self ShioriAct アクションオブジェクト
+--- @param text string マーカー文字列
+--- @return ShioriAct self メソッドチェーン用
+function SHIORI_ACT_IMPL.set_marker(self, text)
+    self._response.headers.Marker = header_value("Marker", text)
+    return self
+end
+
+--- ValueNotify ヘッダーを設定（NOTIFY イベントで実行させるスクリプト）
+--- @param self ShioriAct アクションオブジェクト
+--- @param script string さくらスクリプト
+--- @return ShioriAct self メソッドチェーン用
+function SHIORI_ACT_IMPL.set_value_notify(self, script)
+    self._response.headers.ValueNotify = header_value("ValueNotify", script)
+    return self
+end
+
+--- ErrorLevel / ErrorDescription を追加
+--- 複数回呼び出すと \1 区切りで列挙される。
+--- @param self ShioriAct アクションオブジェクト
+--- @param level string "info" | "notice" | "warning" | "error" | "critical"
+--- @param description string|nil 説明文
+--- @return ShioriAct self メソッドチェーン用
+function SHIORI_ACT_IMPL.add_error(self, level, description)
+    if not ERROR_LEVELS[level] then
+        error(string.format("ErrorLevel: unknown level '%s'", tostring(level)), 2)
+    end
+    local text = header_value("ErrorDescription", description or "")
+    if text:find("\1", 1, true) then
+        error("ErrorDescription: value must not contain \\1", 2)
+    end
+    table.insert(self._response.errors, { level = level, description = text })
+    return self
+end
+
+--- BalloonOffset を設定
+--- スコープごとに保持し、スコープ番号順に \1 区切りで出力する（未指定スコープは "0,0"）。
+--- @param self ShioriAct アクションオブジェクト
+--- @param x integer X方向オフセット
+--- @param y integer Y方向オフセット
+--- @param scope integer|nil スコープ番号（デフォルト0）
+--- @return ShioriAct self メソッドチェーン用
+function SHIORI_ACT_IMPL.set_balloon_offset(self, x, y, scope)
+    if math.type(x) ~= "integer" or math.type(y) ~= "integer" then
+        error(string.format("BalloonOffset: integer x, y expected, got %s, %s", tostring(x), tostring(y)), 2)
+    end
+    scope = non_negative_integer("scope", scope or 0)
+    self._response.balloon_offsets[scope] = x .. "," .. y
+    return self
+end
+
+--- 設定済みレスポンスヘッダーを辞書として取得
+--- EVENT.fire がレスポンス生成時に呼び出す。並び順は RES.build が決定する。
+--- @param self ShioriAct アクションオブジェクト
+--- @return HeaderDic ヘッダー辞書（新しいテーブル）
+function SHIORI_ACT_IMPL.response_headers(self)
+    local response = self._response
+    local dic = {}
+    for k, v in pairs(response.headers) do
+        dic[k] = v
+    end
+
+    if #response.errors > 0 then
+        local levels, descriptions = {}, {}
+        for i, e in ipairs(response.errors) do
+            levels[i] = e.level
+            descriptions[i] = e.description
+        end
+        dic.ErrorLevel = table.concat(levels, "\1")
+        dic.ErrorDescription = table.concat(descriptions, "\1")
+    end
+
+    local max_scope = -1
+    for scope in pairs(response.balloon_offsets) do
+        if scope > max_scope then max_scope = scope end
+    end
+    if max_scope >= 0 then
+        local offsets = {}
+        for scope = 0, max_scope do
+            offsets[scope + 1] = response.balloon_offsets[scope] or "0,0"
+        end
+        dic.BalloonOffset = table.concat(offsets, "\1")
+    end
+
+    return dic
+end
+
+-- ============================================================================
+-- 入力ボックス (OnUserInput / OnUserInputCancel)
+-- ============================================================================
+
+--- 入力ボックスの種類（\![open,種類,...]）
+local INPUT_KINDS = {
+    inputbox = true,
+    passwordinput = true,
+    dateinput = true,
+    timeinput = true,
+    sliderinput = true,
+    ipinput = true,
+}
+
+--- さくらスクリプトのタグ引数をエスケープ（カンマ・ダブルクォートを含む場合は引用）
+--- @param value any 引数
+--- @return string
+local function tag_arg(value)
+    local text = tostring(value)
+    if text:find('[,"]') then
+        return '"' .. text:gsub('"', '""') .. '"'
+    end
+    return text
+end
+
+--- 入力ボックスを開き、ユーザーの入力が届くまでシーンを中断する
+---
+--- `\![open,inputbox,ID,timeout,default]` を出力してそれまでのトークンを yield し、
+--- Reference0 が id と一致する OnUserInput / OnUserInputCancel で再開する
+--- （pasta.shiori.event.input 参照）。シーンコルーチン内でのみ呼び出せる。
+--- 再開後の act.req と応答ヘッダー（set_reference / set_marker 等）は再開したイベントのものになる。
+--- @param self ShioriAct アクションオブジェクト
+--- @param id string 入力ID（OnUserInput の Reference0）
+--- @param opts table|nil { var = 格納先変数名, default = 初期値, timeout = ミリ秒（-1 で無制限）, kind = 種類, args = timeout 以降の引数リスト }
+--- @return string|nil value 入力値（キャンセル時は nil）
+--- @return string|nil reason キャンセル理由（"cancel" / "timeout" / "close" 等）
+function SHIORI_ACT_IMPL.input(self, id, opts)
+    opts = opts or {}
+    if type(id) ~= "string" or id == "" then
+        error("input: id must be a non-empty string", 2)
+    end
+    local kind = opts.kind or "inputbox"
+    if not INPUT_KINDS[kind] then
+        error(string.format("input: unknown kind '%s'", tostring(kind)), 2)
+    end
+    if not coroutine.isyieldable() then
+        error("input: must be called inside a scene coroutine", 2)
+    end
+
+    local args = { kind, tag_arg(id), tag_arg(opts.timeout or -1) }
+    for _, arg in ipairs(opts.args or { opts.default }) do
+        table.insert(args, tag_arg(arg))
+    end
+    self:raw_script("\\![open," .. table.concat(args, ",") .. "]")
+
+    -- EVENT.fire が再開時に新しいイベントの act を渡す
+    STORE.input_wait = { id = id, co = coroutine.running() }
+    local resumed = coroutine.yield(self:build())
+    STORE.input_wait = nil
+
+    -- 以降のシーンは再開したイベントのリクエストを読み、そのレスポンスにヘッダーを設定する
+    if type(resumed) == "table" and resumed.req then
+        self.req = resumed.req
+        self._response = resumed._response
+    end
+
+    local req = type(resumed) == "table" and resumed.req or {}
+    local ref = req.reference or {}
+    local value, reason
+    if req.id == "OnUserInput" and ref[0] == id then
+        value = ref[1] or ""
+    elseif req.id == "OnUserInputCancel" and ref[0] == id then
+        reason = ref[1] or "cancel"
+    else
+        reason = "interrupted"
+    end
+
+    if opts.var then
+        self.var[opts.var] = value
+    end
+    return value, reason
+end
+
 return SHIORI_ACT
@@ -0,0 +1,203 @@
+---@module pasta.shiori.event.communicate
+--- OnCommunicate デフォルトハンドラ（ゴースト間コミュニケーション）
+---
+--- 送信者（Reference0）と発言（Reference1）に応じて OnCommunicate シーンを選び、
+--- 送信者への返信先（レスポンスの Reference0）を自動で設定する。
+---
+--- シーン命名規則（優先度の高い順、同じ規則では発言の前方一致が長い方を優先）:
+---   ＊OnCommunicate_送信者_発言の先頭  送信者が一致し、発言が前方一致
+---   ＊OnCommunicate_送信者             送信者が一致（任意の発言）
+---   ＊OnCommunicate__発言の先頭        任意の送信者で、発言が前方一致
+---   ＊OnCommunicate                    上記に該当しない全て
+---
+--- 候補は @pasta_search の SceneTable 前方一致検索（scene_candidates）で収集し、
+--- 最も優先度の高い候補が複数ある場合は @pasta_search の select_scene で選ぶ
+--- （シーン検索と同じ乱数で、set_scene_selector で固定できる）。
+---
+--- 無限の往復を防ぐため、同じ相手との連続した往復回数を
+--- pasta.toml の [ghost] communicate_max_turns（デフォルト10、0で無制限）に制限する。
+--- 上限に達した場合は返信しない（204）。ユーザー（送信者 "user"）からの発言は数えない。
+--- 前回の発言から [ghost] communicate_timeout_secs（デフォルト60、0で無効）秒以上
+--- 空いた場合は、同じ相手でも新しい会話として数え直す。
+---
+--- ゴースト開発者は REG.OnCommunicate を上書きしてカスタムハンドラを設定可能。
+
+local REG = require("pasta.shiori.event.register")
+local SCENE = require("pasta.scene")
+local CONFIG = require("pasta.config")
+local STORE = require("pasta.store")
+
+local COMMUNICATE = {}
+
+--- シーン名のプレフィックス
+COMMUNICATE.PREFIX = "OnCommunicate"
+
+--- ユーザーからの発言を表す送信者名
+COMMUNICATE.USER = "user"
+
+--- 往復回数上限のデフォルト値
+local DEFAULT_MAX_TURNS = 10
+
+--- 会話が途切れたとみなすまでの秒数のデフォルト値
+local DEFAULT_TIMEOUT_SECS = 60
+
+--- @param s string
+--- @param prefix string
+--- @return boolean
+local function starts_with(s, prefix)
+    return s:sub(1, #prefix) == prefix
+end
+
+--- シーン名を送信者・発言と照合する
+--- @param name string シーン名（DSL 上の名前、例: "OnCommunicate_まゆら_こんにちは"）
+--- @param sender string 送信者名
+--- @param message string 発言
+--- @return integer|nil priority 優先度（3: 送信者+発言, 2: 送信者, 1: 発言, 0: 汎用）、不一致はnil
+--- @return integer match_len 発言の前方一致長（バイト数）
+function COMMUNICATE.match(name, sender, message)
+    if not starts_with(name, COMMUNICATE.PREFIX) then
+        return nil, 0
+    end
+    local rest = name:sub(#COMMUNICATE.PREFIX + 1)
+    if rest == "" then
+        return 0, 0
+    end
+    -- 命名規則外（例: OnCommunicateX）は対象外
+    if not starts_with(rest, "_") then
+        return nil, 0
+    end
+    rest = rest:sub(2)
+
+    -- "__発言": 任意の送信者
+    if starts_with(rest, "_") then
+        local prefix = rest:sub(2)
+        if prefix ~= "" and starts_with(message, prefix) then
+            return 1, #prefix
+        end
+        return nil, 0
+    end
+
+    if rest == sender then
+        return 2, 0
+    end
+    if starts_with(rest, sender .. "_") then
+        local prefix = rest:sub(#sender + 2)
+        if starts_with(message, prefix) then
+            return 3, #prefix
+        end
+    end
+    return nil, 0
+end
+
+--- OnCommunicate シーンの候補を取得
+--- @return table[] { name = シーン名, global_name = グローバルシーン名 } の配列
+function COMMUNICATE.candidates()
+    local SEARCH = require("@pasta_search")
+    local list = {}
+    for _, global_name in ipairs(SEARCH:scene_candidates(COMMUNICATE.PREFIX)) do
+        local name = SCENE.get_base_name(global_name)
+        if name then
+            table.insert(list, { name = name, global_name = global_name })
+        end
+    end
+    return list
+end
+
+--- 送信者・発言に最も合うシーンを選ぶ
+--- @param sender string 送信者名
+--- @param message string 発言
+--- @return string|nil グローバルシーン名、見つからなければnil
+function COMMUNICATE.find(sender, message)
+    local best, best_priority, best_len = {}, -1, -1
+    for _, candidate in ipairs(COMMUNICATE.candidates()) do
+        local priority, len = COMMUNICATE.match(candidate.name, sender, message)
+        if priority then
+            if priority > best_priority or (priority == best_priority and len > best_len) then
+                best, best_priority, best_len = { candidate.global_name }, priority, len
+            elseif priority == best_priority and len == best_len then
+                table.insert(best, candidate.global_name)
+            end
+        end
+    end
+    if #best == 0 then
+        return nil
+    end
+    return require("@pasta_search"):select_scene(best)
+end
+
+--- 往復回数上限
+--- @return integer 上限（0 は無制限）
+function COMMUNICATE.max_turns()
+    return CONFIG.get("ghost", "communicate_max_turns", DEFAULT_MAX_TURNS)
+end
+
+--- 会話が途切れたとみなすまでの秒数
+--- @return integer 秒数（0 は無効）
+function COMMUNICATE.timeout_secs()
+    return CONFIG.get("ghost", "communicate_timeout_secs", DEFAULT_TIMEOUT_SECS)
+end
+
+--- 相手からの発言を1往復として数え、返信してよいか判定する
+--- 相手が変わった場合、前回の発言から timeout_secs 秒以上空いた場合は数え直す。
+--- 上限に達した場合は状態をリセットして false を返す。
+--- @param sender string 送信者名
+--- @param now integer|nil 現在時刻（Unix秒、省略時は os.time()）
+--- @return boolean 返信してよければ true
+function COMMUNICATE.count_turn(sender, now)
+    if sender == COMMUNICATE.USER then
+        return true
+    end
+    now = now or os.time()
+    local state = STORE.communicate
+    local timeout = COMMUNICATE.timeout_secs()
+    if not state or state.partner ~= sender
+        or (timeout > 0 and now - state.last_at >= timeout) then
+        state = { partner = sender, turns = 0 }
+        STORE.communicate = state
+    end
+    state.turns = state.turns + 1
+    state.last_at = now
+
+    local max_turns = COMMUNICATE.max_turns()
+    if max_turns > 0 and state.turns > max_turns then
+        STORE.communicate = nil
+        return false
+    end
+    return true
+end
+
+--- 往復回数をリセット（会話の終了・自分から話しかけた時）
+function COMMUNICATE.reset()
+    STORE.communicate = nil
+end
+
+---OnCommunicate デフォルトハンドラ
+---@param act ShioriAct actオブジェクト（act.req でリクエスト情報にアクセス可能）
+---@return thread|nil シーンコルーチン、またはnil
+REG.OnCommunicate = function(act)
+    local ref = act.req.reference or {}
+    local sender = ref[0]
+    if sender == nil or sender == "" then
+        return nil
+    end
+    local now = (act.req.date and act.req.date.unix) or os.time()
+    if not COMMUNICATE.count_turn(sender, now) then
+        return nil
+    end
+
+    local global_name = COMMUNICATE.find(sender, ref[1] or "")
+    local co = global_name and SCENE.co_start(global_name)
+    if not co then
+        -- 返信しない = 会話の終了
+        COMMUNICATE.reset()
+        return nil
+    end
+
+    -- 送信者へ返信（シーン内で act:communicate_to() により変更可能）
+    if sender ~= COMMUNICATE.USER then
+        act:set_reference(0, sender)
+    end
+    return co
+end
+
+return COMMUNICATE
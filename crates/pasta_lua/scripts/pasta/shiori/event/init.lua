@@ -11,8 +11,9 @@
 ---
 --- act オブジェクト経由で SHIORI リクエスト情報にアクセス:
 ---   - act.req.id: イベント名（例: "OnBoot", "OnClose"）
----   - act.req.method: "get" | "notify"
----   - act.req.version: 30（SHIORI/3.0）
+---   - act.req.method: "get" | "notify" | "teach"（teach は SHIORI/2.x のみ）
+---   - act.req.version: 30（SHIORI/3.0）、20～29（SHIORI/2.x）
+---   - act.req.command: SHIORI/2.x のリクエスト種別（例: "Sentence"）。3.0 では nil
 ---   - act.req.charset: 文字セット（例: "UTF-8"）
 ---   - act.req.sender: 送信者名（例: "SSP"）
 ---   - act.req.reference: 参照テーブル（reference[0], reference[1], ...）
@@ -43,6 +44,7 @@
 ---
 --- シーン関数フォールバック（alpha01）:
 --- REG にハンドラが未登録の場合、SCENE.search(req.id) でグローバルシーンを検索。
+--- 入力イベントは pasta.shiori.event.route の規則で Reference から組み立てたキーを先に検索する。
 --- 見つかった場合はシーン関数を実行（alpha01 では戻り値無視、204 返却）。
 --- alpha03 で act オブジェクト生成・さくらスクリプト変換を統合予定。
 ---
@@ -60,10 +62,15 @@ local REG = require("pasta.shiori.event.register")
 local RES = require("pasta.shiori.res")
 local SHIORI_ACT = require("pasta.shiori.act")
 local STORE = require("pasta.store")
+local ENV = require("pasta.areka.env")
+local ROUTE = require("pasta.shiori.event.route")
+local ACTOR = require("pasta.actor")
 
 -- 1.5. デフォルトイベントハンドラをロード
 require("pasta.shiori.event.boot")
 require("pasta.shiori.event.second_change")
+require("pasta.shiori.event.communicate")
+require("pasta.shiori.event.input")
 
 -- 2. モジュールテーブル宣言
 --- @class EVENT
@@ -75,7 +82,7 @@ local EVENT = {}
 --- @param req table SHIORIリクエストテーブル
 --- @return ShioriAct actオブジェクト
 local function create_act(req)
-    return SHIORI_ACT.new(STORE.actors, req)
+    return SHIORI_ACT.new(STORE.actors, req):transfer_env_to_var()
 end
 
 --- STORE.co_sceneを統一管理するローカル関数
@@ -136,23 +143,45 @@ end
 
 --- デフォルトハンドラ（未登録イベント用）
 --- シーン関数をイベント名で検索し、見つかった場合はthreadを返す。
+--- 入力イベント（OnMouseDoubleClick 等）は ROUTE のキー（例: "さくら頭つつかれ"）を先に検索する。
 --- 見つからない場合はnilを返す（EVENT.fireでRES.no_content()に変換される）。
 --- @param act ShioriAct actオブジェクト
 --- @return thread|nil シーンコルーチン、またはnil
 function EVENT.no_entry(act)
-    -- シーン関数をイベント名で検索（遅延ロードで循環参照回避）
-    local SCENE = require("pasta.scene")
-    return SCENE.co_exec(act.req.id, nil, nil)
+    return ROUTE.co_exec(act)
 end
 
 --- イベント振り分け
 --- ハンドラを実行し、コルーチンの場合はresumeして状態管理を行う
 --- @param req table リクエストテーブル（req.id にイベント名）
---- @return string SHIORI レスポンス
+--- @return string|table SHIORI レスポンス（文字列、または構造化レスポンステーブル）
 function EVENT.fire(req)
+    -- レスポンス形式をリクエストのプロトコルバージョンに合わせる
+    RES.set_protocol(req)
+
+    -- 定期オートセーブ（REG.OnSecondChange の上書きに影響されないようここで駆動）
+    if req.id == "OnSecondChange" then
+        local PERSISTENCE = package.loaded["@pasta_persistence"]
+        if PERSISTENCE and PERSISTENCE.autosave_tick then
+            PERSISTENCE.autosave_tick()
+        end
+    end
+
+    -- ベースウェア通知（basewareversion, OnNotifyUserInfo 等）を環境情報にキャッシュ
+    -- （REG の上書きやシーン関数フォールバックに影響されないようここで捕捉）
+    ENV.capture(req)
+
     -- act オブジェクトを作成
     local act = create_act(req)
 
+    -- 保持時間（emotion_reset_secs）を過ぎた感情を中立へ戻す（サーフェス変更は act に蓄積）
+    -- （REG.OnSecondChange の上書きに影響されないようここで駆動）
+    local reset_emotions = 0
+    if req.id == "OnSecondChange" then
+        local now = (req.date and req.date.unix) or os.time()
+        reset_emotions = ACTOR.reset_expired_emotions(act, now)
+    end
+
     -- ハンドラを呼び出し
     -- エラーは SHIORI.request の xpcall でキャッチされる
     local handler = REG[req.id] or EVENT.no_entry
@@ -169,13 +198,24 @@ function EVENT.fire(req)
         end
         -- 状態保存（set_co_scene内部でstatus判断）
         set_co_scene(result)
-        return RES.ok(yielded_value)
+        return RES.ok(yielded_value, act:response_headers())
     elseif type(result) == "string" then
-        -- 既存互換: 文字列をそのまま返す
-        return RES.ok(result)
+        -- 既存互換: RES.* で組み立て済みのレスポンスはそのまま返す
+        if result:match("^SHIORI/%d%.%d ") then
+            return result
+        end
+        -- それ以外の文字列は値として扱う
+        return RES.ok(result, act:response_headers())
+    elseif type(result) == "table" then
+        -- 構造化レスポンス（{ status, value, headers }）は Rust 側で検証・シリアライズする
+        return result
     else
-        -- nil
-        return RES.no_content()
+        -- nil: 感情を戻したサーフェス変更だけがあればそれを出力する
+        local script = reset_emotions > 0 and act:build() or nil
+        if script then
+            return RES.ok(script, act:response_headers())
+        end
+        return RES.no_content(act:response_headers())
     end
 end
 
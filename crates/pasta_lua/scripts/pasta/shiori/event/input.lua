@@ -0,0 +1,63 @@
+---@module pasta.shiori.event.input
+--- OnUserInput / OnUserInputCancel デフォルトハンドラ（入力ボックス）
+---
+--- act:input(id, opts) で入力待ちになったシーンコルーチン（STORE.co_scene）を、
+--- Reference0 が入力IDと一致する OnUserInput / OnUserInputCancel で再開する。
+--- 入力値は act:input() の戻り値（opts.var 指定時は act.var にも格納）として受け取る。
+---
+--- 入力待ちでない、または ID が一致しない場合は通常のシーン関数フォールバック
+--- （＊OnUserInput シーン等）で処理する。
+---
+--- 入力待ちの間は OnTalk / OnHour（仮想イベント）を発行しない。
+--- 他のイベントで新しいシーンが始まった場合は、入力待ちのシーンは破棄される。
+---
+--- ゴースト開発者は REG.OnUserInput を上書きしてカスタムハンドラを設定可能
+--- （その場合は INPUT.resume(act) で同じ再開処理を呼び出せる）。
+
+local REG = require("pasta.shiori.event.register")
+local ROUTE = require("pasta.shiori.event.route")
+local STORE = require("pasta.store")
+
+local INPUT = {}
+
+--- 入力待ち中のシーンを取得
+--- 入力待ちのシーンが他のシーンに置き換えられていた場合は待ち状態を破棄する。
+--- @param id string|nil 入力ID（nil の場合は ID を問わない）
+--- @return thread|nil 入力待ちのシーンコルーチン
+function INPUT.waiting(id)
+    local wait = STORE.input_wait
+    if not wait then
+        return nil
+    end
+    if STORE.co_scene ~= wait.co or coroutine.status(wait.co) ~= "suspended" then
+        STORE.input_wait = nil
+        return nil
+    end
+    if id ~= nil and wait.id ~= id then
+        return nil
+    end
+    return wait.co
+end
+
+--- 入力待ちのシーンを再開する（Reference0 が入力IDと一致する場合）
+--- @param act ShioriAct actオブジェクト
+--- @return thread|nil 再開するシーンコルーチン、入力待ちでなければnil
+function INPUT.resume(act)
+    local ref = act.req.reference or {}
+    return INPUT.waiting(ref[0])
+end
+
+--- OnUserInput / OnUserInputCancel 共通ハンドラ
+--- @param act ShioriAct actオブジェクト
+--- @return thread|nil シーンコルーチン、またはnil
+local function handler(act)
+    return INPUT.resume(act) or ROUTE.co_exec(act)
+end
+
+---OnUserInput デフォルトハンドラ（Reference0: 入力ID, Reference1: 入力値）
+REG.OnUserInput = handler
+
+---OnUserInputCancel デフォルトハンドラ（Reference0: 入力ID, Reference1: 理由）
+REG.OnUserInputCancel = handler
+
+return INPUT
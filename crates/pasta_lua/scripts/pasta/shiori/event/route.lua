@@ -0,0 +1,207 @@
+---@module pasta.shiori.event.route
+--- 入力イベント（マウス・キー）のシーンルーティング
+---
+--- イベント ID と Reference（キャラクター、当たり判定、ボタン等）からシーン検索キーを
+--- 組み立て、具体的なキーから汎用的なキーへ順にシーンを検索する。
+--- 最後はイベント ID そのもので検索する（従来のシーン関数フォールバックと同じ）。
+---
+--- 例（OnMouseDoubleClick、スコープ0=さくら、当たり判定 "Head"→"頭"）:
+---   ＊さくら頭つつかれ → ＊頭つつかれ → ＊さくらつつかれ → ＊OnMouseDoubleClick
+---
+--- テンプレートのプレースホルダー:
+---   {char}   スコープ番号（Reference3、"0", "1", ...）
+---   {actor}  スコープに立っているアクター名（STORE.actor_spots から逆引き）
+---   {area}   当たり判定名（Reference4、[input.areas] で別名に変換）
+---   {button} ボタン名（Reference5、左/右/中）
+---   {wheel}  ホイール方向（Reference2、上/下）
+---   {ref0}～ Reference の生の値
+--- 値が空のプレースホルダーを含むキーは使わない。
+---
+--- DSL のシーン名は数字で始められないため、デフォルトは {char} ではなく {actor} を使う。
+---
+--- なでなで（撫でられ）判定:
+---   同じキャラクター・当たり判定上での OnMouseMove を数え、
+---   [input] stroke_count 回（デフォルト40）に達したら ROUTE.stroke_templates で検索する。
+---   [input] stroke_timeout 秒（デフォルト2）以上間が空く、または当たり判定が変わると数え直す。
+---
+--- pasta.toml の [input.routes] でイベントごとのテンプレートを上書きできる。
+--- EVENT.no_entry から ROUTE.co_exec(act) が呼ばれる。REG にハンドラを登録した場合も
+--- ハンドラ内で ROUTE.co_exec(act) を呼べば同じルーティングを使える。
+
+local CONFIG = require("pasta.config")
+local STORE = require("pasta.store")
+
+local ROUTE = {}
+
+--- イベント別のシーン検索キーテンプレート（具体的なものから順に）
+--- ゴースト側で ROUTE.rules[id] = { ... } として追加・上書きできる。
+--- @type table<string, string[]>
+ROUTE.rules = {
+    OnMouseDoubleClick = { "{actor}{area}つつかれ", "{area}つつかれ", "{actor}つつかれ" },
+    OnMouseClick = { "{actor}{area}{button}クリック", "{area}{button}クリック", "{actor}{button}クリック" },
+    OnMouseWheel = { "{actor}{area}ころころ", "{area}ころころ", "{actor}ころころ" },
+    OnKeyPress = { "{ref0}キー" },
+}
+
+--- なでなで判定時のシーン検索キーテンプレート
+--- @type string[]
+ROUTE.stroke_templates = { "{actor}{area}なでられ", "{area}なでられ", "{actor}なでられ" }
+
+--- ボタン番号 → ボタン名（Reference5）
+ROUTE.button_names = { ["0"] = "左", ["1"] = "右", ["2"] = "中" }
+
+--- なでなで判定のデフォルト値
+local DEFAULT_STROKE_COUNT = 40
+local DEFAULT_STROKE_TIMEOUT = 2
+
+--- スコープ番号に立っているアクター名を取得
+--- @param scope string|nil スコープ番号
+--- @return string|nil
+local function actor_at(scope)
+    local spot = tonumber(scope)
+    if not spot then
+        return nil
+    end
+    local found
+    for name, actor_spot in pairs(STORE.actor_spots) do
+        if actor_spot == spot and (found == nil or name < found) then
+            found = name
+        end
+    end
+    return found
+end
+
+--- 当たり判定名を [input.areas] の別名に変換
+--- @param area string|nil
+--- @return string|nil
+local function area_name(area)
+    if area == nil or area == "" then
+        return nil
+    end
+    local areas = CONFIG.get("input", "areas", {})
+    return areas[area] or area
+end
+
+--- リクエストからプレースホルダーの値を作成
+--- @param req table SHIORIリクエストテーブル
+--- @return table<string, string> プレースホルダー名 → 値
+function ROUTE.fields(req)
+    local ref = req.reference or {}
+    local fields = {
+        char = ref[3],
+        actor = actor_at(ref[3]),
+        area = area_name(ref[4]),
+        button = ROUTE.button_names[ref[5] or "0"],
+    }
+    local wheel = tonumber(ref[2])
+    if wheel and wheel > 0 then
+        fields.wheel = "上"
+    elseif wheel and wheel < 0 then
+        fields.wheel = "下"
+    end
+    local i = 0
+    while ref[i] ~= nil do
+        fields["ref" .. i] = ref[i]
+        i = i + 1
+    end
+    return fields
+end
+
+--- テンプレートを展開する
+--- @param template string テンプレート（例: "{actor}{area}つつかれ"）
+--- @param fields table<string, string> プレースホルダーの値
+--- @return string|nil 展開したキー、空のプレースホルダーを含む場合はnil
+function ROUTE.expand(template, fields)
+    local missing = false
+    local key = template:gsub("{(%w+)}", function(name)
+        local value = fields[name]
+        if value == nil or value == "" then
+            missing = true
+            return ""
+        end
+        return tostring(value)
+    end)
+    if missing then
+        return nil
+    end
+    return key
+end
+
+--- OnMouseMove を数え、なでなでと判定できるか調べる
+--- 判定した場合はカウントをリセットする。
+--- @param req table SHIORIリクエストテーブル
+--- @return boolean なでなでと判定した場合 true
+function ROUTE.detect_stroke(req)
+    local ref = req.reference or {}
+    local scope, area = ref[3], ref[4]
+    if area == nil or area == "" then
+        STORE.stroke = nil
+        return false
+    end
+
+    local now = (req.date and req.date.unix) or os.time()
+    local timeout = CONFIG.get("input", "stroke_timeout", DEFAULT_STROKE_TIMEOUT)
+    local state = STORE.stroke
+    if not state or state.scope ~= scope or state.area ~= area or now - state.last > timeout then
+        state = { scope = scope, area = area, count = 0 }
+        STORE.stroke = state
+    end
+    state.count = state.count + 1
+    state.last = now
+
+    if state.count >= CONFIG.get("input", "stroke_count", DEFAULT_STROKE_COUNT) then
+        STORE.stroke = nil
+        return true
+    end
+    return false
+end
+
+--- イベントのテンプレートを取得（[input.routes] を優先）
+--- @param id string イベントID
+--- @return string[]
+function ROUTE.templates(id)
+    local routes = CONFIG.get("input", "routes", {})
+    return routes[id] or ROUTE.rules[id] or {}
+end
+
+--- シーン検索キーを具体的なものから順に列挙する（最後はイベントID）
+--- OnMouseMove ではなでなでの判定（カウント）も行う。
+--- @param req table SHIORIリクエストテーブル
+--- @return string[] シーン検索キーのリスト
+function ROUTE.keys(req)
+    local templates = ROUTE.templates(req.id)
+    if req.id == "OnMouseMove" and ROUTE.detect_stroke(req) then
+        templates = ROUTE.stroke_templates
+    end
+
+    local fields = ROUTE.fields(req)
+    local keys, seen = {}, {}
+    for _, template in ipairs(templates) do
+        local key = ROUTE.expand(template, fields)
+        if key and not seen[key] then
+            seen[key] = true
+            table.insert(keys, key)
+        end
+    end
+    if not seen[req.id] then
+        table.insert(keys, req.id)
+    end
+    return keys
+end
+
+--- ルーティングしてシーンコルーチンを作成する
+--- @param act ShioriAct actオブジェクト
+--- @return thread|nil シーンコルーチン、見つからなければnil
+function ROUTE.co_exec(act)
+    -- 遅延ロードで循環参照回避
+    local SCENE = require("pasta.scene")
+    for _, key in ipairs(ROUTE.keys(act.req)) do
+        local co = SCENE.co_exec(key, nil, nil)
+        if co then
+            return co
+        end
+    end
+    return nil
+end
+
+return ROUTE
@@ -164,6 +164,13 @@ function M.dispatch(act)
         return nil
     end
 
+    -- 入力ボックスの入力待ち中は発行しない（待機中のシーンを置き換えないため）
+    -- 入力待ちのシーンが他のシーンに置き換えられていれば INPUT.waiting() が待ち状態を破棄する
+    local INPUT = require("pasta.shiori.event.input")
+    if INPUT.waiting() then
+        return nil
+    end
+
     -- OnHour 判定（優先）
     local hour_result = M.check_hour(act)
     if hour_result then
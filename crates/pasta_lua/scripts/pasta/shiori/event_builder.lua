@@ -0,0 +1,86 @@
+--- @module pasta.shiori.event_builder
+--- スクリプトイベントビルダーモジュール
+---
+--- グループ化されたトークン配列を構造化イベント配列に変換する純粋関数モジュール。
+--- sakura_builder と同じトークン列・スポット状態を扱い、さくらスクリプトの代わりに
+--- `@pasta_script_event` でJSON化できるイベントを生成する。
+--- pasta.shiori.act の build() から出力形式 "json" のときに呼び出される。
+
+local BUILDER = require("pasta.shiori.sakura_builder")
+
+local EVENT_BUILDER = {}
+
+--- @class ScriptEvent
+--- @field type string イベント種別（actor/talk/surface/wait/newline/clear/clear_spots/raw_script）
+
+--- グループ化されたトークン配列をイベント配列に変換
+--- @param grouped_tokens table[] グループ化されたトークン配列
+--- @param input_actor_spots table<string, integer>|nil アクターごとのスポット位置マップ（nilの場合は{}として扱う）
+--- @return ScriptEvent[] イベント配列
+--- @return table<string, integer> 更新後のactor_spotsマップ
+function EVENT_BUILDER.build(grouped_tokens, input_actor_spots)
+    local events = {}
+
+    -- 入力テーブルを変更しない（純粋関数性の保証）
+    local actor_spots = {}
+    if input_actor_spots then
+        for name, spot in pairs(input_actor_spots) do
+            actor_spots[name] = spot
+        end
+    end
+    local last_actor = nil -- 最後に発言したActor
+
+    for _, token in ipairs(grouped_tokens) do
+        local t = token.type
+
+        if t == "spot" then
+            if token.actor and token.actor.name then
+                actor_spots[token.actor.name] = token.spot
+            end
+        elseif t == "clear_spot" then
+            for name in pairs(actor_spots) do
+                actor_spots[name] = nil
+            end
+            last_actor = nil
+            table.insert(events, { type = "clear_spots" })
+        elseif t == "actor" then
+            local actor = token.actor
+            local actor_name = actor and actor.name
+
+            -- アクター切り替え時に話者とスポットを通知
+            if actor and last_actor ~= actor then
+                table.insert(events, {
+                    type = "actor",
+                    name = actor_name,
+                    spot = BUILDER.resolve_spot(actor, actor_spots),
+                    balloon = actor.balloon,
+                })
+                last_actor = actor
+            end
+
+            for _, inner in ipairs(token.tokens) do
+                local inner_type = inner.type
+
+                if inner_type == "talk" then
+                    -- ウェイト・折り返しは付けず、テキストのまま渡す（表示速度はフロントエンドが決める）
+                    table.insert(events, { type = "talk", actor = actor_name, text = inner.text })
+                elseif inner_type == "surface" then
+                    table.insert(events, { type = "surface", actor = actor_name, id = tostring(inner.id) })
+                elseif inner_type == "wait" then
+                    table.insert(events, { type = "wait", ms = math.floor(inner.ms) })
+                elseif inner_type == "newline" then
+                    table.insert(events, { type = "newline", n = math.floor(inner.n) })
+                elseif inner_type == "clear" then
+                    table.insert(events, { type = "clear" })
+                elseif inner_type == "raw_script" then
+                    table.insert(events, { type = "raw_script", text = inner.text })
+                end
+                -- yield は無視
+            end
+        end
+    end
+
+    return events, actor_spots
+end
+
+return EVENT_BUILDER
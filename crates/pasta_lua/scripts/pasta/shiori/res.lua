@@ -3,6 +3,7 @@
 ---
 --- SHIORI/3.0プロトコルに準拠したレスポンス文字列を構築する。
 --- ステータスコード別の便利関数を提供し、標準ヘッダー（Charset, Sender, SecurityLevel）を自動付与する。
+--- SHIORI/2.xリクエストに対しては RES.set_protocol(req) により2.x形式のレスポンスを返す。
 
 -- 1. require文（なし - 外部依存ゼロ）
 
@@ -15,7 +16,7 @@ local SPLIT = ": "
 
 -- 4. 環境設定テーブル
 --- @class RESEnv 環境設定テーブル
---- @field charset string 文字セット（デフォルト: "UTF-8"）
+--- @field charset string 文字セット（デフォルト: "UTF-8"、リクエストの Charset があればそちらを優先）
 --- @field sender string 送信者名（デフォルト: "Pasta"）
 --- @field security_level string セキュリティレベル（デフォルト: "local"）
 RES.env = {
@@ -24,14 +25,121 @@ RES.env = {
     security_level = "local",
 }
 
+--- SHIORI/2.xのリクエスト種別ごとの値ヘッダー名
+local VALUE_KEYS_2X = {
+    Sentence = "Sentence",
+    String = "String",
+    Word = "Word",
+    Status = "Status",
+    Version = "Version",
+}
+
+--- 値ヘッダー直後に出力するヘッダー（この順で出力し、Reference* はこの後に番号順）
+local LEADING_KEYS = { "ValueNotify" }
+
+--- Reference* の後に出力するヘッダー（この順で出力し、残りはキー名の昇順）
+local TRAILING_KEYS = { "Marker", "BalloonOffset", "ErrorLevel", "ErrorDescription" }
+
+--- 追加ヘッダーの出力順を決定する
+--- 値ヘッダー → LEADING_KEYS → Reference0..n（番号順）→ TRAILING_KEYS → その他（昇順）
+--- @param dic HeaderDic 追加ヘッダー辞書
+--- @param value_key string 値ヘッダー名（"Value" / "Sentence" 等）
+--- @return string[] 出力順のキー配列
+local function ordered_keys(dic, value_key)
+    local keys, placed = {}, {}
+    local function place(k)
+        if dic[k] ~= nil and not placed[k] then
+            table.insert(keys, k)
+            placed[k] = true
+        end
+    end
+
+    place(value_key)
+    for _, k in ipairs(LEADING_KEYS) do place(k) end
+
+    local refs = {}
+    for k in pairs(dic) do
+        local n = type(k) == "string" and k:match("^Reference(%d+)$")
+        if n then table.insert(refs, { n = tonumber(n), key = k }) end
+    end
+    table.sort(refs, function(a, b) return a.n < b.n end)
+    for _, r in ipairs(refs) do place(r.key) end
+
+    for _, k in ipairs(TRAILING_KEYS) do place(k) end
+
+    local rest = {}
+    for k in pairs(dic) do
+        if not placed[k] then table.insert(rest, k) end
+    end
+    table.sort(rest, function(a, b) return tostring(a) < tostring(b) end)
+    for _, k in ipairs(rest) do place(k) end
+
+    return keys
+end
+
+--- ヘッダー1行を検証して組み立てる
+--- キーは英数字と "-", ".", "_" のみ、値は文字列または数値で CR/LF を含まないこと。
+--- @param key any ヘッダー名
+--- @param value any ヘッダー値
+--- @return string "Key: Value\r\n"
+local function header_line(key, value)
+    if type(key) ~= "string" or not key:match("^[%w][%w%-%._]*$") then
+        error("RES.build: invalid header name: " .. tostring(key), 3)
+    end
+    local t = type(value)
+    if t ~= "string" and t ~= "number" then
+        error(string.format("RES.build: header '%s' must be string, got %s", key, t), 3)
+    end
+    local text = tostring(value)
+    if text:find("[\r\n]") then
+        error(string.format("RES.build: header '%s' must not contain CR/LF", key), 3)
+    end
+    return key .. SPLIT .. text .. CRLF
+end
+
+-- 4.5. プロトコル設定テーブル
+--- @class RESProtocol プロトコル設定テーブル（EVENT.fire がリクエストごとに設定）
+--- @field version integer プロトコルバージョン（30: SHIORI/3.0、20～29: SHIORI/2.x）
+--- @field value_key string RES.ok が値を格納するヘッダー名
+--- @field charset string|nil リクエストの Charset（nil の場合は RES.env.charset）
+RES.protocol = {
+    version = 30,
+    value_key = "Value",
+}
+
 -- 5. 公開関数
 
+--- リクエストに合わせてレスポンスのプロトコルを切り替える
+--- SHIORI/2.xの場合、ステータス行を "SHIORI/2.x" とし、値ヘッダー名を
+--- リクエスト種別（req.command）に応じて Sentence / String 等に切り替える。
+--- Charset ヘッダーにはリクエストの Charset をそのまま返す
+--- （Shift_JIS 等へのエンコードは Rust 側で行う）。
+--- reqがnil、またはSHIORI/3.0の場合は既定値に戻す。
+---
+--- @param req table|nil リクエストテーブル（req.version, req.command, req.charset を参照）
+function RES.set_protocol(req)
+    local version = req and tonumber(req.version) or 30
+    local charset = req and req.charset or nil
+    if version >= 30 then
+        RES.protocol = { version = 30, value_key = "Value", charset = charset }
+    else
+        RES.protocol = {
+            version = version,
+            value_key = VALUE_KEYS_2X[req.command] or "Sentence",
+            charset = charset,
+        }
+    end
+end
+
 --- @alias HeaderDic table<string, string> ヘッダー辞書
 
 --- 汎用ビルダー関数
 --- SHIORI/3.0レスポンス文字列を生成する。
 --- 標準ヘッダー3種（Charset, Sender, SecurityLevel）を常に出力し、
---- 追加ヘッダーを辞書から取得して付加する。
+--- 追加ヘッダーを辞書から取得して決まった順序で付加する
+--- （値 → ValueNotify → Reference0..n → Marker → BalloonOffset → ErrorLevel → ErrorDescription → その他昇順）。
+--- 不正なヘッダー名や CR/LF を含む値はエラーとなる。
+--- RES.protocol がSHIORI/2.xの場合は "SHIORI/2.x" で応答し、SecurityLevel は出力しない。
 ---
 --- @param code string ステータスコード（例: "200 OK"）
 --- @param dic HeaderDic|nil 追加ヘッダー辞書
@@ -39,18 +147,28 @@ RES.env = {
 function RES.build(code, dic)
     dic = dic or {}
 
+    local version = RES.protocol.version
+    local is_v3 = version >= 30
+
     -- Status line
-    local rc = "SHIORI/3.0 " .. code .. CRLF
+    local rc
+    if is_v3 then
+        rc = "SHIORI/3.0 " .. code .. CRLF
+    else
+        rc = "SHIORI/2." .. (version - 20) .. " " .. code .. CRLF
+    end
 
     -- Standard headers (always in this order)
-    rc = rc .. "Charset" .. SPLIT .. RES.env.charset .. CRLF
+    rc = rc .. "Charset" .. SPLIT .. (RES.protocol.charset or RES.env.charset) .. CRLF
     rc = rc .. "Sender" .. SPLIT .. RES.env.sender .. CRLF
-    rc = rc .. "SecurityLevel" .. SPLIT .. RES.env.security_level .. CRLF
+    if is_v3 then
+        rc = rc .. "SecurityLevel" .. SPLIT .. RES.env.security_level .. CRLF
+    end
 
-    -- Additional headers from dic
+    -- Additional headers from dic (deterministic order)
     if type(dic) == 'table' then
-        for k, v in pairs(dic) do
-            rc = rc .. k .. SPLIT .. v .. CRLF
+        for _, k in ipairs(ordered_keys(dic, RES.protocol.value_key)) do
+            rc = rc .. header_line(k, dic[k])
         end
     end
 
@@ -62,6 +180,7 @@ end
 
 --- 200 OK レスポンス
 --- Valueヘッダー付きの正常レスポンスを生成する。
+--- SHIORI/2.xでは RES.protocol.value_key（Sentence 等）のヘッダーに格納する。
 --- valueがnilまたは空文字列の場合は、204 No Contentを返す。
 ---
 --- @param value string|nil Value ヘッダーの値
@@ -74,7 +193,7 @@ function RES.ok(value, dic)
     end
 
     dic = dic or {}
-    dic["Value"] = value
+    dic[RES.protocol.value_key] = value
     return RES.build("200 OK", dic)
 end
 
@@ -121,7 +240,8 @@ function RES.err(reason, dic)
     if reason == nil or reason == "" then
         reason = "Unknown error"
     end
-    dic["X-Error-Reason"] = reason
+    -- 複数行のエラーメッセージはヘッダーを壊すため1行にまとめる
+    dic["X-Error-Reason"] = tostring(reason):gsub("[\r\n]+", " ")
     return RES.build("500 Internal Server Error", dic)
 end
 
@@ -133,7 +253,7 @@ end
 --- @return string SHIORI/3.0レスポンス文字列
 function RES.warn(reason, dic)
     dic = dic or {}
-    dic["X-Warn-Reason"] = reason
+    dic["X-Warn-Reason"] = tostring(reason):gsub("[\r\n]+", " ")
     return RES.no_content(dic)
 end
 
@@ -28,6 +28,69 @@ local function spot_to_id(spot)
     return 0 -- デフォルトはsakura
 end
 
+--- アクターの現在のスポットIDを決定
+--- 優先順位: actor_spots（act:set_spot / 前回までの位置）→ actor.spot（既定の立ち位置）→ 0
+--- @param actor Actor|nil アクター
+--- @param actor_spots table<string, integer> アクターごとのスポット位置マップ
+--- @return number スポットID番号
+function BUILDER.resolve_spot(actor, actor_spots)
+    local name = actor and actor.name
+    local spot = name and actor_spots[name]
+    if spot == nil and actor then
+        spot = actor.spot
+    end
+    if spot == nil then
+        return 0
+    end
+    return spot_to_id(spot)
+end
+
+--- スポットの既定バルーンID（sakura側は0、それ以外は1）
+--- @param spot_id number スポットID番号
+--- @return number バルーンID
+local function default_balloon(spot_id)
+    return spot_id == 0 and 0 or 1
+end
+
+--- talk_to_scriptに渡すアクターを決定
+--- actor.wait_profile が指定されている場合、プロファイルを既定値とするビューを返す
+--- （アクター自身のフィールド → プロファイル → [talk] の順に解決される）
+--- @param actor Actor|nil アクター
+--- @param profiles table<string, table>|nil ウェイトプロファイル（[talk.profiles]）
+--- @param cache table<Actor, table> ビューのキャッシュ
+--- @return table|nil talk_to_scriptに渡すアクター
+local function talk_actor(actor, profiles, cache)
+    local name = actor and actor.wait_profile
+    local profile = type(name) == "string" and profiles and profiles[name]
+    if type(profile) ~= "table" then
+        return actor
+    end
+    if not cache[actor] then
+        cache[actor] = setmetatable({}, {
+            __index = function(_, key)
+                local value = actor[key]
+                if value == nil then
+                    value = profile[key]
+                end
+                return value
+            end,
+        })
+    end
+    return cache[actor]
+end
+
+--- グループに発話が含まれるか
+--- @param token table type="actor" トークン
+--- @return boolean
+local function has_talk(token)
+    for _, inner in ipairs(token.tokens) do
+        if inner.type == "talk" then
+            return true
+        end
+    end
+    return false
+end
+
 --- スポットタグを生成（SSP ukadoc準拠: 常に\p[ID]形式）
 --- @param spot_id number スポットID番号
 --- @return string スポットタグ
@@ -36,7 +99,8 @@ local function spot_to_tag(spot_id)
 end
 
 --- @class BuildConfig
---- @field spot_newlines number スポット変更時の改行量（デフォルト1.5）
+--- @field spot_newlines number スポット変更時の改行量（デフォルト1.5、actor.spot_newlinesで上書き）
+--- @field wait_profiles table<string, table>|nil ウェイトプロファイル（actor.wait_profileで選択）
 
 --- グループ化されたトークン配列をさくらスクリプト文字列に変換
 --- @param grouped_tokens table[] グループ化されたトークン配列
@@ -59,6 +123,10 @@ function BUILDER.build(grouped_tokens, config, input_actor_spots)
     end
     local last_actor = nil -- 最後に発言したActor
     local last_spot = nil  -- 最後のスポットID
+    local talk_spot = nil  -- 最後に発話を出力したスポットID（段落区切りの判定用）
+    local column = 0       -- 現在行の桁位置（自動折り返し用、半角=1）
+    local balloons = {}    -- {[spot_id]: balloon_id} このスクリプト内で指定したバルーン
+    local talk_actors = {} -- wait_profile適用ビューのキャッシュ
 
     for _, token in ipairs(grouped_tokens) do
         local t = token.type
@@ -75,6 +143,8 @@ function BUILDER.build(grouped_tokens, config, input_actor_spots)
             end
             last_actor = nil
             last_spot = nil
+            talk_spot = nil
+            column = 0
         elseif t == "actor" then
             -- actorトークン処理: グループ内のトークンを順次処理
             local actor = token.actor
@@ -82,15 +152,34 @@ function BUILDER.build(grouped_tokens, config, input_actor_spots)
 
             -- アクター切り替え検出
             if actor and last_actor ~= actor then
-                local spot = actor_spots[actor_name] or 0
+                local spot = BUILDER.resolve_spot(actor, actor_spots)
 
-                -- spot変更時に段落区切り改行を出力
-                if last_spot ~= nil and last_spot ~= spot then
-                    local percent = math.floor(spot_newlines * 100)
+                -- spot変更時に段落区切り改行を出力（改行量は切り替え先アクターの設定を優先）
+                -- 表情だけを変えるグループ（感情リセット等）では改行せず、
+                -- 表情だけを変えたスポットは改行の対象にしない（空のバルーンに改行を入れないため）
+                if talk_spot ~= nil and talk_spot ~= spot and has_talk(token) then
+                    local percent = math.floor((actor.spot_newlines or spot_newlines) * 100)
                     table.insert(buffer, string.format("\\n[%d]", percent))
                 end
 
+                -- スポットが変わると別のバルーンの新しい行から始まる
+                if last_spot ~= spot then
+                    column = 0
+                end
+
                 table.insert(buffer, spot_to_tag(spot))
+
+                -- バルーン指定: アクターのバルーン、未指定なら同じスポットで変更済みの場合のみ既定に戻す
+                local balloon = actor.balloon
+                if balloon == nil and balloons[spot] ~= nil then
+                    balloon = default_balloon(spot)
+                end
+                if balloon ~= nil and balloons[spot] ~= balloon then
+                    table.insert(buffer, string.format("\\b[%d]", balloon))
+                    balloons[spot] = balloon
+                    column = 0
+                end
+
                 last_actor = actor
                 last_spot = spot
             end
@@ -100,7 +189,11 @@ function BUILDER.build(grouped_tokens, config, input_actor_spots)
                 local inner_type = inner.type
 
                 if inner_type == "talk" then
-                    table.insert(buffer, SAKURA_SCRIPT.talk_to_script(actor, inner.text))
+                    -- 桁位置を引き継いで変換（折り返し幅は actor.wrap_width / プロファイル / [talk] wrap_width）
+                    local speaker = talk_actor(actor, config.wait_profiles, talk_actors)
+                    local script, next_column = SAKURA_SCRIPT.talk_to_script(speaker, inner.text, column)
+                    table.insert(buffer, script)
+                    column = next_column or column
                 elseif inner_type == "surface" then
                     table.insert(buffer, string.format("\\s[%s]", tostring(inner.id)))
                 elseif inner_type == "wait" then
@@ -109,13 +202,19 @@ function BUILDER.build(grouped_tokens, config, input_actor_spots)
                     for _ = 1, inner.n do
                         table.insert(buffer, "\\n")
                     end
+                    column = 0
                 elseif inner_type == "clear" then
                     table.insert(buffer, "\\c")
+                    column = 0
                 elseif inner_type == "raw_script" then
                     table.insert(buffer, inner.text)
                 end
                 -- yield は無視
             end
+
+            if has_talk(token) then
+                talk_spot = last_spot
+            end
         end
     end
 
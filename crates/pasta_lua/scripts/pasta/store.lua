@@ -17,6 +17,10 @@
 --- @field local_words table<string, table> ローカル単語レジストリ（scene_name → {key → values[][]}）
 --- @field actor_words table<string, table> アクター単語レジストリ（actor_name → {key → values[][]}）
 --- @field app_ctx table アプリケーション実行中の汎用コンテキストデータ
+--- @field env AREKA_ENV|nil ベースウェア環境情報キャッシュ（pasta.areka.env が管理）
+--- @field communicate table|nil OnCommunicate 往復状態（{ partner, turns }）
+--- @field stroke table|nil なでなで判定状態（{ scope, area, count, last }）
+--- @field input_wait table|nil 入力ボックスの入力待ち状態（{ id, co }）
 local STORE = {}
 
 --- アクターキャッシュ（名前→アクター）
@@ -51,6 +55,22 @@ STORE.actor_words = {}
 --- @type table
 STORE.app_ctx = {}
 
+--- ベースウェア環境情報キャッシュ（pasta.areka.env が管理、未作成時はnil）
+--- @type AREKA_ENV|nil
+STORE.env = nil
+
+--- OnCommunicate 往復状態（相手ゴースト名と連続往復回数、会話していない時はnil）
+--- @type { partner: string, turns: integer }|nil
+STORE.communicate = nil
+
+--- なでなで判定状態（OnMouseMove のスコープ・当たり判定・回数・最終時刻、判定していない時はnil）
+--- @type { scope: string, area: string, count: integer, last: integer }|nil
+STORE.stroke = nil
+
+--- 入力ボックスの入力待ち状態（入力IDと待機中のシーンコルーチン、待っていない時はnil）
+--- @type { id: string, co: thread }|nil
+STORE.input_wait = nil
+
 --- 継続用コルーチン（OnTalkチェイントーク用）
 --- @type thread|nil
 STORE.co_scene = nil
@@ -70,6 +90,10 @@ function STORE.reset()
     STORE.actor_spots = {}
     STORE.scenes = {}
     STORE.app_ctx = {}
+    STORE.env = nil
+    STORE.communicate = nil
+    STORE.stroke = nil
+    STORE.input_wait = nil
     STORE.counters = {}
     STORE.global_words = {}
     STORE.local_words = {}
@@ -11,8 +11,9 @@ use pasta_core::parser::{
 use pasta_core::registry::SceneRegistry;
 
 use super::config::LineEnding;
-use super::context::TranspileContext;
+use super::context::{SakuraScriptWarning, TranspileContext};
 use super::error::TranspileError;
+use super::sakura_script::validator::{self, ValidationMode};
 use super::string_literalizer::StringLiteralizer;
 
 use std::collections::HashMap;
@@ -28,6 +29,8 @@ pub struct LuaCodeGenerator<'a, W: Write> {
     indent_level: usize,
     /// Line ending style
     line_ending: LineEnding,
+    /// Sakura script validation warnings
+    sakura_warnings: Vec<SakuraScriptWarning>,
 }
 
 impl<'a, W: Write> LuaCodeGenerator<'a, W> {
@@ -37,6 +40,7 @@ impl<'a, W: Write> LuaCodeGenerator<'a, W> {
             writer,
             indent_level: 0,
             line_ending: LineEnding::default(),
+            sakura_warnings: Vec::new(),
         }
     }
 
@@ -46,9 +50,15 @@ impl<'a, W: Write> LuaCodeGenerator<'a, W> {
             writer,
             indent_level: 0,
             line_ending,
+            sakura_warnings: Vec::new(),
         }
     }
 
+    /// Take sakura script validation warnings collected so far.
+    pub fn take_sakura_warnings(&mut self) -> Vec<SakuraScriptWarning> {
+        std::mem::take(&mut self.sakura_warnings)
+    }
+
     /// Write indentation at current level.
     fn write_indent(&mut self) -> Result<(), TranspileError> {
         let indent = "    ".repeat(self.indent_level);
@@ -100,6 +110,7 @@ impl<'a, W: Write> LuaCodeGenerator<'a, W> {
     /// ```lua
     /// do
     ///     local ACTOR = PASTA.create_actor("アクター名")
+    ///     ACTOR:set_attr("spot", 2)
     ///     ACTOR.通常 = { [=[\s[0]]=], [=[\s[100]]=] }
     ///     
     ///     function ACTOR.時刻(act)
@@ -118,6 +129,20 @@ impl<'a, W: Write> LuaCodeGenerator<'a, W> {
             actor.name
         ))?;
 
+        // Actor attributes (＆spot：2 etc.) become actor settings unless pasta.toml sets them
+        // (unknown keys are ignored by ACTOR_IMPL.set_attr)
+        for attr in &actor.attrs {
+            let value = match &attr.value {
+                AttrValue::Integer(v) => v.to_string(),
+                AttrValue::Float(v) => format!("{:?}", v),
+                AttrValue::String(v) | AttrValue::AttrString(v) => {
+                    StringLiteralizer::literalize_with_span(v, &attr.span)?
+                }
+            };
+            let key = StringLiteralizer::literalize_with_span(&attr.key, &attr.span)?;
+            self.writeln(&format!("ACTOR:set_attr({}, {})", key, value))?;
+        }
+
         // Generate word definitions (Requirement 2, actor-word-dictionary Task 3.1)
         // ACTOR:create_word() registers both in word.lua (L2 prefix search) and as actor attribute (L1 exact match)
         for word_def in &actor.words {
@@ -523,25 +548,18 @@ impl<'a, W: Write> LuaCodeGenerator<'a, W> {
             Action::FnCall {
                 name, args, scope, ..
             } => {
-                // SCENE:関数名(act, 引数...)
-                let args_str = self.generate_args_string(args)?;
-                let prefix = match scope {
-                    pasta_core::parser::FnScope::Local => "SCENE:",
-                    pasta_core::parser::FnScope::Global => "SCENE:", // Same for now
-                };
-                self.writeln(&format!(
-                    "act.{}:talk(tostring({}{}(act{})))",
-                    actor,
-                    prefix,
-                    name,
-                    if args_str.is_empty() {
-                        String::new()
-                    } else {
-                        format!(", {}", args_str)
-                    }
-                ))?;
+                // act.アクター:talk(tostring(act:call_fn(SCENE, "関数名", 引数...)))
+                let call = self.generate_fn_call(name, args, scope)?;
+                self.writeln(&format!("act.{}:talk(tostring({}))", actor, call))?;
             }
-            Action::SakuraScript { script, .. } => {
+            Action::SakuraScript { script, span } => {
+                // タグ単位の検証（トグル対応や \e はスクリプト全体でしか判定できない）
+                for diagnostic in validator::validate(script, ValidationMode::Fragment) {
+                    self.sakura_warnings.push(SakuraScriptWarning {
+                        span: *span,
+                        diagnostic,
+                    });
+                }
                 // SakuraScript is output as act:sakura_script()
                 let literal = StringLiteralizer::literalize(script)?;
                 self.writeln(&format!("act:sakura_script({})", literal))?;
@@ -585,22 +603,8 @@ impl<'a, W: Write> LuaCodeGenerator<'a, W> {
                 write!(self.writer, "{}", var_path)?;
             }
             Expr::FnCall { name, args, scope } => {
-                let args_str = self.generate_args_string(args)?;
-                let prefix = match scope {
-                    pasta_core::parser::FnScope::Local => "SCENE.",
-                    pasta_core::parser::FnScope::Global => "SCENE.",
-                };
-                write!(
-                    self.writer,
-                    "{}{}(act{})",
-                    prefix,
-                    name,
-                    if args_str.is_empty() {
-                        String::new()
-                    } else {
-                        format!(", {}", args_str)
-                    }
-                )?;
+                let call = self.generate_fn_call(name, args, scope)?;
+                write!(self.writer, "{}", call)?;
             }
             Expr::Paren(inner) => {
                 write!(self.writer, "(")?;
@@ -653,22 +657,8 @@ impl<'a, W: Write> LuaCodeGenerator<'a, W> {
                 write!(buf, "{}", var_path)?;
             }
             Expr::FnCall { name, args, scope } => {
-                let args_str = self.generate_args_string(args)?;
-                let prefix = match scope {
-                    pasta_core::parser::FnScope::Local => "SCENE.",
-                    pasta_core::parser::FnScope::Global => "SCENE.",
-                };
-                write!(
-                    buf,
-                    "{}{}(act{})",
-                    prefix,
-                    name,
-                    if args_str.is_empty() {
-                        String::new()
-                    } else {
-                        format!(", {}", args_str)
-                    }
-                )?;
+                let call = self.generate_fn_call(name, args, scope)?;
+                write!(buf, "{}", call)?;
             }
             Expr::Paren(inner) => {
                 write!(buf, "(")?;
@@ -692,6 +682,39 @@ impl<'a, W: Write> LuaCodeGenerator<'a, W> {
         Ok(())
     }
 
+    /// Generate a function call expression.
+    ///
+    /// `＠関数名（引数）` resolves the scene table first, then `pasta.global`
+    /// (SAORI functions etc.). `＠＊関数名（引数）` skips the scene table.
+    ///
+    /// ```lua
+    /// act:call_fn(SCENE, "関数名", 引数...)
+    /// act:call_fn(nil, "関数名", 引数...)
+    /// ```
+    fn generate_fn_call(
+        &self,
+        name: &str,
+        args: &Args,
+        scope: &pasta_core::parser::FnScope,
+    ) -> Result<String, TranspileError> {
+        let args_str = self.generate_args_string(args)?;
+        let scene = match scope {
+            pasta_core::parser::FnScope::Local => "SCENE",
+            pasta_core::parser::FnScope::Global => "nil",
+        };
+        let name_literal = StringLiteralizer::literalize(name)?;
+        Ok(format!(
+            "act:call_fn({}, {}{})",
+            scene,
+            name_literal,
+            if args_str.is_empty() {
+                String::new()
+            } else {
+                format!(", {}", args_str)
+            }
+        ))
+    }
+
     /// Generate arguments as a string.
     fn generate_args_string(&self, args: &Args) -> Result<String, TranspileError> {
         let mut parts = Vec::new();
@@ -999,4 +1022,40 @@ mod tests {
             result
         );
     }
+
+    #[test]
+    fn test_generate_actor_attrs() {
+        use pasta_core::parser::Attr;
+
+        let mut output = Vec::new();
+        let mut codegen = LuaCodeGenerator::new(&mut output);
+
+        let mut actor = ActorScope::new("まゆら".to_string());
+        for (key, value) in [
+            ("spot", AttrValue::AttrString("char2".to_string())),
+            ("balloon", AttrValue::Integer(4)),
+            ("spot_newlines", AttrValue::Float(0.5)),
+        ] {
+            actor.attrs.push(Attr {
+                key: key.to_string(),
+                value,
+                span: Span::default(),
+            });
+        }
+        codegen.generate_actor(&actor).unwrap();
+
+        let result = String::from_utf8(output).unwrap();
+        assert!(
+            result.contains(r#"ACTOR:set_attr("spot", "char2")"#),
+            "{result}"
+        );
+        assert!(
+            result.contains(r#"ACTOR:set_attr("balloon", 4)"#),
+            "{result}"
+        );
+        assert!(
+            result.contains(r#"ACTOR:set_attr("spot_newlines", 0.5)"#),
+            "{result}"
+        );
+    }
 }
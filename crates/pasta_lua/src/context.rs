@@ -2,10 +2,20 @@
 //!
 //! This module provides context management for the transpilation process.
 
-use pasta_core::parser::{Attr, AttrValue, GlobalSceneScope, KeyWords, LocalSceneScope};
+use crate::sakura_script::validator::Diagnostic;
+use pasta_core::parser::{Attr, AttrValue, GlobalSceneScope, KeyWords, LocalSceneScope, Span};
 use pasta_core::registry::{SceneRegistry, WordDefRegistry};
 use std::collections::HashMap;
 
+/// Sakura script problem found at transpile time (non-fatal).
+#[derive(Debug, Clone)]
+pub struct SakuraScriptWarning {
+    /// Span of the sakura script action in the Pasta source
+    pub span: Span,
+    /// Validation diagnostic (range is relative to the action's script)
+    pub diagnostic: Diagnostic,
+}
+
 /// Transpile context for sharing state during transpilation.
 #[derive(Default)]
 pub struct TranspileContext {
@@ -17,6 +27,8 @@ pub struct TranspileContext {
     pub current_module: Option<String>,
     /// File-level attributes accumulated from FileAttr items (MAJOR-1)
     file_attrs: HashMap<String, AttrValue>,
+    /// Sakura script validation warnings
+    pub sakura_warnings: Vec<SakuraScriptWarning>,
 }
 
 impl TranspileContext {
@@ -131,13 +143,14 @@ impl TranspileContext {
 
         // Merge word registry
         self.word_registry.merge_from(other.word_registry);
+
+        self.sakura_warnings.extend(other.sakura_warnings);
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use pasta_core::parser::Span;
 
     fn create_test_scene(name: &str) -> GlobalSceneScope {
         GlobalSceneScope {
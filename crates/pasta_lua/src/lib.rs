@@ -39,6 +39,7 @@ pub mod logging;
 pub mod normalize;
 pub mod runtime;
 pub mod sakura_script;
+pub mod script_event;
 pub mod search;
 pub mod string_literalizer;
 pub mod transpiler;
@@ -46,17 +47,19 @@ pub mod transpiler;
 // Re-export main types
 pub use code_generator::LuaCodeGenerator;
 pub use config::{LineEnding, TranspilerConfig};
-pub use context::TranspileContext;
+pub use context::{SakuraScriptWarning, TranspileContext};
 pub use encoding::{Encoder, Encoding};
 pub use error::{ConfigError, TranspileError};
 pub use loader::{
-    CacheManager, LoaderConfig, LoaderContext, LoaderError, LoggingConfig, LuaConfig, PastaConfig,
-    PastaLoader, TalkConfig, TranspileFailure, TranspileResult, default_libs,
+    CacheManager, LoaderConfig, LoaderContext, LoaderError, LoggingConfig, LuaConfig, PacingConfig,
+    PastaConfig, PastaLoader, SstpConfig, TalkConfig, TranspileFailure, TranspileResult,
+    WaitRuleConfig, WaitRuleMode, default_libs,
 };
 pub use logging::{
     GlobalLoggerRegistry, LoadDirGuard, PastaLogger, get_current_load_dir, set_current_load_dir,
 };
-pub use runtime::{PastaLuaRuntime, RuntimeConfig};
+pub use runtime::{PastaLuaRuntime, RuntimeConfig, SceneRun};
+pub use script_event::{SCRIPT_EVENT_VERSION, ScriptEvent, ScriptEventError, ScriptEventStream};
 pub use search::{SearchContext, SearchError};
 pub use string_literalizer::StringLiteralizer;
 
@@ -4,6 +4,7 @@
 //! for the pasta loader startup sequence.
 
 use serde::Deserialize;
+use std::collections::BTreeMap;
 use std::fs;
 use std::path::Path;
 
@@ -121,6 +122,17 @@ impl PastaConfig {
             .and_then(|v| v.clone().try_into().ok())
     }
 
+    /// Get SSTP client configuration from [sstp] section.
+    ///
+    /// # Returns
+    /// * `Some(SstpConfig)` - If [sstp] section exists and is valid
+    /// * `None` - If [sstp] section is missing or invalid
+    pub fn sstp(&self) -> Option<SstpConfig> {
+        self.custom_fields
+            .get("sstp")
+            .and_then(|v| v.clone().try_into().ok())
+    }
+
     /// Create from TOML string (for testing).
     #[cfg(test)]
     fn from_str(s: &str) -> Result<Self, toml::de::Error> {
@@ -254,6 +266,17 @@ pub struct PersistenceConfig {
     #[serde(default)]
     pub obfuscate: bool,
 
+    /// Enable authenticated encryption (ChaCha20-Poly1305) for saved data.
+    /// Requires `secret`. Takes precedence over `obfuscate`.
+    /// Default: false
+    #[serde(default)]
+    pub encrypt: bool,
+
+    /// Ghost-specific secret used to derive the encryption key.
+    /// Default: None
+    #[serde(default)]
+    pub secret: Option<String>,
+
     /// Save file path relative to load_dir.
     /// Default: "profile/pasta/save/save.json" (or .dat if obfuscate=true)
     #[serde(default = "default_persistence_file_path")]
@@ -263,35 +286,134 @@ pub struct PersistenceConfig {
     /// Default: false
     #[serde(default)]
     pub debug_mode: bool,
+
+    /// Autosave interval for `pasta.save` in seconds (counted in OnSecondChange events).
+    /// Unchanged data is not rewritten. 0 disables autosave.
+    /// Default: 0
+    #[serde(default)]
+    pub autosave_interval: u64,
+
+    /// Additional named stores ([persistence.stores.<name>] sections).
+    /// Unset fields inherit from the [persistence] section.
+    /// Default: empty
+    #[serde(default)]
+    pub stores: BTreeMap<String, PersistenceStoreConfig>,
 }
 
 impl Default for PersistenceConfig {
     fn default() -> Self {
         Self {
             obfuscate: false,
+            encrypt: false,
+            secret: None,
             file_path: default_persistence_file_path(),
             debug_mode: false,
+            autosave_interval: 0,
+            stores: BTreeMap::new(),
         }
     }
 }
 
+/// Named store configuration from [persistence.stores.<name>] section.
+///
+/// # Examples
+///
+/// ```toml
+/// [persistence.stores.settings]
+/// file_path = "profile/pasta/save/settings.json"
+///
+/// [persistence.stores.progress]
+/// obfuscate = true
+/// ```
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct PersistenceStoreConfig {
+    /// Save file path relative to load_dir.
+    /// Default: "<directory of [persistence].file_path>/<name>.json"
+    #[serde(default)]
+    pub file_path: Option<String>,
+
+    /// Enable obfuscation (gzip compression). Default: inherited
+    #[serde(default)]
+    pub obfuscate: Option<bool>,
+
+    /// Enable authenticated encryption. Default: inherited
+    #[serde(default)]
+    pub encrypt: Option<bool>,
+
+    /// Secret used to derive the encryption key. Default: inherited
+    #[serde(default)]
+    pub secret: Option<String>,
+}
+
 fn default_persistence_file_path() -> String {
     "profile/pasta/save/save.json".to_string()
 }
 
 impl PersistenceConfig {
-    /// Get the effective file path based on obfuscate setting.
+    /// Get the effective file path based on obfuscate/encrypt settings.
     ///
-    /// If obfuscate is true and file_path ends with .json, changes extension to .dat.
+    /// If obfuscate or encrypt is true and file_path ends with .json, changes extension to .dat.
     pub fn effective_file_path(&self) -> String {
-        if self.obfuscate && self.file_path.ends_with(".json") {
+        let binary = self.obfuscate || self.encrypt;
+        if binary && self.file_path.ends_with(".json") {
             self.file_path.replace(".json", ".dat")
-        } else if self.obfuscate && !self.file_path.ends_with(".dat") {
+        } else if binary && !self.file_path.ends_with(".dat") {
             format!("{}.dat", self.file_path)
         } else {
             self.file_path.clone()
         }
     }
+
+    /// Check whether a store name is usable as a file name component.
+    ///
+    /// Valid names are non-empty and consist of ASCII alphanumerics, `_` or `-`.
+    pub fn is_valid_store_name(name: &str) -> bool {
+        !name.is_empty()
+            && name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+    }
+
+    /// Resolve the configuration of a named store.
+    ///
+    /// Stores not listed in [persistence.stores] are resolved with defaults,
+    /// so scripts can create ad-hoc stores (e.g. per-user data).
+    /// Unset fields inherit from this configuration.
+    ///
+    /// # Returns
+    /// * `Some(PersistenceConfig)` - Resolved configuration for the store
+    /// * `None` - Invalid store name, or the store would use the same file as
+    ///   the default store (e.g. `store("save")` with the default `file_path`)
+    pub fn store(&self, name: &str) -> Option<PersistenceConfig> {
+        if !Self::is_valid_store_name(name) {
+            return None;
+        }
+        let store = self.stores.get(name).cloned().unwrap_or_default();
+        let file_path =
+            store
+                .file_path
+                .unwrap_or_else(|| match Path::new(&self.file_path).parent() {
+                    Some(dir) if !dir.as_os_str().is_empty() => {
+                        format!("{}/{}.json", dir.to_string_lossy(), name)
+                    }
+                    _ => format!("{}.json", name),
+                });
+        let resolved = PersistenceConfig {
+            obfuscate: store.obfuscate.unwrap_or(self.obfuscate),
+            encrypt: store.encrypt.unwrap_or(self.encrypt),
+            secret: store.secret.or_else(|| self.secret.clone()),
+            file_path,
+            debug_mode: self.debug_mode,
+            autosave_interval: 0,
+            stores: BTreeMap::new(),
+        };
+        if resolved.file_path == self.file_path
+            || resolved.effective_file_path() == self.effective_file_path()
+        {
+            return None;
+        }
+        Some(resolved)
+    }
 }
 
 /// Lua library configuration from [lua] section in pasta.toml.
@@ -368,6 +490,8 @@ impl Default for LuaConfig {
 /// script_wait_comma = 500
 /// script_wait_strong = 500
 /// script_wait_leader = 200
+/// # Per-character display time already applied by the baseware
+/// script_wait_base = 50
 ///
 /// # Character sets
 /// chars_period = "｡。．."
@@ -376,6 +500,27 @@ impl Default for LuaConfig {
 /// chars_leader = "･・‥…"
 /// chars_line_start_prohibited = "゛゜ヽヾゝゞ々ー）］｝」』):;]}｣､･ｰﾞﾟ"
 /// chars_line_end_prohibited = "（［｛「『([{｢"
+///
+/// # Line wrapping (half-width columns, 0 = disabled)
+/// wrap_width = 24
+///
+/// # Wait rules (evaluated in order before the built-in character class rules)
+/// [[talk.wait_rules]]
+/// pattern = "……」"
+/// wait = 1200
+/// mode = "once"
+///
+/// [[talk.wait_rules]]
+/// pattern = "[A-Za-z]"
+/// after = "[A-Za-z]"
+/// wait = 0
+///
+/// # Pacing variance for per-character waits (percent, 0 = disabled)
+/// [talk.pacing]
+/// variance = 20
+/// burst = 30
+/// clause_slowdown = 50
+/// merge_waits = true
 /// ```
 #[derive(Debug, Clone, Deserialize)]
 #[serde(default)]
@@ -391,6 +536,9 @@ pub struct TalkConfig {
     pub script_wait_strong: i64,
     /// Wait for leader characters (default: 200ms)
     pub script_wait_leader: i64,
+    /// Display time per character already applied by the baseware,
+    /// subtracted from every wait (default: 50ms)
+    pub script_wait_base: i64,
 
     // Character sets
     /// Period characters (default: "｡。．.")
@@ -405,6 +553,18 @@ pub struct TalkConfig {
     pub chars_line_start_prohibited: String,
     /// Line end prohibited characters (行末禁則)
     pub chars_line_end_prohibited: String,
+
+    // Line wrapping
+    /// Balloon line width in half-width columns (default: 0 = no wrapping)
+    pub wrap_width: i64,
+
+    // Wait rules
+    /// Ordered wait rules taking priority over the character class waits (default: empty)
+    pub wait_rules: Vec<WaitRuleConfig>,
+
+    // Pacing
+    /// Variance of per-character waits (default: disabled)
+    pub pacing: PacingConfig,
 }
 
 impl Default for TalkConfig {
@@ -415,12 +575,113 @@ impl Default for TalkConfig {
             script_wait_comma: 500,
             script_wait_strong: 500,
             script_wait_leader: 200,
+            script_wait_base: 50,
             chars_period: "｡。．.".into(),
             chars_comma: "、，,".into(),
             chars_strong: "？！!?".into(),
             chars_leader: "･・‥…".into(),
             chars_line_start_prohibited: "゛゜ヽヾゝゞ々ー）］｝」』):;]}｣､･ｰﾞﾟ".into(),
             chars_line_end_prohibited: "（［｛「『([{｢".into(),
+            wrap_width: 0,
+            wait_rules: Vec::new(),
+            pacing: PacingConfig::default(),
+        }
+    }
+}
+
+/// Pacing configuration from `[talk.pacing]` (or an actor's `pacing` table).
+///
+/// Varies the per-character waits inserted by `talk_to_script`. All
+/// percentages apply to the wait actually inserted (after `script_wait_base`
+/// is subtracted); waits of `once` rules and punctuation runs are not varied.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct PacingConfig {
+    /// Random variance in percent (±), 0 = disabled
+    pub variance: i64,
+    /// Shorten waits inside words (before another general character) by this percent
+    pub burst: i64,
+    /// Lengthen the wait of the character before a period or comma by this percent
+    pub clause_slowdown: i64,
+    /// Merge adjacent `\_w[ms]` tags into one
+    pub merge_waits: bool,
+    /// Fixed random seed (default: system entropy)
+    pub seed: Option<u64>,
+}
+
+/// Wait rule entry from `[[talk.wait_rules]]` or an actor's `wait_rules`.
+///
+/// Text matching `pattern` gets `wait` milliseconds (including the baseware's
+/// `script_wait_base`), either after every matched character or once after the
+/// whole match. `before` / `after` / `emotion` restrict where the rule applies.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Deserialize)]
+pub struct WaitRuleConfig {
+    /// Regex matched against talk text (sakura script tags excluded)
+    pub pattern: String,
+    /// Regex that must match the text immediately before the match
+    #[serde(default)]
+    pub before: Option<String>,
+    /// Regex that must match the text immediately after the match
+    #[serde(default)]
+    pub after: Option<String>,
+    /// Actor emotion (`actor.emotion`) the rule is limited to
+    #[serde(default)]
+    pub emotion: Option<String>,
+    /// Wait in milliseconds
+    pub wait: i64,
+    /// Where the wait is inserted (default: per_char)
+    #[serde(default)]
+    pub mode: WaitRuleMode,
+}
+
+/// Where a wait rule inserts its wait.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WaitRuleMode {
+    /// After every matched character
+    #[default]
+    PerChar,
+    /// Once after the whole match
+    Once,
+}
+
+/// SSTP client configuration from [sstp] section in pasta.toml.
+///
+/// Configures the endpoint used by the `@pasta_sstp` module.
+///
+/// # Examples
+///
+/// ```toml
+/// [sstp]
+/// host = "127.0.0.1"
+/// port = 9801
+/// sender = "MyGhost"
+/// charset = "UTF-8"
+/// timeout_ms = 3000
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct SstpConfig {
+    /// Baseware host (default: "127.0.0.1")
+    pub host: String,
+    /// Baseware SSTP port (default: 9801)
+    pub port: u16,
+    /// `Sender` header value (default: "Pasta")
+    pub sender: String,
+    /// Request charset (default: "UTF-8")
+    pub charset: String,
+    /// Connect / read / write timeout in milliseconds (default: 3000)
+    pub timeout_ms: u64,
+}
+
+impl Default for SstpConfig {
+    fn default() -> Self {
+        Self {
+            host: "127.0.0.1".into(),
+            port: 9801,
+            sender: "Pasta".into(),
+            charset: "UTF-8".into(),
+            timeout_ms: 3000,
         }
     }
 }
@@ -660,6 +921,9 @@ debug_mode = true
         assert!(!config.obfuscate);
         assert_eq!(config.file_path, "profile/pasta/save/save.json");
         assert!(!config.debug_mode);
+        assert!(!config.encrypt);
+        assert!(config.secret.is_none());
+        assert_eq!(config.autosave_interval, 0);
     }
 
     #[test]
@@ -669,6 +933,7 @@ debug_mode = true
 obfuscate = true
 file_path = "profile/custom/save.dat"
 debug_mode = true
+autosave_interval = 60
 "#;
         let config = PastaConfig::from_str(toml_str).unwrap();
         let persistence = config
@@ -677,6 +942,94 @@ debug_mode = true
         assert!(persistence.obfuscate);
         assert_eq!(persistence.file_path, "profile/custom/save.dat");
         assert!(persistence.debug_mode);
+        assert_eq!(persistence.autosave_interval, 60);
+    }
+
+    #[test]
+    fn test_persistence_config_encrypt_from_toml() {
+        let toml_str = r#"
+[persistence]
+encrypt = true
+secret = "ghost-secret"
+"#;
+        let config = PastaConfig::from_str(toml_str).unwrap();
+        let persistence = config
+            .persistence()
+            .expect("persistence section should exist");
+        assert!(persistence.encrypt);
+        assert_eq!(persistence.secret.as_deref(), Some("ghost-secret"));
+        assert_eq!(
+            persistence.effective_file_path(),
+            "profile/pasta/save/save.dat"
+        );
+    }
+
+    #[test]
+    fn test_persistence_named_stores_from_toml() {
+        let toml_str = r#"
+[persistence]
+obfuscate = true
+secret = "ghost-secret"
+
+[persistence.stores.settings]
+file_path = "profile/pasta/save/settings.json"
+obfuscate = false
+
+[persistence.stores.progress]
+encrypt = true
+"#;
+        let config = PastaConfig::from_str(toml_str).unwrap();
+        let persistence = config
+            .persistence()
+            .expect("persistence section should exist");
+        assert_eq!(persistence.stores.len(), 2);
+
+        let settings = persistence.store("settings").unwrap();
+        assert!(!settings.obfuscate);
+        assert_eq!(
+            settings.effective_file_path(),
+            "profile/pasta/save/settings.json"
+        );
+
+        let progress = persistence.store("progress").unwrap();
+        assert!(progress.obfuscate); // inherited
+        assert!(progress.encrypt);
+        assert_eq!(progress.secret.as_deref(), Some("ghost-secret")); // inherited
+        assert_eq!(
+            progress.effective_file_path(),
+            "profile/pasta/save/progress.dat"
+        );
+    }
+
+    #[test]
+    fn test_persistence_store_unlisted_and_invalid_names() {
+        let config = PersistenceConfig::default();
+
+        // Unlisted store: placed next to the default save file
+        let user = config.store("user_alice").unwrap();
+        assert_eq!(
+            user.effective_file_path(),
+            "profile/pasta/save/user_alice.json"
+        );
+
+        // Names that could escape the save directory are rejected
+        assert!(config.store("").is_none());
+        assert!(config.store("../evil").is_none());
+        assert!(config.store("a/b").is_none());
+
+        // The default store's file is not shared with a named store
+        assert!(config.store("save").is_none());
+        let config = PersistenceConfig {
+            stores: BTreeMap::from([(
+                "main".to_string(),
+                PersistenceStoreConfig {
+                    file_path: Some(config.file_path.clone()),
+                    ..Default::default()
+                },
+            )]),
+            ..config
+        };
+        assert!(config.store("main").is_none());
     }
 
     #[test]
@@ -715,6 +1068,7 @@ debug_mode = true
             obfuscate: true,
             file_path: "profile/pasta/save/save.json".to_string(),
             debug_mode: false,
+            ..Default::default()
         };
         assert_eq!(config.effective_file_path(), "profile/pasta/save/save.dat");
 
@@ -723,6 +1077,7 @@ debug_mode = true
             obfuscate: true,
             file_path: "profile/pasta/save/save.dat".to_string(),
             debug_mode: false,
+            ..Default::default()
         };
         assert_eq!(config.effective_file_path(), "profile/pasta/save/save.dat");
     }
@@ -847,4 +1202,23 @@ debug_mode = true
             "Default LoaderConfig should include user_scripts"
         );
     }
+
+    #[test]
+    fn test_sstp_config_from_toml() {
+        let config = PastaConfig::from_str(
+            r#"
+[sstp]
+port = 9821
+sender = "テスト"
+"#,
+        )
+        .unwrap();
+        let sstp = config.sstp().unwrap();
+        assert_eq!(sstp.host, "127.0.0.1");
+        assert_eq!(sstp.port, 9821);
+        assert_eq!(sstp.sender, "テスト");
+        assert_eq!(sstp.charset, "UTF-8");
+        assert_eq!(sstp.timeout_ms, 3000);
+        assert!(PastaConfig::default().sstp().is_none());
+    }
 }
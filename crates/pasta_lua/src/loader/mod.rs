@@ -30,7 +30,8 @@ mod error;
 
 pub use cache::CacheManager;
 pub use config::{
-    LoaderConfig, LoggingConfig, LuaConfig, PastaConfig, PersistenceConfig, TalkConfig, default_libs,
+    LoaderConfig, LoggingConfig, LuaConfig, PacingConfig, PastaConfig, PersistenceConfig,
+    PersistenceStoreConfig, SstpConfig, TalkConfig, WaitRuleConfig, WaitRuleMode, default_libs,
 };
 pub use context::LoaderContext;
 pub use error::{LoaderError, TranspileFailure};
@@ -287,6 +288,17 @@ impl PastaLoader {
                 }
             };
 
+            for warning in &file_context.sakura_warnings {
+                warn!(
+                    file = %file_path.display(),
+                    line = warning.span.start_line,
+                    column = warning.span.start_col,
+                    kind = warning.diagnostic.kind.as_str(),
+                    "{}",
+                    warning.diagnostic.message
+                );
+            }
+
             // Merge registries
             combined_context.merge_from(file_context);
 
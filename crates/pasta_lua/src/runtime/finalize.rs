@@ -51,7 +51,7 @@ pub fn collect_scenes(lua: &Lua) -> LuaResult<Vec<(String, String)>> {
 
     let mut scenes = Vec::new();
 
-    // Iterate over registry: {global_name: {__global_name__, local_name: func}}
+    // Iterate over registry: {global_name: {__global_name__, __base_name__, local_name: func}}
     for pair in registry.pairs::<String, Table>() {
         let (global_name, scene_table) = pair?;
 
@@ -59,8 +59,8 @@ pub fn collect_scenes(lua: &Lua) -> LuaResult<Vec<(String, String)>> {
         for entry in scene_table.pairs::<String, Value>() {
             let (local_name, _value) = entry?;
 
-            // Skip __global_name__ metadata field
-            if local_name == "__global_name__" {
+            // Skip __global_name__ / __base_name__ metadata fields
+            if local_name == "__global_name__" || local_name == "__base_name__" {
                 continue;
             }
 
@@ -23,13 +23,22 @@ pub mod finalize;
 pub mod log;
 /// Persistence module - Persistent data storage for Lua scripts.
 pub mod persistence;
+/// SAORI module - SAORI/1.0 client for external helper executables.
+pub mod saori;
+/// Scene runner - embedding API for running scenes without SHIORI.
+pub mod scene_run;
+/// Script event module - JSON event stream output.
+pub mod script_event;
+/// SSTP module - SSTP client for sending scripts to the baseware.
+pub mod sstp;
 
 use crate::context::TranspileContext;
 use crate::error::ConfigError;
 use crate::loader::{LoaderContext, LuaConfig, PastaConfig, TranspileResult, default_libs};
 use crate::logging::PastaLogger;
 pub(crate) use finalize::register_finalize_scene;
-use mlua::{Function, Lua, LuaSerdeExt, Result as LuaResult, StdLib, Table, Value};
+use mlua::{Function, IntoLuaMulti, Lua, Result as LuaResult, StdLib, Table, Value};
+pub use scene_run::SceneRun;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
@@ -379,6 +388,16 @@ impl PastaLuaRuntime {
         // Register @pasta_log module (always available, independent of RuntimeConfig.libs)
         Self::register_log_module(&lua)?;
 
+        // Register @pasta_saori module (helper paths relative to the current directory
+        // until a loader provides the ghost's base directory)
+        Self::register_saori_module(&lua, Path::new("."))?;
+
+        // Register @pasta_sstp module (default endpoint until pasta.toml is available)
+        Self::register_sstp_module(&lua, &None)?;
+
+        // Register @pasta_script_event module (always available)
+        Self::register_script_event_module(&lua)?;
+
         Ok(Self {
             lua,
             logger: None,
@@ -455,6 +474,31 @@ impl PastaLuaRuntime {
         Ok(())
     }
 
+    /// Run a scene without a SHIORI request.
+    ///
+    /// The scene is resolved with the same prefix search as `SCENE.search`, and
+    /// its output is returned as [`ScriptEvent`](crate::ScriptEvent)s per yield.
+    ///
+    /// # Arguments
+    /// * `name` - Scene name to search
+    /// * `args` - Arguments passed to the scene after `act`
+    ///
+    /// # Returns
+    /// * `Ok(Some(SceneRun))` - Scene found; call `next_events()` to run it
+    /// * `Ok(None)` - No scene matches `name`
+    /// * `Err(e)` - Lua error while searching or creating the act
+    pub fn run_scene(&self, name: &str, args: impl IntoLuaMulti) -> LuaResult<Option<SceneRun>> {
+        let args = args.into_lua_multi(&self.lua)?;
+        SceneRun::start(&self.lua, name, args)
+    }
+
+    /// Get the persistent variable table (`pasta.save`).
+    ///
+    /// Changes are written by the persistence module (on drop, autosave or `flush()`).
+    pub fn save_table(&self) -> LuaResult<Table> {
+        self.lua.unpack(lua_require(&self.lua, "pasta.save")?)
+    }
+
     /// Create a runtime from LoaderContext with transpiled code.
     ///
     /// This is the factory method used by PastaLoader to create a runtime
@@ -532,7 +576,7 @@ impl PastaLuaRuntime {
     ///
     /// # Initialization Sequence (lua-module-path-resolution spec)
     /// 1. Setup package.path for module resolution
-    /// 2. Register Rust modules (@pasta_config, @enc, @pasta_persistence, @pasta_sakura_script)
+    /// 2. Register Rust modules (@pasta_config, @enc, @pasta_persistence, @pasta_sakura_script, @pasta_saori, @pasta_sstp)
     /// 3. Register finalize_scene Rust binding
     /// 4. require("main") - User initialization (errors logged as warnings, continues)
     /// 5. require("pasta.shiori.entry") - SHIORI handlers (errors logged as warnings, continues)
@@ -585,6 +629,15 @@ impl PastaLuaRuntime {
         // Register @pasta_sakura_script module for wait insertion
         Self::register_sakura_script_module(&runtime.lua, &runtime.config)?;
 
+        // Register @pasta_saori module with helper paths relative to the ghost directory
+        Self::register_saori_module(&runtime.lua, &loader_context.base_dir)?;
+
+        // Register @pasta_sstp module with the [sstp] endpoint
+        Self::register_sstp_module(&runtime.lua, &runtime.config)?;
+
+        // Register @pasta_script_event module for JSON event stream output
+        Self::register_script_event_module(&runtime.lua)?;
+
         // Register finalize_scene Rust binding to overwrite Lua stub (Requirement 4.3)
         // This must be done before loading scene_dic.lua which calls finalize_scene()
         register_finalize_scene(&runtime.lua)?;
@@ -751,6 +804,50 @@ impl PastaLuaRuntime {
         Ok(())
     }
 
+    /// Register @pasta_saori module for SAORI helper calls.
+    ///
+    /// Relative helper paths are resolved against `base_dir`.
+    fn register_saori_module(lua: &Lua, base_dir: &Path) -> LuaResult<()> {
+        let saori_table = saori::register(lua, base_dir)?;
+
+        let package: Table = lua.globals().get("package")?;
+        let loaded: Table = package.get("loaded")?;
+        loaded.set("@pasta_saori", saori_table)?;
+
+        tracing::debug!("Registered @pasta_saori module");
+        Ok(())
+    }
+
+    /// Register @pasta_sstp module for SSTP requests to the baseware.
+    ///
+    /// The default endpoint comes from the [sstp] section of pasta.toml.
+    fn register_sstp_module(lua: &Lua, config: &Option<PastaConfig>) -> LuaResult<()> {
+        let sstp_config = config.as_ref().and_then(|c| c.sstp());
+
+        let sstp_table = sstp::register(lua, sstp_config.as_ref())?;
+
+        let package: Table = lua.globals().get("package")?;
+        let loaded: Table = package.get("loaded")?;
+        loaded.set("@pasta_sstp", sstp_table)?;
+
+        tracing::debug!("Registered @pasta_sstp module");
+        Ok(())
+    }
+
+    /// Register @pasta_script_event module for JSON event stream output.
+    ///
+    /// Validates event tables against ScriptEvent and encodes them as JSON.
+    fn register_script_event_module(lua: &Lua) -> LuaResult<()> {
+        let script_event_table = script_event::register(lua)?;
+
+        let package: Table = lua.globals().get("package")?;
+        let loaded: Table = package.get("loaded")?;
+        loaded.set("@pasta_script_event", script_event_table)?;
+
+        tracing::debug!("Registered @pasta_script_event module");
+        Ok(())
+    }
+
     /// Convert toml::Value to mlua::Value.
     ///
     /// Recursively converts TOML structures to Lua tables.
@@ -781,39 +878,25 @@ impl PastaLuaRuntime {
     /// Save persistence data from ctx.save.
     ///
     /// Called automatically on Drop to save any modified persistent data.
+    /// Shares dirty tracking with the `@pasta_persistence` module, so
+    /// unchanged data is not rewritten.
     fn save_persistence_data(&self) -> Result<(), persistence::PersistenceError> {
-        // Get persistence config
-        let persistence_config = self
-            .config
-            .as_ref()
-            .and_then(|c| c.persistence())
-            .unwrap_or_default();
-
-        let base_dir = self.base_dir.as_deref().unwrap_or(Path::new("."));
-        let file_path = base_dir.join(persistence_config.effective_file_path());
-
-        // Try to get save from Lua
-        let save_table: Table = match self.lua.load(r#"require("pasta.save")"#).eval() {
-            Ok(t) => t,
-            Err(e) => {
-                // save might not exist if runtime wasn't fully initialized
-                tracing::debug!(error = %e, "Could not access pasta.save, skipping persistence save");
-                return Ok(());
+        // Use the module's state if registered, otherwise build from config
+        let state = match self.lua.app_data_ref::<persistence::PersistenceState>() {
+            Some(state) => state.clone(),
+            None => {
+                let persistence_config = self
+                    .config
+                    .as_ref()
+                    .and_then(|c| c.persistence())
+                    .unwrap_or_default();
+                let base_dir = self.base_dir.as_deref().unwrap_or(Path::new("."));
+                persistence::PersistenceState::from_config(&persistence_config, base_dir)?
             }
         };
 
-        // Convert Lua table to serde_json::Value
-        let lua_value = Value::Table(save_table);
-        let json_value: serde_json::Value = self
-            .lua
-            .from_value(lua_value)
-            .map_err(|e| persistence::PersistenceError::LuaConversionError(e.to_string()))?;
-
-        // Save to file
-        persistence::save_to_file(&json_value, &file_path, persistence_config.obfuscate)?;
-
-        if persistence_config.debug_mode {
-            tracing::debug!(path = %file_path.display(), "Saved persistence data on drop");
+        if persistence::flush_session(&self.lua, &state)? {
+            tracing::debug!("Saved persistence data on drop");
         }
 
         Ok(())
@@ -1,7 +1,11 @@
 //! Persistence module for Lua.
 //!
 //! Provides the `@pasta_persistence` module with functions to load and save
-//! persistent data to files with optional gzip compression (obfuscation).
+//! persistent data to files with optional gzip compression (obfuscation)
+//! or authenticated encryption (ChaCha20-Poly1305).
+//!
+//! The on-disk format is detected automatically on load, so plain JSON and
+//! gzip saves are migrated to the configured format on the next save.
 //!
 //! # Example
 //! ```lua
@@ -19,27 +23,53 @@
 //! if not ok then
 //!     print("Save failed:", err)
 //! end
+//!
+//! -- Numbered save slots and named stores
+//! persistence.save(data, 1)
+//! local progress = persistence.store("progress")
+//! progress.copy_slot(0, 2)
+//! for _, slot in ipairs(progress.list_slots()) do print(slot) end
+//!
+//! -- Write pasta.save immediately (skipped if unchanged)
+//! persistence.flush()
 //! ```
 
 use crate::loader::PersistenceConfig;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
 use flate2::Compression;
 use flate2::read::GzDecoder;
 use flate2::write::GzEncoder;
 use mlua::{Lua, LuaSerdeExt, Result as LuaResult, Table, Value};
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
 use std::fs::{self, File};
 use std::io::{Read, Write};
 use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
 use thiserror::Error;
 
 /// Module version.
 const VERSION: &str = "0.1.0";
 
 /// Module description.
-const DESCRIPTION: &str = "Persistent data storage (JSON/gzip)";
+const DESCRIPTION: &str = "Persistent data storage (JSON/gzip/encrypted)";
 
 /// Gzip magic header bytes.
 const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
 
+/// Encrypted file magic header bytes (format version included).
+///
+/// Layout: `MAGIC (8) | nonce (12) | ciphertext + tag (16)`.
+/// The header is bound to the ciphertext as associated data.
+const ENCRYPTED_MAGIC: [u8; 8] = *b"PASTAEN1";
+
+/// ChaCha20-Poly1305 nonce length in bytes.
+const NONCE_LEN: usize = 12;
+
+/// Domain separation prefix for key derivation.
+const KEY_DERIVATION_CONTEXT: &[u8] = b"pasta_persistence/v1\0";
+
 /// Persistence error types.
 #[derive(Debug, Error)]
 pub enum PersistenceError {
@@ -66,6 +96,18 @@ pub enum PersistenceError {
     /// Invalid file format.
     #[error("Invalid file format: {0}")]
     InvalidFormat(String),
+
+    /// Encrypted file found, or encryption enabled, but no secret is configured.
+    #[error("Encrypted save data requires a secret in [persistence]")]
+    MissingKey,
+
+    /// Authentication failed: wrong secret or the file was modified.
+    #[error("Save data authentication failed (wrong secret or tampered file)")]
+    TamperDetected,
+
+    /// The slot's existing file could not be loaded, so it is not overwritten.
+    #[error("Save data '{0}' could not be loaded, refusing to overwrite it")]
+    OverwriteBlocked(PathBuf),
 }
 
 impl From<mlua::Error> for PersistenceError {
@@ -74,16 +116,346 @@ impl From<mlua::Error> for PersistenceError {
     }
 }
 
+/// Symmetric key for encrypted save data.
+///
+/// Derived from the ghost-specific `secret` in `[persistence]` with SHA-256.
+#[derive(Clone)]
+pub struct PersistenceKey([u8; 32]);
+
+impl PersistenceKey {
+    /// Derive a key from a secret string.
+    pub fn from_secret(secret: &str) -> Self {
+        let mut hasher = Sha256::new();
+        hasher.update(KEY_DERIVATION_CONTEXT);
+        hasher.update(secret.as_bytes());
+        Self(hasher.finalize().into())
+    }
+
+    /// Derive the key from persistence configuration.
+    ///
+    /// Returns `Ok(None)` if encryption is disabled, and
+    /// [`PersistenceError::MissingKey`] if encryption is enabled but no secret
+    /// is configured (so save data is never written unencrypted by mistake).
+    pub fn from_config(config: &PersistenceConfig) -> Result<Option<Self>, PersistenceError> {
+        if !config.encrypt {
+            return Ok(None);
+        }
+        match config.secret.as_deref() {
+            Some(secret) if !secret.is_empty() => Ok(Some(Self::from_secret(secret))),
+            _ => Err(PersistenceError::MissingKey),
+        }
+    }
+
+    fn cipher(&self) -> ChaCha20Poly1305 {
+        ChaCha20Poly1305::new(Key::from_slice(&self.0))
+    }
+}
+
+impl std::fmt::Debug for PersistenceKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("PersistenceKey(..)")
+    }
+}
+
 /// Internal state for the persistence module.
 /// Stored as upvalue in Lua closures.
 #[derive(Debug, Clone)]
-struct PersistenceState {
+pub(crate) struct PersistenceState {
     /// Absolute path to the persistence file.
     file_path: PathBuf,
+    /// Path the file had before obfuscate/encrypt changed its extension
+    /// (read once when the renamed file does not exist yet).
+    legacy_file_path: Option<PathBuf>,
     /// Whether to use gzip compression (obfuscation).
     obfuscate: bool,
+    /// Encryption key (takes precedence over obfuscate).
+    key: Option<PersistenceKey>,
     /// Enable debug logging.
     debug_mode: bool,
+    /// Digest of the last loaded/saved content per slot (dirty tracking).
+    digests: Arc<Mutex<HashMap<u32, [u8; 32]>>>,
+    /// Slots whose existing file failed to load (protected from overwrite).
+    unreadable: Arc<Mutex<HashSet<u32>>>,
+}
+
+impl PersistenceState {
+    /// Build state from configuration and base directory.
+    ///
+    /// Fails with [`PersistenceError::MissingKey`] if encryption is enabled
+    /// without a secret.
+    pub(crate) fn from_config(
+        config: &PersistenceConfig,
+        base_dir: &Path,
+    ) -> Result<Self, PersistenceError> {
+        let file_path = base_dir.join(config.effective_file_path());
+        let legacy_file_path = Some(base_dir.join(&config.file_path)).filter(|p| *p != file_path);
+        Ok(Self {
+            file_path,
+            legacy_file_path,
+            obfuscate: config.obfuscate,
+            key: PersistenceKey::from_config(config)?,
+            debug_mode: config.debug_mode,
+            digests: Arc::new(Mutex::new(HashMap::new())),
+            unreadable: Arc::new(Mutex::new(HashSet::new())),
+        })
+    }
+
+    /// Path of a save slot.
+    ///
+    /// Slot 0 is the main file; slot N is stored next to it as
+    /// `<stem>.slotN.<ext>` (e.g. `save.slot1.json`).
+    pub(crate) fn slot_path(&self, slot: u32) -> PathBuf {
+        slot_path_of(&self.file_path, slot)
+    }
+
+    /// Path of a save slot before obfuscate/encrypt changed the extension.
+    fn legacy_slot_path(&self, slot: u32) -> Option<PathBuf> {
+        self.legacy_file_path
+            .as_deref()
+            .map(|path| slot_path_of(path, slot))
+    }
+
+    /// Save data to a slot in the configured format.
+    ///
+    /// The write is skipped if the content is unchanged since the last
+    /// load/save of the slot and the file still exists. Slots whose file
+    /// failed to load are not overwritten until they are deleted or loaded
+    /// successfully ([`PersistenceError::OverwriteBlocked`]).
+    ///
+    /// # Returns
+    /// * `Ok(true)` - File written
+    /// * `Ok(false)` - Unchanged, write skipped
+    /// * `Err(e)` - Save failed
+    pub(crate) fn save_slot(
+        &self,
+        data: &serde_json::Value,
+        slot: u32,
+    ) -> Result<bool, PersistenceError> {
+        let path = self.slot_path(slot);
+        if self.is_unreadable(slot) {
+            return Err(PersistenceError::OverwriteBlocked(path));
+        }
+        let digest = content_digest(data)?;
+        if !self.is_dirty_digest(slot, &digest) && path.exists() {
+            return Ok(false);
+        }
+
+        match &self.key {
+            Some(key) => save_to_file_encrypted(data, &path, key)?,
+            None => save_to_file(data, &path, self.obfuscate)?,
+        }
+        self.set_digest(slot, Some(digest));
+
+        // 移行元の平文ファイルは保存後に削除する（暗号化したデータが平文で残らないように）
+        self.discard_legacy_slot(slot);
+        Ok(true)
+    }
+
+    /// Load data from a slot, detecting the on-disk format.
+    ///
+    /// Files in a different format than configured are not marked as saved,
+    /// so the next save migrates them even if the content is unchanged.
+    /// If the file does not exist, the path it had before obfuscate/encrypt
+    /// changed its extension (e.g. `save.json`) is read instead.
+    pub(crate) fn load_slot(&self, slot: u32) -> Result<serde_json::Value, PersistenceError> {
+        let result = self.read_slot(slot);
+        let unreadable = match &result {
+            Err(PersistenceError::IoError(e)) => e.kind() != std::io::ErrorKind::NotFound,
+            Err(_) => true,
+            Ok(_) => false,
+        };
+        self.set_unreadable(slot, unreadable);
+        result
+    }
+
+    fn read_slot(&self, slot: u32) -> Result<serde_json::Value, PersistenceError> {
+        let data = self.read_slot_bytes(slot)?;
+        let value = decode(&data, self.key.as_ref())?;
+        let digest = if self.is_configured_format(&data) {
+            Some(content_digest(&value)?)
+        } else {
+            None
+        };
+        self.set_digest(slot, digest);
+        Ok(value)
+    }
+
+    /// Read the raw file of a slot, falling back to its legacy path.
+    fn read_slot_bytes(&self, slot: u32) -> std::io::Result<Vec<u8>> {
+        match fs::read(self.slot_path(slot)) {
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                match self.legacy_slot_path(slot).map(fs::read) {
+                    Some(Ok(data)) => Ok(data),
+                    _ => Err(e),
+                }
+            }
+            result => result,
+        }
+    }
+
+    /// Remove the legacy file of a slot, if any.
+    ///
+    /// Returns whether a file was removed.
+    fn remove_legacy_slot(&self, slot: u32) -> std::io::Result<bool> {
+        let Some(legacy) = self.legacy_slot_path(slot) else {
+            return Ok(false);
+        };
+        match fs::remove_file(&legacy) {
+            Ok(()) => Ok(true),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Check whether file contents are in the configured on-disk format.
+    fn is_configured_format(&self, data: &[u8]) -> bool {
+        let encrypted = data.starts_with(&ENCRYPTED_MAGIC);
+        let gzip = data.starts_with(&GZIP_MAGIC);
+        if self.key.is_some() {
+            encrypted
+        } else if self.obfuscate {
+            gzip
+        } else {
+            !encrypted && !gzip
+        }
+    }
+
+    /// Check whether data differs from the last loaded/saved content of a slot.
+    pub(crate) fn is_dirty(
+        &self,
+        data: &serde_json::Value,
+        slot: u32,
+    ) -> Result<bool, PersistenceError> {
+        Ok(self.is_dirty_digest(slot, &content_digest(data)?))
+    }
+
+    fn is_dirty_digest(&self, slot: u32, digest: &[u8; 32]) -> bool {
+        let digests = self.digests.lock().unwrap_or_else(|e| e.into_inner());
+        digests.get(&slot) != Some(digest)
+    }
+
+    fn is_unreadable(&self, slot: u32) -> bool {
+        let unreadable = self.unreadable.lock().unwrap_or_else(|e| e.into_inner());
+        unreadable.contains(&slot)
+    }
+
+    fn set_unreadable(&self, slot: u32, value: bool) {
+        let mut unreadable = self.unreadable.lock().unwrap_or_else(|e| e.into_inner());
+        if value {
+            unreadable.insert(slot);
+        } else {
+            unreadable.remove(&slot);
+        }
+    }
+
+    fn set_digest(&self, slot: u32, digest: Option<[u8; 32]>) {
+        let mut digests = self.digests.lock().unwrap_or_else(|e| e.into_inner());
+        match digest {
+            Some(digest) => digests.insert(slot, digest),
+            None => digests.remove(&slot),
+        };
+    }
+
+    /// Remove the legacy file of a slot after it was replaced, logging failures.
+    fn discard_legacy_slot(&self, slot: u32) {
+        if let Err(e) = self.remove_legacy_slot(slot) {
+            tracing::warn!(error = %e, slot, "Failed to remove migrated persistence file");
+        }
+    }
+
+    /// List existing numbered slots (slot 0 is not included), sorted ascending.
+    ///
+    /// Slots not yet migrated to the configured extension are included.
+    pub(crate) fn list_slots(&self) -> Result<Vec<u32>, PersistenceError> {
+        let mut slots = list_slots_of(&self.file_path)?;
+        if let Some(legacy) = &self.legacy_file_path {
+            slots.extend(list_slots_of(legacy)?);
+        }
+        slots.sort_unstable();
+        slots.dedup();
+        Ok(slots)
+    }
+
+    /// Copy a slot to another slot (atomic write on the destination).
+    ///
+    /// A source slot that is not migrated yet is read from its legacy path.
+    pub(crate) fn copy_slot(&self, from: u32, to: u32) -> Result<(), PersistenceError> {
+        if from == to {
+            return Ok(());
+        }
+        let bytes = self.read_slot_bytes(from)?;
+        write_atomic(&self.slot_path(to), &bytes)?;
+        self.discard_legacy_slot(to);
+        self.set_digest(to, None);
+        self.set_unreadable(to, false);
+        Ok(())
+    }
+
+    /// Delete a slot, including its file at the legacy path.
+    ///
+    /// Returns `Ok(false)` if the slot did not exist.
+    pub(crate) fn delete_slot(&self, slot: u32) -> Result<bool, PersistenceError> {
+        self.set_digest(slot, None);
+        self.set_unreadable(slot, false);
+        let removed = match fs::remove_file(self.slot_path(slot)) {
+            Ok(()) => true,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => false,
+            Err(e) => return Err(e.into()),
+        };
+        Ok(self.remove_legacy_slot(slot)? || removed)
+    }
+}
+
+/// List numbered slots stored next to a main file.
+fn list_slots_of(file_path: &Path) -> Result<Vec<u32>, PersistenceError> {
+    let Some(dir) = file_path.parent() else {
+        return Ok(Vec::new());
+    };
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.into()),
+    };
+
+    let (stem, ext) = slot_name_parts_of(file_path);
+    let prefix = format!("{}.slot", stem);
+    let suffix = ext.map(|e| format!(".{}", e)).unwrap_or_default();
+
+    Ok(entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let name = entry.file_name().into_string().ok()?;
+            let number = name.strip_prefix(&prefix)?.strip_suffix(&suffix)?;
+            number.parse::<u32>().ok().filter(|&n| n > 0)
+        })
+        .collect())
+}
+
+/// Path of a save slot next to a main file.
+///
+/// Slot 0 is the main file; slot N is `<stem>.slotN.<ext>`.
+fn slot_path_of(file_path: &Path, slot: u32) -> PathBuf {
+    if slot == 0 {
+        return file_path.to_path_buf();
+    }
+    let (stem, ext) = slot_name_parts_of(file_path);
+    let file_name = match ext {
+        Some(ext) => format!("{}.slot{}.{}", stem, slot, ext),
+        None => format!("{}.slot{}", stem, slot),
+    };
+    file_path.with_file_name(file_name)
+}
+
+/// File stem and extension of a file path.
+fn slot_name_parts_of(file_path: &Path) -> (String, Option<String>) {
+    let stem = file_path
+        .file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let ext = file_path
+        .extension()
+        .map(|e| e.to_string_lossy().into_owned());
+    (stem, ext)
 }
 
 /// Register the @pasta_persistence module with the Lua state.
@@ -91,8 +463,19 @@ struct PersistenceState {
 /// Creates a module table with:
 /// - `_VERSION` - Module version string
 /// - `_DESCRIPTION` - Module description
-/// - `load()` - Load data from persistence file
-/// - `save(data)` - Save data to persistence file
+/// - `load(slot?)` - Load data from persistence file (or save slot)
+/// - `save(data, slot?)` - Save data to persistence file (or save slot)
+/// - `list_slots()` - List existing numbered save slots
+/// - `copy_slot(from, to)` - Copy a save slot
+/// - `delete_slot(slot)` - Delete a save slot
+/// - `store(name)` - Get a named store with the same functions
+/// - `stores()` - List store names configured in pasta.toml
+/// - `flush()` - Save `pasta.save` now if it has changed
+/// - `is_dirty()` - Check whether `pasta.save` has unsaved changes
+/// - `autosave_tick()` - Advance the autosave timer (called on OnSecondChange)
+///
+/// The default store state is also stored as Lua app data so that the
+/// runtime can flush `pasta.save` on drop with the same dirty tracking.
 ///
 /// # Arguments
 /// * `lua` - The Lua state to register the module with
@@ -101,7 +484,8 @@ struct PersistenceState {
 ///
 /// # Returns
 /// * `Ok(Table)` - The module table
-/// * `Err(e)` - Registration failed
+/// * `Err(e)` - Registration failed, or encryption is enabled without a secret
+///   (for the default store or any store in `[persistence.stores]`)
 pub fn register(lua: &Lua, config: &PersistenceConfig, base_dir: &Path) -> LuaResult<Table> {
     let module = lua.create_table()?;
 
@@ -110,47 +494,195 @@ pub fn register(lua: &Lua, config: &PersistenceConfig, base_dir: &Path) -> LuaRe
     module.set("_DESCRIPTION", DESCRIPTION)?;
 
     // Create state for closures
-    let file_path = base_dir.join(config.effective_file_path());
-    let state = PersistenceState {
-        file_path: file_path.clone(),
-        obfuscate: config.obfuscate,
-        debug_mode: config.debug_mode,
-    };
+    let state = PersistenceState::from_config(config, base_dir).map_err(mlua::Error::external)?;
+    for name in config.stores.keys() {
+        if let Some(resolved) = config.store(name) {
+            PersistenceKey::from_config(&resolved).map_err(|e| {
+                mlua::Error::external(format!("[persistence.stores.{}]: {}", name, e))
+            })?;
+        }
+    }
 
     if config.debug_mode {
         tracing::debug!(
-            path = %file_path.display(),
+            path = %state.file_path.display(),
             obfuscate = config.obfuscate,
+            encrypt = state.key.is_some(),
+            stores = config.stores.len(),
+            autosave_interval = config.autosave_interval,
             "Persistence module initialized"
         );
     }
 
+    // Share the default store state with the runtime (Drop-time flush)
+    lua.set_app_data(state.clone());
+
+    // Register store functions for the default store
+    set_store_functions(lua, &module, state.clone())?;
+
+    // Register flush function
+    let flush_state = state.clone();
+    module.set(
+        "flush",
+        lua.create_function(move |lua, ()| match flush_session(lua, &flush_state) {
+            Ok(_) => Ok((Some(true), None)),
+            Err(e) => {
+                let err_msg = format!("Failed to flush: {}", e);
+                tracing::error!(error = %err_msg, "Persistence flush error");
+                Ok((None, Some(err_msg)))
+            }
+        })?,
+    )?;
+
+    // Register is_dirty function
+    let dirty_state = state.clone();
+    module.set(
+        "is_dirty",
+        lua.create_function(move |lua, ()| match session_data(lua)? {
+            Some(data) => dirty_state
+                .is_dirty(&data, 0)
+                .map_err(|e| mlua::Error::external(e.to_string())),
+            None => Ok(false),
+        })?,
+    )?;
+
+    // Register autosave_tick function
+    let autosave_state = state;
+    let autosave_interval = config.autosave_interval;
+    let ticks = Arc::new(Mutex::new(0u64));
+    module.set(
+        "autosave_tick",
+        lua.create_function(move |lua, ()| {
+            if autosave_interval == 0 {
+                return Ok(false);
+            }
+            {
+                let mut ticks = ticks.lock().unwrap_or_else(|e| e.into_inner());
+                *ticks += 1;
+                if *ticks < autosave_interval {
+                    return Ok(false);
+                }
+                *ticks = 0;
+            }
+            match flush_session(lua, &autosave_state) {
+                Ok(written) => Ok(written),
+                Err(e) => {
+                    tracing::error!(error = %e, "Persistence autosave error");
+                    Ok(false)
+                }
+            }
+        })?,
+    )?;
+
+    // Register store(name) function
+    let store_config = config.clone();
+    let store_base_dir = base_dir.to_path_buf();
+    module.set(
+        "store",
+        lua.create_function(move |lua, name: String| match store_config.store(&name) {
+            Some(resolved) => {
+                let state = match PersistenceState::from_config(&resolved, &store_base_dir) {
+                    Ok(state) => state,
+                    Err(e) => return Ok((None, Some(e.to_string()))),
+                };
+                let table = lua.create_table()?;
+                table.set("name", name)?;
+                set_store_functions(lua, &table, state)?;
+                Ok((Some(table), None))
+            }
+            None => Ok((
+                None,
+                Some(format!(
+                    "Invalid store name (or same file as the default store): {}",
+                    name
+                )),
+            )),
+        })?,
+    )?;
+
+    // Register stores() function
+    let store_names: Vec<String> = config.stores.keys().cloned().collect();
+    module.set(
+        "stores",
+        lua.create_function(move |_, ()| Ok(store_names.clone()))?,
+    )?;
+
+    Ok(module)
+}
+
+/// Set load/save/slot functions bound to a store on a Lua table.
+fn set_store_functions(lua: &Lua, table: &Table, state: PersistenceState) -> LuaResult<()> {
     // Register load function
     let load_state = state.clone();
-    module.set(
+    table.set(
         "load",
-        lua.create_function(move |lua, ()| load_impl(lua, &load_state))?,
+        lua.create_function(move |lua, slot: Option<u32>| {
+            load_impl(lua, &load_state, slot.unwrap_or(0))
+        })?,
     )?;
 
     // Register save function
-    let save_state = state;
-    module.set(
+    let save_state = state.clone();
+    table.set(
         "save",
-        lua.create_function(move |lua, data: Table| save_impl(lua, &save_state, data))?,
+        lua.create_function(move |lua, (data, slot): (Table, Option<u32>)| {
+            save_impl(lua, &save_state, data, slot.unwrap_or(0))
+        })?,
     )?;
 
-    Ok(module)
+    // Register list_slots function
+    let list_state = state.clone();
+    table.set(
+        "list_slots",
+        lua.create_function(move |_, ()| match list_state.list_slots() {
+            Ok(slots) => Ok((Some(slots), None)),
+            Err(e) => Ok((None, Some(format!("Failed to list slots: {}", e)))),
+        })?,
+    )?;
+
+    // Register copy_slot function
+    let copy_state = state.clone();
+    table.set(
+        "copy_slot",
+        lua.create_function(move |_, (from, to): (u32, u32)| {
+            match copy_state.copy_slot(from, to) {
+                Ok(()) => Ok((Some(true), None)),
+                Err(e) => {
+                    let err_msg = format!("Failed to copy slot {} to {}: {}", from, to, e);
+                    tracing::warn!(error = %err_msg, "Persistence copy error");
+                    Ok((None, Some(err_msg)))
+                }
+            }
+        })?,
+    )?;
+
+    // Register delete_slot function
+    let delete_state = state;
+    table.set(
+        "delete_slot",
+        lua.create_function(move |_, slot: u32| match delete_state.delete_slot(slot) {
+            Ok(deleted) => Ok((Some(deleted), None)),
+            Err(e) => {
+                let err_msg = format!("Failed to delete slot {}: {}", slot, e);
+                tracing::warn!(error = %err_msg, "Persistence delete error");
+                Ok((None, Some(err_msg)))
+            }
+        })?,
+    )?;
+
+    Ok(())
 }
 
-/// Implementation of `persistence.load()`.
+/// Implementation of `persistence.load(slot?)`.
 ///
-/// Loads data from the persistence file.
+/// Loads data from the persistence file (or save slot).
 /// Returns empty table if file doesn't exist or is corrupted.
-fn load_impl(lua: &Lua, state: &PersistenceState) -> LuaResult<Table> {
-    match load_from_file(&state.file_path) {
+fn load_impl(lua: &Lua, state: &PersistenceState, slot: u32) -> LuaResult<Table> {
+    let path = state.slot_path(slot);
+    match state.load_slot(slot) {
         Ok(value) => {
             if state.debug_mode {
-                tracing::debug!(path = %state.file_path.display(), "Loaded persistence data");
+                tracing::debug!(path = %path.display(), "Loaded persistence data");
             }
             // Convert serde_json::Value to Lua Value, then extract table
             let lua_value: Value = lua.to_value(&value)?;
@@ -158,7 +690,7 @@ fn load_impl(lua: &Lua, state: &PersistenceState) -> LuaResult<Table> {
                 Value::Table(t) => Ok(t),
                 _ => {
                     tracing::warn!(
-                        path = %state.file_path.display(),
+                        path = %path.display(),
                         "Persistence data is not an object, using empty table"
                     );
                     lua.create_table()
@@ -167,25 +699,30 @@ fn load_impl(lua: &Lua, state: &PersistenceState) -> LuaResult<Table> {
         }
         Err(PersistenceError::IoError(ref e)) if e.kind() == std::io::ErrorKind::NotFound => {
             // File not found is expected on first run
-            tracing::warn!(path = %state.file_path.display(), "Persistence file not found, using empty table");
+            tracing::warn!(path = %path.display(), "Persistence file not found, using empty table");
+            lua.create_table()
+        }
+        Err(e @ (PersistenceError::TamperDetected | PersistenceError::MissingKey)) => {
+            tracing::error!(error = %e, path = %path.display(), "Rejected persistence data, using empty table");
             lua.create_table()
         }
         Err(e) => {
             // Other errors: log warning and return empty table
-            tracing::warn!(error = %e, path = %state.file_path.display(), "Failed to load persistence data, using empty table");
+            tracing::warn!(error = %e, path = %path.display(), "Failed to load persistence data, using empty table");
             lua.create_table()
         }
     }
 }
 
-/// Implementation of `persistence.save(data)`.
+/// Implementation of `persistence.save(data, slot?)`.
 ///
-/// Saves data to the persistence file.
+/// Saves data to the persistence file (or save slot).
 /// Returns (true, nil) on success, (nil, error_message) on error.
 fn save_impl(
     lua: &Lua,
     state: &PersistenceState,
     data: Table,
+    slot: u32,
 ) -> LuaResult<(Option<bool>, Option<String>)> {
     // Convert Lua table to serde_json::Value
     let lua_value = Value::Table(data);
@@ -199,24 +736,60 @@ fn save_impl(
     };
 
     // Save to file
-    match save_to_file(&json_value, &state.file_path, state.obfuscate) {
-        Ok(()) => {
+    let path = state.slot_path(slot);
+    match state.save_slot(&json_value, slot) {
+        Ok(written) => {
             if state.debug_mode {
-                tracing::debug!(path = %state.file_path.display(), "Saved persistence data");
+                tracing::debug!(path = %path.display(), written, "Saved persistence data");
             }
             Ok((Some(true), None))
         }
         Err(e) => {
             let err_msg = format!("Failed to save: {}", e);
-            tracing::error!(error = %err_msg, path = %state.file_path.display(), "Persistence save error");
+            tracing::error!(error = %err_msg, path = %path.display(), "Persistence save error");
             Ok((None, Some(err_msg)))
         }
     }
 }
 
+/// Get the session table (`pasta.save`) as JSON, if it has been loaded.
+fn session_data(lua: &Lua) -> LuaResult<Option<serde_json::Value>> {
+    let package: Table = lua.globals().get("package")?;
+    let loaded: Table = package.get("loaded")?;
+    match loaded.get::<Value>("pasta.save")? {
+        Value::Table(t) => Ok(Some(lua.from_value(Value::Table(t))?)),
+        _ => Ok(None),
+    }
+}
+
+/// Save the session table (`pasta.save`) to slot 0 if it has changed.
+///
+/// # Returns
+/// * `Ok(true)` - File written
+/// * `Ok(false)` - `pasta.save` not loaded, or unchanged since last load/save
+/// * `Err(e)` - Conversion or save failed
+pub(crate) fn flush_session(lua: &Lua, state: &PersistenceState) -> Result<bool, PersistenceError> {
+    let Some(data) = session_data(lua)? else {
+        tracing::debug!("pasta.save not loaded, skipping persistence save");
+        return Ok(false);
+    };
+    let written = state.save_slot(&data, 0)?;
+    if state.debug_mode {
+        tracing::debug!(path = %state.file_path.display(), written, "Flushed pasta.save");
+    }
+    Ok(written)
+}
+
+/// Digest of the canonical JSON serialization (object keys are sorted).
+fn content_digest(data: &serde_json::Value) -> Result<[u8; 32], PersistenceError> {
+    Ok(Sha256::digest(serde_json::to_vec(data)?).into())
+}
+
 /// Load data from a persistence file.
 ///
 /// Automatically detects format (JSON or gzip) based on file content.
+/// Encrypted files fail with [`PersistenceError::MissingKey`];
+/// use [`load_from_file_with_key`] for those.
 ///
 /// # Arguments
 /// * `path` - Path to the persistence file
@@ -225,25 +798,96 @@ fn save_impl(
 /// * `Ok(Value)` - Loaded JSON value
 /// * `Err(e)` - Load failed
 pub fn load_from_file(path: &Path) -> Result<serde_json::Value, PersistenceError> {
+    load_from_file_with_key(path, None)
+}
+
+/// Load data from a persistence file, decrypting it if necessary.
+///
+/// Automatically detects format (JSON, gzip or encrypted) based on file content.
+///
+/// # Arguments
+/// * `path` - Path to the persistence file
+/// * `key` - Encryption key, required only for encrypted files
+///
+/// # Returns
+/// * `Ok(Value)` - Loaded JSON value
+/// * `Err(PersistenceError::TamperDetected)` - Authentication failed
+/// * `Err(e)` - Load failed
+pub fn load_from_file_with_key(
+    path: &Path,
+    key: Option<&PersistenceKey>,
+) -> Result<serde_json::Value, PersistenceError> {
     let data = fs::read(path)?;
+    decode(&data, key)
+}
 
+/// Decode file contents, detecting the format by magic header.
+fn decode(
+    data: &[u8],
+    key: Option<&PersistenceKey>,
+) -> Result<serde_json::Value, PersistenceError> {
     if data.is_empty() {
         return Ok(serde_json::Value::Object(serde_json::Map::new()));
     }
 
-    // Detect format by magic header
-    if data.len() >= 2 && data[0] == GZIP_MAGIC[0] && data[1] == GZIP_MAGIC[1] {
+    if data.starts_with(&ENCRYPTED_MAGIC) {
+        // Encrypted (payload is gzip compressed JSON)
+        let key = key.ok_or(PersistenceError::MissingKey)?;
+        let plain = decrypt(data, key)?;
+        decode(&plain, None)
+    } else if data.starts_with(&GZIP_MAGIC) {
         // Gzip compressed
-        let mut decoder = GzDecoder::new(&data[..]);
+        let mut decoder = GzDecoder::new(data);
         let mut json_bytes = Vec::new();
         decoder.read_to_end(&mut json_bytes)?;
         Ok(serde_json::from_slice(&json_bytes)?)
     } else {
         // Plain JSON
-        Ok(serde_json::from_slice(&data)?)
+        Ok(serde_json::from_slice(data)?)
     }
 }
 
+/// Encrypt a payload into the encrypted file layout.
+fn encrypt(plain: &[u8], key: &PersistenceKey) -> Result<Vec<u8>, PersistenceError> {
+    let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let ciphertext = key
+        .cipher()
+        .encrypt(
+            &nonce,
+            Payload {
+                msg: plain,
+                aad: &ENCRYPTED_MAGIC,
+            },
+        )
+        .map_err(|_| PersistenceError::InvalidFormat("encryption failed".to_string()))?;
+
+    let mut bytes = Vec::with_capacity(ENCRYPTED_MAGIC.len() + NONCE_LEN + ciphertext.len());
+    bytes.extend_from_slice(&ENCRYPTED_MAGIC);
+    bytes.extend_from_slice(&nonce);
+    bytes.extend_from_slice(&ciphertext);
+    Ok(bytes)
+}
+
+/// Decrypt and authenticate the encrypted file layout.
+fn decrypt(data: &[u8], key: &PersistenceKey) -> Result<Vec<u8>, PersistenceError> {
+    let body = &data[ENCRYPTED_MAGIC.len()..];
+    if body.len() < NONCE_LEN {
+        return Err(PersistenceError::InvalidFormat(
+            "encrypted file is truncated".to_string(),
+        ));
+    }
+    let (nonce, ciphertext) = body.split_at(NONCE_LEN);
+    key.cipher()
+        .decrypt(
+            Nonce::from_slice(nonce),
+            Payload {
+                msg: ciphertext,
+                aad: &ENCRYPTED_MAGIC,
+            },
+        )
+        .map_err(|_| PersistenceError::TamperDetected)
+}
+
 /// Save data to a persistence file.
 ///
 /// Uses atomic write (temp file + rename) to prevent corruption.
@@ -261,32 +905,64 @@ pub fn save_to_file(
     path: &Path,
     obfuscate: bool,
 ) -> Result<(), PersistenceError> {
-    // Ensure parent directory exists
-    if let Some(parent) = path.parent() {
-        if !parent.exists() {
-            fs::create_dir_all(parent)?;
-            tracing::debug!(path = %parent.display(), "Created persistence directory");
-        }
-    }
-
     // Serialize data
     let bytes = if obfuscate {
         // Gzip compressed
-        let json_bytes = serde_json::to_vec(data)?;
-        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
-        encoder.write_all(&json_bytes)?;
-        encoder.finish()?
+        gzip_json(data)?
     } else {
         // Pretty-printed JSON
         serde_json::to_vec_pretty(data)?
     };
 
+    write_atomic(path, &bytes)
+}
+
+/// Save data to a persistence file with authenticated encryption.
+///
+/// The JSON is gzip compressed, then encrypted with ChaCha20-Poly1305
+/// using a fresh random nonce. Uses atomic write (temp file + rename).
+///
+/// # Arguments
+/// * `data` - JSON value to save
+/// * `path` - Path to the persistence file
+/// * `key` - Encryption key
+///
+/// # Returns
+/// * `Ok(())` - Save successful
+/// * `Err(e)` - Save failed
+pub fn save_to_file_encrypted(
+    data: &serde_json::Value,
+    path: &Path,
+    key: &PersistenceKey,
+) -> Result<(), PersistenceError> {
+    let bytes = encrypt(&gzip_json(data)?, key)?;
+    write_atomic(path, &bytes)
+}
+
+/// Serialize data as gzip compressed JSON.
+fn gzip_json(data: &serde_json::Value) -> Result<Vec<u8>, PersistenceError> {
+    let json_bytes = serde_json::to_vec(data)?;
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(&json_bytes)?;
+    Ok(encoder.finish()?)
+}
+
+/// Write bytes atomically (temp file + rename), creating parent directories.
+fn write_atomic(path: &Path, bytes: &[u8]) -> Result<(), PersistenceError> {
+    // Ensure parent directory exists
+    if let Some(parent) = path.parent() {
+        if !parent.exists() {
+            fs::create_dir_all(parent)?;
+            tracing::debug!(path = %parent.display(), "Created persistence directory");
+        }
+    }
+
     // Atomic write: write to temp file, then rename
     let temp_path = path.with_extension("tmp");
 
     // Write to temp file
     let mut file = File::create(&temp_path)?;
-    file.write_all(&bytes)?;
+    file.write_all(bytes)?;
     file.sync_all()?;
     drop(file);
 
@@ -311,6 +987,7 @@ mod tests {
             obfuscate,
             file_path: file_name.to_string(),
             debug_mode: true,
+            ..Default::default()
         };
         let base_dir = temp_dir.path().to_path_buf();
         (config, base_dir)
@@ -361,6 +1038,112 @@ mod tests {
         assert_eq!(loaded, data);
     }
 
+    #[test]
+    fn test_save_load_encrypted() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("save.dat");
+        let key = PersistenceKey::from_secret("ghost-secret");
+
+        let data = serde_json::json!({"affection": 100, "name": "Alice"});
+        save_to_file_encrypted(&data, &file_path, &key).unwrap();
+
+        // Neither plain JSON nor gzip
+        let raw = fs::read(&file_path).unwrap();
+        assert!(raw.starts_with(&ENCRYPTED_MAGIC));
+        assert!(!raw.windows(5).any(|w| w == b"Alice"));
+
+        let loaded = load_from_file_with_key(&file_path, Some(&key)).unwrap();
+        assert_eq!(loaded, data);
+    }
+
+    #[test]
+    fn test_encrypted_tamper_detected() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("save.dat");
+        let key = PersistenceKey::from_secret("ghost-secret");
+
+        save_to_file_encrypted(&serde_json::json!({"affection": 1}), &file_path, &key).unwrap();
+
+        // Flip one ciphertext bit
+        let mut raw = fs::read(&file_path).unwrap();
+        let last = raw.len() - 1;
+        raw[last] ^= 0x01;
+        fs::write(&file_path, &raw).unwrap();
+
+        let result = load_from_file_with_key(&file_path, Some(&key));
+        assert!(matches!(result, Err(PersistenceError::TamperDetected)));
+    }
+
+    #[test]
+    fn test_encrypted_wrong_or_missing_key() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("save.dat");
+
+        let key = PersistenceKey::from_secret("ghost-secret");
+        save_to_file_encrypted(&serde_json::json!({"k": "v"}), &file_path, &key).unwrap();
+
+        let other = PersistenceKey::from_secret("other-secret");
+        let result = load_from_file_with_key(&file_path, Some(&other));
+        assert!(matches!(result, Err(PersistenceError::TamperDetected)));
+
+        let result = load_from_file(&file_path);
+        assert!(matches!(result, Err(PersistenceError::MissingKey)));
+    }
+
+    #[test]
+    fn test_encrypted_migrates_legacy_formats() {
+        let temp_dir = TempDir::new().unwrap();
+        let key = PersistenceKey::from_secret("ghost-secret");
+        let data = serde_json::json!({"key": "value"});
+
+        // Legacy plain JSON and gzip files load with a key configured
+        let json_path = temp_dir.path().join("legacy.json");
+        save_to_file(&data, &json_path, false).unwrap();
+        let gzip_path = temp_dir.path().join("legacy.dat");
+        save_to_file(&data, &gzip_path, true).unwrap();
+
+        assert_eq!(
+            load_from_file_with_key(&json_path, Some(&key)).unwrap(),
+            data
+        );
+        assert_eq!(
+            load_from_file_with_key(&gzip_path, Some(&key)).unwrap(),
+            data
+        );
+    }
+
+    #[test]
+    fn test_key_from_config_requires_secret() {
+        let config = PersistenceConfig {
+            encrypt: true,
+            ..Default::default()
+        };
+        assert!(matches!(
+            PersistenceKey::from_config(&config),
+            Err(PersistenceError::MissingKey)
+        ));
+
+        let config = PersistenceConfig {
+            encrypt: true,
+            secret: Some("ghost-secret".to_string()),
+            ..Default::default()
+        };
+        assert!(PersistenceKey::from_config(&config).unwrap().is_some());
+    }
+
+    #[test]
+    fn test_register_rejects_encrypt_without_secret() {
+        let temp_dir = TempDir::new().unwrap();
+        let config = PersistenceConfig {
+            encrypt: true,
+            ..Default::default()
+        };
+
+        // 鍵が無いまま平文で .dat に書き込まないよう、登録自体を失敗させる
+        let lua = Lua::new();
+        assert!(register(&lua, &config, temp_dir.path()).is_err());
+    }
+
     #[test]
     fn test_load_nonexistent_returns_error() {
         let temp_dir = TempDir::new().unwrap();
@@ -503,4 +1286,347 @@ mod tests {
         assert_eq!(name, "Test");
         assert_eq!(count, 42);
     }
+
+    #[test]
+    fn test_slot_paths() {
+        let temp_dir = TempDir::new().unwrap();
+        let config = PersistenceConfig::default();
+        let state = PersistenceState::from_config(&config, temp_dir.path()).unwrap();
+
+        assert_eq!(state.slot_path(0), state.file_path);
+        assert_eq!(
+            state.slot_path(3),
+            temp_dir.path().join("profile/pasta/save/save.slot3.json")
+        );
+    }
+
+    #[test]
+    fn test_slots_list_copy_delete() {
+        let temp_dir = TempDir::new().unwrap();
+        let config = PersistenceConfig::default();
+        let state = PersistenceState::from_config(&config, temp_dir.path()).unwrap();
+
+        assert!(state.list_slots().unwrap().is_empty());
+
+        let data = serde_json::json!({"chapter": 3});
+        state.save_slot(&data, 0).unwrap();
+        state.save_slot(&data, 2).unwrap();
+        state.copy_slot(2, 10).unwrap();
+        assert_eq!(state.list_slots().unwrap(), vec![2, 10]);
+        assert_eq!(state.load_slot(10).unwrap(), data);
+
+        assert!(state.delete_slot(2).unwrap());
+        assert!(!state.delete_slot(2).unwrap());
+        assert_eq!(state.list_slots().unwrap(), vec![10]);
+        assert!(state.copy_slot(5, 6).is_err());
+    }
+
+    /// 平文の save.slot2.json を残したまま encrypt = true に切り替えた状態を作る
+    fn encrypted_state_with_legacy_slot(base_dir: &Path) -> (PersistenceState, PathBuf) {
+        let plain = PersistenceState::from_config(&PersistenceConfig::default(), base_dir).unwrap();
+        plain
+            .save_slot(&serde_json::json!({"chapter": 2}), 2)
+            .unwrap();
+        let config = PersistenceConfig {
+            encrypt: true,
+            secret: Some("ghost-secret".to_string()),
+            ..Default::default()
+        };
+        let state = PersistenceState::from_config(&config, base_dir).unwrap();
+        assert_ne!(state.slot_path(2), plain.slot_path(2));
+        (state, plain.slot_path(2))
+    }
+
+    #[test]
+    fn test_list_slots_includes_legacy_slots() {
+        let temp_dir = TempDir::new().unwrap();
+        let (state, _) = encrypted_state_with_legacy_slot(temp_dir.path());
+
+        assert_eq!(state.list_slots().unwrap(), vec![2]);
+        state
+            .save_slot(&serde_json::json!({"chapter": 4}), 4)
+            .unwrap();
+        assert_eq!(state.list_slots().unwrap(), vec![2, 4]);
+    }
+
+    #[test]
+    fn test_copy_slot_reads_legacy_slot() {
+        let temp_dir = TempDir::new().unwrap();
+        let (state, _) = encrypted_state_with_legacy_slot(temp_dir.path());
+
+        state.copy_slot(2, 5).unwrap();
+        assert_eq!(
+            state.load_slot(5).unwrap(),
+            serde_json::json!({"chapter": 2})
+        );
+        assert_eq!(state.list_slots().unwrap(), vec![2, 5]);
+    }
+
+    #[test]
+    fn test_delete_slot_removes_legacy_slot() {
+        let temp_dir = TempDir::new().unwrap();
+        let (state, legacy_path) = encrypted_state_with_legacy_slot(temp_dir.path());
+
+        assert!(state.delete_slot(2).unwrap());
+        assert!(!legacy_path.exists());
+        // 削除したスロットが平文ファイルから復活しない
+        assert!(state.load_slot(2).is_err());
+        assert!(state.list_slots().unwrap().is_empty());
+        assert!(!state.delete_slot(2).unwrap());
+    }
+
+    #[test]
+    fn test_lua_module_named_stores_and_slots() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut config = PersistenceConfig::default();
+        config.stores.insert(
+            "progress".to_string(),
+            crate::loader::PersistenceStoreConfig {
+                obfuscate: Some(true),
+                ..Default::default()
+            },
+        );
+
+        let lua = Lua::new();
+        let module = register(&lua, &config, temp_dir.path()).unwrap();
+        lua.globals().set("persistence", module).unwrap();
+
+        lua.load(
+            r#"
+            local names = persistence.stores()
+            assert(#names == 1 and names[1] == "progress")
+
+            local progress = assert(persistence.store("progress"))
+            assert(progress.save({ chapter = 1 }))
+            assert(progress.save({ chapter = 5 }, 1))
+            assert(progress.copy_slot(1, 2))
+            local slots = progress.list_slots()
+            assert(#slots == 2 and slots[1] == 1 and slots[2] == 2)
+            assert(progress.load(2).chapter == 5)
+            assert(progress.load().chapter == 1)
+            assert(progress.delete_slot(1) == true)
+
+            -- Stores are independent of the default store
+            assert(next(persistence.load()) == nil)
+
+            local bad, err = persistence.store("../x")
+            assert(bad == nil and err ~= nil)
+        "#,
+        )
+        .exec()
+        .unwrap();
+
+        // Store-specific obfuscation setting
+        let raw = fs::read(temp_dir.path().join("profile/pasta/save/progress.dat")).unwrap();
+        assert!(raw.starts_with(&GZIP_MAGIC));
+        assert!(
+            temp_dir
+                .path()
+                .join("profile/pasta/save/progress.slot2.dat")
+                .exists()
+        );
+    }
+
+    #[test]
+    fn test_save_slot_skips_unchanged() {
+        let temp_dir = TempDir::new().unwrap();
+        let config = PersistenceConfig::default();
+        let state = PersistenceState::from_config(&config, temp_dir.path()).unwrap();
+
+        let data = serde_json::json!({"affection": 1});
+        assert!(state.save_slot(&data, 0).unwrap());
+        assert!(!state.save_slot(&data, 0).unwrap());
+        assert!(!state.is_dirty(&data, 0).unwrap());
+
+        let changed = serde_json::json!({"affection": 2});
+        assert!(state.is_dirty(&changed, 0).unwrap());
+        assert!(state.save_slot(&changed, 0).unwrap());
+
+        // Loaded content counts as saved
+        let reloaded = PersistenceState::from_config(&config, temp_dir.path()).unwrap();
+        let loaded = reloaded.load_slot(0).unwrap();
+        assert!(!reloaded.save_slot(&loaded, 0).unwrap());
+
+        // Deleted file is rewritten even if unchanged
+        fs::remove_file(state.slot_path(0)).unwrap();
+        assert!(state.save_slot(&changed, 0).unwrap());
+    }
+
+    #[test]
+    fn test_lua_module_flush_and_autosave() {
+        let temp_dir = TempDir::new().unwrap();
+        let config = PersistenceConfig {
+            autosave_interval: 3,
+            ..Default::default()
+        };
+        let file_path = temp_dir.path().join(config.effective_file_path());
+
+        let lua = Lua::new();
+        let module = register(&lua, &config, temp_dir.path()).unwrap();
+        lua.globals().set("persistence", module).unwrap();
+
+        // Nothing to flush before pasta.save is loaded
+        lua.load(
+            r#"
+            assert(persistence.flush())
+            assert(persistence.is_dirty() == false)
+            package.loaded["pasta.save"] = persistence.load()
+        "#,
+        )
+        .exec()
+        .unwrap();
+        assert!(!file_path.exists());
+
+        // Autosave fires on the 3rd tick when dirty
+        lua.load(
+            r#"
+            local save = package.loaded["pasta.save"]
+            save.count = 1
+            assert(persistence.is_dirty() == true)
+            assert(persistence.autosave_tick() == false)
+            assert(persistence.autosave_tick() == false)
+            assert(persistence.autosave_tick() == true)
+            assert(persistence.is_dirty() == false)
+
+            -- Unchanged: no rewrite
+            for _ = 1, 3 do assert(persistence.autosave_tick() == false) end
+
+            -- Explicit flush
+            save.count = 2
+            assert(persistence.flush())
+        "#,
+        )
+        .exec()
+        .unwrap();
+
+        let loaded = load_from_file(&file_path).unwrap();
+        assert_eq!(loaded["count"], 2);
+    }
+
+    #[test]
+    fn test_lua_module_autosave_disabled_by_default() {
+        let temp_dir = TempDir::new().unwrap();
+        let config = PersistenceConfig::default();
+
+        let lua = Lua::new();
+        let module = register(&lua, &config, temp_dir.path()).unwrap();
+        lua.globals().set("persistence", module).unwrap();
+
+        lua.load(
+            r#"
+            package.loaded["pasta.save"] = { count = 1 }
+            for _ = 1, 100 do assert(persistence.autosave_tick() == false) end
+        "#,
+        )
+        .exec()
+        .unwrap();
+        assert!(!temp_dir.path().join(config.effective_file_path()).exists());
+    }
+
+    #[test]
+    fn test_lua_module_does_not_overwrite_unreadable_save() {
+        let temp_dir = TempDir::new().unwrap();
+        let base_dir = temp_dir.path().to_path_buf();
+        let config = PersistenceConfig {
+            encrypt: true,
+            secret: Some("ghost-secret".to_string()),
+            ..Default::default()
+        };
+        let file_path = base_dir.join(config.effective_file_path());
+        fs::create_dir_all(file_path.parent().unwrap()).unwrap();
+        let other = PersistenceKey::from_secret("other-secret");
+        save_to_file_encrypted(&serde_json::json!({"count": 3}), &file_path, &other).unwrap();
+        let original = fs::read(&file_path).unwrap();
+
+        let lua = Lua::new();
+        let module = register(&lua, &config, &base_dir).unwrap();
+
+        // 鍵が合わない: 空テーブルを返すが、元のファイルは上書きしない
+        let load_fn: mlua::Function = module.get("load").unwrap();
+        let result: Table = load_fn.call(()).unwrap();
+        assert_eq!(result.raw_len(), 0);
+
+        let save_fn: mlua::Function = module.get("save").unwrap();
+        let (ok, err): (Option<bool>, Option<String>) = save_fn.call(result.clone()).unwrap();
+        assert_eq!(ok, None);
+        assert!(err.is_some());
+
+        let state = lua.app_data_ref::<PersistenceState>().unwrap().clone();
+        assert!(matches!(
+            state.save_slot(&serde_json::json!({}), 0),
+            Err(PersistenceError::OverwriteBlocked(_))
+        ));
+        assert_eq!(fs::read(&file_path).unwrap(), original);
+
+        // 明示的に削除した後は保存できる
+        let delete_fn: mlua::Function = module.get("delete_slot").unwrap();
+        delete_fn.call::<mlua::MultiValue>(0).unwrap();
+        let (ok, _): (Option<bool>, Option<String>) = save_fn.call(result).unwrap();
+        assert_eq!(ok, Some(true));
+    }
+
+    #[test]
+    fn test_lua_module_encrypted_migrates_plain_json() {
+        let temp_dir = TempDir::new().unwrap();
+        let base_dir = temp_dir.path().to_path_buf();
+
+        // 平文の save.json がある状態で encrypt = true に切り替える
+        let plain_config = PersistenceConfig::default();
+        let plain_path = base_dir.join(plain_config.effective_file_path());
+        save_to_file(&serde_json::json!({"count": 3}), &plain_path, false).unwrap();
+
+        let config = PersistenceConfig {
+            encrypt: true,
+            secret: Some("ghost-secret".to_string()),
+            ..Default::default()
+        };
+        let file_path = base_dir.join(config.effective_file_path());
+        assert_ne!(file_path, plain_path);
+
+        let lua = Lua::new();
+        let module = register(&lua, &config, &base_dir).unwrap();
+
+        let load_fn: mlua::Function = module.get("load").unwrap();
+        let result: Table = load_fn.call(()).unwrap();
+        assert_eq!(result.get::<i32>("count").unwrap(), 3);
+
+        // 保存で .dat に暗号化して書き出し、平文の .json は削除する
+        let save_fn: mlua::Function = module.get("save").unwrap();
+        let (ok, _): (Option<bool>, Option<String>) = save_fn.call(result).unwrap();
+        assert_eq!(ok, Some(true));
+        assert!(fs::read(&file_path).unwrap().starts_with(&ENCRYPTED_MAGIC));
+        assert!(!plain_path.exists());
+
+        // 以降は .dat から読み込む
+        let result: Table = load_fn.call(()).unwrap();
+        assert_eq!(result.get::<i32>("count").unwrap(), 3);
+    }
+
+    #[test]
+    fn test_lua_module_encrypted_migrates_gzip() {
+        let temp_dir = TempDir::new().unwrap();
+        let base_dir = temp_dir.path().to_path_buf();
+        let config = PersistenceConfig {
+            encrypt: true,
+            secret: Some("ghost-secret".to_string()),
+            ..Default::default()
+        };
+        let file_path = base_dir.join(config.effective_file_path());
+
+        // Existing gzip save from an obfuscate=true ghost
+        save_to_file(&serde_json::json!({"count": 7}), &file_path, true).unwrap();
+
+        let lua = Lua::new();
+        let module = register(&lua, &config, &base_dir).unwrap();
+
+        let load_fn: mlua::Function = module.get("load").unwrap();
+        let result: Table = load_fn.call(()).unwrap();
+        assert_eq!(result.get::<i32>("count").unwrap(), 7);
+
+        // Saving rewrites the file in encrypted format
+        let save_fn: mlua::Function = module.get("save").unwrap();
+        let (ok, _): (Option<bool>, Option<String>) = save_fn.call(result).unwrap();
+        assert_eq!(ok, Some(true));
+        assert!(fs::read(&file_path).unwrap().starts_with(&ENCRYPTED_MAGIC));
+    }
 }
@@ -0,0 +1,398 @@
+//! SAORI/1.0 client module for Lua.
+//!
+//! Provides the `@pasta_saori` module for calling external SAORI helper
+//! executables. Helpers are driven SAORI-basic style: one process per request,
+//! the SAORI/1.0 request is written to stdin and the response is read from stdout.
+//!
+//! # Example
+//! ```lua
+//! local SAORI = require "@pasta_saori"
+//!
+//! local calc = SAORI.load("saori/calc", { timeout = 3 })
+//! local res, err = calc:execute("1", "+", "2")
+//! if res then
+//!     print(res.status, res.result, res.values[1])
+//! end
+//! local ver = calc:version()   -- GET Version SAORI/1.0
+//! ```
+//!
+//! # Result table
+//! | field         | type            | description                         |
+//! | ------------- | --------------- | ----------------------------------- |
+//! | `status`      | integer         | Status code (200, 204, 400, 500)    |
+//! | `status_text` | string          | Reason phrase ("OK" etc.)           |
+//! | `version`     | string          | Protocol in the status line         |
+//! | `result`      | string \| nil   | `Result` header                     |
+//! | `values`      | string[]        | `Value0`, `Value1`, ... in order    |
+//! | `headers`     | table           | All headers by name                 |
+
+use mlua::{Lua, MultiValue, Result as LuaResult, Table, UserData, UserDataMethods, Value};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::time::{Duration, Instant};
+use thiserror::Error;
+
+/// Module version.
+const VERSION: &str = "0.1.0";
+
+/// Module description.
+const DESCRIPTION: &str = "SAORI/1.0 client for external helper executables";
+
+/// Default request timeout.
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Poll interval while waiting for a helper to exit.
+const POLL_INTERVAL: Duration = Duration::from_millis(5);
+
+/// Errors raised while talking to a SAORI helper.
+#[derive(Debug, Error)]
+pub enum SaoriError {
+    #[error("failed to start SAORI helper '{path}': {source}")]
+    Spawn {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+
+    #[error("SAORI helper I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("SAORI helper timed out after {0:?}")]
+    Timeout(Duration),
+
+    #[error("invalid SAORI response: {0}")]
+    InvalidResponse(String),
+}
+
+/// Parsed SAORI/1.0 response.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SaoriResponse {
+    /// Protocol in the status line (e.g. "SAORI/1.0")
+    pub version: String,
+    /// Status code
+    pub status: u16,
+    /// Reason phrase
+    pub status_text: String,
+    /// Headers in response order
+    pub headers: Vec<(String, String)>,
+}
+
+impl SaoriResponse {
+    /// Parse a SAORI/1.0 response.
+    pub fn parse(text: &str) -> Result<Self, SaoriError> {
+        let mut lines = text.lines();
+        let status_line = lines
+            .next()
+            .filter(|line| !line.is_empty())
+            .ok_or_else(|| SaoriError::InvalidResponse("empty response".into()))?;
+        let mut parts = status_line.splitn(3, ' ');
+        let version = parts.next().unwrap_or_default();
+        if !version.starts_with("SAORI/") {
+            return Err(SaoriError::InvalidResponse(format!(
+                "unexpected status line '{status_line}'"
+            )));
+        }
+        let status = parts
+            .next()
+            .and_then(|code| code.parse().ok())
+            .ok_or_else(|| {
+                SaoriError::InvalidResponse(format!("missing status code in '{status_line}'"))
+            })?;
+        let status_text = parts.next().unwrap_or_default().to_string();
+
+        let headers = lines
+            .take_while(|line| !line.is_empty())
+            .filter_map(|line| {
+                let (key, value) = line.split_once(": ")?;
+                Some((key.to_string(), value.to_string()))
+            })
+            .collect();
+
+        Ok(SaoriResponse {
+            version: version.to_string(),
+            status,
+            status_text,
+            headers,
+        })
+    }
+
+    /// Get a header value by exact name.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// `Value0`, `Value1`, ... up to the first missing index.
+    pub fn values(&self) -> Vec<&str> {
+        (0..)
+            .map_while(|i| self.header(&format!("Value{i}")))
+            .collect()
+    }
+
+    fn to_lua(&self, lua: &Lua) -> LuaResult<Table> {
+        let table = lua.create_table()?;
+        table.set("version", self.version.as_str())?;
+        table.set("status", self.status)?;
+        table.set("status_text", self.status_text.as_str())?;
+        table.set("result", self.header("Result"))?;
+        table.set("values", lua.create_sequence_from(self.values())?)?;
+        let headers = lua.create_table()?;
+        for (key, value) in &self.headers {
+            headers.set(key.as_str(), value.as_str())?;
+        }
+        table.set("headers", headers)?;
+        Ok(table)
+    }
+}
+
+/// A SAORI helper executable.
+#[derive(Debug, Clone)]
+pub struct SaoriHelper {
+    path: PathBuf,
+    timeout: Duration,
+    sender: String,
+}
+
+impl SaoriHelper {
+    /// Create a helper for the executable at `path`.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            timeout: DEFAULT_TIMEOUT,
+            sender: "Pasta".to_string(),
+        }
+    }
+
+    /// Set the per-request timeout.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Set the `Sender` header value.
+    pub fn with_sender(mut self, sender: impl Into<String>) -> Self {
+        self.sender = sender.into();
+        self
+    }
+
+    /// Helper executable path.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Send `GET Version SAORI/1.0`.
+    pub fn version(&self) -> Result<SaoriResponse, SaoriError> {
+        self.send(&self.build_request::<&str>("GET Version SAORI/1.0", &[]))
+    }
+
+    /// Send `EXECUTE SAORI/1.0` with `Argument0..n`.
+    pub fn execute<S: AsRef<str>>(&self, args: &[S]) -> Result<SaoriResponse, SaoriError> {
+        self.send(&self.build_request("EXECUTE SAORI/1.0", args))
+    }
+
+    fn build_request<S: AsRef<str>>(&self, request_line: &str, args: &[S]) -> String {
+        let mut request = format!(
+            "{request_line}\r\nCharset: UTF-8\r\nSender: {}\r\nSecurityLevel: Local\r\n",
+            self.sender
+        );
+        for (i, arg) in args.iter().enumerate() {
+            // Arguments are single header values; line breaks would split the request
+            let arg = arg.as_ref().replace(['\r', '\n'], " ");
+            request.push_str(&format!("Argument{i}: {arg}\r\n"));
+        }
+        request.push_str("\r\n");
+        request
+    }
+
+    fn send(&self, request: &str) -> Result<SaoriResponse, SaoriError> {
+        let output = self.run(request.as_bytes())?;
+        let text = String::from_utf8_lossy(&output);
+        let response = SaoriResponse::parse(&text)?;
+        tracing::debug!(
+            path = %self.path.display(),
+            status = response.status,
+            "SAORI request completed"
+        );
+        Ok(response)
+    }
+
+    /// Run the helper once, feeding `input` to stdin and collecting stdout.
+    fn run(&self, input: &[u8]) -> Result<Vec<u8>, SaoriError> {
+        let mut command = Command::new(&self.path);
+        command
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null());
+        // SAORI helpers resolve their own files relative to their directory
+        if let Some(dir) = self.path.parent().filter(|d| !d.as_os_str().is_empty()) {
+            command.current_dir(dir);
+        }
+        let mut child = command.spawn().map_err(|source| SaoriError::Spawn {
+            path: self.path.clone(),
+            source,
+        })?;
+
+        // Feed stdin and drain stdout on separate threads to avoid pipe deadlocks
+        let mut stdin = child.stdin.take().expect("stdin is piped");
+        let input = input.to_vec();
+        let writer = std::thread::spawn(move || stdin.write_all(&input));
+        let mut stdout = child.stdout.take().expect("stdout is piped");
+        let reader = std::thread::spawn(move || {
+            let mut output = Vec::new();
+            stdout.read_to_end(&mut output).map(|_| output)
+        });
+
+        wait_with_timeout(&mut child, self.timeout)?;
+        // A helper may exit without reading its input (broken pipe is not an error)
+        let _ = writer.join();
+        let output = reader
+            .join()
+            .map_err(|_| SaoriError::InvalidResponse("stdout reader panicked".into()))??;
+        Ok(output)
+    }
+}
+
+fn wait_with_timeout(child: &mut Child, timeout: Duration) -> Result<(), SaoriError> {
+    let deadline = Instant::now() + timeout;
+    loop {
+        if child.try_wait()?.is_some() {
+            return Ok(());
+        }
+        if Instant::now() >= deadline {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(SaoriError::Timeout(timeout));
+        }
+        std::thread::sleep(POLL_INTERVAL);
+    }
+}
+
+impl UserData for SaoriHelper {
+    fn add_methods<M: UserDataMethods<Self>>(methods: &mut M) {
+        // helper:execute(arg0, arg1, ...) / helper:execute({ arg0, arg1, ... })
+        // Returns result table, or nil and an error message.
+        methods.add_method("execute", |lua, this, args: MultiValue| {
+            let args = lua_arguments(args)?;
+            respond(lua, this.execute(&args))
+        });
+        methods.add_method("version", |lua, this, ()| respond(lua, this.version()));
+        methods.add_method("path", |_lua, this, ()| {
+            Ok(this.path.to_string_lossy().to_string())
+        });
+    }
+}
+
+/// Map a client result to Lua's `value` / `nil, err` convention.
+fn respond(lua: &Lua, result: Result<SaoriResponse, SaoriError>) -> LuaResult<MultiValue> {
+    match result {
+        Ok(response) => Ok(MultiValue::from_vec(vec![Value::Table(
+            response.to_lua(lua)?,
+        )])),
+        Err(e) => {
+            tracing::warn!(error = %e, "SAORI request failed");
+            Ok(MultiValue::from_vec(vec![
+                Value::Nil,
+                Value::String(lua.create_string(e.to_string())?),
+            ]))
+        }
+    }
+}
+
+/// Convert Lua arguments (varargs or a single sequence table) to strings.
+fn lua_arguments(args: MultiValue) -> LuaResult<Vec<String>> {
+    let values: Vec<Value> = match args.into_iter().collect::<Vec<_>>().as_slice() {
+        [Value::Table(table)] => table.sequence_values::<Value>().collect::<LuaResult<_>>()?,
+        other => other.to_vec(),
+    };
+    values
+        .into_iter()
+        .enumerate()
+        .map(|(i, value)| match value {
+            Value::String(s) => Ok(s.to_str()?.to_string()),
+            Value::Integer(n) => Ok(n.to_string()),
+            Value::Number(n) => Ok(n.to_string()),
+            Value::Boolean(b) => Ok(b.to_string()),
+            Value::Nil => Ok(String::new()),
+            other => Err(mlua::Error::RuntimeError(format!(
+                "SAORI argument {i}: string expected, got {}",
+                other.type_name()
+            ))),
+        })
+        .collect()
+}
+
+/// Register the `@pasta_saori` module.
+///
+/// Relative helper paths are resolved against `base_dir` (the ghost's load directory).
+pub fn register(lua: &Lua, base_dir: &Path) -> LuaResult<Table> {
+    let module = lua.create_table()?;
+    module.set("_VERSION", VERSION)?;
+    module.set("_DESCRIPTION", DESCRIPTION)?;
+
+    // SAORI.load(path, { timeout = seconds, sender = "name" }) -> helper
+    let base_dir = base_dir.to_path_buf();
+    let load = lua.create_function(move |_lua, (path, opts): (String, Option<Table>)| {
+        // Absolute path: the helper runs with its own directory as the working directory
+        let path = std::path::absolute(base_dir.join(path))
+            .map_err(|e| mlua::Error::ExternalError(std::sync::Arc::new(e)))?;
+        let mut helper = SaoriHelper::new(path);
+        if let Some(opts) = opts {
+            if let Some(timeout) = opts.get::<Option<f64>>("timeout")? {
+                let timeout = Duration::try_from_secs_f64(timeout).map_err(|_| {
+                    mlua::Error::RuntimeError(format!("invalid SAORI timeout {timeout}"))
+                })?;
+                helper = helper.with_timeout(timeout);
+            }
+            if let Some(sender) = opts.get::<Option<String>>("sender")? {
+                helper = helper.with_sender(sender);
+            }
+        }
+        Ok(helper)
+    })?;
+    module.set("load", load)?;
+
+    Ok(module)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_response() {
+        let res = SaoriResponse::parse(
+            "SAORI/1.0 200 OK\r\nCharset: UTF-8\r\nResult: 3\r\nValue0: a\r\nValue1: b\r\n\r\n",
+        )
+        .unwrap();
+        assert_eq!(res.version, "SAORI/1.0");
+        assert_eq!(res.status, 200);
+        assert_eq!(res.status_text, "OK");
+        assert_eq!(res.header("Result"), Some("3"));
+        assert_eq!(res.values(), vec!["a", "b"]);
+    }
+
+    #[test]
+    fn test_parse_response_rejects_garbage() {
+        assert!(SaoriResponse::parse("").is_err());
+        assert!(SaoriResponse::parse("SHIORI/3.0 200 OK\r\n\r\n").is_err());
+        assert!(SaoriResponse::parse("SAORI/1.0 OK\r\n\r\n").is_err());
+    }
+
+    #[test]
+    fn test_build_request() {
+        let helper = SaoriHelper::new("calc").with_sender("テスト");
+        assert_eq!(
+            helper.build_request("EXECUTE SAORI/1.0", &["1", "a\r\nb"]),
+            "EXECUTE SAORI/1.0\r\nCharset: UTF-8\r\nSender: テスト\r\nSecurityLevel: Local\r\n\
+             Argument0: 1\r\nArgument1: a  b\r\n\r\n"
+        );
+    }
+
+    #[test]
+    fn test_spawn_error() {
+        let helper = SaoriHelper::new("/nonexistent/saori/helper");
+        assert!(matches!(helper.version(), Err(SaoriError::Spawn { .. })));
+    }
+}
@@ -0,0 +1,153 @@
+//! Scene runner - embedding API for running scenes without SHIORI.
+//!
+//! [`PastaLuaRuntime::run_scene`](super::PastaLuaRuntime::run_scene) resolves a
+//! scene through `@pasta_search` (the runtime's [`SearchContext`](crate::SearchContext)),
+//! runs it in a coroutine with a JSON-output act, and hands each yield back as
+//! typed [`ScriptEvent`]s. Chain talk (`act:yield()`) is driven by calling
+//! [`SceneRun::next_events`] again.
+//!
+//! # Example
+//! ```rust,ignore
+//! let runtime = PastaLoader::load(ghost_dir)?;
+//! if let Some(mut run) = runtime.run_scene("メイン", ())? {
+//!     run.set_var("名前", "ユーザー")?;
+//!     while let Some(events) = run.next_events()? {
+//!         show(&events);
+//!     }
+//!     let count: Option<i64> = run.save()?.get("会話回数")?;
+//! }
+//! ```
+
+use crate::script_event::{ScriptEvent, ScriptEventStream};
+use mlua::{
+    FromLua, IntoLua, Lua, MultiValue, ObjectLike, Result as LuaResult, Table, Thread,
+    ThreadStatus, Value,
+};
+
+use super::lua_require;
+
+/// A running scene.
+///
+/// Each call to [`next_events`](Self::next_events) resumes the scene until it
+/// yields output or finishes. Dropping a `SceneRun` abandons the rest of the scene.
+pub struct SceneRun {
+    thread: Thread,
+    act: Table,
+    global_name: String,
+    local_name: String,
+    /// Arguments for the first resume (`act, ...`), taken on start
+    start_args: Option<MultiValue>,
+}
+
+impl SceneRun {
+    /// Search a scene and prepare it for running.
+    ///
+    /// # Returns
+    /// * `Ok(Some(SceneRun))` - Scene found
+    /// * `Ok(None)` - No scene matches `name`
+    /// * `Err(e)` - Lua error while searching or creating the act
+    pub(super) fn start(lua: &Lua, name: &str, args: MultiValue) -> LuaResult<Option<Self>> {
+        let scene: Table = lua.unpack(lua_require(lua, "pasta.scene")?)?;
+        let co_exec: mlua::Function = scene.get("co_exec")?;
+        let (thread, result): (Option<Thread>, Option<Table>) = co_exec.call(name)?;
+        let (Some(thread), Some(result)) = (thread, result) else {
+            return Ok(None);
+        };
+
+        // SHIORI リクエストなしの act を作り、出力を構造化イベントにする
+        let store: Table = lua.unpack(lua_require(lua, "pasta.store")?)?;
+        let act_module: Table = lua.unpack(lua_require(lua, "pasta.shiori.act")?)?;
+        let act: Table = act_module
+            .get::<mlua::Function>("new")?
+            .call(store.get::<Value>("actors")?)?;
+        act.call_method::<()>("set_output", "json")?;
+
+        let mut start_args = args;
+        start_args.push_front(Value::Table(act.clone()));
+
+        Ok(Some(Self {
+            thread,
+            act,
+            global_name: result.get("global_name")?,
+            local_name: result.get("local_name")?,
+            start_args: Some(start_args),
+        }))
+    }
+
+    /// Global scene name the search resolved to (e.g. `"メイン1"`).
+    pub fn global_name(&self) -> &str {
+        &self.global_name
+    }
+
+    /// Local scene name the search resolved to (e.g. `"__start__"`).
+    pub fn local_name(&self) -> &str {
+        &self.local_name
+    }
+
+    /// Whether the scene has run to the end.
+    pub fn is_finished(&self) -> bool {
+        self.thread.status() != ThreadStatus::Resumable
+    }
+
+    /// Resume the scene until it yields output.
+    ///
+    /// Yields without output (e.g. `act:yield()` with nothing to say) are skipped.
+    ///
+    /// # Returns
+    /// * `Ok(Some(events))` - Output of one yield (or of the end of the scene)
+    /// * `Ok(None)` - The scene has finished
+    /// * `Err(e)` - Lua error in the scene (the run is finished afterwards)
+    pub fn next_events(&mut self) -> LuaResult<Option<Vec<ScriptEvent>>> {
+        while !self.is_finished() {
+            let args = self.start_args.take().unwrap_or_default();
+            match self.thread.resume::<Value>(args)? {
+                Value::Nil => continue,
+                Value::String(json) => {
+                    let stream = ScriptEventStream::from_json(&json.to_str()?)
+                        .map_err(mlua::Error::external)?;
+                    return Ok(Some(stream.events));
+                }
+                other => {
+                    return Err(mlua::Error::runtime(format!(
+                        "scene yielded {} instead of script output",
+                        other.type_name()
+                    )));
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    /// Get an action-local variable (`act.var`).
+    pub fn var<V: FromLua>(&self, name: &str) -> LuaResult<V> {
+        self.vars()?.get(name)
+    }
+
+    /// Set an action-local variable (`act.var`).
+    pub fn set_var(&self, name: &str, value: impl IntoLua) -> LuaResult<()> {
+        self.vars()?.set(name, value)
+    }
+
+    /// Action-local variable table (`act.var`).
+    pub fn vars(&self) -> LuaResult<Table> {
+        self.act.get("var")
+    }
+
+    /// Persistent variable table (`act.save`, the same table as `pasta.save`).
+    pub fn save(&self) -> LuaResult<Table> {
+        self.act.get("save")
+    }
+
+    /// The act object passed to the scene.
+    pub fn act(&self) -> &Table {
+        &self.act
+    }
+}
+
+impl Iterator for SceneRun {
+    type Item = LuaResult<Vec<ScriptEvent>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_events().transpose()
+    }
+}
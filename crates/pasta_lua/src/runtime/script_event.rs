@@ -0,0 +1,78 @@
+//! Script event module for Lua - JSON event stream output.
+//!
+//! Provides the `@pasta_script_event` module, which validates event tables built
+//! by `pasta.shiori.event_builder` against [`ScriptEvent`] and serializes them
+//! as a versioned [`ScriptEventStream`].
+//!
+//! # Example
+//! ```lua
+//! local SCRIPT_EVENT = require "@pasta_script_event"
+//!
+//! local json, err = SCRIPT_EVENT.encode({
+//!     { type = "actor", name = "さくら", spot = 0 },
+//!     { type = "talk", actor = "さくら", text = "こんにちは" },
+//! })
+//! -- {"version":1,"events":[...]}
+//!
+//! local stream = SCRIPT_EVENT.decode(json)
+//! print(stream.version, #stream.events)
+//! ```
+
+use crate::script_event::{SCRIPT_EVENT_VERSION, ScriptEvent, ScriptEventStream};
+use mlua::{Lua, LuaSerdeExt, Result as LuaResult, Table, Value};
+
+/// Module version.
+const VERSION: &str = "0.1.0";
+
+/// Module description.
+const DESCRIPTION: &str = "Structured script event stream (JSON)";
+
+/// Register the @pasta_script_event module.
+pub fn register(lua: &Lua) -> LuaResult<Table> {
+    let module = lua.create_table()?;
+    module.set("_VERSION", VERSION)?;
+    module.set("_DESCRIPTION", DESCRIPTION)?;
+    module.set("FORMAT_VERSION", SCRIPT_EVENT_VERSION)?;
+
+    module.set(
+        "encode",
+        lua.create_function(|lua, events: Value| encode_impl(lua, events))?,
+    )?;
+    module.set(
+        "decode",
+        lua.create_function(|lua, json: String| decode_impl(lua, &json))?,
+    )?;
+
+    Ok(module)
+}
+
+/// Implementation of encode function.
+///
+/// Returns the JSON envelope, or `nil, error message` when an event is invalid.
+fn encode_impl(lua: &Lua, events: Value) -> LuaResult<(Option<String>, Option<String>)> {
+    // 空テーブルは配列として扱う
+    if let Value::Table(table) = &events
+        && table.is_empty()
+    {
+        return Ok((Some(ScriptEventStream::new(Vec::new()).to_json()), None));
+    }
+    match lua.from_value::<Vec<ScriptEvent>>(events) {
+        // 読み飛ばし用の Unknown は出力側では使わない（種別の誤りとして扱う）
+        Ok(events) if events.contains(&ScriptEvent::Unknown) => Ok((
+            None,
+            Some("Invalid script event: unknown event type".to_string()),
+        )),
+        Ok(events) => Ok((Some(ScriptEventStream::new(events).to_json()), None)),
+        Err(e) => Ok((None, Some(format!("Invalid script event: {}", e)))),
+    }
+}
+
+/// Implementation of decode function.
+///
+/// Returns `{ version, events }`, or `nil, error message`.
+fn decode_impl(lua: &Lua, json: &str) -> LuaResult<(Value, Option<String>)> {
+    match ScriptEventStream::from_json(json) {
+        Ok(stream) => Ok((lua.to_value(&stream)?, None)),
+        Err(e) => Ok((Value::Nil, Some(e.to_string()))),
+    }
+}
@@ -0,0 +1,632 @@
+//! SSTP client module for Lua.
+//!
+//! Provides the `@pasta_sstp` module for pushing scripts and events back to the
+//! baseware over SSTP/1.x (TCP). Each request opens one connection, writes the
+//! request in the configured charset and reads the response until the blank line
+//! (or until the server closes the connection).
+//!
+//! The default endpoint comes from the `[sstp]` section of pasta.toml
+//! (`127.0.0.1:9801` when omitted).
+//!
+//! # Example
+//! ```lua
+//! local SSTP = require "@pasta_sstp"
+//!
+//! -- SEND SSTP/1.4
+//! local res, err = SSTP.send("\\h\\s[0]時間だよ。\\e")
+//! if res then
+//!     print(res.status, res.status_text)
+//! end
+//!
+//! -- NOTIFY SSTP/1.1
+//! SSTP.notify({ event = "OnTimerTalk", references = { "10", "a" } })
+//!
+//! -- EXECUTE SSTP/1.2 (additional response lines in res.data)
+//! local name = SSTP.execute("GetName")
+//!
+//! -- Explicit endpoint
+//! local client = SSTP.client({ port = 9821, sender = "MyGhost", timeout = 1 })
+//! client:send({ script = "\\0\\e", option = "nodescript" })
+//! ```
+//!
+//! # Request table
+//! | field        | header                      |
+//! | ------------ | --------------------------- |
+//! | `script`     | `Script`                    |
+//! | `event`      | `Event`                     |
+//! | `command`    | `Command`                   |
+//! | `option`     | `Option`                    |
+//! | `references` | `Reference0`, `Reference1`… |
+//! | `headers`    | Any other headers by name   |
+//!
+//! # Result table
+//! | field         | type     | description                          |
+//! | ------------- | -------- | ------------------------------------ |
+//! | `status`      | integer  | Status code (200, 204, 210, 400 ...) |
+//! | `status_text` | string   | Reason phrase ("OK" etc.)            |
+//! | `version`     | string   | Protocol in the status line          |
+//! | `data`        | string[] | Lines following the status line      |
+
+use crate::loader::SstpConfig;
+use encoding_rs::Encoding;
+use mlua::{Lua, MultiValue, Result as LuaResult, Table, UserData, UserDataMethods, Value};
+use std::io::{ErrorKind, Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::time::Duration;
+use thiserror::Error;
+
+/// Module version.
+const VERSION: &str = "0.1.0";
+
+/// Module description.
+const DESCRIPTION: &str = "SSTP client for sending scripts to the baseware";
+
+/// Errors raised while talking to an SSTP server.
+#[derive(Debug, Error)]
+pub enum SstpError {
+    #[error("failed to connect to SSTP server {addr}: {source}")]
+    Connect {
+        addr: String,
+        source: std::io::Error,
+    },
+
+    #[error("SSTP I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("SSTP request timed out after {0:?}")]
+    Timeout(Duration),
+
+    #[error("unknown SSTP charset '{0}'")]
+    UnknownCharset(String),
+
+    #[error("cannot encode SSTP request in {0}")]
+    Encoding(&'static str),
+
+    #[error("invalid SSTP response: {0}")]
+    InvalidResponse(String),
+
+    #[error("invalid SSTP header name '{0}'")]
+    InvalidHeaderName(String),
+}
+
+/// SSTP request method.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SstpMethod {
+    /// `SEND SSTP/1.4` - Display a script
+    Send,
+    /// `NOTIFY SSTP/1.1` - Raise an event in the ghost
+    Notify,
+    /// `EXECUTE SSTP/1.2` - Query / control the baseware
+    Execute,
+}
+
+impl SstpMethod {
+    fn request_line(self) -> &'static str {
+        match self {
+            SstpMethod::Send => "SEND SSTP/1.4",
+            SstpMethod::Notify => "NOTIFY SSTP/1.1",
+            SstpMethod::Execute => "EXECUTE SSTP/1.2",
+        }
+    }
+
+    /// Header that every request of this method must carry.
+    fn required_header(self) -> &'static str {
+        match self {
+            SstpMethod::Send => "Script",
+            SstpMethod::Notify => "Event",
+            SstpMethod::Execute => "Command",
+        }
+    }
+}
+
+/// SSTP request without the `Charset` / `Sender` headers (added by the client).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SstpRequest {
+    method: SstpMethod,
+    headers: Vec<(String, String)>,
+}
+
+impl SstpRequest {
+    /// Create an empty request.
+    pub fn new(method: SstpMethod) -> Self {
+        Self {
+            method,
+            headers: Vec::new(),
+        }
+    }
+
+    /// `SEND` request with a `Script` header.
+    pub fn send(script: impl Into<String>) -> Self {
+        Self::new(SstpMethod::Send).header("Script", script)
+    }
+
+    /// `NOTIFY` request with an `Event` header.
+    pub fn notify(event: impl Into<String>) -> Self {
+        Self::new(SstpMethod::Notify).header("Event", event)
+    }
+
+    /// `EXECUTE` request with a `Command` header.
+    pub fn execute(command: impl Into<String>) -> Self {
+        Self::new(SstpMethod::Execute).header("Command", command)
+    }
+
+    /// Append a header. Line breaks in the value are replaced with spaces.
+    ///
+    /// Names are checked when the request is sent
+    /// ([`SstpError::InvalidHeaderName`]).
+    pub fn header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        let value = value.into().replace(['\r', '\n'], " ");
+        self.headers.push((name.into(), value));
+        self
+    }
+
+    /// Append `Reference0..n` headers.
+    pub fn references<S: Into<String>>(mut self, refs: impl IntoIterator<Item = S>) -> Self {
+        for (i, value) in refs.into_iter().enumerate() {
+            self = self.header(format!("Reference{i}"), value);
+        }
+        self
+    }
+
+    /// Request method.
+    pub fn method(&self) -> SstpMethod {
+        self.method
+    }
+
+    /// Check that every header name is alphanumerics, `-`, `.` or `_`
+    /// (starting with an alphanumeric), so that no name can inject lines.
+    fn validate(&self) -> Result<(), SstpError> {
+        match self
+            .headers
+            .iter()
+            .find(|(name, _)| !is_valid_header_name(name))
+        {
+            Some((name, _)) => Err(SstpError::InvalidHeaderName(name.clone())),
+            None => Ok(()),
+        }
+    }
+
+    fn to_text(&self, sender: &str, charset: &str) -> String {
+        let mut text = format!(
+            "{}\r\nCharset: {charset}\r\nSender: {sender}\r\n",
+            self.method.request_line()
+        );
+        for (name, value) in &self.headers {
+            text.push_str(&format!("{name}: {value}\r\n"));
+        }
+        text.push_str("\r\n");
+        text
+    }
+}
+
+/// Parsed SSTP response.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SstpResponse {
+    /// Protocol in the status line (e.g. "SSTP/1.4")
+    pub version: String,
+    /// Status code
+    pub status: u16,
+    /// Reason phrase
+    pub status_text: String,
+    /// Additional lines following the status line (EXECUTE results)
+    pub data: Vec<String>,
+}
+
+impl SstpResponse {
+    /// Parse an SSTP response.
+    pub fn parse(text: &str) -> Result<Self, SstpError> {
+        let mut lines = text.lines();
+        let status_line = lines
+            .next()
+            .filter(|line| !line.is_empty())
+            .ok_or_else(|| SstpError::InvalidResponse("empty response".into()))?;
+        let mut parts = status_line.splitn(3, ' ');
+        let version = parts.next().unwrap_or_default();
+        if !version.starts_with("SSTP/") {
+            return Err(SstpError::InvalidResponse(format!(
+                "unexpected status line '{status_line}'"
+            )));
+        }
+        let status = parts
+            .next()
+            .and_then(|code| code.parse().ok())
+            .ok_or_else(|| {
+                SstpError::InvalidResponse(format!("missing status code in '{status_line}'"))
+            })?;
+        let status_text = parts.next().unwrap_or_default().to_string();
+        let data = lines
+            .take_while(|line| !line.is_empty())
+            .map(str::to_string)
+            .collect();
+
+        Ok(SstpResponse {
+            version: version.to_string(),
+            status,
+            status_text,
+            data,
+        })
+    }
+
+    /// 2xx status.
+    pub fn is_success(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+
+    fn to_lua(&self, lua: &Lua) -> LuaResult<Table> {
+        let table = lua.create_table()?;
+        table.set("version", self.version.as_str())?;
+        table.set("status", self.status)?;
+        table.set("status_text", self.status_text.as_str())?;
+        table.set("data", lua.create_sequence_from(self.data.iter().cloned())?)?;
+        Ok(table)
+    }
+}
+
+/// SSTP client for one endpoint.
+#[derive(Debug, Clone)]
+pub struct SstpClient {
+    host: String,
+    port: u16,
+    sender: String,
+    encoding: &'static Encoding,
+    timeout: Duration,
+}
+
+impl SstpClient {
+    /// Create a client for `host:port` (UTF-8, sender "Pasta", 3 second timeout).
+    pub fn new(host: impl Into<String>, port: u16) -> Self {
+        Self::try_from(&SstpConfig::default())
+            .expect("default SSTP config is valid")
+            .with_endpoint(host, port)
+    }
+
+    /// Set the endpoint.
+    pub fn with_endpoint(mut self, host: impl Into<String>, port: u16) -> Self {
+        self.host = host.into();
+        self.port = port;
+        self
+    }
+
+    /// Set the `Sender` header value.
+    pub fn with_sender(mut self, sender: impl Into<String>) -> Self {
+        self.sender = sender.into();
+        self
+    }
+
+    /// Set the request charset (`UTF-8`, `Shift_JIS`, ...).
+    pub fn with_charset(mut self, charset: &str) -> Result<Self, SstpError> {
+        self.encoding = Encoding::for_label(charset.as_bytes())
+            .ok_or_else(|| SstpError::UnknownCharset(charset.to_string()))?;
+        Ok(self)
+    }
+
+    /// Set the connect / read / write timeout.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// `host:port` of the endpoint.
+    pub fn addr(&self) -> String {
+        format!("{}:{}", self.host, self.port)
+    }
+
+    /// Send a request and parse the response.
+    pub fn request(&self, request: &SstpRequest) -> Result<SstpResponse, SstpError> {
+        request.validate()?;
+        let text = request.to_text(&self.sender, self.encoding.name());
+        let (bytes, _, had_errors) = self.encoding.encode(&text);
+        if had_errors {
+            return Err(SstpError::Encoding(self.encoding.name()));
+        }
+
+        let output = self.exchange(&bytes).map_err(|e| match e {
+            SstpError::Io(io) if is_timeout(&io) => SstpError::Timeout(self.timeout),
+            other => other,
+        })?;
+        let (text, _, _) = self.encoding.decode(&output);
+        let response = SstpResponse::parse(&text)?;
+        tracing::debug!(
+            addr = %self.addr(),
+            method = request.method().request_line(),
+            status = response.status,
+            "SSTP request completed"
+        );
+        Ok(response)
+    }
+
+    fn exchange(&self, request: &[u8]) -> Result<Vec<u8>, SstpError> {
+        let addr = self.addr();
+        let connect_error = |source| SstpError::Connect {
+            addr: addr.clone(),
+            source,
+        };
+        let socket_addr = (self.host.as_str(), self.port)
+            .to_socket_addrs()
+            .map_err(connect_error)?
+            .next()
+            .ok_or_else(|| connect_error(ErrorKind::AddrNotAvailable.into()))?;
+        let mut stream = TcpStream::connect_timeout(&socket_addr, self.timeout).map_err(|e| {
+            if is_timeout(&e) {
+                SstpError::Timeout(self.timeout)
+            } else {
+                connect_error(e)
+            }
+        })?;
+        stream.set_read_timeout(Some(self.timeout))?;
+        stream.set_write_timeout(Some(self.timeout))?;
+        stream.write_all(request)?;
+        stream.flush()?;
+
+        // The response ends with a blank line; servers usually close right after it
+        let mut output = Vec::new();
+        let mut chunk = [0u8; 1024];
+        loop {
+            let n = stream.read(&mut chunk)?;
+            if n == 0 {
+                break;
+            }
+            output.extend_from_slice(&chunk[..n]);
+            if output.windows(4).any(|w| w == b"\r\n\r\n") {
+                break;
+            }
+        }
+        Ok(output)
+    }
+}
+
+impl TryFrom<&SstpConfig> for SstpClient {
+    type Error = SstpError;
+
+    fn try_from(config: &SstpConfig) -> Result<Self, SstpError> {
+        SstpClient {
+            host: config.host.clone(),
+            port: config.port,
+            sender: config.sender.clone(),
+            encoding: encoding_rs::UTF_8,
+            timeout: Duration::from_millis(config.timeout_ms),
+        }
+        .with_charset(&config.charset)
+    }
+}
+
+fn is_timeout(e: &std::io::Error) -> bool {
+    matches!(e.kind(), ErrorKind::TimedOut | ErrorKind::WouldBlock)
+}
+
+impl UserData for SstpClient {
+    fn add_methods<M: UserDataMethods<Self>>(methods: &mut M) {
+        // client:send(script | request_table) etc.
+        // Returns result table, or nil and an error message.
+        methods.add_method("send", |lua, this, req: Value| {
+            lua_request(lua, this, SstpMethod::Send, req)
+        });
+        methods.add_method("notify", |lua, this, req: Value| {
+            lua_request(lua, this, SstpMethod::Notify, req)
+        });
+        methods.add_method("execute", |lua, this, req: Value| {
+            lua_request(lua, this, SstpMethod::Execute, req)
+        });
+        methods.add_method("addr", |_lua, this, ()| Ok(this.addr()));
+    }
+}
+
+/// Build a request from Lua and send it, following the `value` / `nil, err` convention.
+fn lua_request(
+    lua: &Lua,
+    client: &SstpClient,
+    method: SstpMethod,
+    req: Value,
+) -> LuaResult<MultiValue> {
+    let request = lua_to_request(method, req)?;
+    match client.request(&request) {
+        Ok(response) => Ok(MultiValue::from_vec(vec![Value::Table(
+            response.to_lua(lua)?,
+        )])),
+        Err(e) => {
+            tracing::warn!(error = %e, "SSTP request failed");
+            Ok(MultiValue::from_vec(vec![
+                Value::Nil,
+                Value::String(lua.create_string(e.to_string())?),
+            ]))
+        }
+    }
+}
+
+/// Header name rule shared with SHIORI responses.
+fn is_valid_header_name(name: &str) -> bool {
+    name.chars()
+        .next()
+        .is_some_and(|c| c.is_ascii_alphanumeric())
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '.' | '_'))
+}
+
+/// Convert a Lua string (the required header) or request table to a request.
+fn lua_to_request(method: SstpMethod, req: Value) -> LuaResult<SstpRequest> {
+    let required = method.required_header();
+    let table = match req {
+        Value::String(s) => {
+            return Ok(SstpRequest::new(method).header(required, s.to_str()?.to_string()));
+        }
+        Value::Table(table) => table,
+        other => {
+            return Err(mlua::Error::RuntimeError(format!(
+                "SSTP {}: string or table expected, got {}",
+                method.request_line(),
+                other.type_name()
+            )));
+        }
+    };
+
+    let mut request = SstpRequest::new(method);
+    for (key, name) in [
+        ("script", "Script"),
+        ("event", "Event"),
+        ("command", "Command"),
+        ("option", "Option"),
+    ] {
+        if let Some(value) = table.get::<Option<String>>(key)? {
+            request = request.header(name, value);
+        }
+    }
+    if !request.headers.iter().any(|(name, _)| name == required) {
+        return Err(mlua::Error::RuntimeError(format!(
+            "SSTP {}: '{}' is required",
+            method.request_line(),
+            required.to_lowercase()
+        )));
+    }
+    if let Some(refs) = table.get::<Option<Table>>("references")? {
+        let refs = refs
+            .sequence_values::<String>()
+            .collect::<LuaResult<Vec<_>>>()?;
+        request = request.references(refs);
+    }
+    if let Some(headers) = table.get::<Option<Table>>("headers")? {
+        // Sort for a deterministic header order
+        let mut headers = headers
+            .pairs::<String, String>()
+            .collect::<LuaResult<Vec<_>>>()?;
+        headers.sort();
+        for (name, value) in headers {
+            request = request.header(name, value);
+        }
+    }
+    Ok(request)
+}
+
+/// Apply `{ host, port, sender, charset, timeout }` overrides from Lua.
+fn lua_client(base: &SstpClient, opts: Option<Table>) -> LuaResult<SstpClient> {
+    let mut client = base.clone();
+    let Some(opts) = opts else {
+        return Ok(client);
+    };
+    if let Some(host) = opts.get::<Option<String>>("host")? {
+        client.host = host;
+    }
+    if let Some(port) = opts.get::<Option<u16>>("port")? {
+        client.port = port;
+    }
+    if let Some(sender) = opts.get::<Option<String>>("sender")? {
+        client = client.with_sender(sender);
+    }
+    if let Some(charset) = opts.get::<Option<String>>("charset")? {
+        client = client
+            .with_charset(&charset)
+            .map_err(|e| mlua::Error::RuntimeError(e.to_string()))?;
+    }
+    if let Some(timeout) = opts.get::<Option<f64>>("timeout")? {
+        let timeout = Duration::try_from_secs_f64(timeout)
+            .map_err(|_| mlua::Error::RuntimeError(format!("invalid SSTP timeout {timeout}")))?;
+        client = client.with_timeout(timeout);
+    }
+    Ok(client)
+}
+
+/// Register the `@pasta_sstp` module.
+///
+/// `send` / `notify` / `execute` use the endpoint from `config`
+/// (defaults when `None`); `client(opts)` creates a client with overrides.
+pub fn register(lua: &Lua, config: Option<&SstpConfig>) -> LuaResult<Table> {
+    let module = lua.create_table()?;
+    module.set("_VERSION", VERSION)?;
+    module.set("_DESCRIPTION", DESCRIPTION)?;
+
+    let default_config = SstpConfig::default();
+    let config = config.unwrap_or(&default_config);
+    let client = SstpClient::try_from(config).unwrap_or_else(|e| {
+        tracing::warn!(error = %e, "Invalid [sstp] config, using UTF-8");
+        SstpClient::try_from(&SstpConfig {
+            charset: default_config.charset.clone(),
+            ..config.clone()
+        })
+        .expect("UTF-8 is a valid SSTP charset")
+    });
+
+    for (name, method) in [
+        ("send", SstpMethod::Send),
+        ("notify", SstpMethod::Notify),
+        ("execute", SstpMethod::Execute),
+    ] {
+        let client = client.clone();
+        let function =
+            lua.create_function(move |lua, req: Value| lua_request(lua, &client, method, req))?;
+        module.set(name, function)?;
+    }
+
+    // SSTP.client({ host, port, sender, charset, timeout = seconds }) -> client
+    let function =
+        lua.create_function(move |_lua, opts: Option<Table>| lua_client(&client, opts))?;
+    module.set("client", function)?;
+
+    Ok(module)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_request_text() {
+        let request = SstpRequest::notify("OnTest")
+            .references(["a", "b\r\nc"])
+            .header("Option", "nobreak");
+        assert_eq!(
+            request.to_text("テスト", "UTF-8"),
+            "NOTIFY SSTP/1.1\r\nCharset: UTF-8\r\nSender: テスト\r\nEvent: OnTest\r\n\
+             Reference0: a\r\nReference1: b  c\r\nOption: nobreak\r\n\r\n"
+        );
+    }
+
+    #[test]
+    fn test_rejects_invalid_header_names() {
+        // 接続前に検証するため、サーバーがなくても名前のエラーになる
+        let client = SstpClient::new("127.0.0.1", 9);
+        for name in ["X\r\nScript", "Bad:Name", "", "-Lead", "Sp ace"] {
+            let request = SstpRequest::send("\\0\\e").header(name, "v");
+            assert!(
+                matches!(client.request(&request), Err(SstpError::InvalidHeaderName(n)) if n == name),
+                "{name:?}"
+            );
+        }
+        assert!(
+            SstpRequest::send("x")
+                .header("X-Option_1.a", "v")
+                .validate()
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn test_parse_response() {
+        let res = SstpResponse::parse("SSTP/1.2 200 OK\r\nさくら\r\n\r\n").unwrap();
+        assert_eq!(res.version, "SSTP/1.2");
+        assert_eq!(res.status, 200);
+        assert_eq!(res.status_text, "OK");
+        assert_eq!(res.data, vec!["さくら"]);
+        assert!(res.is_success());
+
+        let res = SstpResponse::parse("SSTP/1.4 512 Invisible\r\n\r\n").unwrap();
+        assert_eq!(res.status, 512);
+        assert!(res.data.is_empty());
+        assert!(!res.is_success());
+    }
+
+    #[test]
+    fn test_parse_response_rejects_garbage() {
+        assert!(SstpResponse::parse("").is_err());
+        assert!(SstpResponse::parse("SHIORI/3.0 200 OK\r\n\r\n").is_err());
+        assert!(SstpResponse::parse("SSTP/1.4 OK\r\n\r\n").is_err());
+    }
+
+    #[test]
+    fn test_unknown_charset() {
+        let client = SstpClient::new("127.0.0.1", 9801);
+        assert!(matches!(
+            client.with_charset("no-such-charset"),
+            Err(SstpError::UnknownCharset(_))
+        ));
+    }
+}
@@ -0,0 +1,207 @@
+//! Line wrapping for sakura script talk text.
+//!
+//! Inserts `\n` tags into a token sequence so that no balloon line exceeds
+//! a configured width, measured in half-width columns (full-width = 2).
+//!
+//! # Rules
+//! 1. Sakura script tags have no width; `\n`, `\c` and scope switches reset the column
+//! 2. A character that would exceed the width starts a new line
+//! 3. 行頭禁則: line start prohibited characters (including period, comma and
+//!    strong emphasis characters) never start a line; they hang past the width
+//!    (ぶら下げ) instead
+//! 4. 行末禁則: line end prohibited characters at the end of a line are moved to
+//!    the next line together with the character that caused the break
+
+use super::tokenizer::{CharSets, Token, TokenKind};
+
+/// Display width of a character in half-width columns.
+///
+/// East Asian Wide/Fullwidth characters and the ambiguous-width symbols
+/// commonly rendered full-width in Japanese balloons (…, ※, ○, ★ etc.) count as 2.
+pub fn char_width(c: char) -> usize {
+    match c as u32 {
+        0x1100..=0x115F
+        | 0x2010..=0x206F
+        | 0x2190..=0x21FF
+        | 0x2460..=0x24FF
+        | 0x2500..=0x27BF
+        | 0x2E80..=0x303E
+        | 0x3041..=0x33FF
+        | 0x3400..=0x4DBF
+        | 0x4E00..=0x9FFF
+        | 0xA000..=0xA4CF
+        | 0xAC00..=0xD7A3
+        | 0xF900..=0xFAFF
+        | 0xFE30..=0xFE4F
+        | 0xFF00..=0xFF60
+        | 0xFFE0..=0xFFE6
+        | 0x1F300..=0x1F64F
+        | 0x1F900..=0x1F9FF
+        | 0x20000..=0x3FFFD => 2,
+        _ => 1,
+    }
+}
+
+/// Whether a sakura script tag starts a new balloon line.
+fn resets_column(tag: &str) -> bool {
+    let name = tag
+        .trim_start_matches('\\')
+        .split('[')
+        .next()
+        .unwrap_or_default();
+    matches!(name, "n" | "c" | "h" | "u" | "0" | "1")
+        || name
+            .strip_prefix('p')
+            .is_some_and(|rest| rest.chars().all(|c| c.is_ascii_digit()))
+}
+
+/// Whether a character must not start a line (行頭禁則).
+fn is_line_start_prohibited(c: char, char_sets: &CharSets) -> bool {
+    char_sets.line_start_prohibited.contains(&c)
+        || char_sets.period.contains(&c)
+        || char_sets.comma.contains(&c)
+        || char_sets.strong.contains(&c)
+}
+
+/// Character of a non-tag token.
+fn token_char(token: &Token) -> Option<char> {
+    if token.kind == TokenKind::SakuraScript {
+        return None;
+    }
+    token.text.chars().next()
+}
+
+/// Insert `\n` tokens so that lines fit in `width` columns.
+///
+/// # Arguments
+/// * `tokens` - Token sequence from the tokenizer
+/// * `width` - Line width in half-width columns (0 disables wrapping)
+/// * `column` - Column at which the text starts (text already on the line)
+/// * `char_sets` - Character sets for kinsoku
+///
+/// # Returns
+/// Wrapped tokens and the column after the last token
+pub fn wrap_tokens(
+    tokens: Vec<Token>,
+    width: usize,
+    column: usize,
+    char_sets: &CharSets,
+) -> (Vec<Token>, usize) {
+    let mut out: Vec<Token> = Vec::with_capacity(tokens.len());
+    let mut col = column;
+    let mut line_start = 0;
+    // 前のテキストが同じ行に出力済みか（column > 0 で開始した最初の行）
+    let mut carried = column > 0;
+
+    for token in tokens {
+        let Some(c) = token_char(&token) else {
+            if resets_column(&token.text) {
+                col = 0;
+                line_start = out.len() + 1;
+                carried = false;
+            }
+            out.push(token);
+            continue;
+        };
+
+        let w = char_width(c);
+        if width > 0 && col > 0 && col + w > width && !is_line_start_prohibited(c, char_sets) {
+            // 行末禁則: 行末の禁則文字を次の行へ送る（行が空になる場合は送らない）
+            let mut split = out.len();
+            while split > line_start
+                && token_char(&out[split - 1])
+                    .is_some_and(|prev| char_sets.line_end_prohibited.contains(&prev))
+            {
+                split -= 1;
+            }
+            if !carried
+                && !out[line_start..split]
+                    .iter()
+                    .any(|t| token_char(t).is_some())
+            {
+                split = out.len();
+            }
+
+            let moved = out.split_off(split);
+            out.push(Token::new(TokenKind::SakuraScript, r"\n"));
+            line_start = out.len();
+            carried = false;
+            col = moved.iter().filter_map(token_char).map(char_width).sum();
+            out.extend(moved);
+        }
+
+        out.push(token);
+        col += w;
+    }
+
+    (out, col)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::loader::TalkConfig;
+    use crate::sakura_script::tokenizer::Tokenizer;
+
+    fn wrap(input: &str, width: usize, column: usize) -> (String, usize) {
+        let config = TalkConfig::default();
+        let tokenizer = Tokenizer::new(&config).unwrap();
+        let (tokens, col) = wrap_tokens(
+            tokenizer.tokenize(input),
+            width,
+            column,
+            tokenizer.char_sets(),
+        );
+        (tokens.into_iter().map(|t| t.text).collect(), col)
+    }
+
+    #[test]
+    fn test_char_width() {
+        assert_eq!(char_width('a'), 1);
+        assert_eq!(char_width('ｱ'), 1);
+        assert_eq!(char_width('あ'), 2);
+        assert_eq!(char_width('漢'), 2);
+        assert_eq!(char_width('Ａ'), 2);
+        assert_eq!(char_width('…'), 2);
+    }
+
+    #[test]
+    fn test_wrap_disabled() {
+        assert_eq!(wrap("あいうえお", 0, 0), ("あいうえお".to_string(), 10));
+    }
+
+    #[test]
+    fn test_wrap_full_and_half_width() {
+        assert_eq!(wrap("あいうえお", 6, 0).0, r"あいう\nえお");
+        assert_eq!(wrap("abcdefgh", 6, 0).0, r"abcdef\ngh");
+        assert_eq!(wrap("aあいう", 6, 0).0, r"aあい\nう");
+    }
+
+    #[test]
+    fn test_wrap_skips_tags_and_resets_column() {
+        assert_eq!(wrap(r"あい\s[10]うえ", 6, 0).0, r"あい\s[10]う\nえ");
+        assert_eq!(wrap(r"あい\nうえお", 6, 0).0, r"あい\nうえお");
+        assert_eq!(wrap(r"あいう\p[1]えお", 6, 0).0, r"あいう\p[1]えお");
+    }
+
+    #[test]
+    fn test_wrap_starting_column() {
+        assert_eq!(wrap("あいう", 6, 4), (r"あ\nいう".to_string(), 4));
+    }
+
+    #[test]
+    fn test_hanging_punctuation() {
+        // 行頭禁則文字はぶら下げる
+        assert_eq!(wrap("あいう。えお", 6, 0).0, r"あいう。\nえお");
+        assert_eq!(wrap("あいう」。え", 6, 0).0, r"あいう」。\nえ");
+    }
+
+    #[test]
+    fn test_line_end_prohibited_moves_to_next_line() {
+        assert_eq!(wrap("あい「うえ」", 6, 0).0, r"あい\n「うえ」");
+        // 行が空になる場合は送らない
+        assert_eq!(wrap("「「「う", 6, 0).0, r"「「「\nう");
+        // 前のテキストが同じ行にあれば送る
+        assert_eq!(wrap("「う", 4, 2).0, r"\n「う");
+    }
+}
@@ -1,7 +1,11 @@
-//! Sakura Script Module - Wait insertion for talk text.
+//! Sakura Script Module - Wait insertion, parsing, validation and preview.
 //!
 //! This module provides the `@pasta_sakura_script` Lua module for inserting
-//! sakura script wait tags (`\_w[ms]`) into conversation text.
+//! sakura script wait tags (`\_w[ms]`) into conversation text (see
+//! [`wait_rules`] for the configurable rule engine), for
+//! parsing/validating sakura script (see [`parser`] and [`validator`]), for
+//! shrinking built scripts (see [`optimizer`]), and for rendering built
+//! scripts as readable previews (see [`render`]).
 //!
 //! # Usage from Lua
 //!
@@ -14,27 +18,65 @@
 //!
 //! -- With nil actor (uses pasta.toml defaults)
 //! local script = SAKURA.talk_to_script(nil, "こんにちは。")
+//!
+//! -- Line wrapping ([talk] wrap_width / actor.wrap_width) continuing from column 10
+//! local script, column = SAKURA.talk_to_script(actor, "こんにちは。", 10)
+//!
+//! -- Validation (empty table if no problems)
+//! local diagnostics = SAKURA.validate("\\h\\q[はい]\\e")
+//! -- → { { kind = "malformed_arguments", pos = 3, raw = "\\q[はい]", message = ... } }
+//!
+//! -- Shrink a built script without changing its timing
+//! local optimized = SAKURA.optimize(script)
+//!
+//! -- Preview (speaker names by scope number)
+//! local text = SAKURA.render(script, { names = { [0] = "さくら", [1] = "うにゅう" } })
 //! ```
 
+pub mod line_wrapper;
+pub mod optimizer;
+pub mod pacing;
+pub mod parser;
+pub mod render;
 pub mod tokenizer;
+pub mod validator;
 pub mod wait_inserter;
+pub mod wait_rules;
 
-use crate::loader::TalkConfig;
-use mlua::{Lua, Result as LuaResult, Table, Value};
-use std::sync::Arc;
+use crate::loader::{PacingConfig, TalkConfig, WaitRuleConfig};
+use mlua::{IntoLuaMulti, Lua, LuaSerdeExt, MultiValue, Result as LuaResult, Table, Value};
+use optimizer::OptimizeOptions;
+use pacing::Pacer;
+use parser::{Node, NodeKind, Toggle, Wait};
+use pasta_core::registry::{DefaultRandomSelector, MockRandomSelector, RandomSelector};
+use render::RenderOptions;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
 use tokenizer::Tokenizer;
+use validator::ValidationMode;
 use wait_inserter::WaitValues;
+use wait_rules::{WaitContext, WaitRule};
 
 /// Module version.
 const VERSION: &str = "1.0.0";
 
 /// Module description.
-const DESCRIPTION: &str = "Sakura Script wait insertion module for natural conversation tempo";
+const DESCRIPTION: &str =
+    "Sakura Script wait insertion, parsing and validation module for natural conversation tempo";
 
 /// Internal state for the sakura script module.
 struct SakuraScriptState {
     tokenizer: Tokenizer,
     default_wait_values: WaitValues,
+    default_wrap_width: i64,
+    /// Compiled `[talk] wait_rules`
+    config_wait_rules: Vec<WaitRule>,
+    /// Compiled actor `wait_rules` (compiled once per distinct rule)
+    actor_wait_rules: Mutex<HashMap<WaitRuleConfig, WaitRule>>,
+    /// `[talk.pacing]` defaults
+    default_pacing: PacingConfig,
+    /// Random selector for pacing variance (replaceable for tests)
+    pacing_selector: Mutex<Box<dyn RandomSelector>>,
 }
 
 /// Register the `@pasta_sakura_script` module to Lua.
@@ -44,7 +86,8 @@ struct SakuraScriptState {
 /// * `config` - TalkConfig (uses hardcoded defaults if None)
 ///
 /// # Returns
-/// Module table containing `talk_to_script` function
+/// Module table containing `talk_to_script`, `set_pacing_selector`, `optimize`,
+/// `parse`, `validate`, `render` and `render_html` functions
 pub fn register(lua: &Lua, config: Option<&TalkConfig>) -> LuaResult<Table> {
     let config = config.cloned().unwrap_or_default();
 
@@ -55,10 +98,21 @@ pub fn register(lua: &Lua, config: Option<&TalkConfig>) -> LuaResult<Table> {
 
     let default_wait_values = WaitValues::from_config(&config);
 
+    let config_wait_rules = config
+        .wait_rules
+        .iter()
+        .map(|rule| compile_wait_rule(rule, "[talk] wait_rules"))
+        .collect::<LuaResult<Vec<_>>>()?;
+
     // Store state in Arc for sharing with closure
     let state = Arc::new(SakuraScriptState {
         tokenizer,
         default_wait_values,
+        default_wrap_width: config.wrap_width,
+        config_wait_rules,
+        actor_wait_rules: Mutex::new(HashMap::new()),
+        pacing_selector: Mutex::new(pacing_selector(config.pacing.seed)),
+        default_pacing: config.pacing,
     });
 
     // Create module table
@@ -70,27 +124,184 @@ pub fn register(lua: &Lua, config: Option<&TalkConfig>) -> LuaResult<Table> {
 
     // Create talk_to_script function
     let state_clone = Arc::clone(&state);
-    let talk_to_script =
-        lua.create_function(move |lua, (actor, talk): (Value, Option<String>)| {
-            talk_to_script_impl(lua, &state_clone, actor, talk)
-        })?;
+    let talk_to_script = lua.create_function(
+        move |lua, (actor, talk, column): (Value, Option<String>, Option<usize>)| {
+            talk_to_script_impl(lua, &state_clone, actor, talk, column)
+        },
+    )?;
 
     module.set("talk_to_script", talk_to_script)?;
 
+    // set_pacing_selector(n1, n2, ...) or set_pacing_selector() to reset
+    let state_clone = Arc::clone(&state);
+    let set_pacing_selector = lua.create_function(move |_, args: MultiValue| {
+        let selector: Box<dyn RandomSelector> = if args.is_empty() {
+            pacing_selector(state_clone.default_pacing.seed)
+        } else {
+            let sequence: Result<Vec<usize>, _> = args
+                .iter()
+                .map(|v| {
+                    v.as_integer()
+                        .ok_or_else(|| {
+                            mlua::Error::RuntimeError("expected integer argument".into())
+                        })
+                        .map(|i| i as usize)
+                })
+                .collect();
+            Box::new(MockRandomSelector::new(sequence?))
+        };
+        *lock(&state_clone.pacing_selector)? = selector;
+        Ok(())
+    })?;
+    module.set("set_pacing_selector", set_pacing_selector)?;
+    let state_clone = Arc::clone(&state);
+    module.set(
+        "optimize",
+        lua.create_function(move |_, (script, opts): (String, Option<Table>)| {
+            optimize_impl(&state_clone, &script, opts)
+        })?,
+    )?;
+    module.set("parse", lua.create_function(parse_impl)?)?;
+    module.set("validate", lua.create_function(validate_impl)?)?;
+    module.set(
+        "render",
+        lua.create_function(|_, (script, opts): (String, Option<Table>)| {
+            render_impl(&script, opts, render::render_text)
+        })?,
+    )?;
+    module.set(
+        "render_html",
+        lua.create_function(|_, (script, opts): (String, Option<Table>)| {
+            render_impl(&script, opts, render::render_html)
+        })?,
+    )?;
+
     Ok(module)
 }
 
+/// Implementation of parse function.
+///
+/// Returns an array of node tables, or `nil, error message` on a lexical error.
+fn parse_impl(lua: &Lua, script: String) -> LuaResult<(Value, Option<String>)> {
+    match parser::parse(&script) {
+        Ok(nodes) => {
+            let list = lua.create_table()?;
+            for node in &nodes {
+                list.push(node_to_table(lua, &script, node)?)?;
+            }
+            Ok((Value::Table(list), None))
+        }
+        Err(e) => Ok((Value::Nil, Some(e.to_string()))),
+    }
+}
+
+/// Convert a parsed node to a Lua table.
+///
+/// Common fields: `type`, `pos` (1-based byte offset) and `raw` (source text).
+fn node_to_table(lua: &Lua, script: &str, node: &Node) -> LuaResult<Table> {
+    let t = lua.create_table()?;
+    t.set("pos", node.range.start + 1)?;
+    t.set("raw", &script[node.range.clone()])?;
+    let kind = match &node.kind {
+        NodeKind::Text(text) => {
+            t.set("text", text.as_str())?;
+            "text"
+        }
+        NodeKind::Scope(scope) => {
+            t.set("scope", *scope)?;
+            "scope"
+        }
+        NodeKind::Surface(id) => {
+            t.set("id", id.as_str())?;
+            "surface"
+        }
+        NodeKind::Wait(wait) => {
+            match wait {
+                Wait::Short(n) => t.set("ms", n * 50)?,
+                Wait::Millis(ms) => t.set("ms", *ms)?,
+                Wait::Until(arg) => t.set("until", arg.as_str())?,
+                Wait::Animation(id) => t.set("animation", id.as_str())?,
+            }
+            "wait"
+        }
+        NodeKind::Choice(choice) => {
+            t.set("title", choice.title.as_str())?;
+            t.set("id", choice.id.as_str())?;
+            t.set(
+                "args",
+                lua.create_sequence_from(choice.args.iter().cloned())?,
+            )?;
+            "choice"
+        }
+        NodeKind::Command { name, args } => {
+            t.set("name", name.as_str())?;
+            t.set("args", lua.create_sequence_from(args.iter().cloned())?)?;
+            "command"
+        }
+        NodeKind::Toggle(Toggle::Quick) => {
+            t.set("name", "_q")?;
+            "toggle"
+        }
+        NodeKind::Toggle(Toggle::Sync(args)) => {
+            t.set("name", "_s")?;
+            t.set("args", lua.create_sequence_from(args.iter().cloned())?)?;
+            "toggle"
+        }
+        NodeKind::End => "end",
+        NodeKind::Tag(tag) => {
+            t.set("name", tag.name.as_str())?;
+            if let Some(args) = &tag.args {
+                t.set("args", lua.create_sequence_from(args.iter().cloned())?)?;
+            }
+            "tag"
+        }
+    };
+    t.set("type", kind)?;
+    Ok(t)
+}
+
+/// Implementation of validate function.
+///
+/// `opts.fragment = true` skips the toggle balance and `\e` checks.
+fn validate_impl(lua: &Lua, (script, opts): (String, Option<Table>)) -> LuaResult<Table> {
+    let fragment = match &opts {
+        Some(opts) => opts.get::<Option<bool>>("fragment")?.unwrap_or(false),
+        None => false,
+    };
+    let mode = if fragment {
+        ValidationMode::Fragment
+    } else {
+        ValidationMode::Script
+    };
+
+    let list = lua.create_table()?;
+    for diagnostic in validator::validate(&script, mode) {
+        let t = lua.create_table()?;
+        t.set("kind", diagnostic.kind.as_str())?;
+        t.set("pos", diagnostic.range.start + 1)?;
+        t.set("raw", &script[diagnostic.range])?;
+        t.set("message", diagnostic.message)?;
+        list.push(t)?;
+    }
+    Ok(list)
+}
+
 /// Implementation of talk_to_script function.
+///
+/// When `column` (the column at which the talk starts) is given, the column
+/// after the talk is returned as a second value so that callers can continue
+/// wrapping across talk fragments. Without it, only the script is returned.
 fn talk_to_script_impl(
     lua: &Lua,
     state: &SakuraScriptState,
     actor: Value,
     talk: Option<String>,
-) -> LuaResult<String> {
+    column: Option<usize>,
+) -> LuaResult<MultiValue> {
     // Requirement 6.1: Return empty string for nil or empty input
     let talk = match talk {
         Some(s) if !s.is_empty() => s,
-        _ => return Ok(String::new()),
+        _ => return with_column(lua, String::new(), column, column),
     };
 
     // Resolve wait values from actor table or use defaults
@@ -99,10 +310,129 @@ fn talk_to_script_impl(
     // Tokenize the input
     let tokens = state.tokenizer.tokenize(&talk);
 
-    // Insert waits and return result
-    let result = wait_inserter::insert_waits(&tokens, &wait_values);
+    // Wrap lines (no-op if wrap width is 0)
+    let width = resolve_wrap_width(&actor, state.default_wrap_width);
+    let (tokens, end_column) = line_wrapper::wrap_tokens(
+        tokens,
+        width,
+        column.unwrap_or(0),
+        state.tokenizer.char_sets(),
+    );
 
-    Ok(result)
+    // Insert waits: actor rules -> [talk] rules -> character class defaults
+    let rules = resolve_wait_rules(lua, state, &actor, &wait_values)?;
+    let emotion = match &actor {
+        Value::Table(t) => t.get::<Option<String>>("emotion").ok().flatten(),
+        _ => None,
+    };
+    let context = WaitContext {
+        emotion: emotion.as_deref(),
+    };
+    let pacing = resolve_pacing(&actor, &state.default_pacing)?;
+    let mut selector = lock(&state.pacing_selector)?;
+    let mut pacer = Pacer::new(pacing, Some(selector.as_mut()));
+    let result = wait_rules::apply_rules(&tokens, &rules, wait_values.base, &context, &mut pacer);
+
+    with_column(lua, result, column, Some(end_column))
+}
+
+/// Return the script, plus the end column if the caller passed a start column.
+fn with_column(
+    lua: &Lua,
+    script: String,
+    column: Option<usize>,
+    end_column: Option<usize>,
+) -> LuaResult<MultiValue> {
+    match column {
+        Some(_) => (script, end_column).into_lua_multi(lua),
+        None => script.into_lua_multi(lua),
+    }
+}
+
+/// Resolve wrap width from actor table with fallback to config.
+fn resolve_wrap_width(actor: &Value, default: i64) -> usize {
+    let width = match actor {
+        Value::Table(t) => t.get::<i64>("wrap_width").ok().unwrap_or(default),
+        _ => default,
+    };
+    width.max(0) as usize
+}
+
+/// Implementation of render / render_html functions.
+///
+/// Returns the rendered string, or `nil, error message` on a lexical error.
+fn render_impl(
+    script: &str,
+    opts: Option<Table>,
+    render: fn(&str, &RenderOptions) -> Result<String, parser::ParseError>,
+) -> LuaResult<(Option<String>, Option<String>)> {
+    let options = render_options(opts)?;
+    match render(script, &options) {
+        Ok(out) => Ok((Some(out), None)),
+        Err(e) => Ok((None, Some(e.to_string()))),
+    }
+}
+
+/// Implementation of optimize function.
+///
+/// Returns the optimized script, or `nil, error message` on a lexical error.
+/// `char_wait` defaults to `[talk] script_wait_base`.
+fn optimize_impl(
+    state: &SakuraScriptState,
+    script: &str,
+    opts: Option<Table>,
+) -> LuaResult<(Option<String>, Option<String>)> {
+    let mut options = OptimizeOptions {
+        char_wait_ms: state.default_wait_values.base.max(0) as u64,
+        ..Default::default()
+    };
+    if let Some(opts) = opts {
+        if let Some(ms) = opts.get::<Option<u64>>("char_wait")? {
+            options.char_wait_ms = ms;
+        }
+        if let Some(enabled) = opts.get::<Option<bool>>("balloon_wait")? {
+            options.balloon_wait = enabled;
+        }
+    }
+    match optimizer::optimize(script, &options) {
+        Ok(out) => Ok((Some(out), None)),
+        Err(e) => Ok((None, Some(e.to_string()))),
+    }
+}
+
+/// Build RenderOptions from a Lua options table.
+///
+/// `names` accepts both `{ [0] = "さくら" }` and the `STORE.actor_spots`
+/// form `{ さくら = 0 }`.
+fn render_options(opts: Option<Table>) -> LuaResult<RenderOptions> {
+    let mut options = RenderOptions::default();
+    let Some(opts) = opts else {
+        return Ok(options);
+    };
+    if let Some(names) = opts.get::<Option<Table>>("names")? {
+        for pair in names.pairs::<Value, Value>() {
+            match pair? {
+                (Value::Integer(scope), Value::String(name)) => {
+                    options
+                        .names
+                        .insert(scope as u32, name.to_str()?.to_string());
+                }
+                (Value::String(name), Value::Integer(scope)) => {
+                    options
+                        .names
+                        .insert(scope as u32, name.to_str()?.to_string());
+                }
+                _ => {}
+            }
+        }
+    }
+    if let Some(ms) = opts.get::<Option<u64>>("wait_threshold")? {
+        options.wait_threshold_ms = ms;
+    }
+    if let Some(ms) = opts.get::<Option<u64>>("char_wait")? {
+        options.char_wait_ms = ms;
+    }
+    Ok(options)
 }
 
 /// Resolve wait values from actor table with fallback to defaults.
@@ -125,6 +455,88 @@ fn resolve_wait_values(_lua: &Lua, actor: &Value, defaults: &WaitValues) -> LuaR
         comma: get_wait("script_wait_comma", defaults.comma),
         strong: get_wait("script_wait_strong", defaults.strong),
         leader: get_wait("script_wait_leader", defaults.leader),
+        base: get_wait("script_wait_base", defaults.base),
+    })
+}
+
+/// Resolve the ordered wait rule list for an actor.
+///
+/// Order: actor `wait_rules` -> `[talk] wait_rules` -> character class defaults
+fn resolve_wait_rules(
+    lua: &Lua,
+    state: &SakuraScriptState,
+    actor: &Value,
+    wait_values: &WaitValues,
+) -> LuaResult<Vec<WaitRule>> {
+    let mut rules = Vec::new();
+
+    if let Value::Table(actor_table) = actor
+        && let Some(actor_rules) = actor_table.get::<Option<Table>>("wait_rules")?
+    {
+        let mut cache = lock(&state.actor_wait_rules)?;
+        for value in actor_rules.sequence_values::<Value>() {
+            let config: WaitRuleConfig = lua.from_value(value?)?;
+            if let Some(rule) = cache.get(&config) {
+                rules.push(rule.clone());
+                continue;
+            }
+            let rule = compile_wait_rule(&config, "actor wait_rules")?;
+            cache.insert(config, rule.clone());
+            rules.push(rule);
+        }
+    }
+
+    rules.extend(state.config_wait_rules.iter().cloned());
+    rules.extend(wait_rules::default_rules(wait_values));
+    Ok(rules)
+}
+
+/// Resolve pacing from the actor's `pacing` table with fallback to `[talk.pacing]`.
+fn resolve_pacing(actor: &Value, defaults: &PacingConfig) -> LuaResult<PacingConfig> {
+    let Value::Table(actor_table) = actor else {
+        return Ok(defaults.clone());
+    };
+    let Some(pacing) = actor_table.get::<Option<Table>>("pacing")? else {
+        return Ok(defaults.clone());
+    };
+    let get_percent =
+        |key: &str, default: i64| -> i64 { pacing.get::<i64>(key).ok().unwrap_or(default) };
+
+    Ok(PacingConfig {
+        variance: get_percent("variance", defaults.variance),
+        burst: get_percent("burst", defaults.burst),
+        clause_slowdown: get_percent("clause_slowdown", defaults.clause_slowdown),
+        merge_waits: pacing
+            .get::<Option<bool>>("merge_waits")
+            .ok()
+            .flatten()
+            .unwrap_or(defaults.merge_waits),
+        seed: defaults.seed,
+    })
+}
+
+/// Random selector for pacing variance (seeded if `[talk.pacing] seed` is set).
+fn pacing_selector(seed: Option<u64>) -> Box<dyn RandomSelector> {
+    match seed {
+        Some(seed) => Box::new(DefaultRandomSelector::with_seed(seed)),
+        None => Box::new(DefaultRandomSelector::new()),
+    }
+}
+
+/// Lock module state, mapping poisoning to a Lua error.
+fn lock<T>(mutex: &Mutex<T>) -> LuaResult<std::sync::MutexGuard<'_, T>> {
+    mutex
+        .lock()
+        .map_err(|_| mlua::Error::external("sakura script state is poisoned"))
+}
+
+/// Compile a wait rule, reporting regex errors with the rule's origin.
+fn compile_wait_rule(rule: &WaitRuleConfig, origin: &str) -> LuaResult<WaitRule> {
+    WaitRule::compile(rule).map_err(|e| {
+        mlua::Error::external(format!(
+            "Failed to compile {} pattern {:?}: {}",
+            origin, rule.pattern, e
+        ))
     })
 }
 
@@ -141,6 +553,10 @@ mod tests {
         assert!(module.contains_key("_VERSION").unwrap());
         assert!(module.contains_key("_DESCRIPTION").unwrap());
         assert!(module.contains_key("talk_to_script").unwrap());
+        assert!(module.contains_key("set_pacing_selector").unwrap());
+        assert!(module.contains_key("optimize").unwrap());
+        assert!(module.contains_key("parse").unwrap());
+        assert!(module.contains_key("validate").unwrap());
     }
 
     #[test]
@@ -0,0 +1,347 @@
+//! Output optimizer for built sakura script.
+//!
+//! Shrinks a built script without changing when anything is displayed
+//! (as modelled by [`super::render::timeline`]):
+//!
+//! 1. No-op scope switches (to the current scope, or immediately followed by
+//!    another switch) are removed
+//! 2. No-op surface changes (to the surface the scope already shows, or
+//!    immediately replaced by another surface) are removed
+//! 3. Adjacent waits (`\w1`-`\w9`, `\_w[ms]`, `\w[ms]`) are merged into one `\_w[ms]`
+//! 4. Runs of characters that each carry a `\_w[ms]` wait are rewritten to a
+//!    `\![set,balloonwait,x]` speed setting for the run (longer waits keep the
+//!    difference as `\_w[ms]`), when that is shorter. The balloonwait in effect
+//!    before the run (the last one set in the script, or 1) is restored after it
+
+use super::parser::{self, Node, NodeKind, ParseError, Toggle, Wait};
+use std::collections::HashMap;
+
+/// Optimizer options.
+#[derive(Debug, Clone)]
+pub struct OptimizeOptions {
+    /// Baseware display time per character (balloonwait 1.0)
+    pub char_wait_ms: u64,
+    /// Rewrite per-character waits as `\![set,balloonwait,x]`
+    pub balloon_wait: bool,
+}
+
+impl Default for OptimizeOptions {
+    fn default() -> Self {
+        Self {
+            char_wait_ms: 50,
+            balloon_wait: true,
+        }
+    }
+}
+
+/// Script piece: parsed node with its source text.
+#[derive(Debug, Clone)]
+struct Piece {
+    kind: NodeKind,
+    raw: String,
+}
+
+/// Optimize a built script.
+///
+/// # Returns
+/// * `Ok(String)` - Optimized script
+/// * `Err(ParseError)` - The script has a lexical error (nothing is changed)
+pub fn optimize(script: &str, options: &OptimizeOptions) -> Result<String, ParseError> {
+    let pieces: Vec<Piece> = parser::parse(script)?
+        .into_iter()
+        .map(|Node { kind, range }| Piece {
+            kind,
+            raw: script[range].to_string(),
+        })
+        .collect();
+
+    let pieces = remove_noop_switches(pieces);
+    let pieces = merge_waits(pieces);
+    let out: String = if options.balloon_wait && options.char_wait_ms > 0 {
+        balloon_wait_runs(&pieces, options.char_wait_ms)
+    } else {
+        pieces.iter().map(|p| p.raw.as_str()).collect()
+    };
+    Ok(out)
+}
+
+/// Remove scope switches and surface changes that have no visible effect.
+fn remove_noop_switches(pieces: Vec<Piece>) -> Vec<Piece> {
+    let mut out: Vec<Piece> = Vec::with_capacity(pieces.len());
+    let mut scope: Option<u32> = None;
+    let mut surfaces: HashMap<u32, String> = HashMap::new();
+    // 直前に出力した切り替えの前の状態（上書きで取り消すときに戻す）
+    let mut scope_before: Option<u32> = None;
+    let mut surface_before: Option<String> = None;
+
+    for piece in pieces {
+        match &piece.kind {
+            NodeKind::Scope(next) => {
+                // 直前のスコープ切り替えは何も表示せずに上書きされる
+                if let Some(NodeKind::Scope(_)) = out.last().map(|p| &p.kind) {
+                    out.pop();
+                    scope = scope_before;
+                }
+                if scope == Some(*next) {
+                    continue;
+                }
+                scope_before = scope;
+                scope = Some(*next);
+            }
+            NodeKind::Surface(id) => {
+                let current = scope.unwrap_or(0);
+                // 直前の同じスコープのサーフェス変更は表示されずに上書きされる
+                if let Some(NodeKind::Surface(_)) = out.last().map(|p| &p.kind) {
+                    out.pop();
+                    match surface_before.take() {
+                        Some(before) => surfaces.insert(current, before),
+                        None => surfaces.remove(&current),
+                    };
+                }
+                if surfaces.get(&current) == Some(id) {
+                    continue;
+                }
+                surface_before = surfaces.insert(current, id.clone());
+            }
+            // シェル切り替えなどでサーフェスが変わりうるため、設定系以外のコマンドで追跡をやめる
+            NodeKind::Command { name, .. } if name != "set" => surfaces.clear(),
+            _ => {}
+        }
+        out.push(piece);
+    }
+    out
+}
+
+/// Milliseconds of a relative wait.
+fn relative_wait(kind: &NodeKind) -> Option<u64> {
+    match kind {
+        NodeKind::Wait(Wait::Short(n)) => Some(u64::from(*n) * 50),
+        NodeKind::Wait(Wait::Millis(ms)) => Some(*ms),
+        _ => None,
+    }
+}
+
+/// Wait piece for the given milliseconds.
+fn wait_piece(ms: u64) -> Piece {
+    Piece {
+        kind: NodeKind::Wait(Wait::Millis(ms)),
+        raw: format!(r"\_w[{ms}]"),
+    }
+}
+
+/// Merge adjacent relative waits into one `\_w[ms]`.
+fn merge_waits(pieces: Vec<Piece>) -> Vec<Piece> {
+    let mut out: Vec<Piece> = Vec::with_capacity(pieces.len());
+    for piece in pieces {
+        if let Some(ms) = relative_wait(&piece.kind)
+            && let Some(previous) = out.last().and_then(|p| relative_wait(&p.kind))
+        {
+            *out.last_mut().unwrap() = wait_piece(previous + ms);
+            continue;
+        }
+        out.push(piece);
+    }
+    out
+}
+
+/// Format a balloonwait factor given in percent (`200` → `2`, `150` → `1.5`).
+fn factor(percent: u64) -> String {
+    let text = format!("{}.{:02}", percent / 100, percent % 100);
+    text.trim_end_matches('0').trim_end_matches('.').to_string()
+}
+
+/// balloonwait in effect: factor in percent and its text as written.
+#[derive(Debug, Clone)]
+struct BalloonWait {
+    percent: u64,
+    text: String,
+}
+
+impl BalloonWait {
+    /// Parse a `\![set,balloonwait,x]` command (same rule as the timeline).
+    fn from_command(kind: &NodeKind) -> Option<Self> {
+        let NodeKind::Command { name, args } = kind else {
+            return None;
+        };
+        if name != "set" || args.first().is_none_or(|a| a != "balloonwait") {
+            return None;
+        }
+        let text = args.get(1)?.trim();
+        let x = text.parse::<f64>().ok()?;
+        Some(Self {
+            percent: (x.max(0.0) * 100.0).round() as u64,
+            text: text.to_string(),
+        })
+    }
+}
+
+impl Default for BalloonWait {
+    fn default() -> Self {
+        Self {
+            percent: 100,
+            text: "1".to_string(),
+        }
+    }
+}
+
+/// Rewrite runs of "character + wait" as balloonwait sections.
+fn balloon_wait_runs(pieces: &[Piece], char_wait_ms: u64) -> String {
+    let mut out = String::new();
+    let mut quick = false;
+    let mut balloon_wait = BalloonWait::default();
+    let mut i = 0;
+
+    while i < pieces.len() {
+        if let NodeKind::Toggle(Toggle::Quick) = pieces[i].kind {
+            quick = !quick;
+        }
+        if let Some(set) = BalloonWait::from_command(&pieces[i].kind) {
+            balloon_wait = set;
+        }
+        if quick {
+            out.push_str(&pieces[i].raw);
+            i += 1;
+            continue;
+        }
+
+        // 「1文字 + 相対ウェイト」が連続する区間を探す
+        let mut end = i;
+        let mut waits = Vec::new();
+        while let (Some(text), Some(wait)) = (pieces.get(end), pieces.get(end + 1)) {
+            let single_char = matches!(&text.kind, NodeKind::Text(t) if t.chars().count() == 1);
+            match relative_wait(&wait.kind) {
+                Some(ms) if single_char && ms > 0 => {
+                    waits.push(ms);
+                    end += 2;
+                }
+                _ => break,
+            }
+        }
+
+        if let Some(rewritten) = rewrite_run(&pieces[i..end], &waits, char_wait_ms, &balloon_wait) {
+            out.push_str(&rewritten);
+            i = end;
+        } else {
+            out.push_str(&pieces[i].raw);
+            i += 1;
+        }
+    }
+    out
+}
+
+/// Rewrite a run of (character, wait) pairs, if the result is shorter.
+///
+/// `current` is the balloonwait in effect before the run; it is raised for
+/// the run and restored afterwards.
+fn rewrite_run(
+    run: &[Piece],
+    waits: &[u64],
+    char_wait_ms: u64,
+    current: &BalloonWait,
+) -> Option<String> {
+    if waits.len() < 2 {
+        return None;
+    }
+    let base = *waits.iter().min()?;
+    // 倍率は小数点以下2桁で正確に表せる場合のみ
+    if !(base * 100).is_multiple_of(char_wait_ms) {
+        return None;
+    }
+    let percent = current.percent + base * 100 / char_wait_ms;
+
+    let mut rewritten = format!(r"\![set,balloonwait,{}]", factor(percent));
+    for (pair, ms) in run.chunks(2).zip(waits) {
+        rewritten.push_str(&pair[0].raw);
+        if *ms > base {
+            rewritten.push_str(&wait_piece(ms - base).raw);
+        }
+    }
+    rewritten.push_str(&format!(r"\![set,balloonwait,{}]", current.text));
+
+    let original: usize = run.iter().map(|p| p.raw.len()).sum();
+    (rewritten.len() < original).then_some(rewritten)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn opt(script: &str) -> String {
+        optimize(script, &OptimizeOptions::default()).unwrap()
+    }
+
+    fn opt_without_balloon_wait(script: &str) -> String {
+        let options = OptimizeOptions {
+            balloon_wait: false,
+            ..Default::default()
+        };
+        optimize(script, &options).unwrap()
+    }
+
+    #[test]
+    fn test_remove_noop_scope_switches() {
+        assert_eq!(opt(r"\p[0]あ\0い\p[1]\p[0]う\e"), r"\p[0]あいう\e");
+        assert_eq!(opt(r"\0あ\1\p[2]い\e"), r"\0あ\p[2]い\e");
+        assert_eq!(opt(r"\0あ\1い\h\e"), r"\0あ\1い\h\e");
+    }
+
+    #[test]
+    fn test_remove_noop_surfaces() {
+        assert_eq!(
+            opt(r"\0\s[0]あ\s[0]い\1\s[10]\0\s0う\e"),
+            r"\0\s[0]あい\1\s[10]\0う\e"
+        );
+        // 直後に上書きされるサーフェス
+        assert_eq!(opt(r"\0\s[0]\s[5]あ\e"), r"\0\s[5]あ\e");
+        assert_eq!(opt(r"\0\s[0]あ\s[5]\s[0]い\e"), r"\0\s[0]あい\e");
+        // コマンドの後は追跡しない
+        assert_eq!(
+            opt(r"\0\s[0]\![change,shell,別]\s[0]\e"),
+            r"\0\s[0]\![change,shell,別]\s[0]\e"
+        );
+    }
+
+    #[test]
+    fn test_merge_adjacent_waits() {
+        assert_eq!(
+            opt_without_balloon_wait(r"あ\_w[300]\w5\w[200]い\__w[clear]\_w[50]\e"),
+            r"あ\_w[750]い\__w[clear]\_w[50]\e"
+        );
+    }
+
+    #[test]
+    fn test_balloon_wait_runs() {
+        assert_eq!(
+            opt(
+                r"お\_w[50]は\_w[50]よ\_w[50]う\_w[50]ご\_w[50]ざ\_w[50]い\_w[50]ま\_w[50]す\_w[50]。\_w[950]\e"
+            ),
+            r"\![set,balloonwait,2]おはようございます。\_w[900]\![set,balloonwait,1]\e"
+        );
+        // 短くならない場合はそのまま
+        assert_eq!(opt(r"あ\_w[50]い\_w[50]\e"), r"あ\_w[50]い\_w[50]\e");
+    }
+
+    #[test]
+    fn test_balloon_wait_runs_restore_previous_setting() {
+        // スクリプト中で設定済みの balloonwait を基準にし、区間の後はその値に戻す
+        assert_eq!(
+            opt(
+                r"\![set,balloonwait,0.5]あ\_w[50]い\_w[50]う\_w[50]え\_w[50]お\_w[50]か\_w[50]き\_w[50]く\_w[50]け\_w[50]こ\_w[50]\e"
+            ),
+            r"\![set,balloonwait,0.5]\![set,balloonwait,1.5]あいうえおかきくけこ\![set,balloonwait,0.5]\e"
+        );
+    }
+
+    #[test]
+    fn test_balloon_wait_skips_quick_sections() {
+        let script = r"\_qあ\_w[50]い\_w[50]う\_w[50]え\_w[50]お\_w[50]\_q\e";
+        assert_eq!(opt(script), script);
+    }
+
+    #[test]
+    fn test_factor() {
+        assert_eq!(factor(200), "2");
+        assert_eq!(factor(150), "1.5");
+        assert_eq!(factor(125), "1.25");
+    }
+}
@@ -0,0 +1,169 @@
+//! Pacing variance for per-character waits.
+//!
+//! Makes the typewriter rhythm of per-character waits less uniform:
+//!
+//! - **burst**: characters inside a word (followed by another general
+//!   character) wait shorter
+//! - **clause_slowdown**: the character before a period or comma waits longer
+//! - **variance**: every per-character wait is scaled by a random percentage
+//!   drawn from a [`RandomSelector`], so a mock or seeded selector makes the
+//!   output deterministic
+//!
+//! Adjacent `\_w[ms]` tags can also be merged to keep the script short.
+
+use crate::loader::PacingConfig;
+use pasta_core::registry::RandomSelector;
+
+/// Position of a character relative to words and clauses.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Position {
+    /// Followed by another general character
+    pub inside_word: bool,
+    /// Followed by a period or comma
+    pub before_clause: bool,
+}
+
+/// Applies a [`PacingConfig`] to per-character waits.
+pub struct Pacer<'a> {
+    config: PacingConfig,
+    selector: Option<&'a mut dyn RandomSelector>,
+}
+
+impl<'a> Pacer<'a> {
+    /// Create a pacer. Without a selector, `variance` is ignored.
+    pub fn new(config: PacingConfig, selector: Option<&'a mut dyn RandomSelector>) -> Self {
+        Self { config, selector }
+    }
+
+    /// Pacer that leaves waits unchanged.
+    pub fn none() -> Self {
+        Self::new(PacingConfig::default(), None)
+    }
+
+    /// Whether adjacent wait tags are merged.
+    pub fn merge_waits(&self) -> bool {
+        self.config.merge_waits
+    }
+
+    /// Adjust an effective per-character wait (milliseconds, > 0).
+    pub fn adjust(&mut self, wait: i64, position: Position) -> i64 {
+        let mut percent = 100;
+        if position.inside_word {
+            percent -= self.config.burst;
+        }
+        if position.before_clause {
+            percent += self.config.clause_slowdown;
+        }
+
+        let variance = self.config.variance.clamp(0, 100);
+        if variance > 0
+            && let Some(selector) = self.selector.as_deref_mut()
+            && let Some(index) = selector.select_index((variance * 2 + 1) as usize)
+        {
+            percent = percent * (100 - variance + index as i64) / 100;
+        }
+
+        wait * percent.max(0) / 100
+    }
+}
+
+/// Append a `\_w[ms]` tag, merging it into a directly preceding one if requested.
+pub fn push_wait(result: &mut String, wait: i64, merge: bool) {
+    if merge && let Some((start, previous)) = trailing_wait(result) {
+        result.truncate(start);
+        result.push_str(&format!(r"\_w[{}]", previous + wait));
+    } else {
+        result.push_str(&format!(r"\_w[{}]", wait));
+    }
+}
+
+/// Parse a `\_w[ms]` tag (milliseconds only).
+pub fn parse_wait(tag: &str) -> Option<i64> {
+    let ms = tag.strip_prefix(r"\_w[")?.strip_suffix(']')?;
+    if ms.is_empty() || !ms.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    ms.parse().ok()
+}
+
+/// Start offset and milliseconds of a `\_w[ms]` tag at the end of `script`.
+fn trailing_wait(script: &str) -> Option<(usize, i64)> {
+    let start = script.rfind(r"\_w[")?;
+    // 直前の `\` が奇数個ならエスケープされた文字列（`\\_w[..]`）なので対象外
+    let backslashes = script[..start]
+        .bytes()
+        .rev()
+        .take_while(|&b| b == b'\\')
+        .count();
+    if backslashes % 2 == 1 {
+        return None;
+    }
+    parse_wait(&script[start..]).map(|ms| (start, ms))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pasta_core::registry::MockRandomSelector;
+
+    fn config(variance: i64, burst: i64, clause_slowdown: i64) -> PacingConfig {
+        PacingConfig {
+            variance,
+            burst,
+            clause_slowdown,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_burst_and_clause_slowdown() {
+        let mut pacer = Pacer::new(config(0, 40, 50), None);
+        let inside = Position {
+            inside_word: true,
+            before_clause: false,
+        };
+        let before = Position {
+            inside_word: false,
+            before_clause: true,
+        };
+        assert_eq!(pacer.adjust(100, inside), 60);
+        assert_eq!(pacer.adjust(100, before), 150);
+        assert_eq!(pacer.adjust(100, Position::default()), 100);
+    }
+
+    #[test]
+    fn test_variance_uses_selector() {
+        // variance 20 → 80%..120%（インデックス 0..=40）
+        let mut selector = MockRandomSelector::new(vec![0, 20, 40]);
+        let mut pacer = Pacer::new(config(20, 0, 0), Some(&mut selector));
+        assert_eq!(pacer.adjust(100, Position::default()), 80);
+        assert_eq!(pacer.adjust(100, Position::default()), 100);
+        assert_eq!(pacer.adjust(100, Position::default()), 120);
+    }
+
+    #[test]
+    fn test_variance_without_selector() {
+        let mut pacer = Pacer::new(config(20, 0, 0), None);
+        assert_eq!(pacer.adjust(100, Position::default()), 100);
+    }
+
+    #[test]
+    fn test_push_wait_merge() {
+        let mut script = r"あ\_w[100]".to_string();
+        push_wait(&mut script, 50, true);
+        assert_eq!(script, r"あ\_w[150]");
+        push_wait(&mut script, 50, false);
+        assert_eq!(script, r"あ\_w[150]\_w[50]");
+
+        let mut script = r"\\_w[100]".to_string();
+        push_wait(&mut script, 50, true);
+        assert_eq!(script, r"\\_w[100]\_w[50]");
+    }
+
+    #[test]
+    fn test_parse_wait() {
+        assert_eq!(parse_wait(r"\_w[300]"), Some(300));
+        assert_eq!(parse_wait(r"\_w[abc]"), None);
+        assert_eq!(parse_wait(r"\w[300]"), None);
+    }
+}
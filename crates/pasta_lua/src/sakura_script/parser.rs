@@ -0,0 +1,482 @@
+//! Parser for sakura script.
+//!
+//! Parses sakura script text into a flat sequence of [`Node`]s.
+//! Tag arguments follow the escape rules of spec 12.16:
+//!
+//! - `\]` inside brackets is a literal `]`
+//! - An argument starting with `"` is quoted; `,` and `]` lose their meaning
+//!   inside quotes and `""` is a literal `"` (e.g. `\![raise,OnTest,"100,2"]`)
+//! - `\\` is a literal backslash and `\%` a literal percent sign
+//!
+//! Tags with a well-known meaning (scope switches, surfaces, waits, choices,
+//! `\![...]` commands, `\_q`/`\_s` toggles and `\e`) are classified into
+//! dedicated [`NodeKind`] variants. Everything else, including unknown tags and
+//! tags whose arguments do not fit the expected shape, is kept as
+//! [`NodeKind::Tag`] so that the validator can report it.
+
+use std::ops::Range;
+use thiserror::Error;
+
+/// Lexical error in sakura script.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum ParseError {
+    /// `[` without matching `]`
+    #[error("unclosed bracket at byte {0}")]
+    UnclosedBracket(usize),
+    /// `"` without matching `"` inside brackets
+    #[error("unterminated quoted argument at byte {0}")]
+    UnterminatedQuote(usize),
+    /// `\` at the end of the script
+    #[error("trailing backslash at byte {0}")]
+    TrailingBackslash(usize),
+}
+
+impl ParseError {
+    /// Byte offset where the error starts.
+    pub fn offset(&self) -> usize {
+        match self {
+            ParseError::UnclosedBracket(offset)
+            | ParseError::UnterminatedQuote(offset)
+            | ParseError::TrailingBackslash(offset) => *offset,
+        }
+    }
+}
+
+/// Parsed node with its byte range in the source script.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Node {
+    pub kind: NodeKind,
+    pub range: Range<usize>,
+}
+
+/// Sakura script node kind.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NodeKind {
+    /// Plain text (escapes `\\` and `\%` already resolved)
+    Text(String),
+    /// Scope switch (`\h`, `\u`, `\0`, `\1`, `\p[n]`, `\p2`)
+    Scope(u32),
+    /// Surface change (`\s[n]`, `\s0`); the id may be negative or an alias
+    Surface(String),
+    /// Wait
+    Wait(Wait),
+    /// Choice (`\q[title,ID,...]`, old form `\q0[ID][title]`)
+    Choice(Choice),
+    /// Command (`\![name,args...]`)
+    Command { name: String, args: Vec<String> },
+    /// Toggle tag (`\_q`, `\_s`, `\_s[ID,...]`)
+    Toggle(Toggle),
+    /// End of script (`\e`)
+    End,
+    /// Any other tag
+    Tag(Tag),
+}
+
+/// Wait tag.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Wait {
+    /// `\w1`-`\w9` (units of 50ms)
+    Short(u32),
+    /// `\_w[ms]` (`\w[ms]` as emitted by `act:wait()` is treated the same)
+    Millis(u64),
+    /// `\__w[ms]` / `\__w[clear]` etc. (wait until elapsed time from the last clear)
+    Until(String),
+    /// `\__w[animation,ID]` (wait until the surface animation ID finishes)
+    Animation(String),
+}
+
+/// Choice tag.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Choice {
+    /// Display title
+    pub title: String,
+    /// Event name, scene ID or `script:...`
+    pub id: String,
+    /// Extra references passed with the choice
+    pub args: Vec<String>,
+}
+
+/// Toggle tag.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Toggle {
+    /// `\_q` (quick section)
+    Quick,
+    /// `\_s` / `\_s[ID,...]` (synchronized section)
+    Sync(Vec<String>),
+}
+
+/// Unclassified tag.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Tag {
+    /// Tag name without the leading backslash, including a digit suffix
+    /// (e.g. `"n"`, `"_a"`, `"b2"`)
+    pub name: String,
+    /// Bracket arguments, `None` if the tag has no brackets
+    pub args: Option<Vec<String>>,
+}
+
+/// Tags that accept a single digit suffix (`\s0`, `\w9`, `\b2`, `\p3`, `\q0`).
+const DIGIT_SUFFIX_TAGS: &[char] = &['s', 'w', 'b', 'p', 'q'];
+
+/// Parse sakura script into nodes.
+///
+/// Adjacent text is merged into a single [`NodeKind::Text`] node.
+pub fn parse(script: &str) -> Result<Vec<Node>, ParseError> {
+    let mut parser = Parser {
+        src: script,
+        pos: 0,
+        nodes: Vec::new(),
+    };
+    parser.run()?;
+    Ok(parser.nodes)
+}
+
+struct Parser<'a> {
+    src: &'a str,
+    pos: usize,
+    nodes: Vec<Node>,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<char> {
+        self.src[self.pos..].chars().next()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += c.len_utf8();
+        Some(c)
+    }
+
+    fn push_text(&mut self, text: &str, start: usize) {
+        if let Some(Node {
+            kind: NodeKind::Text(prev),
+            range,
+        }) = self.nodes.last_mut()
+            && range.end == start
+        {
+            prev.push_str(text);
+            range.end = self.pos;
+            return;
+        }
+        self.nodes.push(Node {
+            kind: NodeKind::Text(text.to_string()),
+            range: start..self.pos,
+        });
+    }
+
+    fn run(&mut self) -> Result<(), ParseError> {
+        while let Some(c) = self.peek() {
+            let start = self.pos;
+            if c != '\\' {
+                self.bump();
+                let text = &self.src[start..self.pos];
+                self.push_text(text, start);
+                continue;
+            }
+            self.bump();
+            match self.peek() {
+                None => return Err(ParseError::TrailingBackslash(start)),
+                Some(escaped @ ('\\' | '%')) => {
+                    self.bump();
+                    self.push_text(&escaped.to_string(), start);
+                }
+                Some(_) => {
+                    let tag = self.tag(start)?;
+                    self.nodes.push(Node {
+                        kind: classify(tag),
+                        range: start..self.pos,
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Read a tag name and its bracket arguments (the leading `\` is consumed).
+    fn tag(&mut self, start: usize) -> Result<Tag, ParseError> {
+        let mut name = String::new();
+        while name.len() < 2 && self.peek() == Some('_') {
+            self.bump();
+            name.push('_');
+        }
+        if let Some(c) = self.bump() {
+            name.push(c);
+            if name.len() == 1
+                && DIGIT_SUFFIX_TAGS.contains(&c)
+                && let Some(d) = self.peek().filter(char::is_ascii_digit)
+            {
+                self.bump();
+                name.push(d);
+            }
+        }
+        if name.is_empty() {
+            return Err(ParseError::TrailingBackslash(start));
+        }
+
+        let mut args = None;
+        if self.peek() == Some('[') {
+            let mut list = self.bracket()?;
+            // 旧形式の選択肢 \q0[ID][title]
+            if name.starts_with('q') && name.len() == 2 && self.peek() == Some('[') {
+                list.extend(self.bracket()?);
+            }
+            args = Some(list);
+        }
+        Ok(Tag { name, args })
+    }
+
+    /// Read `[...]` and split it into arguments.
+    fn bracket(&mut self) -> Result<Vec<String>, ParseError> {
+        let open = self.pos;
+        self.bump();
+        let mut args = Vec::new();
+        let mut current = String::new();
+        let mut quote_start = None;
+        let mut quoted = false;
+        loop {
+            let Some(c) = self.bump() else {
+                return Err(match quote_start {
+                    Some(offset) => ParseError::UnterminatedQuote(offset),
+                    None => ParseError::UnclosedBracket(open),
+                });
+            };
+            if quote_start.is_some() {
+                if c == '"' {
+                    if self.peek() == Some('"') {
+                        self.bump();
+                        current.push('"');
+                    } else {
+                        quote_start = None;
+                    }
+                } else {
+                    current.push(c);
+                }
+                continue;
+            }
+            match c {
+                '\\' if self.peek() == Some(']') => {
+                    self.bump();
+                    current.push(']');
+                }
+                '"' if current.is_empty() && !quoted => {
+                    quote_start = Some(self.pos - 1);
+                    quoted = true;
+                }
+                ',' => {
+                    args.push(std::mem::take(&mut current));
+                    quoted = false;
+                }
+                ']' => {
+                    args.push(current);
+                    return Ok(args);
+                }
+                _ => current.push(c),
+            }
+        }
+    }
+}
+
+/// Classify a tag into a dedicated node kind where possible.
+fn classify(tag: Tag) -> NodeKind {
+    let digit = |name: &str| -> Option<u32> { name[1..].parse().ok() };
+    let single = |args: &Option<Vec<String>>| -> Option<String> {
+        match args.as_deref() {
+            Some([arg]) if !arg.is_empty() => Some(arg.clone()),
+            _ => None,
+        }
+    };
+
+    let name = tag.name.as_str();
+    let kind = match (name, &tag.args) {
+        ("h" | "0", None) => Some(NodeKind::Scope(0)),
+        ("u" | "1", None) => Some(NodeKind::Scope(1)),
+        ("p", Some(_)) => single(&tag.args)
+            .and_then(|n| n.parse().ok())
+            .map(NodeKind::Scope),
+        ("s", Some(_)) => single(&tag.args).map(NodeKind::Surface),
+        ("_w" | "w", Some(_)) => single(&tag.args)
+            .and_then(|ms| ms.parse().ok())
+            .map(|ms| NodeKind::Wait(Wait::Millis(ms))),
+        ("__w", Some(args)) if args.len() == 2 && args[0] == "animation" => {
+            Some(NodeKind::Wait(Wait::Animation(args[1].clone())))
+        }
+        ("__w", Some(_)) => single(&tag.args).map(|arg| NodeKind::Wait(Wait::Until(arg))),
+        ("q", Some(args)) if args.len() >= 2 => Some(NodeKind::Choice(Choice {
+            title: args[0].clone(),
+            id: args[1].clone(),
+            args: args[2..].to_vec(),
+        })),
+        ("!", Some(args)) if !args[0].is_empty() => Some(NodeKind::Command {
+            name: args[0].clone(),
+            args: args[1..].to_vec(),
+        }),
+        ("_q", None) => Some(NodeKind::Toggle(Toggle::Quick)),
+        ("_s", None) => Some(NodeKind::Toggle(Toggle::Sync(Vec::new()))),
+        ("_s", Some(args)) => Some(NodeKind::Toggle(Toggle::Sync(args.clone()))),
+        ("e", None) => Some(NodeKind::End),
+        _ if name.len() == 2 && name.is_ascii() && tag.args.is_none() => {
+            match (&name[..1], digit(name)) {
+                ("p", Some(n)) => Some(NodeKind::Scope(n)),
+                ("s", Some(_)) => Some(NodeKind::Surface(name[1..].to_string())),
+                ("w", Some(n)) if n > 0 => Some(NodeKind::Wait(Wait::Short(n))),
+                _ => None,
+            }
+        }
+        _ if name.len() == 2 && name.is_ascii() && name.starts_with('q') => {
+            match tag.args.as_deref() {
+                Some([id, title]) => Some(NodeKind::Choice(Choice {
+                    title: title.clone(),
+                    id: id.clone(),
+                    args: Vec::new(),
+                })),
+                _ => None,
+            }
+        }
+        _ => None,
+    };
+    kind.unwrap_or(NodeKind::Tag(tag))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kinds(script: &str) -> Vec<NodeKind> {
+        parse(script)
+            .unwrap()
+            .into_iter()
+            .map(|node| node.kind)
+            .collect()
+    }
+
+    fn text(s: &str) -> NodeKind {
+        NodeKind::Text(s.to_string())
+    }
+
+    #[test]
+    fn test_parse_scope_and_surface() {
+        assert_eq!(
+            kinds(r"\h\s[0]こんにちは\u\s10\p[2]\p3"),
+            vec![
+                NodeKind::Scope(0),
+                NodeKind::Surface("0".into()),
+                text("こんにちは"),
+                NodeKind::Scope(1),
+                NodeKind::Surface("1".into()),
+                text("0"),
+                NodeKind::Scope(2),
+                NodeKind::Scope(3),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_waits() {
+        assert_eq!(
+            kinds(r"\w9\_w[500]\w[300]\__w[clear]\__w[animation,400]"),
+            vec![
+                NodeKind::Wait(Wait::Short(9)),
+                NodeKind::Wait(Wait::Millis(500)),
+                NodeKind::Wait(Wait::Millis(300)),
+                NodeKind::Wait(Wait::Until("clear".into())),
+                NodeKind::Wait(Wait::Animation("400".into())),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_choices() {
+        assert_eq!(
+            kinds(r"\q[タイトル,ID]\q0[OnOld][旧形式]"),
+            vec![
+                NodeKind::Choice(Choice {
+                    title: "タイトル".into(),
+                    id: "ID".into(),
+                    args: vec![],
+                }),
+                NodeKind::Choice(Choice {
+                    title: "旧形式".into(),
+                    id: "OnOld".into(),
+                    args: vec![],
+                }),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_quoted_arguments() {
+        assert_eq!(
+            kinds(r#"\![raise,OnTest,"100,2"]\![call,ghost,"the ""MobileMaster"""]"#),
+            vec![
+                NodeKind::Command {
+                    name: "raise".into(),
+                    args: vec!["OnTest".into(), "100,2".into()],
+                },
+                NodeKind::Command {
+                    name: "call".into(),
+                    args: vec!["ghost".into(), r#"the "MobileMaster""#.into()],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_escapes() {
+        assert_eq!(
+            kinds(r"\s[0\]]100\%\\"),
+            vec![NodeKind::Surface("0]".into()), text(r"100%\")]
+        );
+    }
+
+    #[test]
+    fn test_parse_toggles_and_end() {
+        assert_eq!(
+            kinds(r"\_q\_s[0,1]\_s\e"),
+            vec![
+                NodeKind::Toggle(Toggle::Quick),
+                NodeKind::Toggle(Toggle::Sync(vec!["0".into(), "1".into()])),
+                NodeKind::Toggle(Toggle::Sync(vec![])),
+                NodeKind::End,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_other_tags() {
+        assert_eq!(
+            kinds(r"\n[half]\_a[OnAnchor]\x"),
+            vec![
+                NodeKind::Tag(Tag {
+                    name: "n".into(),
+                    args: Some(vec!["half".into()]),
+                }),
+                NodeKind::Tag(Tag {
+                    name: "_a".into(),
+                    args: Some(vec!["OnAnchor".into()]),
+                }),
+                NodeKind::Tag(Tag {
+                    name: "x".into(),
+                    args: None,
+                }),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_ranges() {
+        let nodes = parse(r"\h\s[0]あ").unwrap();
+        let ranges: Vec<_> = nodes.iter().map(|node| node.range.clone()).collect();
+        assert_eq!(ranges, vec![0..2, 2..7, 7..10]);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(parse(r"\s[0"), Err(ParseError::UnclosedBracket(2)));
+        assert_eq!(
+            parse(r#"\![raise,"OnTest]"#),
+            Err(ParseError::UnterminatedQuote(9))
+        );
+        assert_eq!(parse(r"abc\"), Err(ParseError::TrailingBackslash(3)));
+    }
+}
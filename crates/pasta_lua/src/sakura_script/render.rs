@@ -0,0 +1,465 @@
+//! Preview renderer for built sakura script.
+//!
+//! Turns a built script (e.g. `\p[0]\s[0]こ\_w[50]ん...\e`) into a readable
+//! transcript for reviewing talks in CI or simulators:
+//!
+//! ```text
+//! さくら：[surface 0]こんにちは。[wait 1000ms]
+//!   元気？
+//! うにゅう：[surface 10]まあまあや。
+//! [choice] はい -> OnYes
+//! ```
+//!
+//! - A scope switch starts a new line labelled with the speaker name
+//! - `\n` continues the same speaker on an indented line
+//! - Surfaces, waits (at or above [`RenderOptions::wait_threshold_ms`]),
+//!   `\![...]` commands, `\c` and `\x` are shown as `[...]` annotations
+//! - Choices are listed on their own lines
+//! - `\![set,balloonwait,x]` scales the display time per character and is not annotated
+//!
+//! [`render_html`] renders the same lines as an HTML timeline where every
+//! character carries the time (ms) at which the baseware displays it.
+
+use super::parser::{self, NodeKind, ParseError, Toggle, Wait};
+use std::collections::HashMap;
+use std::fmt::Write;
+
+/// Renderer options.
+#[derive(Debug, Clone)]
+pub struct RenderOptions {
+    /// Speaker names by scope number (falls back to `scope{n}`)
+    pub names: HashMap<u32, String>,
+    /// Waits shorter than this are not annotated (consecutive waits are summed)
+    pub wait_threshold_ms: u64,
+    /// Baseware display time per character outside `\_q` sections
+    pub char_wait_ms: u64,
+}
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        Self {
+            names: HashMap::new(),
+            wait_threshold_ms: 200,
+            char_wait_ms: 50,
+        }
+    }
+}
+
+impl RenderOptions {
+    /// Speaker label for a scope.
+    fn label(&self, scope: u32) -> String {
+        self.names
+            .get(&scope)
+            .cloned()
+            .unwrap_or_else(|| format!("scope{scope}"))
+    }
+}
+
+/// Timed item in a rendered line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Item {
+    /// Time (ms from the start of the script) at which the item is shown
+    pub t: u64,
+    pub kind: ItemKind,
+}
+
+/// Rendered item kind.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ItemKind {
+    /// Displayed character
+    Char(char),
+    /// Surface change
+    Surface(String),
+    /// Wait (summed, only at or above the threshold)
+    Wait(u64),
+    /// `\![...]` command (arguments joined with `,`)
+    Command(String),
+    /// Balloon clear (`\c`)
+    Clear,
+    /// Click wait (`\x`)
+    Click,
+}
+
+/// Rendered line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Line {
+    /// Talk line of a scope (`continued` is true after `\n`)
+    Talk {
+        scope: u32,
+        continued: bool,
+        items: Vec<Item>,
+    },
+    /// Choice
+    Choice { t: u64, title: String, id: String },
+}
+
+/// Render result with the total duration of the script.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Timeline {
+    pub lines: Vec<Line>,
+    /// Time (ms) at which the last item has been shown
+    pub duration: u64,
+}
+
+/// Build the line/timing structure of a script.
+pub fn timeline(script: &str, options: &RenderOptions) -> Result<Timeline, ParseError> {
+    let mut builder = TimelineBuilder {
+        options,
+        lines: Vec::new(),
+        scope: 0,
+        continued: false,
+        items: Vec::new(),
+        t: 0,
+        pending_wait: 0,
+        clear_base: 0,
+        quick: false,
+        balloon_wait_percent: 100,
+    };
+    for node in parser::parse(script)? {
+        if builder.node(node.kind) {
+            break;
+        }
+    }
+    builder.flush_wait();
+    builder.flush_line();
+    Ok(Timeline {
+        lines: builder.lines,
+        duration: builder.t,
+    })
+}
+
+struct TimelineBuilder<'a> {
+    options: &'a RenderOptions,
+    lines: Vec<Line>,
+    scope: u32,
+    continued: bool,
+    items: Vec<Item>,
+    t: u64,
+    pending_wait: u64,
+    clear_base: u64,
+    quick: bool,
+    /// `\![set,balloonwait,x]` in percent
+    balloon_wait_percent: u64,
+}
+
+impl TimelineBuilder<'_> {
+    /// Process a node. Returns true at `\e`.
+    fn node(&mut self, kind: NodeKind) -> bool {
+        match kind {
+            NodeKind::Text(text) => {
+                for c in text.chars() {
+                    self.push(ItemKind::Char(c));
+                    if !self.quick {
+                        self.t += self.options.char_wait_ms * self.balloon_wait_percent / 100;
+                    }
+                }
+            }
+            NodeKind::Scope(scope) => {
+                if scope != self.scope {
+                    self.flush_wait();
+                    self.flush_line();
+                    self.scope = scope;
+                    self.continued = false;
+                }
+            }
+            NodeKind::Surface(id) => self.push(ItemKind::Surface(id)),
+            NodeKind::Wait(wait) => {
+                let ms = match wait {
+                    Wait::Short(n) => u64::from(n) * 50,
+                    Wait::Millis(ms) => ms,
+                    Wait::Until(arg) if arg == "clear" => {
+                        self.clear_base = self.t + self.pending_wait;
+                        0
+                    }
+                    Wait::Until(arg) => arg.parse::<u64>().map_or(0, |ms| {
+                        (self.clear_base + ms).saturating_sub(self.t + self.pending_wait)
+                    }),
+                    // アニメーションの長さはシェル次第なので待ち時間に数えない
+                    Wait::Animation(_) => 0,
+                };
+                self.pending_wait += ms;
+            }
+            NodeKind::Choice(choice) => {
+                self.flush_wait();
+                self.flush_line();
+                self.lines.push(Line::Choice {
+                    t: self.t,
+                    title: choice.title,
+                    id: choice.id,
+                });
+                self.continued = true;
+            }
+            NodeKind::Command { name, args }
+                if name == "set" && args.first().is_some_and(|a| a == "balloonwait") =>
+            {
+                if let Some(x) = args.get(1).and_then(|x| x.trim().parse::<f64>().ok()) {
+                    self.balloon_wait_percent = (x.max(0.0) * 100.0).round() as u64;
+                }
+            }
+            NodeKind::Command { name, args } => {
+                let mut text = name;
+                for arg in args {
+                    text.push(',');
+                    text.push_str(&arg);
+                }
+                self.push(ItemKind::Command(text));
+            }
+            NodeKind::Toggle(Toggle::Quick) => self.quick = !self.quick,
+            NodeKind::Toggle(Toggle::Sync(_)) => {}
+            NodeKind::End => return true,
+            NodeKind::Tag(tag) => match tag.name.as_str() {
+                "n" | "_n" => {
+                    self.flush_wait();
+                    self.flush_line();
+                    self.continued = true;
+                }
+                "c" => self.push(ItemKind::Clear),
+                "x" => self.push(ItemKind::Click),
+                _ => {}
+            },
+        }
+        false
+    }
+
+    /// Push an item at the current time (after pending waits).
+    fn push(&mut self, kind: ItemKind) {
+        self.flush_wait();
+        self.items.push(Item { t: self.t, kind });
+    }
+
+    /// Apply pending waits, annotating them if long enough.
+    fn flush_wait(&mut self) {
+        if self.pending_wait == 0 {
+            return;
+        }
+        let ms = std::mem::take(&mut self.pending_wait);
+        if ms >= self.options.wait_threshold_ms {
+            self.items.push(Item {
+                t: self.t,
+                kind: ItemKind::Wait(ms),
+            });
+        }
+        self.t += ms;
+    }
+
+    /// Finish the current line (empty lines are dropped).
+    fn flush_line(&mut self) {
+        if self.items.is_empty() {
+            return;
+        }
+        self.lines.push(Line::Talk {
+            scope: self.scope,
+            continued: self.continued,
+            items: std::mem::take(&mut self.items),
+        });
+    }
+}
+
+/// Annotation text of a non-character item.
+fn annotation(kind: &ItemKind) -> Option<String> {
+    match kind {
+        ItemKind::Char(_) => None,
+        ItemKind::Surface(id) => Some(format!("surface {id}")),
+        ItemKind::Wait(ms) => Some(format!("wait {ms}ms")),
+        ItemKind::Command(command) => Some(format!("! {command}")),
+        ItemKind::Clear => Some("clear".to_string()),
+        ItemKind::Click => Some("click".to_string()),
+    }
+}
+
+/// Render a built script as a plain-text transcript.
+pub fn render_text(script: &str, options: &RenderOptions) -> Result<String, ParseError> {
+    let timeline = timeline(script, options)?;
+    let mut out = String::new();
+    for line in &timeline.lines {
+        match line {
+            Line::Talk {
+                scope,
+                continued,
+                items,
+            } => {
+                if *continued {
+                    out.push_str("  ");
+                } else {
+                    out.push_str(&options.label(*scope));
+                    out.push('：');
+                }
+                for item in items {
+                    match &item.kind {
+                        ItemKind::Char(c) => out.push(*c),
+                        kind => {
+                            let _ = write!(out, "[{}]", annotation(kind).unwrap_or_default());
+                        }
+                    }
+                }
+            }
+            Line::Choice { title, id, .. } => {
+                let _ = write!(out, "[choice] {title} -> {id}");
+            }
+        }
+        out.push('\n');
+    }
+    Ok(out)
+}
+
+/// Escape text for HTML.
+fn escape_html(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// Render a built script as an HTML timeline fragment.
+///
+/// Each character is a `<span class="ch" data-t="ms">`; annotations are
+/// `<span class="note {kind}" data-t="ms">`. No styles or scripts are included.
+pub fn render_html(script: &str, options: &RenderOptions) -> Result<String, ParseError> {
+    let timeline = timeline(script, options)?;
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        r#"<div class="pasta-timeline" data-duration="{}">"#,
+        timeline.duration
+    );
+    for line in &timeline.lines {
+        match line {
+            Line::Talk {
+                scope,
+                continued,
+                items,
+            } => {
+                let _ = write!(out, r#"<p class="line" data-scope="{scope}">"#);
+                if !continued {
+                    let _ = write!(
+                        out,
+                        r#"<span class="speaker">{}</span>"#,
+                        escape_html(&options.label(*scope))
+                    );
+                }
+                for item in items {
+                    let t = item.t;
+                    match &item.kind {
+                        ItemKind::Char(c) => {
+                            let _ = write!(
+                                out,
+                                r#"<span class="ch" data-t="{t}">{}</span>"#,
+                                escape_html(&c.to_string())
+                            );
+                        }
+                        kind => {
+                            let class = match kind {
+                                ItemKind::Surface(_) => "surface",
+                                ItemKind::Wait(_) => "wait",
+                                ItemKind::Command(_) => "command",
+                                ItemKind::Clear => "clear",
+                                _ => "click",
+                            };
+                            let _ = write!(
+                                out,
+                                r#"<span class="note {class}" data-t="{t}">{}</span>"#,
+                                escape_html(&annotation(kind).unwrap_or_default())
+                            );
+                        }
+                    }
+                }
+                out.push_str("</p>\n");
+            }
+            Line::Choice { t, title, id } => {
+                let _ = writeln!(
+                    out,
+                    r#"<p class="choice" data-t="{t}" data-id="{}">{}</p>"#,
+                    escape_html(id),
+                    escape_html(title)
+                );
+            }
+        }
+    }
+    out.push_str("</div>\n");
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options() -> RenderOptions {
+        RenderOptions {
+            names: HashMap::from([(0, "さくら".to_string()), (1, "うにゅう".to_string())]),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_render_text_transcript() {
+        let script = r"\p[0]\s[0]こ\_w[50]ん\_w[50]に\_w[50]ち\_w[50]は。\_w[950]\n元気？\n[150]\p[1]\s[10]まあまあや。\p[0]\q[はい,OnYes]\q[いいえ,OnNo]\e";
+        assert_eq!(
+            render_text(script, &options()).unwrap(),
+            "さくら：[surface 0]こんにちは。[wait 950ms]\n  元気？\nうにゅう：[surface 10]まあまあや。\n[choice] はい -> OnYes\n[choice] いいえ -> OnNo\n"
+        );
+    }
+
+    #[test]
+    fn test_render_text_default_labels_and_annotations() {
+        let script = r"\1あ\w9\![raise,OnTest,1]\x\cい\e無視";
+        assert_eq!(
+            render_text(script, &RenderOptions::default()).unwrap(),
+            "scope1：あ[wait 450ms][! raise,OnTest,1][click][clear]い\n"
+        );
+    }
+
+    #[test]
+    fn test_timeline_timing() {
+        let timeline =
+            timeline(r"\0あ\_w[100]い\_qうえ\_qお\e", &RenderOptions::default()).unwrap();
+        let Line::Talk { items, .. } = &timeline.lines[0] else {
+            panic!("expected talk line");
+        };
+        let times: Vec<u64> = items.iter().map(|item| item.t).collect();
+        // あ(0) +50 +100 → い(150) +50 → う,え(200, quick) → お(200) +50
+        assert_eq!(times, vec![0, 150, 200, 200, 200]);
+        assert_eq!(timeline.duration, 250);
+    }
+
+    #[test]
+    fn test_timeline_balloon_wait() {
+        let timeline = timeline(
+            r"\0\![set,balloonwait,2]あい\![set,balloonwait,0.5]う\e",
+            &RenderOptions::default(),
+        )
+        .unwrap();
+        let Line::Talk { items, .. } = &timeline.lines[0] else {
+            panic!("expected talk line");
+        };
+        let times: Vec<u64> = items.iter().map(|item| item.t).collect();
+        assert_eq!(times, vec![0, 100, 200]);
+        assert_eq!(timeline.duration, 225);
+    }
+
+    #[test]
+    fn test_render_html() {
+        let html = render_html(r"\p[0]\s[5]<a>\_w[500]\q[x&y,OnX]\e", &options()).unwrap();
+        assert_eq!(
+            html,
+            concat!(
+                r#"<div class="pasta-timeline" data-duration="650">"#,
+                "\n",
+                r#"<p class="line" data-scope="0"><span class="speaker">さくら</span>"#,
+                r#"<span class="note surface" data-t="0">surface 5</span>"#,
+                r#"<span class="ch" data-t="0">&lt;</span><span class="ch" data-t="50">a</span>"#,
+                r#"<span class="ch" data-t="100">&gt;</span>"#,
+                r#"<span class="note wait" data-t="150">wait 500ms</span></p>"#,
+                "\n",
+                r#"<p class="choice" data-t="650" data-id="OnX">x&amp;y</p>"#,
+                "\n</div>\n"
+            )
+        );
+    }
+}
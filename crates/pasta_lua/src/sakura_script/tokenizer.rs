@@ -129,6 +129,11 @@ impl Tokenizer {
         })
     }
 
+    /// Character sets used for classification.
+    pub fn char_sets(&self) -> &CharSets {
+        &self.char_sets
+    }
+
     /// Tokenize input text.
     ///
     /// Sakura script tags are matched first (highest priority).
@@ -0,0 +1,370 @@
+//! Validator for sakura script.
+//!
+//! Checks parsed sakura script for problems the baseware would silently
+//! ignore or misinterpret:
+//!
+//! - Lexical errors (unclosed brackets, unterminated quotes)
+//! - Unknown tags
+//! - Malformed arguments (`\q` without ID, `\![raise]` without event name, etc.)
+//! - Unbalanced `\_q` / `\_s` toggles
+//! - Missing `\e`
+//!
+//! Toggle balance and `\e` are only meaningful for a complete script, so they
+//! are skipped in [`ValidationMode::Fragment`] (used at transpile time on a
+//! single `Action::SakuraScript`).
+
+use super::parser::{self, Node, NodeKind, Tag, Toggle};
+use std::ops::Range;
+
+/// What the validated text represents.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValidationMode {
+    /// Complete script (built output sent to the baseware)
+    Script,
+    /// Part of a script (a single tag or talk fragment)
+    Fragment,
+}
+
+/// Diagnostic kind.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiagnosticKind {
+    /// Lexical error
+    Syntax,
+    /// Tag not known to the validator
+    UnknownTag,
+    /// Tag arguments do not fit the expected shape
+    MalformedArguments,
+    /// `\_q` / `\_s` left open at the end of the script
+    UnbalancedToggle,
+    /// Script does not contain `\e`
+    MissingEnd,
+}
+
+impl DiagnosticKind {
+    /// Identifier used in Lua tables and log output.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DiagnosticKind::Syntax => "syntax",
+            DiagnosticKind::UnknownTag => "unknown_tag",
+            DiagnosticKind::MalformedArguments => "malformed_arguments",
+            DiagnosticKind::UnbalancedToggle => "unbalanced_toggle",
+            DiagnosticKind::MissingEnd => "missing_end",
+        }
+    }
+}
+
+/// Validation diagnostic.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub kind: DiagnosticKind,
+    /// Byte range of the offending tag in the script
+    pub range: Range<usize>,
+    pub message: String,
+}
+
+/// Bracket requirement of a known tag.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Args {
+    /// No brackets
+    None,
+    /// Brackets are optional
+    Optional,
+    /// Brackets are required
+    Required,
+}
+
+/// Known tags that are not classified by the parser (or whose classification failed).
+const KNOWN_TAGS: &[(&str, Args)] = &[
+    // スコープ・サーフェス
+    ("h", Args::None),
+    ("u", Args::None),
+    ("0", Args::None),
+    ("1", Args::None),
+    ("p", Args::Required),
+    ("s", Args::Required),
+    ("i", Args::Required),
+    ("b", Args::Required),
+    // 改行・クリア・表示
+    ("n", Args::Optional),
+    ("_n", Args::None),
+    ("c", Args::Optional),
+    ("x", Args::Optional),
+    ("t", Args::None),
+    ("_l", Args::Required),
+    ("f", Args::Required),
+    ("_b", Args::Required),
+    ("_u", Args::Required),
+    ("_m", Args::Required),
+    ("_?", Args::None),
+    ("_!", Args::None),
+    ("__c", Args::None),
+    ("__t", Args::None),
+    ("_V", Args::None),
+    ("_v", Args::Required),
+    ("8", Args::Required),
+    ("C", Args::None),
+    // ウェイト
+    ("w", Args::Required),
+    ("_w", Args::Required),
+    ("__w", Args::Required),
+    // 選択肢・アンカー
+    ("q", Args::Required),
+    ("__q", Args::Optional),
+    ("_a", Args::Optional),
+    ("z", Args::None),
+    ("y", Args::None),
+    ("*", Args::None),
+    // コマンド・その他
+    ("!", Args::Required),
+    ("&", Args::Required),
+    ("j", Args::Required),
+    ("m", Args::Required),
+    ("a", Args::None),
+    ("v", Args::None),
+    ("-", Args::None),
+    ("+", Args::None),
+    ("_+", Args::None),
+    ("4", Args::None),
+    ("5", Args::None),
+    ("6", Args::None),
+    ("7", Args::None),
+    ("_s", Args::Optional),
+    ("_q", Args::None),
+    ("e", Args::None),
+];
+
+/// `\![...]` commands whose event name is at the given argument index.
+const EVENT_COMMANDS: &[(&str, usize)] = &[
+    ("raise", 1),
+    ("notify", 1),
+    ("embed", 1),
+    ("raiseother", 2),
+    ("notifyother", 2),
+    ("raiseplugin", 2),
+    ("notifyplugin", 2),
+    ("timerraise", 3),
+    ("timerraiseother", 4),
+    ("timerraiseplugin", 4),
+];
+
+/// Parse and validate sakura script.
+///
+/// Returns an empty list if no problems were found.
+pub fn validate(script: &str, mode: ValidationMode) -> Vec<Diagnostic> {
+    match parser::parse(script) {
+        Ok(nodes) => validate_nodes(&nodes, script.len(), mode),
+        Err(e) => vec![Diagnostic {
+            kind: DiagnosticKind::Syntax,
+            range: e.offset()..script.len(),
+            message: e.to_string(),
+        }],
+    }
+}
+
+/// Validate parsed nodes.
+///
+/// `len` is the byte length of the source script (used for end-of-script diagnostics).
+pub fn validate_nodes(nodes: &[Node], len: usize, mode: ValidationMode) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    let mut quick: Option<Range<usize>> = None;
+    let mut sync: Option<Range<usize>> = None;
+    let mut has_end = false;
+
+    for node in nodes {
+        let mut report = |kind, message: String| {
+            diagnostics.push(Diagnostic {
+                kind,
+                range: node.range.clone(),
+                message,
+            })
+        };
+        match &node.kind {
+            NodeKind::Tag(tag) => check_tag(tag, &mut report),
+            NodeKind::Command { name, args } => check_command(name, args, &mut report),
+            NodeKind::Toggle(Toggle::Quick) => toggle(&mut quick, &node.range),
+            // \_s[ID,...] は開始のみ、\_s は開始/終了の切り替え
+            NodeKind::Toggle(Toggle::Sync(args)) if args.is_empty() || sync.is_none() => {
+                toggle(&mut sync, &node.range)
+            }
+            NodeKind::End => has_end = true,
+            _ => {}
+        }
+    }
+
+    if mode == ValidationMode::Script {
+        for (range, tag) in [(quick, r"\_q"), (sync, r"\_s")] {
+            if let Some(range) = range {
+                diagnostics.push(Diagnostic {
+                    kind: DiagnosticKind::UnbalancedToggle,
+                    range,
+                    message: format!("{tag} is not closed"),
+                });
+            }
+        }
+        if !has_end {
+            diagnostics.push(Diagnostic {
+                kind: DiagnosticKind::MissingEnd,
+                range: len..len,
+                message: r"script does not end with \e".to_string(),
+            });
+        }
+    }
+    diagnostics
+}
+
+/// Flip a toggle state.
+fn toggle(state: &mut Option<Range<usize>>, range: &Range<usize>) {
+    *state = match state {
+        Some(_) => None,
+        None => Some(range.clone()),
+    };
+}
+
+/// Look up the bracket requirement of a tag name (digit suffix forms included).
+fn known_args(name: &str) -> Option<Args> {
+    if let Some((_, args)) = KNOWN_TAGS.iter().find(|(known, _)| *known == name) {
+        return Some(*args);
+    }
+    let mut chars = name.chars();
+    match (chars.next(), chars.next(), chars.next()) {
+        (Some('q'), Some(d), None) if d.is_ascii_digit() => Some(Args::Required),
+        (Some('s' | 'w' | 'b' | 'p'), Some(d), None) if d.is_ascii_digit() => Some(Args::None),
+        _ => None,
+    }
+}
+
+/// Check an unclassified tag.
+fn check_tag(tag: &Tag, report: &mut impl FnMut(DiagnosticKind, String)) {
+    let name = &tag.name;
+    let Some(spec) = known_args(name) else {
+        report(DiagnosticKind::UnknownTag, format!(r"unknown tag \{name}"));
+        return;
+    };
+
+    let malformed = match (spec, &tag.args) {
+        (Args::None, Some(_)) => Some("does not take arguments".to_string()),
+        (Args::Required, None) => Some("requires arguments".to_string()),
+        (_, Some(args)) => match name.as_str() {
+            // 分類に失敗した \q / \q0 / \s / \p / \_w / \__w / \![] は引数の形が不正
+            // （\__w は [ms] / [clear] / [animation,ID] のいずれか）
+            "q" => Some(format!(
+                "requires title and ID, got {} argument(s)",
+                args.len()
+            )),
+            "s" | "p" => Some("requires a single argument".to_string()),
+            "__w" => Some("requires a single argument or animation,ID".to_string()),
+            "_w" | "w" => Some("requires milliseconds".to_string()),
+            "!" => Some("requires a command name".to_string()),
+            _ if name.starts_with('q') => Some("requires [ID][title]".to_string()),
+            _ => None,
+        },
+        _ => None,
+    };
+    if let Some(reason) = malformed {
+        report(
+            DiagnosticKind::MalformedArguments,
+            format!(r"\{name} {reason}"),
+        );
+    }
+}
+
+/// Check a `\![...]` command.
+fn check_command(name: &str, args: &[String], report: &mut impl FnMut(DiagnosticKind, String)) {
+    let Some((_, index)) = EVENT_COMMANDS.iter().find(|(command, _)| *command == name) else {
+        return;
+    };
+    // args は \![name,...] の name を除いた引数
+    if args.get(index - 1).is_none_or(|event| event.is_empty()) {
+        report(
+            DiagnosticKind::MalformedArguments,
+            format!(r"\![{name}] requires an event name"),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kinds(script: &str, mode: ValidationMode) -> Vec<DiagnosticKind> {
+        validate(script, mode).into_iter().map(|d| d.kind).collect()
+    }
+
+    #[test]
+    fn test_valid_script() {
+        let script = r#"\0\s[0]こんにちは\w9\__w[animation,400]\n\1\s10\_q\q[はい,OnYes]\q[いいえ,OnNo,1]\_q\![raise,OnTest,"100,2"]\e"#;
+        assert_eq!(validate(script, ValidationMode::Script), vec![]);
+    }
+
+    #[test]
+    fn test_unknown_tag() {
+        let diagnostics = validate(r"\h\k\e", ValidationMode::Script);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].kind, DiagnosticKind::UnknownTag);
+        assert_eq!(diagnostics[0].range, 2..4);
+    }
+
+    #[test]
+    fn test_malformed_choice_and_raise() {
+        assert_eq!(
+            kinds(r"\q[タイトルだけ]", ValidationMode::Fragment),
+            vec![DiagnosticKind::MalformedArguments]
+        );
+        assert_eq!(
+            kinds(r"\![raise]", ValidationMode::Fragment),
+            vec![DiagnosticKind::MalformedArguments]
+        );
+        assert_eq!(
+            kinds(r"\![raise,]\![timerraise,1000,1]", ValidationMode::Fragment),
+            vec![
+                DiagnosticKind::MalformedArguments,
+                DiagnosticKind::MalformedArguments
+            ]
+        );
+        assert_eq!(
+            kinds(r"\![open,inputbox,id,-1]", ValidationMode::Fragment),
+            vec![]
+        );
+    }
+
+    #[test]
+    fn test_argument_shape() {
+        assert_eq!(
+            kinds(r"\_w[abc]\s\h[0]\__w[1,2]", ValidationMode::Fragment),
+            vec![DiagnosticKind::MalformedArguments; 4]
+        );
+    }
+
+    #[test]
+    fn test_unbalanced_toggles() {
+        assert_eq!(
+            kinds(r"\_q\_s[0,1]あ\e", ValidationMode::Script),
+            vec![
+                DiagnosticKind::UnbalancedToggle,
+                DiagnosticKind::UnbalancedToggle
+            ]
+        );
+        assert_eq!(
+            kinds(r"\_q\_s[0,1]あ\_s\_q\e", ValidationMode::Script),
+            vec![]
+        );
+        assert_eq!(kinds(r"\_q", ValidationMode::Fragment), vec![]);
+    }
+
+    #[test]
+    fn test_missing_end() {
+        assert_eq!(
+            kinds(r"\h\s[0]", ValidationMode::Script),
+            vec![DiagnosticKind::MissingEnd]
+        );
+        assert_eq!(kinds(r"\h\s[0]", ValidationMode::Fragment), vec![]);
+    }
+
+    #[test]
+    fn test_syntax_error() {
+        let diagnostics = validate(r"\h\s[0", ValidationMode::Fragment);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].kind, DiagnosticKind::Syntax);
+        assert_eq!(diagnostics[0].range, 4..6);
+    }
+}
@@ -1,13 +1,17 @@
 //! Wait insertion logic for sakura script.
 //!
 //! Inserts `\_w[ms]` wait tags based on token types and wait values.
+//! The character class behaviour is implemented as the default rule set of
+//! the rule engine in [`super::wait_rules`].
 
 use crate::loader::TalkConfig;
 
+use super::pacing::Pacer;
 use super::tokenizer::{Token, TokenKind};
+use super::wait_rules::{self, WaitContext};
 
 /// Wait values in milliseconds.
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone)]
 pub struct WaitValues {
     /// Wait for general characters (default: 50ms)
     pub normal: i64,
@@ -19,6 +23,22 @@ pub struct WaitValues {
     pub strong: i64,
     /// Wait for leader characters (default: 200ms)
     pub leader: i64,
+    /// Display time per character already applied by the baseware (default: 50ms)
+    pub base: i64,
+}
+
+impl Default for WaitValues {
+    /// No waits, with the baseware's default display time per character.
+    fn default() -> Self {
+        Self {
+            normal: 0,
+            period: 0,
+            comma: 0,
+            strong: 0,
+            leader: 0,
+            base: 50,
+        }
+    }
 }
 
 impl WaitValues {
@@ -30,6 +50,7 @@ impl WaitValues {
             comma: config.script_wait_comma,
             strong: config.script_wait_strong,
             leader: config.script_wait_leader,
+            base: config.script_wait_base,
         }
     }
 
@@ -54,10 +75,10 @@ impl WaitValues {
 ///
 /// # Rules
 /// 1. SakuraScript and LineEndProhibited tokens: no wait
-/// 2. General tokens: insert (normal - 50)ms after each character
-/// 3. Leader tokens: insert (leader - 50)ms after each character
+/// 2. General tokens: insert (normal - base)ms after each character
+/// 3. Leader tokens: insert (leader - base)ms after each character
 /// 4. Period/Comma/Strong/LineStartProhibited: accumulate until end of consecutive run
-/// 5. At end of consecutive punctuation: insert (max_wait - 50)ms
+/// 5. At end of consecutive punctuation: insert (max_wait - base)ms
 /// 6. If calculated wait <= 0, skip insertion
 ///
 /// # Arguments
@@ -67,88 +88,13 @@ impl WaitValues {
 /// # Returns
 /// String with wait tags inserted
 pub fn insert_waits(tokens: &[Token], wait_values: &WaitValues) -> String {
-    let mut result = String::new();
-    let mut pending_max_wait: Option<i64> = None;
-    let mut pending_text = String::new();
-
-    for token in tokens {
-        match &token.kind {
-            // Rule 1: SakuraScript - output as-is, flush pending if any
-            TokenKind::SakuraScript => {
-                flush_pending(&mut result, &mut pending_text, &mut pending_max_wait);
-                result.push_str(&token.text);
-            }
-
-            // Rule 1: LineEndProhibited - output as-is, flush pending if any
-            TokenKind::LineEndProhibited => {
-                flush_pending(&mut result, &mut pending_text, &mut pending_max_wait);
-                result.push_str(&token.text);
-            }
-
-            // Rule 2: General - output with wait per character
-            TokenKind::General => {
-                flush_pending(&mut result, &mut pending_text, &mut pending_max_wait);
-                append_with_per_char_wait(&mut result, &token.text, wait_values.normal);
-            }
-
-            // Rule 3: Leader - output with wait per character
-            TokenKind::Leader => {
-                flush_pending(&mut result, &mut pending_text, &mut pending_max_wait);
-                append_with_per_char_wait(&mut result, &token.text, wait_values.leader);
-            }
-
-            // Rules 4-5: Punctuation - accumulate and track max
-            TokenKind::Period | TokenKind::Comma | TokenKind::Strong | TokenKind::LineStartProhibited => {
-                let wait = match &token.kind {
-                    TokenKind::Period => wait_values.period,
-                    TokenKind::Comma => wait_values.comma,
-                    TokenKind::Strong => wait_values.strong,
-                    TokenKind::LineStartProhibited => {
-                        // LineStartProhibited doesn't add its own wait, but extends the pending run
-                        pending_max_wait.unwrap_or(0)
-                    }
-                    _ => unreachable!(),
-                };
-
-                pending_text.push_str(&token.text);
-                pending_max_wait = Some(pending_max_wait.map_or(wait, |m| m.max(wait)));
-            }
-        }
-    }
-
-    // Flush any remaining pending punctuation
-    flush_pending(&mut result, &mut pending_text, &mut pending_max_wait);
-
-    result
-}
-
-/// Flush pending punctuation with wait tag.
-fn flush_pending(result: &mut String, pending_text: &mut String, pending_max_wait: &mut Option<i64>) {
-    if !pending_text.is_empty() {
-        result.push_str(pending_text);
-
-        if let Some(max_wait) = pending_max_wait.take() {
-            let effective_wait = max_wait - 50;
-            if effective_wait > 0 {
-                result.push_str(&format!(r"\_w[{}]", effective_wait));
-            }
-        }
-
-        pending_text.clear();
-    }
-    *pending_max_wait = None;
-}
-
-/// Append text with per-character wait.
-fn append_with_per_char_wait(result: &mut String, text: &str, wait_ms: i64) {
-    let effective_wait = wait_ms - 50;
-
-    for c in text.chars() {
-        result.push(c);
-        if effective_wait > 0 {
-            result.push_str(&format!(r"\_w[{}]", effective_wait));
-        }
-    }
+    wait_rules::apply_rules(
+        tokens,
+        &wait_rules::default_rules(wait_values),
+        wait_values.base,
+        &WaitContext::default(),
+        &mut Pacer::none(),
+    )
 }
 
 #[cfg(test)]
@@ -166,6 +112,7 @@ mod tests {
             comma: 500,   // effective: 450
             strong: 500,  // effective: 450
             leader: 200,  // effective: 150
+            base: 50,
         }
     }
 
@@ -376,6 +323,9 @@ mod tests {
 
         let result = insert_waits(&tokens, &wait_values);
 
-        assert_eq!(result, r"\h\s[0]こ\_w[50]ん\_w[50]に\_w[50]ち\_w[50]は\_w[50]");
+        assert_eq!(
+            result,
+            r"\h\s[0]こ\_w[50]ん\_w[50]に\_w[50]ち\_w[50]は\_w[50]"
+        );
     }
 }
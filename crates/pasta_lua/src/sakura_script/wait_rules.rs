@@ -0,0 +1,491 @@
+//! Rule-based wait insertion engine.
+//!
+//! Waits are decided by an ordered list of [`WaitRule`]s. For every run of talk
+//! text between sakura script tags, each rule is matched in order and claims the
+//! characters it matches; characters already claimed by an earlier rule are left
+//! alone. The built-in character class behaviour is expressed as
+//! [`default_rules`] and placed at the end of the list, so user rules
+//! (`[talk] wait_rules` / actor `wait_rules`) only override the parts they match.
+//!
+//! # Rule evaluation
+//! 1. `pattern` is matched against the text run (empty matches are ignored)
+//! 2. `before` / `after` must match the text immediately before / after the match
+//! 3. `emotion` must equal the emotion in [`WaitContext`]
+//! 4. A match is applied only if none of its characters are claimed yet
+//! 5. `per_char` waits after every matched character, `once` after the last one
+//! 6. The inserted wait is `wait - base`; if it is <= 0, nothing is inserted
+//! 7. Waits of `per_char` rules are then adjusted by the [`Pacer`]
+
+use crate::loader::{WaitRuleConfig, WaitRuleMode};
+use regex::Regex;
+
+use super::pacing::{self, Pacer, Position};
+use super::tokenizer::{Token, TokenKind};
+use super::wait_inserter::WaitValues;
+
+/// What a rule matches.
+#[derive(Debug, Clone)]
+enum Matcher {
+    /// Regex over the text run
+    Regex(Regex),
+    /// Maximal runs of characters of the given kinds
+    Run(&'static [TokenKind]),
+    /// Single characters of the given kind
+    Kind(TokenKind),
+}
+
+/// How much a rule waits.
+#[derive(Debug, Clone)]
+enum RuleWait {
+    /// Fixed milliseconds
+    Fixed(i64),
+    /// Largest punctuation wait among the matched characters
+    ClassMax(WaitValues),
+}
+
+/// Compiled wait rule.
+#[derive(Debug, Clone)]
+pub struct WaitRule {
+    matcher: Matcher,
+    before: Option<Regex>,
+    after: Option<Regex>,
+    emotion: Option<String>,
+    wait: RuleWait,
+    mode: WaitRuleMode,
+}
+
+impl WaitRule {
+    /// Compile a rule from its configuration.
+    ///
+    /// # Returns
+    /// * `Ok(WaitRule)` - Successfully compiled rule
+    /// * `Err(regex::Error)` - `pattern`, `before` or `after` is not a valid regex
+    pub fn compile(config: &WaitRuleConfig) -> Result<Self, regex::Error> {
+        Ok(Self {
+            matcher: Matcher::Regex(Regex::new(&config.pattern)?),
+            before: config
+                .before
+                .as_deref()
+                .map(|p| Regex::new(&format!("(?:{p})$")))
+                .transpose()?,
+            after: config
+                .after
+                .as_deref()
+                .map(|p| Regex::new(&format!("^(?:{p})")))
+                .transpose()?,
+            emotion: config.emotion.clone(),
+            wait: RuleWait::Fixed(config.wait),
+            mode: config.mode,
+        })
+    }
+
+    fn builtin(matcher: Matcher, wait: RuleWait, mode: WaitRuleMode) -> Self {
+        Self {
+            matcher,
+            before: None,
+            after: None,
+            emotion: None,
+            wait,
+            mode,
+        }
+    }
+}
+
+/// Runtime context for rule conditions.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct WaitContext<'a> {
+    /// Current emotion of the speaking actor
+    pub emotion: Option<&'a str>,
+}
+
+/// Built-in rules reproducing the character class waits.
+///
+/// 1. Runs of period/comma/strong/line start prohibited characters wait once,
+///    using the largest wait of the run (line start prohibited counts as 0)
+/// 2. Leader characters wait `leader` per character
+/// 3. Line end prohibited characters do not wait
+/// 4. General characters wait `normal` per character
+pub fn default_rules(values: &WaitValues) -> Vec<WaitRule> {
+    const PUNCTUATION: &[TokenKind] = &[
+        TokenKind::Period,
+        TokenKind::Comma,
+        TokenKind::Strong,
+        TokenKind::LineStartProhibited,
+    ];
+    vec![
+        WaitRule::builtin(
+            Matcher::Run(PUNCTUATION),
+            RuleWait::ClassMax(values.clone()),
+            WaitRuleMode::Once,
+        ),
+        WaitRule::builtin(
+            Matcher::Kind(TokenKind::Leader),
+            RuleWait::Fixed(values.leader),
+            WaitRuleMode::PerChar,
+        ),
+        WaitRule::builtin(
+            Matcher::Kind(TokenKind::LineEndProhibited),
+            RuleWait::Fixed(0),
+            WaitRuleMode::PerChar,
+        ),
+        WaitRule::builtin(
+            Matcher::Kind(TokenKind::General),
+            RuleWait::Fixed(values.normal),
+            WaitRuleMode::PerChar,
+        ),
+    ]
+}
+
+/// Insert wait tags into a token sequence using ordered rules.
+///
+/// # Arguments
+/// * `tokens` - Token sequence
+/// * `rules` - Rules in priority order (usually ending with [`default_rules`])
+/// * `base` - Display time per character already applied by the baseware
+/// * `context` - Runtime context for rule conditions
+/// * `pacer` - Pacing variance for per-character waits ([`Pacer::none`] to disable)
+///
+/// # Returns
+/// String with wait tags inserted
+pub fn apply_rules(
+    tokens: &[Token],
+    rules: &[WaitRule],
+    base: i64,
+    context: &WaitContext,
+    pacer: &mut Pacer,
+) -> String {
+    let mut result = String::new();
+    let mut run: Vec<&Token> = Vec::new();
+
+    for token in tokens {
+        if token.kind == TokenKind::SakuraScript {
+            flush_run(&mut result, &run, rules, base, context, pacer);
+            run.clear();
+            match pacing::parse_wait(&token.text) {
+                Some(ms) if pacer.merge_waits() => pacing::push_wait(&mut result, ms, true),
+                _ => result.push_str(&token.text),
+            }
+        } else {
+            run.push(token);
+        }
+    }
+    flush_run(&mut result, &run, rules, base, context, pacer);
+
+    result
+}
+
+/// Character of a text run.
+struct RunChar {
+    c: char,
+    kind: TokenKind,
+    /// Byte offset in the run text
+    offset: usize,
+    /// Claimed by a rule
+    claimed: bool,
+    /// Wait after the character
+    wait: Option<i64>,
+    /// Wait comes from a `per_char` rule
+    per_char: bool,
+}
+
+/// Apply rules to a run of text tokens and append the result.
+fn flush_run(
+    result: &mut String,
+    run: &[&Token],
+    rules: &[WaitRule],
+    base: i64,
+    context: &WaitContext,
+    pacer: &mut Pacer,
+) {
+    if run.is_empty() {
+        return;
+    }
+
+    let mut text = String::new();
+    let mut chars = Vec::new();
+    for token in run {
+        for c in token.text.chars() {
+            chars.push(RunChar {
+                c,
+                kind: token.kind.clone(),
+                offset: text.len(),
+                claimed: false,
+                wait: None,
+                per_char: false,
+            });
+            text.push(c);
+        }
+    }
+
+    for rule in rules {
+        if rule
+            .emotion
+            .as_deref()
+            .is_some_and(|emotion| context.emotion != Some(emotion))
+        {
+            continue;
+        }
+        for range in matches(rule, &text, &chars) {
+            if chars[range.clone()].iter().any(|ch| ch.claimed) {
+                continue;
+            }
+            let wait = match &rule.wait {
+                RuleWait::Fixed(ms) => *ms,
+                RuleWait::ClassMax(values) => chars[range.clone()]
+                    .iter()
+                    .map(|ch| class_wait(&ch.kind, values))
+                    .max()
+                    .unwrap_or(0),
+            };
+            let last = range.end - 1;
+            for (i, ch) in chars[range.clone()].iter_mut().enumerate() {
+                ch.claimed = true;
+                if rule.mode == WaitRuleMode::PerChar || range.start + i == last {
+                    ch.wait = Some(wait);
+                    ch.per_char = rule.mode == WaitRuleMode::PerChar;
+                }
+            }
+        }
+    }
+
+    for (i, ch) in chars.iter().enumerate() {
+        result.push(ch.c);
+        let Some(wait) = ch.wait else {
+            continue;
+        };
+        let mut effective_wait = wait - base;
+        if effective_wait > 0 && ch.per_char {
+            effective_wait = pacer.adjust(effective_wait, position(ch, chars.get(i + 1)));
+        }
+        if effective_wait > 0 {
+            pacing::push_wait(result, effective_wait, pacer.merge_waits());
+        }
+    }
+}
+
+/// Word / clause position of a character for pacing.
+fn position(ch: &RunChar, next: Option<&RunChar>) -> Position {
+    let is_word = |ch: &RunChar| ch.kind == TokenKind::General && !ch.c.is_whitespace();
+    Position {
+        inside_word: is_word(ch) && next.is_some_and(is_word),
+        before_clause: next
+            .is_some_and(|next| matches!(next.kind, TokenKind::Period | TokenKind::Comma)),
+    }
+}
+
+/// Punctuation wait of a character kind for [`RuleWait::ClassMax`].
+fn class_wait(kind: &TokenKind, values: &WaitValues) -> i64 {
+    match kind {
+        TokenKind::Period => values.period,
+        TokenKind::Comma => values.comma,
+        TokenKind::Strong => values.strong,
+        _ => 0,
+    }
+}
+
+/// Character index ranges matched by a rule (before claim checks).
+fn matches(rule: &WaitRule, text: &str, chars: &[RunChar]) -> Vec<std::ops::Range<usize>> {
+    match &rule.matcher {
+        Matcher::Regex(re) => {
+            let index_of = |offset: usize| chars.partition_point(|ch| ch.offset < offset);
+            re.find_iter(text)
+                .filter(|m| !m.is_empty())
+                .filter(|m| {
+                    rule.before
+                        .as_ref()
+                        .is_none_or(|before| before.is_match(&text[..m.start()]))
+                        && rule
+                            .after
+                            .as_ref()
+                            .is_none_or(|after| after.is_match(&text[m.end()..]))
+                })
+                .map(|m| index_of(m.start())..index_of(m.end()))
+                .collect()
+        }
+        Matcher::Run(kinds) => {
+            let mut ranges = Vec::new();
+            let mut start = None;
+            for (i, ch) in chars.iter().enumerate() {
+                let member = !ch.claimed && kinds.contains(&ch.kind);
+                match (member, start) {
+                    (true, None) => start = Some(i),
+                    (false, Some(s)) => {
+                        ranges.push(s..i);
+                        start = None;
+                    }
+                    _ => {}
+                }
+            }
+            if let Some(s) = start {
+                ranges.push(s..chars.len());
+            }
+            ranges
+        }
+        Matcher::Kind(kind) => chars
+            .iter()
+            .enumerate()
+            .filter(|(_, ch)| ch.kind == *kind)
+            .map(|(i, _)| i..i + 1)
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::loader::{PacingConfig, TalkConfig};
+    use crate::sakura_script::tokenizer::Tokenizer;
+
+    fn rule(pattern: &str, wait: i64, mode: WaitRuleMode) -> WaitRuleConfig {
+        WaitRuleConfig {
+            pattern: pattern.into(),
+            before: None,
+            after: None,
+            emotion: None,
+            wait,
+            mode,
+        }
+    }
+
+    fn convert(input: &str, configs: &[WaitRuleConfig], context: &WaitContext) -> String {
+        convert_paced(input, configs, context, &mut Pacer::none())
+    }
+
+    fn convert_paced(
+        input: &str,
+        configs: &[WaitRuleConfig],
+        context: &WaitContext,
+        pacer: &mut Pacer,
+    ) -> String {
+        let config = TalkConfig {
+            script_wait_normal: 100,
+            ..Default::default()
+        };
+        let tokenizer = Tokenizer::new(&config).unwrap();
+        let values = WaitValues::from_config(&config);
+        let mut rules: Vec<WaitRule> = configs
+            .iter()
+            .map(|c| WaitRule::compile(c).unwrap())
+            .collect();
+        rules.extend(default_rules(&values));
+        apply_rules(
+            &tokenizer.tokenize(input),
+            &rules,
+            values.base,
+            context,
+            pacer,
+        )
+    }
+
+    #[test]
+    fn test_default_rules_only() {
+        assert_eq!(
+            convert("あ、……」。", &[], &WaitContext::default()),
+            r"あ\_w[50]、\_w[450]…\_w[150]…\_w[150]」。\_w[950]"
+        );
+    }
+
+    #[test]
+    fn test_once_rule_overrides_defaults() {
+        let rules = [rule("……」", 1200, WaitRuleMode::Once)];
+        assert_eq!(
+            convert("「……」。", &rules, &WaitContext::default()),
+            r"「……」\_w[1150]。\_w[950]"
+        );
+    }
+
+    #[test]
+    fn test_after_context_no_wait_inside_words() {
+        let mut no_wait = rule("[A-Za-z]", 0, WaitRuleMode::PerChar);
+        no_wait.after = Some("[A-Za-z]".into());
+        // 単語の末尾の文字は通常のウェイト
+        assert_eq!(
+            convert("Hello。", &[no_wait], &WaitContext::default()),
+            r"Hello\_w[50]。\_w[950]"
+        );
+    }
+
+    #[test]
+    fn test_before_context() {
+        let mut after_name = rule("、", 900, WaitRuleMode::Once);
+        after_name.before = Some("さくら".into());
+        assert_eq!(
+            convert("さくら、あ、", &[after_name], &WaitContext::default()),
+            r"さ\_w[50]く\_w[50]ら\_w[50]、\_w[850]あ\_w[50]、\_w[450]"
+        );
+    }
+
+    #[test]
+    fn test_emotion_condition() {
+        let mut hurried = rule(".", 60, WaitRuleMode::PerChar);
+        hurried.emotion = Some("焦り".into());
+        let rules = [hurried];
+        assert_eq!(
+            convert("あい", &rules, &WaitContext::default()),
+            r"あ\_w[50]い\_w[50]"
+        );
+        assert_eq!(
+            convert(
+                "あい",
+                &rules,
+                &WaitContext {
+                    emotion: Some("焦り")
+                }
+            ),
+            r"あ\_w[10]い\_w[10]"
+        );
+    }
+
+    #[test]
+    fn test_rules_do_not_cross_tags() {
+        let rules = [rule("あい", 500, WaitRuleMode::Once)];
+        assert_eq!(
+            convert(r"あ\s[1]いあい", &rules, &WaitContext::default()),
+            r"あ\_w[50]\s[1]い\_w[50]あい\_w[450]"
+        );
+    }
+
+    #[test]
+    fn test_pacing_applies_to_per_char_waits_only() {
+        let config = PacingConfig {
+            burst: 40,
+            clause_slowdown: 100,
+            ..Default::default()
+        };
+        // 語中は短く、読点の前は長く、句読点の once ウェイトはそのまま
+        assert_eq!(
+            convert_paced(
+                "あいう、",
+                &[],
+                &WaitContext::default(),
+                &mut Pacer::new(config, None)
+            ),
+            r"あ\_w[30]い\_w[30]う\_w[100]、\_w[450]"
+        );
+    }
+
+    #[test]
+    fn test_merge_waits() {
+        let config = PacingConfig {
+            merge_waits: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            convert_paced(
+                r"あ。\_w[300]\_w[200]い",
+                &[],
+                &WaitContext::default(),
+                &mut Pacer::new(config, None)
+            ),
+            r"あ\_w[50]。\_w[1450]い\_w[50]"
+        );
+    }
+
+    #[test]
+    fn test_invalid_pattern() {
+        assert!(WaitRule::compile(&rule("(", 0, WaitRuleMode::Once)).is_err());
+        let mut bad_after = rule("a", 0, WaitRuleMode::Once);
+        bad_after.after = Some("[".into());
+        assert!(WaitRule::compile(&bad_after).is_err());
+    }
+}
@@ -0,0 +1,202 @@
+//! Script event stream - structured output alternative to sakura script.
+//!
+//! `pasta.shiori.event_builder` converts the same grouped token stream that
+//! `pasta.shiori.sakura_builder` turns into sakura script into a list of
+//! [`ScriptEvent`]s, for frontends that are not SHIORI baseware.
+//!
+//! The JSON form is a versioned envelope:
+//!
+//! ```json
+//! {
+//!   "version": 1,
+//!   "events": [
+//!     { "type": "actor", "name": "さくら", "spot": 0 },
+//!     { "type": "surface", "actor": "さくら", "id": "5" },
+//!     { "type": "talk", "actor": "さくら", "text": "こんにちは。" },
+//!     { "type": "wait", "ms": 500 },
+//!     { "type": "newline", "n": 1 }
+//!   ]
+//! }
+//! ```
+//!
+//! Fields and event types are only ever added within a version; removing or
+//! changing the meaning of either bumps [`SCRIPT_EVENT_VERSION`]. Readers skip
+//! event types they do not know.
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// Current version of the event stream format.
+pub const SCRIPT_EVENT_VERSION: u32 = 1;
+
+/// One output event.
+///
+/// New event types may be added within a version, so matches need a wildcard arm.
+/// Unknown types deserialize as [`ScriptEvent::Unknown`] and are dropped by
+/// [`ScriptEventStream::from_json`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum ScriptEvent {
+    /// The speaking actor changed
+    Actor {
+        /// Actor name
+        name: String,
+        /// Spot (balloon position) of the actor; 0 = sakura, 1 = kero
+        spot: u32,
+        /// Balloon ID set for the actor (`balloon` actor setting), if any
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        balloon: Option<u32>,
+    },
+    /// Dialogue text, without timing or line wrapping applied
+    Talk {
+        /// Speaking actor, if any
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        actor: Option<String>,
+        /// Text to display
+        text: String,
+    },
+    /// Surface (expression) change of the speaking actor
+    Surface {
+        /// Actor whose surface changes, if any
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        actor: Option<String>,
+        /// Surface ID or alias
+        id: String,
+    },
+    /// Pause in milliseconds
+    Wait {
+        /// Milliseconds
+        ms: u64,
+    },
+    /// Line breaks
+    Newline {
+        /// Number of line breaks
+        n: u32,
+    },
+    /// Clear the balloon
+    Clear,
+    /// Spot assignments were reset (`act:clear_spot()`)
+    ClearSpots,
+    /// Sakura script passed through verbatim (`act:sakura_script()`)
+    RawScript {
+        /// Script text
+        text: String,
+    },
+    /// Event type added by a newer producer of the same version
+    #[serde(other)]
+    Unknown,
+}
+
+/// Versioned envelope of an event list.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScriptEventStream {
+    /// Format version ([`SCRIPT_EVENT_VERSION`])
+    pub version: u32,
+    /// Events in output order
+    pub events: Vec<ScriptEvent>,
+}
+
+/// Error reading an event stream.
+#[derive(Debug, Error)]
+pub enum ScriptEventError {
+    /// Malformed JSON or unknown event
+    #[error("Invalid script event stream: {0}")]
+    Json(#[from] serde_json::Error),
+
+    /// Version this build cannot read
+    #[error("Unsupported script event stream version {0} (expected {SCRIPT_EVENT_VERSION})")]
+    UnsupportedVersion(u32),
+}
+
+impl ScriptEventStream {
+    /// Wrap events in an envelope of the current version.
+    pub fn new(events: Vec<ScriptEvent>) -> Self {
+        Self {
+            version: SCRIPT_EVENT_VERSION,
+            events,
+        }
+    }
+
+    /// Serialize to JSON.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("script events always serialize")
+    }
+
+    /// Parse JSON, rejecting versions other than [`SCRIPT_EVENT_VERSION`].
+    ///
+    /// Events of unknown types are skipped.
+    pub fn from_json(json: &str) -> Result<Self, ScriptEventError> {
+        let mut stream: Self = serde_json::from_str(json)?;
+        if stream.version != SCRIPT_EVENT_VERSION {
+            return Err(ScriptEventError::UnsupportedVersion(stream.version));
+        }
+        stream.events.retain(|event| *event != ScriptEvent::Unknown);
+        Ok(stream)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_event_json_shape() {
+        let stream = ScriptEventStream::new(vec![
+            ScriptEvent::Actor {
+                name: "さくら".into(),
+                spot: 0,
+                balloon: None,
+            },
+            ScriptEvent::Talk {
+                actor: Some("さくら".into()),
+                text: "こんにちは".into(),
+            },
+            ScriptEvent::Wait { ms: 500 },
+            ScriptEvent::Clear,
+            ScriptEvent::RawScript {
+                text: r"\![raise,OnTest]".into(),
+            },
+        ]);
+        assert_eq!(
+            stream.to_json(),
+            r#"{"version":1,"events":[{"type":"actor","name":"さくら","spot":0},{"type":"talk","actor":"さくら","text":"こんにちは"},{"type":"wait","ms":500},{"type":"clear"},{"type":"raw_script","text":"\\![raise,OnTest]"}]}"#
+        );
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let stream = ScriptEventStream::new(vec![
+            ScriptEvent::Surface {
+                actor: None,
+                id: "5".into(),
+            },
+            ScriptEvent::Newline { n: 2 },
+            ScriptEvent::ClearSpots,
+        ]);
+        let parsed = ScriptEventStream::from_json(&stream.to_json()).unwrap();
+        assert_eq!(parsed, stream);
+    }
+
+    #[test]
+    fn test_rejects_unknown_version() {
+        assert!(matches!(
+            ScriptEventStream::from_json(r#"{"version":2,"events":[]}"#),
+            Err(ScriptEventError::UnsupportedVersion(2))
+        ));
+        assert!(matches!(
+            ScriptEventStream::from_json(r#"{"version":1,"events":[{"type":"wait"}]}"#),
+            Err(ScriptEventError::Json(_))
+        ));
+    }
+
+    #[test]
+    fn test_skips_unknown_event_types() {
+        // 同じバージョンの新しい出力側が追加したイベント種別は読み飛ばす
+        let stream = ScriptEventStream::from_json(
+            r#"{"version":1,"events":[{"type":"dance","speed":3},{"type":"wait","ms":10}]}"#,
+        )
+        .unwrap();
+        assert_eq!(stream.events, vec![ScriptEvent::Wait { ms: 10 }]);
+    }
+}
@@ -110,6 +110,38 @@ impl SearchContext {
         }
     }
 
+    /// Collect global scenes whose name starts with `prefix`.
+    ///
+    /// Unlike `search_scene`, this returns every candidate without selecting one,
+    /// so callers can apply their own matching rules (e.g. OnCommunicate scenes).
+    ///
+    /// # Returns
+    /// * Global scene names in registration order (transpiler output, e.g. "メイン1")
+    pub fn scene_candidates(&self, prefix: &str) -> Vec<String> {
+        let mut ids = self
+            .scene_table
+            .collect_scene_candidates("", prefix)
+            .unwrap_or_default();
+        ids.sort_by_key(|id| id.0);
+        ids.into_iter()
+            .filter_map(|id| self.scene_table.get_scene(id))
+            .map(|scene| Self::parse_fn_name(&scene.fn_name).0)
+            .collect()
+    }
+
+    /// Pick one of `candidates` with the scene selector.
+    ///
+    /// Used with [`Self::scene_candidates`] so custom scene matching stays
+    /// controllable by `set_scene_selector`.
+    ///
+    /// # Returns
+    /// * `Some(name)` - Selected candidate
+    /// * `None` - `candidates` is empty
+    pub fn select_scene(&mut self, mut candidates: Vec<String>) -> Option<String> {
+        let index = self.scene_table.select_index(candidates.len())?;
+        Some(candidates.swap_remove(index))
+    }
+
     /// Parse fn_name to extract global_name and local_name in transpiler output format.
     ///
     /// # Arguments
@@ -165,10 +197,7 @@ impl SearchContext {
             None => Box::new(DefaultRandomSelector::new()),
         };
 
-        // Recreate scene table with new selector
-        // Note: This is a workaround since SceneTable doesn't have replace_selector
-        // In a production implementation, we'd add that method to SceneTable
-        self.scene_table = SceneTable::new(selector);
+        self.scene_table.set_random_selector(selector);
         Ok(())
     }
 
@@ -202,6 +231,16 @@ impl UserData for SearchContext {
             },
         );
 
+        // scene_candidates(prefix) -> { global_name, ... }
+        methods.add_method("scene_candidates", |lua, this, prefix: String| {
+            lua.create_sequence_from(this.scene_candidates(&prefix))
+        });
+
+        // select_scene({ global_name, ... }) -> global_name or nil
+        methods.add_method_mut("select_scene", |_lua, this, candidates: Vec<String>| {
+            Ok(this.select_scene(candidates))
+        });
+
         // search_word(name, global_scene_name?) -> string or nil
         methods.add_method_mut(
             "search_word",
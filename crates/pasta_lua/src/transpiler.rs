@@ -130,6 +130,8 @@ impl LuaTranspiler {
             }
         }
 
+        context.sakura_warnings = codegen.take_sakura_warnings();
+
         // Convert intermediate buffer to UTF-8 string and normalize
         let raw_output = String::from_utf8(intermediate_buffer)
             .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
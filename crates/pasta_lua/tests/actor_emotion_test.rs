@@ -0,0 +1,201 @@
+//! Actor emotion state integration tests.
+//!
+//! ％ アクタースコープの `＆emotions：…` と pasta.toml の [actor.*.emotions] で定義した
+//! 感情を、会話中の `＠感情名` で切り替えられること、トーク終了時・時間経過で
+//! 中立のサーフェスへ自動的に戻ることを検証する。
+
+mod common;
+
+use common::ghost_fixture::load_ghost;
+use pasta_lua::PastaLuaRuntime;
+use tempfile::TempDir;
+
+const SCENES: &str = r#"％さくら
+　＆spot：0
+　＆emotions：通常=0,笑顔=5,怒り=7
+
+％うにゅう
+　＆spot：kero
+
+＊OnTest
+  さくら：＠笑顔　やあ
+  うにゅう：＠怒り　おい
+  さくら：＠怒り　なに
+
+＊OnSmile
+  さくら：＠笑顔　ふふ
+"#;
+
+const PASTA_TOML: &str = r#"[loader]
+debug_mode = true
+
+[ghost]
+spot_newlines = 1.0
+
+[actor."さくら"]
+emotion_reset = "talk"
+
+[actor."うにゅう"]
+neutral = "普通"
+emotion_reset_secs = 30
+
+[actor."うにゅう".emotions]
+"普通" = 10
+"怒り" = 17
+"#;
+
+fn load(pasta_toml: &str) -> (TempDir, PastaLuaRuntime) {
+    load_ghost("actors.pasta", SCENES, pasta_toml)
+}
+
+/// Fire an event and return the Value header (None for 204).
+fn fire(runtime: &PastaLuaRuntime, req: &str) -> Option<String> {
+    let res = runtime
+        .lua()
+        .load(format!(
+            r#"
+            local EVENT = require("pasta.shiori.event")
+            return EVENT.fire({req})
+            "#
+        ))
+        .eval::<String>()
+        .unwrap();
+    res.lines()
+        .find_map(|line| line.strip_prefix("Value: "))
+        .map(str::to_string)
+}
+
+#[test]
+fn test_emotion_sugar_and_reset_on_talk_end() {
+    let (_temp, runtime) = load(PASTA_TOML);
+
+    let value = fire(
+        &runtime,
+        r#"{ id = "OnTest", method = "get", version = 30, reference = {} }"#,
+    )
+    .unwrap();
+    assert_eq!(
+        value,
+        concat!(
+            r"\p[0]\s[5]やあ",
+            r"\n[100]\p[1]\s[17]おい",
+            r"\n[100]\p[0]\s[7]なに",
+            // さくら: emotion_reset = "talk" で中立に戻す。うにゅうは保持
+            r"\s[0]\e",
+        )
+    );
+
+    let (sakura, unyu): (String, String) = runtime
+        .lua()
+        .load(
+            r#"
+            local actors = require("pasta.store").actors
+            return actors["さくら"].emotion, actors["うにゅう"].emotion
+            "#,
+        )
+        .eval()
+        .unwrap();
+    assert_eq!(sakura, "通常");
+    assert_eq!(unyu, "怒り");
+}
+
+#[test]
+fn test_emotion_reset_after_seconds_on_second_change() {
+    let (_temp, runtime) = load(PASTA_TOML);
+    runtime
+        .lua()
+        .load(r#"require("pasta.store").actors["うにゅう"]:set_emotion("怒り", 1000)"#)
+        .exec()
+        .unwrap();
+
+    let second_change = |unix: i64| {
+        fire(
+            &runtime,
+            &format!(
+                r#"{{ id = "OnSecondChange", method = "get", version = 30, reference = {{}}, date = {{ unix = {unix}, hour = 12, min = 30, sec = 0 }} }}"#
+            ),
+        )
+    };
+
+    // 保持時間内は何もしない
+    assert_eq!(second_change(1010), None);
+    // 30秒経過で中立のサーフェスへ戻す
+    assert_eq!(second_change(1030).as_deref(), Some(r"\p[1]\s[10]\e"));
+    assert_eq!(second_change(1031), None);
+}
+
+#[test]
+fn test_emotion_reset_after_seconds_with_custom_second_change_handler() {
+    let (_temp, runtime) = load(PASTA_TOML);
+    runtime
+        .lua()
+        .load(
+            r#"
+            require("pasta.store").actors["うにゅう"]:set_emotion("怒り", 1000)
+            -- 仮想イベントを使わないゴーストの OnSecondChange
+            require("pasta.shiori.event.register").OnSecondChange = function(act)
+                return nil
+            end
+            "#,
+        )
+        .exec()
+        .unwrap();
+
+    let value = fire(
+        &runtime,
+        r#"{ id = "OnSecondChange", method = "get", version = 30, reference = {}, date = { unix = 1030, hour = 12, min = 30, sec = 0 } }"#,
+    );
+    assert_eq!(value.as_deref(), Some(r"\p[1]\s[10]\e"));
+}
+
+#[test]
+fn test_shell_alias_and_toml_emotions_take_precedence_over_dsl() {
+    let toml = format!(
+        "{PASTA_TOML}\n[actor.\"さくら\".emotions]\n\"笑顔\" = 50\n\n[actor.\"さくら\".shells.\"冬服\"]\n\"50\" = 150\n"
+    );
+    let (_temp, runtime) = load(&toml);
+
+    assert_eq!(
+        fire(
+            &runtime,
+            r#"{ id = "OnSmile", method = "get", version = 30, reference = {} }"#,
+        )
+        .as_deref(),
+        Some(r"\p[0]\s[50]ふふ\s[0]\e")
+    );
+
+    // シェル名の通知後はシェル別の別名を使う
+    fire(
+        &runtime,
+        r#"{ id = "OnShellChanged", method = "notify", version = 30, reference = { [0] = "冬服" } }"#,
+    );
+    assert_eq!(
+        fire(
+            &runtime,
+            r#"{ id = "OnSmile", method = "get", version = 30, reference = {} }"#,
+        )
+        .as_deref(),
+        Some(r"\p[0]\s[150]ふふ\s[0]\e")
+    );
+}
+
+#[test]
+fn test_existing_word_takes_precedence_over_emotion() {
+    let scenes = format!("＠通常：\\s[3]\n\n{SCENES}\n＊OnWord\n  さくら：＠通常　やあ\n");
+    let (_temp, runtime) = load_ghost("actors.pasta", &scenes, PASTA_TOML);
+
+    let value = fire(
+        &runtime,
+        r#"{ id = "OnWord", method = "get", version = 30, reference = {} }"#,
+    )
+    .unwrap();
+    // 単語 ＠通常 の展開結果。感情の切り替え（\s[0]）にはならない
+    assert_eq!(value, r"\p[0]\s[3]やあ\e");
+
+    let emotion: Option<String> = runtime
+        .lua()
+        .load(r#"return require("pasta.store").actors["さくら"].emotion"#)
+        .eval()
+        .unwrap();
+    assert_eq!(emotion, None);
+}
@@ -0,0 +1,123 @@
+//! Actor-level output settings integration tests.
+//!
+//! ％ アクタースコープの属性（＆spot：2 など）と pasta.toml の [actor.*] から
+//! 既定スポット・バルーン・改行量・ウェイトプロファイルが BUILDER.build に
+//! 反映されることを、3人以上の会話で検証する。
+
+mod common;
+
+use common::ghost_fixture::load_ghost;
+use pasta_lua::PastaLuaRuntime;
+use tempfile::TempDir;
+
+const SCENES: &str = r#"％さくら
+　＆spot：0
+
+％うにゅう
+　＆spot：kero
+　＆balloon：3
+
+％まゆら
+　＆spot：char2
+　＆spot_newlines：0.5
+　＆wait_profile：ゆっくり
+
+＊OnTest
+  さくら：あ
+  うにゅう：い
+  まゆら：う
+  さくら：え
+"#;
+
+const PASTA_TOML: &str = r#"[loader]
+debug_mode = true
+
+[ghost]
+spot_newlines = 1.0
+
+[talk.profiles."ゆっくり"]
+script_wait_normal = 100
+
+[actor."さくら"]
+balloon = 2
+"#;
+
+fn load(pasta_toml: &str) -> (TempDir, PastaLuaRuntime) {
+    load_ghost("actors.pasta", SCENES, pasta_toml)
+}
+
+#[test]
+fn test_actor_settings_in_three_actor_conversation() {
+    let (_temp, runtime) = load(PASTA_TOML);
+
+    let res = runtime
+        .lua()
+        .load(
+            r#"
+            local EVENT = require("pasta.shiori.event")
+            return EVENT.fire({ id = "OnTest", method = "get", version = 30, reference = {} })
+            "#,
+        )
+        .eval::<String>()
+        .unwrap();
+    let value = res
+        .lines()
+        .find_map(|line| line.strip_prefix("Value: "))
+        .unwrap_or_else(|| panic!("no Value header: {res}"));
+
+    assert_eq!(
+        value,
+        concat!(
+            r"\p[0]\b[2]あ",
+            r"\n[100]\p[1]\b[3]い",
+            // まゆら: 改行量 0.5、プロファイルで1文字ごとに \_w[50]
+            r"\n[50]\p[2]う\_w[50]",
+            r"\n[100]\p[0]え\e",
+        )
+    );
+}
+
+#[test]
+fn test_toml_actor_settings_take_precedence_over_dsl() {
+    let toml = format!("{PASTA_TOML}\n[actor.\"うにゅう\"]\nballoon = 5\n");
+    let (_temp, runtime) = load(&toml);
+
+    let balloon: i64 = runtime
+        .lua()
+        .load(r#"return require("pasta.store").actors["うにゅう"].balloon"#)
+        .eval()
+        .unwrap();
+    assert_eq!(balloon, 5);
+}
+
+#[test]
+fn test_unknown_attrs_do_not_shadow_actor_methods() {
+    let scenes = "％さくら\n　＆talk：上書き\n　＆create_word：上書き\n　＆spot：1\n\n＊OnTest\n  さくら：あ\n";
+    let (_temp, runtime) = load_ghost("actors.pasta", scenes, PASTA_TOML);
+
+    let (talk, create_word, spot): (bool, bool, i64) = runtime
+        .lua()
+        .load(
+            r#"
+            local actor = require("pasta.store").actors["さくら"]
+            return rawget(actor, "talk") == nil, rawget(actor, "create_word") == nil, actor.spot
+            "#,
+        )
+        .eval()
+        .unwrap();
+    assert!(talk);
+    assert!(create_word);
+    assert_eq!(spot, 1);
+
+    let res = runtime
+        .lua()
+        .load(
+            r#"
+            local EVENT = require("pasta.shiori.event")
+            return EVENT.fire({ id = "OnTest", method = "get", version = 30, reference = {} })
+            "#,
+        )
+        .eval::<String>()
+        .unwrap();
+    assert!(res.contains(r"Value: \p[1]\b[2]あ\e"), "{res}");
+}
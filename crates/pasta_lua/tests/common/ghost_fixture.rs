@@ -0,0 +1,64 @@
+//! Ghost directory fixtures for loader-based integration tests.
+//!
+//! - `create_ghost()` - Create a temporary ghost directory with the crate's
+//!   `scripts` / `scriptlibs`, one Pasta file and `pasta.toml`
+//! - `load_ghost()` - Create the ghost directory and load it with `PastaLoader`
+
+use pasta_lua::PastaLuaRuntime;
+use pasta_lua::loader::PastaLoader;
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+/// Minimal `pasta.toml` for tests that need no extra configuration.
+#[allow(dead_code)]
+pub const DEBUG_PASTA_TOML: &str = "[loader]\ndebug_mode = true\n";
+
+/// Recursively copy a directory's contents into an existing directory.
+pub fn copy_dir_recursive(src: &Path, dst: &Path) -> std::io::Result<()> {
+    for entry in std::fs::read_dir(src)? {
+        let entry = entry?;
+        let path = entry.path();
+        let dest_path = dst.join(entry.file_name());
+        if path.is_dir() {
+            std::fs::create_dir_all(&dest_path)?;
+            copy_dir_recursive(&path, &dest_path)?;
+        } else {
+            std::fs::copy(&path, &dest_path)?;
+        }
+    }
+    Ok(())
+}
+
+/// Create a temporary ghost directory.
+///
+/// # Arguments
+/// * `pasta_file` - File name of the scene file under `dic/test/`
+/// * `pasta` - Contents of the scene file
+/// * `pasta_toml` - Contents of `pasta.toml`
+///
+/// # Returns
+/// * `TempDir` - The ghost directory (removed when dropped)
+pub fn create_ghost(pasta_file: &str, pasta: &str, pasta_toml: &str) -> TempDir {
+    let temp = TempDir::new().unwrap();
+    let base_dir = temp.path();
+    let crate_root = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+
+    std::fs::create_dir_all(base_dir.join("dic/test")).unwrap();
+    std::fs::write(base_dir.join("dic/test").join(pasta_file), pasta).unwrap();
+    std::fs::write(base_dir.join("pasta.toml"), pasta_toml).unwrap();
+    for dir in ["scripts", "scriptlibs"] {
+        std::fs::create_dir_all(base_dir.join(dir)).unwrap();
+        copy_dir_recursive(&crate_root.join(dir), &base_dir.join(dir)).unwrap();
+    }
+    temp
+}
+
+/// Create a temporary ghost directory and load it.
+///
+/// The returned `TempDir` must be kept alive while the runtime is used.
+#[allow(dead_code)]
+pub fn load_ghost(pasta_file: &str, pasta: &str, pasta_toml: &str) -> (TempDir, PastaLuaRuntime) {
+    let temp = create_ghost(pasta_file, pasta, pasta_toml);
+    let runtime = PastaLoader::load(temp.path()).unwrap();
+    (temp, runtime)
+}
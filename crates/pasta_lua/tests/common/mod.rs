@@ -4,3 +4,4 @@
 //! Pasta DSL to Lua transpilation and runtime execution pipeline.
 
 pub mod e2e_helpers;
+pub mod ghost_fixture;
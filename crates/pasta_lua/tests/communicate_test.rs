@@ -0,0 +1,205 @@
+//! OnCommunicate framework integration tests.
+//!
+//! DSL の `＊OnCommunicate_送信者_発言` シーンの選択、返信先（Reference0）の自動設定、
+//! 往復回数上限を EVENT.fire 経由で検証する。
+
+mod common;
+
+use common::ghost_fixture::load_ghost;
+use pasta_lua::PastaLuaRuntime;
+use tempfile::TempDir;
+
+const SCENES: &str = r#"％さくら
+
+＊OnCommunicate
+  さくら：だれ？
+
+＊OnCommunicate_まゆら
+  さくら：まゆらさん、なあに？
+
+＊OnCommunicate_まゆら_こんにちは
+  さくら：まゆらさん、こんにちは。
+
+＊OnCommunicate_まゆら_こんにちは元気
+  さくら：元気だよ。
+
+＊OnCommunicate__おやすみ
+  さくら：おやすみなさい。
+
+＊OnCommunicate_user
+  さくら：ユーザーさんだ。
+
+＊OnCommunicate_ゆうか
+  さくら：ゆうかさん、いらっしゃい。
+
+＊OnCommunicate_ゆうか
+  さくら：ゆうかさん、ひさしぶり。
+
+＊OnCommunicateX
+  さくら：命名規則外。
+"#;
+
+fn load(pasta_toml: &str) -> (TempDir, PastaLuaRuntime) {
+    load_ghost("communicate.pasta", SCENES, pasta_toml)
+}
+
+fn communicate(runtime: &PastaLuaRuntime, sender: &str, message: &str) -> String {
+    runtime
+        .lua()
+        .load(format!(
+            r#"
+            local EVENT = require("pasta.shiori.event")
+            return EVENT.fire({{
+                id = "OnCommunicate",
+                method = "get",
+                version = 30,
+                reference = {{ [0] = "{sender}", [1] = "{message}" }},
+            }})
+            "#
+        ))
+        .eval::<String>()
+        .unwrap()
+}
+
+#[test]
+fn test_selects_most_specific_scene() {
+    let (_temp, runtime) = load("[loader]\ndebug_mode = true\n");
+
+    let res = communicate(&runtime, "まゆら", "こんにちは元気？");
+    assert!(res.contains("元気だよ。"), "{res}");
+    assert!(res.contains("Reference0: まゆら\r\n"), "{res}");
+
+    let res = communicate(&runtime, "まゆら", "こんにちは！");
+    assert!(res.contains("こんにちは。"), "{res}");
+
+    let res = communicate(&runtime, "まゆら", "ねえねえ");
+    assert!(res.contains("なあに？"), "{res}");
+
+    let res = communicate(&runtime, "えもこ", "おやすみ～");
+    assert!(res.contains("おやすみなさい。"), "{res}");
+    assert!(res.contains("Reference0: えもこ\r\n"), "{res}");
+
+    let res = communicate(&runtime, "えもこ", "やあ");
+    assert!(res.contains("だれ？"), "{res}");
+    assert!(!res.contains("命名規則外"), "{res}");
+}
+
+#[test]
+fn test_equal_candidates_use_scene_selector() {
+    let (_temp, runtime) = load("[loader]\ndebug_mode = true\n");
+
+    // 同じ優先度の候補は @pasta_search のシーン選択で選ぶ
+    let select = |index: usize| {
+        runtime
+            .lua()
+            .load(format!(
+                r#"require("@pasta_search"):set_scene_selector({index})"#
+            ))
+            .exec()
+            .unwrap();
+        communicate(&runtime, "ゆうか", "やあ")
+    };
+    let first = select(0);
+    let second = select(1);
+    assert_ne!(first, second);
+    assert!(
+        first.contains("いらっしゃい") || first.contains("ひさしぶり"),
+        "{first}"
+    );
+    assert!(
+        second.contains("いらっしゃい") || second.contains("ひさしぶり"),
+        "{second}"
+    );
+    assert_eq!(select(0), first);
+    assert_eq!(select(1), second);
+}
+
+#[test]
+fn test_user_message_has_no_reply_target() {
+    let (_temp, runtime) = load("[loader]\ndebug_mode = true\n");
+
+    let res = communicate(&runtime, "user", "やあ");
+    assert!(res.contains("ユーザーさんだ。"), "{res}");
+    assert!(!res.contains("Reference0"), "{res}");
+}
+
+#[test]
+fn test_turn_limit_stops_ping_pong() {
+    let (_temp, runtime) =
+        load("[loader]\ndebug_mode = true\n\n[ghost]\ncommunicate_max_turns = 2\n");
+
+    for _ in 0..2 {
+        let res = communicate(&runtime, "まゆら", "ねえねえ");
+        assert!(res.starts_with("SHIORI/3.0 200 OK"), "{res}");
+    }
+    // 3往復目は返信しない
+    let res = communicate(&runtime, "まゆら", "ねえねえ");
+    assert!(res.starts_with("SHIORI/3.0 204"), "{res}");
+    assert!(!res.contains("Reference0"), "{res}");
+
+    // 上限到達でリセットされ、次の会話は再開できる
+    let res = communicate(&runtime, "まゆら", "ねえねえ");
+    assert!(res.starts_with("SHIORI/3.0 200 OK"), "{res}");
+
+    // 相手が変わると数え直す
+    communicate(&runtime, "まゆら", "ねえねえ");
+    let res = communicate(&runtime, "えもこ", "やあ");
+    assert!(res.starts_with("SHIORI/3.0 200 OK"), "{res}");
+}
+
+#[test]
+fn test_turn_count_restarts_after_timeout() {
+    let (_temp, runtime) = load(
+        "[loader]\ndebug_mode = true\n\n[ghost]\ncommunicate_max_turns = 2\ncommunicate_timeout_secs = 60\n",
+    );
+
+    let communicate_at = |unix: i64| {
+        runtime
+            .lua()
+            .load(format!(
+                r#"
+                local EVENT = require("pasta.shiori.event")
+                return EVENT.fire({{
+                    id = "OnCommunicate",
+                    method = "get",
+                    version = 30,
+                    reference = {{ [0] = "まゆら", [1] = "ねえねえ" }},
+                    date = {{ unix = {unix} }},
+                }})
+                "#
+            ))
+            .eval::<String>()
+            .unwrap()
+    };
+
+    assert!(communicate_at(1000).starts_with("SHIORI/3.0 200 OK"));
+    assert!(communicate_at(1010).starts_with("SHIORI/3.0 200 OK"));
+    // 時間が空いた後の同じ相手からの発言は新しい会話として数える
+    assert!(communicate_at(2000).starts_with("SHIORI/3.0 200 OK"));
+    assert!(communicate_at(2010).starts_with("SHIORI/3.0 200 OK"));
+    assert!(communicate_at(2020).starts_with("SHIORI/3.0 204"));
+}
+
+#[test]
+fn test_communicate_to_from_other_event() {
+    let (_temp, runtime) = load("[loader]\ndebug_mode = true\n");
+
+    let res: String = runtime
+        .lua()
+        .load(
+            r#"
+            local REG = require("pasta.shiori.event.register")
+            local EVENT = require("pasta.shiori.event")
+            REG.OnTalkToMayura = function(act)
+                act:communicate_to("まゆら")
+                act.さくら:talk("まゆらさん、聞いて。")
+                return act:build()
+            end
+            return EVENT.fire({ id = "OnTalkToMayura", method = "get", version = 30 })
+            "#,
+        )
+        .eval()
+        .unwrap();
+    assert!(res.contains("聞いて。"), "{res}");
+    assert!(res.contains("Reference0: まゆら\r\n"), "{res}");
+}
@@ -0,0 +1,55 @@
+#!/bin/sh
+# Mock SAORI/1.0 helper for tests (SAORI-basic style).
+# Reads the request from stdin and writes the response to stdout.
+#
+# Argument0 selects the behaviour:
+#   add A B      -> Result: A+B
+#   echo A B     -> Result: A, Value0: A, Value1: B
+#   cwd          -> Result: basename of the working directory
+#   sleep        -> never answers (timeout test)
+#   garbage      -> malformed response
+#   (other)      -> 400 Bad Request
+
+method=""
+while IFS= read -r line; do
+    line=$(printf '%s' "$line" | tr -d '\r')
+    [ -z "$line" ] && break
+    if [ -z "$method" ]; then
+        method="$line"
+        continue
+    fi
+    case "$line" in
+        Argument[0-9]*": "*)
+            key=${line%%: *}
+            val=${line#*: }
+            eval "arg_${key#Argument}=\$val"
+            ;;
+    esac
+done
+
+if [ "$method" = "GET Version SAORI/1.0" ]; then
+    printf 'SAORI/1.0 200 OK\r\nCharset: UTF-8\r\n\r\n'
+    exit 0
+fi
+
+case "$arg_0" in
+    add)
+        printf 'SAORI/1.0 200 OK\r\nCharset: UTF-8\r\nResult: %s\r\n\r\n' "$((arg_1 + arg_2))"
+        ;;
+    echo)
+        printf 'SAORI/1.0 200 OK\r\nCharset: UTF-8\r\nResult: %s\r\nValue0: %s\r\nValue1: %s\r\n\r\n' \
+            "$arg_1" "$arg_1" "$arg_2"
+        ;;
+    cwd)
+        printf 'SAORI/1.0 200 OK\r\nResult: %s\r\n\r\n' "$(basename "$(pwd)")"
+        ;;
+    sleep)
+        exec sleep 10
+        ;;
+    garbage)
+        echo "not a saori response"
+        ;;
+    *)
+        printf 'SAORI/1.0 400 Bad Request\r\nCharset: UTF-8\r\n\r\n'
+        ;;
+esac
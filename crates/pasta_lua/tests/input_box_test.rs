@@ -0,0 +1,174 @@
+//! Input box (OnUserInput / OnUserInputCancel) integration tests.
+//!
+//! `＠入力（「ID」）` / `act:input()` で入力ボックスを開いたシーンが中断し、
+//! 一致する OnUserInput で入力値を変数に受け取って再開することを EVENT.fire 経由で検証する。
+
+mod common;
+
+use common::ghost_fixture::{DEBUG_PASTA_TOML, load_ghost};
+use pasta_lua::PastaLuaRuntime;
+use tempfile::TempDir;
+
+const SCENES: &str = r#"％さくら
+
+＊OnAskName
+  さくら：お名前は？
+  ＄名前＝＠入力（「name」）
+  さくら：＄名前　さん、よろしくね。
+
+＊OnUserInput
+  さくら：入力待ちではありません。
+
+＊OnOther
+  さくら：別の話。
+
+＊OnTalk
+  さくら：ひまだなあ。
+"#;
+
+fn load() -> (TempDir, PastaLuaRuntime) {
+    load_ghost("input.pasta", SCENES, DEBUG_PASTA_TOML)
+}
+
+fn fire(runtime: &PastaLuaRuntime, id: &str, references: &[&str]) -> String {
+    let reference = references
+        .iter()
+        .enumerate()
+        .map(|(i, r)| format!("[{i}] = \"{r}\""))
+        .collect::<Vec<_>>()
+        .join(", ");
+    runtime
+        .lua()
+        .load(format!(
+            r#"
+            local EVENT = require("pasta.shiori.event")
+            return EVENT.fire({{
+                id = "{id}",
+                method = "get",
+                version = 30,
+                reference = {{ {reference} }},
+            }})
+            "#
+        ))
+        .eval::<String>()
+        .unwrap()
+}
+
+#[test]
+fn test_dsl_input_suspends_and_resumes_with_value() {
+    let (_temp, runtime) = load();
+
+    // 入力待ちでない時は通常のシーン
+    let res = fire(&runtime, "OnUserInput", &["name", "x"]);
+    assert!(res.contains("入力待ちではありません。"), "{res}");
+
+    let res = fire(&runtime, "OnAskName", &[]);
+    assert!(res.contains("お名前は？"), "{res}");
+    assert!(res.contains(r"\![open,inputbox,name,-1]"), "{res}");
+    assert!(!res.contains("よろしくね"), "{res}");
+
+    let res = fire(&runtime, "OnUserInput", &["name", "たろう"]);
+    assert!(res.contains("たろう"), "{res}");
+    assert!(res.contains("よろしくね。"), "{res}");
+
+    // 入力待ちが終わった後は通常のシーン
+    let res = fire(&runtime, "OnUserInput", &["name", "じろう"]);
+    assert!(res.contains("入力待ちではありません。"), "{res}");
+}
+
+#[test]
+fn test_act_input_cancel_and_options() {
+    let (_temp, runtime) = load();
+
+    let res: String = runtime
+        .lua()
+        .load(
+            r#"
+            local REG = require("pasta.shiori.event.register")
+            local EVENT = require("pasta.shiori.event")
+            REG.OnAskBirthday = function(act)
+                return coroutine.create(function(act)
+                    act.さくら:talk("誕生日は？")
+                    local value, reason = act:input("birthday", {
+                        kind = "dateinput", timeout = 30000, args = { 2000, 1, 1 }, var = "誕生日",
+                    })
+                    act.さくら:talk(value or ("キャンセル:" .. reason))
+                    act.さくら:talk("/" .. tostring(act.var.誕生日))
+                    act.さくら:talk("/" .. act.req.id)
+                    act:set_marker("入力終了")
+                    return act:build()
+                end)
+            end
+            local first = EVENT.fire({ id = "OnAskBirthday", method = "get", version = 30 })
+            local second = EVENT.fire({
+                id = "OnUserInputCancel", method = "get", version = 30,
+                reference = { [0] = "birthday", [1] = "timeout" },
+            })
+            return first .. "\n---\n" .. second
+            "#,
+        )
+        .eval()
+        .unwrap();
+    let (first, second) = res.split_once("\n---\n").unwrap();
+    assert!(
+        first.contains(r"\![open,dateinput,birthday,30000,2000,1,1]"),
+        "{first}"
+    );
+    assert!(second.contains("キャンセル:timeout"), "{second}");
+    assert!(second.contains("/nil"), "{second}");
+    // 再開後の act.req と応答ヘッダーは OnUserInputCancel のもの
+    assert!(second.contains("/OnUserInputCancel"), "{second}");
+    assert!(second.contains("Marker: 入力終了\r\n"), "{second}");
+}
+
+#[test]
+fn test_virtual_events_wait_while_input_pending() {
+    let (_temp, runtime) = load();
+
+    fire(&runtime, "OnAskName", &[]);
+    let waiting: bool = runtime
+        .lua()
+        .load(
+            r#"
+            local INPUT = require("pasta.shiori.event.input")
+            local dispatcher = require("pasta.shiori.event.virtual_dispatcher")
+            local act = { req = { id = "OnSecondChange", date = { unix = 4102444800, hour = 0, min = 0, sec = 0 } } }
+            return INPUT.waiting("name") ~= nil and dispatcher.dispatch(act) == nil
+            "#,
+        )
+        .eval()
+        .unwrap();
+    assert!(waiting);
+}
+
+#[test]
+fn test_replaced_input_wait_no_longer_blocks_virtual_events() {
+    let (_temp, runtime) = load();
+    let second_change = |unix: i64| {
+        runtime
+            .lua()
+            .load(format!(
+                r#"
+                local EVENT = require("pasta.shiori.event")
+                return EVENT.fire({{
+                    id = "OnSecondChange", method = "get", version = 30, reference = {{}},
+                    date = {{ unix = {unix}, hour = 0, min = 10, sec = 0 }},
+                }})
+                "#
+            ))
+            .eval::<String>()
+            .unwrap()
+    };
+    // 正時から10分後。OnHour の時報マージンに掛からない
+    let start = 4102444800 + 600;
+
+    fire(&runtime, "OnAskName", &[]);
+    // 入力待ちのシーンを別のシーンで置き換える
+    let res = fire(&runtime, "OnOther", &[]);
+    assert!(res.contains("別の話。"), "{res}");
+
+    // 初回は次回トーク時刻を決めるだけ。トーク間隔（最大300秒）の経過後に OnTalk を発行する
+    second_change(start);
+    let res = second_change(start + 301);
+    assert!(res.contains("ひまだなあ。"), "{res}");
+}
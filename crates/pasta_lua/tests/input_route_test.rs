@@ -0,0 +1,104 @@
+//! Input event routing integration tests.
+//!
+//! マウスイベントの Reference（スコープ・当たり判定）から `＊さくら頭つつかれ` 形式の
+//! シーンへ振り分けられること、汎用シーンへのフォールバック、なでなで判定を
+//! EVENT.fire 経由で検証する。
+
+mod common;
+
+use common::ghost_fixture::load_ghost;
+use pasta_lua::PastaLuaRuntime;
+use tempfile::TempDir;
+
+const SCENES: &str = r#"％さくら
+
+＊さくら頭つつかれ
+  さくら：頭をつつかないで。
+
+＊つつかれ
+  さくら：ここは通らない。
+
+＊さくらつつかれ
+  さくら：なあに？
+
+＊さくら頭なでられ
+  さくら：えへへ。
+
+＊OnMouseDoubleClick
+  さくら：だれかがダブルクリックした。
+"#;
+
+const PASTA_TOML: &str = r#"[loader]
+debug_mode = true
+
+[actor."さくら"]
+spot = 0
+
+[input]
+stroke_count = 3
+
+[input.areas]
+Head = "頭"
+"#;
+
+fn load() -> (TempDir, PastaLuaRuntime) {
+    load_ghost("input.pasta", SCENES, PASTA_TOML)
+}
+
+fn mouse(runtime: &PastaLuaRuntime, id: &str, scope: &str, area: &str) -> String {
+    runtime
+        .lua()
+        .load(format!(
+            r#"
+            local EVENT = require("pasta.shiori.event")
+            return EVENT.fire({{
+                id = "{id}",
+                method = "get",
+                version = 30,
+                reference = {{ [0] = "10", [1] = "20", [2] = "0", [3] = "{scope}", [4] = "{area}" }},
+            }})
+            "#
+        ))
+        .eval::<String>()
+        .unwrap()
+}
+
+#[test]
+fn test_double_click_routes_by_actor_and_area() {
+    let (_temp, runtime) = load();
+
+    let res = mouse(&runtime, "OnMouseDoubleClick", "0", "Head");
+    assert!(res.contains("頭をつつかないで。"), "{res}");
+
+    // 当たり判定なし → アクター名のみのシーン
+    let res = mouse(&runtime, "OnMouseDoubleClick", "0", "");
+    assert!(res.contains("なあに？"), "{res}");
+
+    // アクター不明・当たり判定なし → イベント名のシーン
+    let res = mouse(&runtime, "OnMouseDoubleClick", "1", "");
+    assert!(res.contains("ダブルクリックした。"), "{res}");
+}
+
+#[test]
+fn test_unrouted_event_keeps_event_id_fallback() {
+    let (_temp, runtime) = load();
+
+    let res = mouse(&runtime, "OnMouseClick", "0", "Head");
+    assert!(res.starts_with("SHIORI/3.0 204"), "{res}");
+}
+
+#[test]
+fn test_stroke_after_repeated_mouse_moves() {
+    let (_temp, runtime) = load();
+
+    for _ in 0..2 {
+        let res = mouse(&runtime, "OnMouseMove", "0", "Head");
+        assert!(res.starts_with("SHIORI/3.0 204"), "{res}");
+    }
+    let res = mouse(&runtime, "OnMouseMove", "0", "Head");
+    assert!(res.contains("えへへ。"), "{res}");
+
+    // 判定後は数え直す
+    let res = mouse(&runtime, "OnMouseMove", "0", "Head");
+    assert!(res.starts_with("SHIORI/3.0 204"), "{res}");
+}
@@ -0,0 +1,50 @@
+-- ACT_IMPL.call_fn 関数呼び出しテスト（DSL の ＠関数名（引数））
+local describe = require("lua_test.test").describe
+local test = require("lua_test.test").test
+local expect = require("lua_test.test").expect
+
+local ACT = require("pasta.act")
+local GLOBAL = require("pasta.global")
+
+describe("ACT_IMPL.call_fn", function()
+    test("シーンテーブルの関数を (act, ...) で呼び出す", function()
+        local act = ACT.new({})
+        local scene = {
+            add = function(a, x, y)
+                expect(a):toBe(act)
+                return x + y
+            end
+        }
+        expect(act:call_fn(scene, "add", 1, 2)):toBe(3)
+    end)
+
+    test("シーンにない関数は GLOBAL から解決する", function()
+        local act = ACT.new({})
+        GLOBAL.__call_fn_test__ = function(a, x) return "global:" .. x end
+        expect(act:call_fn({}, "__call_fn_test__", "a")):toBe("global:a")
+        GLOBAL.__call_fn_test__ = nil
+    end)
+
+    test("scene = nil（＠＊関数名）はシーンテーブルを参照しない", function()
+        local act = ACT.new({})
+        GLOBAL.__call_fn_test__ = function() return "global" end
+        local scene = { __call_fn_test__ = function() return "scene" end }
+        expect(act:call_fn(scene, "__call_fn_test__")):toBe("scene")
+        expect(act:call_fn(nil, "__call_fn_test__")):toBe("global")
+        GLOBAL.__call_fn_test__ = nil
+    end)
+
+    test("シーンテーブルの関数以外の値は無視する", function()
+        local act = ACT.new({})
+        GLOBAL.__call_fn_test__ = function() return "global" end
+        expect(act:call_fn({ __call_fn_test__ = "word" }, "__call_fn_test__")):toBe("global")
+        GLOBAL.__call_fn_test__ = nil
+    end)
+
+    test("関数が見つからない場合はエラー", function()
+        local act = ACT.new({})
+        local ok, err = pcall(act.call_fn, act, {}, "__missing_fn__")
+        expect(ok):toBe(false)
+        expect(tostring(err):find("function not found: __missing_fn__", 1, true) ~= nil):toBe(true)
+    end)
+end)
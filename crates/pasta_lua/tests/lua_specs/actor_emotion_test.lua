@@ -0,0 +1,174 @@
+-- pasta.actor 感情・サーフェス管理テスト
+-- 感情名 → サーフェス解決、シェル別の別名、トーク終了時・時間経過での中立への自動リセット
+local describe = require("lua_test.test").describe
+local test = require("lua_test.test").test
+local expect = require("lua_test.test").expect
+
+--- 感情付きのテスト用アクターを作成（STORE.actors に登録される）
+local function create_actor(name, fields)
+    local ACTOR = require("pasta.actor")
+    local actor = ACTOR.get_or_create(name)
+    actor:define_emotion("通常", 0)
+    actor:define_emotion("笑顔", 5)
+    actor:define_emotion("怒り", 7)
+    for k, v in pairs(fields or {}) do
+        actor[k] = v
+    end
+    return actor
+end
+
+describe("ACTOR - 感情の状態", function()
+    test("set_emotion で現在の感情とサーフェスを記録する", function()
+        local actor = create_actor("感情テスト1")
+
+        expect(actor:set_emotion("笑顔", 100)):toBe(5)
+        expect(actor.emotion):toBe("笑顔")
+        expect(actor.surface):toBe(5)
+        expect(actor.emotion_at):toBe(100)
+        expect(actor:is_emotional()):toBe(true)
+    end)
+
+    test("未定義の感情はエラー", function()
+        local actor = create_actor("感情テスト2")
+        local ok, err = pcall(actor.set_emotion, actor, "号泣")
+        expect(ok):toBe(false)
+        expect(tostring(err):find("unknown emotion '号泣'", 1, true) ~= nil):toBe(true)
+    end)
+
+    test("reset_emotion で中立の感情に戻る（neutral で変更可能）", function()
+        local actor = create_actor("感情テスト3")
+        actor:set_emotion("怒り")
+        expect(actor:reset_emotion()):toBe(0)
+        expect(actor.emotion):toBe("通常")
+        expect(actor:is_emotional()):toBe(false)
+
+        actor.neutral = "笑顔"
+        actor:set_emotion("怒り")
+        expect(actor:reset_emotion()):toBe(5)
+    end)
+
+    test("中立の感情が未定義なら状態だけを消す", function()
+        local actor = require("pasta.actor").get_or_create("感情テスト4")
+        actor:define_emotion("笑顔", 5)
+        actor:set_emotion("笑顔")
+        expect(actor:reset_emotion()):toBe(nil)
+        expect(actor.emotion):toBe(nil)
+        expect(actor.surface):toBe(nil)
+    end)
+
+    test("set_attr の emotions で感情をまとめて定義する（定義済みは上書きしない）", function()
+        local actor = require("pasta.actor").get_or_create("感情テスト5")
+        actor.emotions = { ["通常"] = 100 }
+        actor:set_attr("emotions", "通常=0,笑顔=5、照れ＝smile")
+        expect(actor.emotions["通常"]):toBe(100)
+        expect(actor.emotions["笑顔"]):toBe(5)
+        expect(actor.emotions["照れ"]):toBe("smile")
+    end)
+
+    test("現在のシェルに別名があればシェルのサーフェスを使う", function()
+        local ENV = require("pasta.areka.env")
+        local actor = create_actor("感情テスト6", {
+            shells = { ["冬服"] = { ["笑顔"] = 105, ["7"] = 107 } },
+        })
+
+        ENV.reset()
+        expect(actor:surface_of("笑顔")):toBe(5)
+        expect(ENV.captured()):toBe(false) -- 通知を捕捉したことにしない
+
+        ENV.get().ghost.shell_name = "冬服"
+        expect(actor:surface_of("笑顔")):toBe(105) -- 感情名の別名
+        expect(actor:surface_of("怒り")):toBe(107) -- サーフェスIDの別名
+        expect(actor:surface_of("通常")):toBe(0)   -- 別名なし
+        ENV.reset()
+    end)
+end)
+
+describe("ACTOR - 感情変更のDSL糖衣（＠感情名）", function()
+    test("プロキシの word で感情名を引くと空文字列を返し、サーフェス変更を蓄積する", function()
+        local ACT = require("pasta.act")
+        local actor = create_actor("感情テスト7")
+        local act = ACT.new({ ["感情テスト7"] = actor })
+
+        local proxy = act["感情テスト7"]
+        expect(proxy:word("笑顔")):toBe("")
+        expect(actor.emotion):toBe("笑顔")
+        expect(act.token[1].type):toBe("surface")
+        expect(act.token[1].id):toBe(5)
+        expect(act.token[1].actor):toBe(actor)
+    end)
+
+    test("同名の単語があれば感情より単語を優先する", function()
+        local ACT = require("pasta.act")
+        local actor = create_actor("感情テスト7b", { ["通常"] = "\\s[0]" })
+        local act = ACT.new({ ["感情テスト7b"] = actor })
+        act.current_scene = { ["怒り"] = "ぷんぷん" }
+
+        local proxy = act["感情テスト7b"]
+        expect(proxy:word("通常")):toBe("\\s[0]") -- アクター単語
+        expect(proxy:word("怒り")):toBe("ぷんぷん") -- シーン単語
+        expect(actor.emotion):toBe(nil)
+        expect(#act.token):toBe(0)
+    end)
+
+    test("アクター付きサーフェスはそのアクターのグループに入る", function()
+        local ACT = require("pasta.act")
+        local BUILDER = require("pasta.shiori.sakura_builder")
+        local sakura = { name = "さくら", spot = 0 }
+        local actor = create_actor("感情テスト8", { spot = 1 })
+        local act = ACT.new({ ["さくら"] = sakura, ["感情テスト8"] = actor })
+
+        act:talk(sakura, "あ")
+        act["感情テスト8"]:talk(act["感情テスト8"]:word("怒り"))
+        act["感情テスト8"]:talk("い")
+
+        local script = BUILDER.build(act:build(), { spot_newlines = 1.0 })
+        expect(script):toBe("\\p[0]あ\\n[100]\\p[1]\\s[7]い\\e")
+    end)
+end)
+
+describe("ACTOR - 感情の自動リセット", function()
+    test("emotion_reset = \"talk\" ならトーク終了時に中立のサーフェスへ戻す（改行なし）", function()
+        local ACT = require("pasta.act")
+        local BUILDER = require("pasta.shiori.sakura_builder")
+        local sakura = create_actor("感情テスト9", { spot = 0, emotion_reset = "talk" })
+        local kero = { name = "うにゅう", spot = 1 }
+        local act = ACT.new({ ["感情テスト9"] = sakura, ["うにゅう"] = kero })
+
+        act["感情テスト9"]:emotion("笑顔")
+        act:talk(sakura, "あ")
+        act:talk(kero, "い")
+
+        local script = BUILDER.build(act:build(), { spot_newlines = 1.0 })
+        expect(script):toBe("\\p[0]\\s[5]あ\\n[100]\\p[1]い\\p[0]\\s[0]\\e")
+        expect(sakura.emotion):toBe("通常")
+    end)
+
+    test("emotion_reset がなければ感情を保持する", function()
+        local ACT = require("pasta.act")
+        local actor = create_actor("感情テスト10")
+        local act = ACT.new({ ["感情テスト10"] = actor })
+
+        act["感情テスト10"]:emotion("笑顔")
+        act:talk(actor, "あ")
+        local grouped = act:build()
+        expect(#grouped[1].tokens):toBe(2)
+        expect(actor.emotion):toBe("笑顔")
+    end)
+
+    test("reset_expired_emotions は emotion_reset_secs を過ぎた感情だけを戻す", function()
+        local ACT = require("pasta.act")
+        local ACTOR = require("pasta.actor")
+        local expired = create_actor("感情テスト11", { emotion_reset_secs = 30 })
+        local recent = create_actor("感情テスト12", { emotion_reset_secs = 30 })
+        expired:set_emotion("笑顔", 1000)
+        recent:set_emotion("怒り", 1020)
+
+        local act = ACT.new({})
+        expect(ACTOR.reset_expired_emotions(act, 1030)):toBe(1)
+        expect(expired.emotion):toBe("通常")
+        expect(recent.emotion):toBe("怒り")
+        expect(#act.token):toBe(1)
+        expect(act.token[1].actor):toBe(expired)
+        expect(act.token[1].id):toBe(0)
+    end)
+end)
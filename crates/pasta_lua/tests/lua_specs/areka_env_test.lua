@@ -0,0 +1,174 @@
+-- pasta.areka.env テストスイート
+-- ベースウェア通知イベント（OnNotify* 等）の環境情報キャッシュと var 転記のテスト
+local describe = require("lua_test.test").describe
+local test = require("lua_test.test").test
+local expect = require("lua_test.test").expect
+
+local ENV = require("pasta.areka.env")
+
+--- テスト用リクエスト（reference は 0 始まり）
+local function notify(id, ...)
+    local reference = {}
+    for i, value in ipairs({ ... }) do
+        reference[i - 1] = value
+    end
+    return { id = id, method = "notify", version = 30, reference = reference }
+end
+
+describe("pasta.areka.env - 通知の捕捉", function()
+    test("captures baseware, owner ghost and user info", function()
+        ENV.reset()
+        expect(ENV.capture(notify("basewareversion", "SSP", "2.6.92", "2.6.92.3"))):toBe(true)
+        ENV.capture(notify("ownerghostname", "さくら"))
+        ENV.capture(notify("OnNotifyUserInfo", "ユーザー", "山田太郎", "2000,1,2", "男性"))
+
+        local env = ENV.get()
+        expect(env.baseware.name):toBe("SSP")
+        expect(env.baseware.version):toBe("2.6.92")
+        expect(env.baseware.build):toBe("2.6.92.3")
+        expect(env.ghost.name):toBe("さくら")
+        expect(env.user.nickname):toBe("ユーザー")
+        expect(env.user.birthday):toBe("2000,1,2")
+        expect(ENV.ghost_name()):toBe("さくら")
+        expect(ENV.user_name()):toBe("ユーザー")
+        local name, version = ENV.baseware()
+        expect(name):toBe("SSP")
+        expect(version):toBe("2.6.92")
+    end)
+
+    test("user_name falls back to full name when nickname is empty", function()
+        ENV.reset()
+        ENV.capture(notify("OnNotifyUserInfo", "", "山田太郎"))
+        expect(ENV.user_name()):toBe("山田太郎")
+    end)
+
+    test("captures OS info with all references", function()
+        ENV.reset()
+        ENV.capture(notify("OnNotifyOSInfo", "Windows", "10.0", "Professional", "CPU", "3000"))
+        local os_info = ENV.get().os
+        expect(os_info.name):toBe("Windows")
+        expect(os_info.version):toBe("10.0")
+        expect(os_info.edition):toBe("Professional")
+        expect(#os_info.references):toBe(5)
+        expect(os_info.references[4]):toBe("CPU")
+    end)
+
+    test("captures installed names from references or \\1 separated value", function()
+        ENV.reset()
+        ENV.capture(notify("installedghostname", "さくら", "まゆら"))
+        ENV.capture(notify("installedballoonname", "SSPデフォルト\1ゴシック"))
+        expect(#ENV.installed()):toBe(2)
+        expect(ENV.is_installed("まゆら")):toBe(true)
+        expect(ENV.is_installed("存在しない")):toBe(false)
+        expect(ENV.installed("balloon")[2]):toBe("ゴシック")
+        expect(ENV.is_installed("ゴシック", "balloon")):toBe(true)
+        expect(#ENV.installed("shell")):toBe(0)
+    end)
+
+    test("captures other running ghosts with surfaces", function()
+        ENV.reset()
+        ENV.capture(notify("otherghostname", "まゆら\0010\00110", "えもこ\0015\00111"))
+        local ghosts = ENV.get().other_ghosts
+        expect(#ghosts):toBe(2)
+        expect(ghosts[1].name):toBe("まゆら")
+        expect(ghosts[1].surfaces[2]):toBe(10)
+        expect(ghosts[2].surfaces[1]):toBe(5)
+        expect(ENV.is_running("えもこ")):toBe(true)
+        expect(ENV.is_running("さくら")):toBe(false)
+
+        -- 通知のたびに置き換える
+        ENV.capture(notify("otherghostname"))
+        expect(#ENV.get().other_ghosts):toBe(0)
+    end)
+
+    test("captures self info, surfaces, shell and balloon changes", function()
+        ENV.reset()
+        ENV.capture(notify("OnNotifySelfInfo", "さくら", "さくら", "うにゅう", "master", "C:/shell/master/",
+            "SSP", "C:/balloon/ssp/"))
+        ENV.capture(notify("OnSurfaceChange", "5", "10"))
+        ENV.capture(notify("OnSurfaceChange", "7"))
+        local ghost = ENV.get().ghost
+        expect(ghost.kero_name):toBe("うにゅう")
+        expect(ghost.shell_name):toBe("master")
+        expect(ghost.balloon_path):toBe("C:/balloon/ssp/")
+        expect(ghost.surfaces[0]):toBe(7)
+        expect(ghost.surfaces[1]):toBe(10)
+
+        ENV.capture(notify("OnShellChanged", "別シェル", "さくら", "C:/shell/other/"))
+        ENV.capture(notify("OnBalloonChange", "ゴシック", "C:/balloon/gothic/"))
+        expect(ghost.shell_name):toBe("別シェル")
+        expect(ghost.shell_path):toBe("C:/shell/other/")
+        expect(ghost.balloon_name):toBe("ゴシック")
+    end)
+
+    test("ignores unrelated events", function()
+        ENV.reset()
+        expect(ENV.capture(notify("OnMouseClick", "0"))):toBe(false)
+        expect(ENV.capture(nil)):toBe(false)
+        expect(ENV.ghost_name()):toBe(nil)
+    end)
+
+    test("handlers can be extended", function()
+        ENV.reset()
+        ENV.handlers.OnTestNotify = function(env, ref)
+            env.test_value = ref[0]
+        end
+        ENV.capture(notify("OnTestNotify", "ok"))
+        ENV.handlers.OnTestNotify = nil
+        expect(ENV.get().test_value):toBe("ok")
+    end)
+end)
+
+describe("pasta.areka.env - EVENT.fire 統合", function()
+    test("captures notifications even when REG handler is registered", function()
+        local EVENT = require("pasta.shiori.event")
+        local REG = require("pasta.shiori.event.register")
+        ENV.reset()
+
+        local seen
+        REG.ownerghostname = function(act)
+            seen = act.req.reference[0]
+            return nil
+        end
+        local res = EVENT.fire(notify("ownerghostname", "さくら"))
+        REG.ownerghostname = nil
+
+        expect(seen):toBe("さくら")
+        expect(res:match("^SHIORI/3.0 204") ~= nil):toBe(true)
+        expect(ENV.ghost_name()):toBe("さくら")
+    end)
+
+    test("exposes environment to act.var in later events", function()
+        local EVENT = require("pasta.shiori.event")
+        local REG = require("pasta.shiori.event.register")
+        ENV.reset()
+        EVENT.fire(notify("ownerghostname", "さくら"))
+        EVENT.fire(notify("OnNotifyUserInfo", "ユーザー"))
+        EVENT.fire(notify("basewareversion", "SSP", "2.6.92"))
+
+        local var
+        REG.OnEnvTest = function(act)
+            var = act.var
+            return nil
+        end
+        EVENT.fire({ id = "OnEnvTest", method = "get", version = 30 })
+        REG.OnEnvTest = nil
+
+        expect(var["ゴースト名"]):toBe("さくら")
+        expect(var.ghost_name):toBe("さくら")
+        expect(var["ユーザー名"]):toBe("ユーザー")
+        expect(var["ベースウェア名"]):toBe("SSP")
+        expect(var.baseware_version):toBe("2.6.92")
+        expect(var.shell_name):toBe(nil)
+    end)
+
+    test("AREKA exposes ghost_name and user_name", function()
+        local AREKA = require("pasta.areka")
+        ENV.reset()
+        ENV.capture(notify("ownerghostname", "さくら"))
+        ENV.capture(notify("OnNotifyUserInfo", "ユーザー"))
+        expect(AREKA.env):toBe(ENV)
+        expect(AREKA.ghost_name()):toBe("さくら")
+        expect(AREKA.user_name()):toBe("ユーザー")
+    end)
+end)
@@ -0,0 +1,171 @@
+-- event_builder module tests
+-- Tests for pasta.shiori.event_builder module - grouped token to script event conversion
+local describe = require("lua_test.test").describe
+local test = require("lua_test.test").test
+local expect = require("lua_test.test").expect
+
+-- ============================================================================
+-- ヘルパー: モックアクター作成
+-- ============================================================================
+
+local function create_mock_actors()
+    return {
+        sakura = { name = "さくら", spot = 0 },
+        kero = { name = "うにゅう", spot = 1 },
+    }
+end
+
+describe("EVENT_BUILDER - tokens", function()
+    test("各トークンがイベントに変換される", function()
+        local EVENT_BUILDER = require("pasta.shiori.event_builder")
+        local actors = create_mock_actors()
+
+        local tokens = {
+            {
+                type = "actor",
+                actor = actors.sakura,
+                tokens = {
+                    { type = "surface", id = 5 },
+                    { type = "talk", actor = actors.sakura, text = "こんにちは。" },
+                    { type = "wait", ms = 500 },
+                    { type = "newline", n = 2 },
+                    { type = "clear" },
+                    { type = "raw_script", text = "\\![raise,OnTest]" },
+                }
+            },
+        }
+        local events = EVENT_BUILDER.build(tokens)
+
+        expect(#events):toBe(7)
+        expect(events[1].type):toBe("actor")
+        expect(events[1].name):toBe("さくら")
+        expect(events[1].spot):toBe(0)
+        expect(events[2].type):toBe("surface")
+        expect(events[2].id):toBe("5")
+        -- テキストにはウェイトを付けない
+        expect(events[3].type):toBe("talk")
+        expect(events[3].text):toBe("こんにちは。")
+        expect(events[3].actor):toBe("さくら")
+        expect(events[4].ms):toBe(500)
+        expect(events[5].n):toBe(2)
+        expect(events[6].type):toBe("clear")
+        expect(events[7].text):toBe("\\![raise,OnTest]")
+    end)
+
+    test("同じアクターが続く場合はactorイベントを繰り返さない", function()
+        local EVENT_BUILDER = require("pasta.shiori.event_builder")
+        local actors = create_mock_actors()
+
+        local tokens = {
+            { type = "actor", actor = actors.sakura, tokens = { { type = "talk", actor = actors.sakura, text = "あ" } } },
+            { type = "actor", actor = actors.sakura, tokens = { { type = "talk", actor = actors.sakura, text = "い" } } },
+        }
+        local events = EVENT_BUILDER.build(tokens)
+
+        expect(#events):toBe(3)
+        expect(events[2].text):toBe("あ")
+        expect(events[3].text):toBe("い")
+    end)
+end)
+
+describe("EVENT_BUILDER - spots", function()
+    test("spotトークンとactor_spotsがactorイベントに反映される", function()
+        local EVENT_BUILDER = require("pasta.shiori.event_builder")
+        local actors = create_mock_actors()
+
+        local input_spots = { ["さくら"] = 0 }
+        local tokens = {
+            { type = "spot", actor = actors.kero, spot = 2 },
+            { type = "actor", actor = actors.sakura, tokens = { { type = "talk", actor = actors.sakura, text = "あ" } } },
+            { type = "actor", actor = actors.kero, tokens = { { type = "talk", actor = actors.kero, text = "い" } } },
+        }
+        local events, spots = EVENT_BUILDER.build(tokens, input_spots)
+
+        expect(events[1].spot):toBe(0)
+        expect(events[3].name):toBe("うにゅう")
+        expect(events[3].spot):toBe(2)
+        expect(spots["うにゅう"]):toBe(2)
+        -- 入力テーブルは変更しない
+        expect(input_spots["うにゅう"]):toBe(nil)
+    end)
+
+    test("clear_spotでclear_spotsイベントを出し、スポットをリセットする", function()
+        local EVENT_BUILDER = require("pasta.shiori.event_builder")
+        local actors = create_mock_actors()
+
+        local tokens = {
+            { type = "clear_spot" },
+            { type = "actor", actor = actors.kero, tokens = { { type = "talk", actor = actors.kero, text = "い" } } },
+        }
+        local events, spots = EVENT_BUILDER.build(tokens, { ["うにゅう"] = 1 })
+
+        expect(events[1].type):toBe("clear_spots")
+        -- リセット後はアクターの既定スポット（actor.spot）
+        expect(events[2].spot):toBe(1)
+        expect(spots["うにゅう"]):toBe(nil)
+    end)
+end)
+
+describe("EVENT_BUILDER - アクター設定", function()
+    test("actor.spot・actor.balloonがactorイベントに反映される", function()
+        local EVENT_BUILDER = require("pasta.shiori.event_builder")
+        local c = { name = "C", spot = "char2", balloon = 4 }
+
+        local tokens = {
+            { type = "actor", actor = c, tokens = { { type = "talk", actor = c, text = "う" } } },
+        }
+        local events = EVENT_BUILDER.build(tokens)
+
+        expect(events[1].spot):toBe(2)
+        expect(events[1].balloon):toBe(4)
+    end)
+end)
+
+describe("SHIORI_ACT - JSON出力", function()
+    test("set_output('json')でイベントストリームのJSONを返す", function()
+        local SHIORI_ACT = require("pasta.shiori.act")
+        local SCRIPT_EVENT = require("@pasta_script_event")
+        local actors = create_mock_actors()
+        local act = SHIORI_ACT.new(actors)
+
+        act:set_output("json")
+        act:talk(actors.sakura, "こんにちは")
+        act:surface(5):wait(100)
+        local json = act:build()
+
+        local stream = SCRIPT_EVENT.decode(json)
+        expect(stream.version):toBe(SCRIPT_EVENT.FORMAT_VERSION)
+        expect(stream.events[1].type):toBe("actor")
+        expect(stream.events[2].text):toBe("こんにちは")
+        expect(stream.events[3].id):toBe("5")
+        expect(stream.events[4].ms):toBe(100)
+    end)
+
+    test("出力形式のデフォルトはさくらスクリプト", function()
+        local SHIORI_ACT = require("pasta.shiori.act")
+        local actors = create_mock_actors()
+        local act = SHIORI_ACT.new(actors)
+
+        act:talk(actors.sakura, "こんにちは")
+        local result = act:build()
+
+        expect(result:sub(-2)):toBe("\\e")
+    end)
+
+    test("不明な出力形式はエラー", function()
+        local SHIORI_ACT = require("pasta.shiori.act")
+        local act = SHIORI_ACT.new(create_mock_actors())
+
+        local ok = pcall(function() act:set_output("xml") end)
+        expect(ok):toBe(false)
+    end)
+
+    test("不正なイベントはencodeでエラーを返す", function()
+        local SCRIPT_EVENT = require("@pasta_script_event")
+
+        local json, err = SCRIPT_EVENT.encode({ { type = "dance" } })
+        expect(json):toBe(nil)
+        expect(err:find("Invalid script event")):toBeTruthy()
+        expect(SCRIPT_EVENT.encode({})):toBe('{"version":1,"events":[]}')
+    end)
+end)
@@ -0,0 +1,116 @@
+-- pasta.shiori.event.route テストスイート
+-- 入力イベント（マウス・キー）のシーン検索キー組み立てと、なでなで判定のテスト
+local describe = require("lua_test.test").describe
+local test = require("lua_test.test").test
+local expect = require("lua_test.test").expect
+
+-- 他のテストが pasta.store を再ロードしている場合に備えて、同じ STORE を参照するよう再ロード
+package.loaded["pasta.shiori.event.route"] = nil
+local ROUTE = require("pasta.shiori.event.route")
+local STORE = require("pasta.store")
+
+--- テスト用マウスイベント（x, y, wheel, scope, area, button）
+local function mouse(id, scope, area, button, unix)
+    return {
+        id = id,
+        method = "get",
+        version = 30,
+        reference = { [0] = "10", [1] = "20", [2] = "0", [3] = scope, [4] = area, [5] = button },
+        date = unix and { unix = unix } or nil,
+    }
+end
+
+describe("pasta.shiori.event.route - keys", function()
+    test("builds keys from specific to generic, ending with event id", function()
+        local saved = STORE.actor_spots
+        STORE.actor_spots = { ["さくら"] = 0, ["うにゅう"] = 1 }
+        local keys = ROUTE.keys(mouse("OnMouseDoubleClick", "0", "Head"))
+        STORE.actor_spots = saved
+
+        expect(#keys):toBe(4)
+        expect(keys[1]):toBe("さくらHeadつつかれ")
+        expect(keys[2]):toBe("Headつつかれ")
+        expect(keys[3]):toBe("さくらつつかれ")
+        expect(keys[4]):toBe("OnMouseDoubleClick")
+    end)
+
+    test("skips keys with empty placeholders", function()
+        local saved = STORE.actor_spots
+        STORE.actor_spots = {}
+        local keys = ROUTE.keys(mouse("OnMouseDoubleClick", "0", ""))
+        STORE.actor_spots = saved
+
+        expect(#keys):toBe(1)
+        expect(keys[1]):toBe("OnMouseDoubleClick")
+    end)
+
+    test("uses button names and raw references", function()
+        local saved = STORE.actor_spots
+        STORE.actor_spots = { ["うにゅう"] = 1 }
+        local keys = ROUTE.keys(mouse("OnMouseClick", "1", "Face", "1"))
+        STORE.actor_spots = saved
+        expect(keys[1]):toBe("うにゅうFace右クリック")
+
+        keys = ROUTE.keys({ id = "OnKeyPress", reference = { [0] = "f1" } })
+        expect(keys[1]):toBe("f1キー")
+        expect(keys[2]):toBe("OnKeyPress")
+    end)
+
+    test("expand supports char, wheel and custom rules", function()
+        local fields = ROUTE.fields({ reference = { [2] = "-120", [3] = "0", [4] = "Head" } })
+        expect(fields.wheel):toBe("下")
+        expect(ROUTE.expand("マウス{char}_{area}", fields)):toBe("マウス0_Head")
+        expect(ROUTE.expand("{button}{unknown}", fields)):toBe(nil)
+
+        ROUTE.rules.OnTestInput = { "テスト{ref0}" }
+        local keys = ROUTE.keys({ id = "OnTestInput", reference = { [0] = "A" } })
+        ROUTE.rules.OnTestInput = nil
+        expect(keys[1]):toBe("テストA")
+    end)
+
+    test("unknown events only search event id", function()
+        local keys = ROUTE.keys({ id = "OnUnknownInput" })
+        expect(#keys):toBe(1)
+        expect(keys[1]):toBe("OnUnknownInput")
+    end)
+end)
+
+describe("pasta.shiori.event.route - stroke", function()
+    test("detects stroke after enough moves on the same area", function()
+        STORE.stroke = nil
+        for _ = 1, 39 do
+            expect(ROUTE.detect_stroke(mouse("OnMouseMove", "0", "Head", nil, 100))):toBe(false)
+        end
+        expect(ROUTE.detect_stroke(mouse("OnMouseMove", "0", "Head", nil, 100))):toBe(true)
+        -- 判定後は数え直す
+        expect(ROUTE.detect_stroke(mouse("OnMouseMove", "0", "Head", nil, 100))):toBe(false)
+        STORE.stroke = nil
+    end)
+
+    test("restarts counting on area change, leaving area or timeout", function()
+        STORE.stroke = nil
+        for _ = 1, 30 do
+            ROUTE.detect_stroke(mouse("OnMouseMove", "0", "Head", nil, 100))
+        end
+        ROUTE.detect_stroke(mouse("OnMouseMove", "0", "Face", nil, 100))
+        expect(STORE.stroke.count):toBe(1)
+
+        ROUTE.detect_stroke(mouse("OnMouseMove", "0", "Face", nil, 110))
+        expect(STORE.stroke.count):toBe(1)
+
+        ROUTE.detect_stroke(mouse("OnMouseMove", "0", "", nil, 110))
+        expect(STORE.stroke):toBe(nil)
+    end)
+
+    test("stroke keys replace OnMouseMove rule when detected", function()
+        local saved = STORE.actor_spots
+        STORE.actor_spots = { ["さくら"] = 0 }
+        STORE.stroke = { scope = "0", area = "Head", count = 39, last = 100 }
+        local keys = ROUTE.keys(mouse("OnMouseMove", "0", "Head", nil, 100))
+        STORE.actor_spots = saved
+
+        expect(keys[1]):toBe("さくらHeadなでられ")
+        expect(keys[#keys]):toBe("OnMouseMove")
+        expect(STORE.stroke):toBe(nil)
+    end)
+end)
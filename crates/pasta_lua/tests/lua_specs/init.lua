@@ -11,6 +11,7 @@ local specs = {
     "store_save_test",                   -- store-save-table feature tests
     "persistence_spec",                  -- store-save-persistence feature tests
     "act_impl_call_test",                -- ACT_IMPL.call 4段階検索テスト
+    "act_call_fn_test",                  -- ACT_IMPL.call_fn 関数呼び出しテスト
     "act_test",                          -- pasta.act トークンバッファリファクタリング tests
     "act_grouping_test",                 -- pasta.act グループ化機能 tests (actor-talk-grouping)
     "sakura_builder_test",               -- pasta.shiori.sakura_builder トークン変換テスト
@@ -28,6 +29,12 @@ local specs = {
     "global_chaintalk_integration_test", -- GLOBAL チェイントーク EVENT.fire 統合テスト
     "persist_spot_position_test",        -- persist-spot-position スポット位置継続保持テスト
     "transfer_req_to_var_test",          -- transfer_req_to_var req→var展開テスト
+    "shiori2_compat_test",               -- SHIORI/2.x 互換レスポンステスト
+    "areka_env_test",                    -- pasta.areka.env ベースウェア通知キャッシュテスト
+    "shiori_communicate_test",           -- OnCommunicate シーン照合・往復回数上限テスト
+    "event_route_test",                  -- 入力イベントのシーンルーティング・なでなで判定テスト
+    "event_builder_test",                -- pasta.shiori.event_builder 構造化イベント出力テスト
+    "actor_emotion_test",                -- pasta.actor 感情・サーフェス管理テスト
     -- 将来のテストスイートをここに追加
     -- "code_generator_test",
     -- "context_test",
@@ -121,6 +121,38 @@ describe("act.save integration with persistence", function()
     end)
 end)
 
+describe("@pasta_persistence autosave", function()
+    test("flush / is_dirty / autosave_tick are exposed", function()
+        local p = require("@pasta_persistence")
+        expect(type(p.flush)):toBe("function")
+        expect(type(p.is_dirty)):toBe("function")
+        expect(type(p.autosave_tick)):toBe("function")
+    end)
+
+    test("EVENT.fire ticks autosave only on OnSecondChange", function()
+        local p = require("@pasta_persistence")
+        local EVENT = require("pasta.shiori.event")
+        local REG = require("pasta.shiori.event.register")
+
+        local original_tick = p.autosave_tick
+        local original_handler = REG.OnSecondChange
+        local ticks = 0
+        p.autosave_tick = function()
+            ticks = ticks + 1
+            return false
+        end
+        -- Custom handler does not bypass autosave
+        REG.OnSecondChange = function() return nil end
+
+        EVENT.fire({ id = "OnSecondChange" })
+        EVENT.fire({ id = "OnAutosaveSpecUnknownEvent" })
+
+        p.autosave_tick = original_tick
+        REG.OnSecondChange = original_handler
+        expect(ticks):toBe(1)
+    end)
+end)
+
 describe("STORE.save deprecation", function()
     test("STORE.save is nil (removed)", function()
         local STORE = require("pasta.store")
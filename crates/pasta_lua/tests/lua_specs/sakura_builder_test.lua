@@ -537,6 +537,39 @@ describe("SAKURA_BUILDER - actorトークンのactor切り替え検出", functio
         expect(result:find("\\n%[150%]")):toBeTruthy()
     end)
 
+    test("表情だけを変えたスポットの後に段落改行を出力しない", function()
+        local BUILDER = require("pasta.shiori.sakura_builder")
+        local actors = create_mock_actors()
+
+        -- 感情リセット（うにゅうの表情だけ）の後にさくらが話す
+        local tokens = {
+            {
+                type = "actor",
+                actor = actors.kero,
+                tokens = {
+                    { type = "surface", id = 10 },
+                }
+            },
+            {
+                type = "actor",
+                actor = actors.sakura,
+                tokens = {
+                    { type = "talk", actor = actors.sakura, text = "First" },
+                }
+            },
+            {
+                type = "actor",
+                actor = actors.kero,
+                tokens = {
+                    { type = "talk", actor = actors.kero, text = "Second" },
+                }
+            },
+        }
+        local result = BUILDER.build(tokens, { spot_newlines = 1.5 })
+
+        expect(result):toBe("\\p[1]\\s[10]\\p[0]First\\n[150]\\p[1]Second\\e")
+    end)
+
     test("同じactorの連続actorトークンではスポットタグを出力しない", function()
         local BUILDER = require("pasta.shiori.sakura_builder")
         local actors = create_mock_actors()
@@ -850,3 +883,151 @@ describe("SAKURA_BUILDER - persist-spot-position: spotトークン処理 (Task 5
         expect(updated_spots["うにゅう"]):toBe(1)
     end)
 end)
+
+-- ============================================================================
+-- 自動折り返し（wrap_width）
+-- ============================================================================
+
+describe("SAKURA_BUILDER - 自動折り返し", function()
+    test("talkトークンをまたいで桁位置を引き継ぐ", function()
+        local BUILDER = require("pasta.shiori.sakura_builder")
+        local sakura = { name = "さくら", wrap_width = 6 }
+
+        local tokens = {
+            {
+                type = "actor",
+                actor = sakura,
+                tokens = {
+                    { type = "talk", actor = sakura, text = "あいう" },
+                    { type = "talk", actor = sakura, text = "えお" },
+                }
+            },
+        }
+        local result = BUILDER.build(tokens, {})
+        expect(result):toBe("\\p[0]あいう\\nえお\\e")
+    end)
+
+    test("改行とスポット変更で桁位置をリセットする", function()
+        local BUILDER = require("pasta.shiori.sakura_builder")
+        local sakura = { name = "さくら", wrap_width = 6 }
+        local kero = { name = "うにゅう", wrap_width = 6 }
+
+        local tokens = {
+            { type = "spot", actor = kero, spot = 1 },
+            {
+                type = "actor",
+                actor = sakura,
+                tokens = {
+                    { type = "talk", actor = sakura, text = "あい" },
+                    { type = "newline", n = 1 },
+                    { type = "talk", actor = sakura, text = "うえお" },
+                }
+            },
+            {
+                type = "actor",
+                actor = kero,
+                tokens = {
+                    { type = "talk", actor = kero, text = "かきく" },
+                }
+            },
+        }
+        local result = BUILDER.build(tokens, { spot_newlines = 1 })
+        expect(result):toBe("\\p[0]あい\\nうえお\\n[100]\\p[1]かきく\\e")
+    end)
+
+    test("wrap_width未設定では折り返さない", function()
+        local BUILDER = require("pasta.shiori.sakura_builder")
+        local sakura = { name = "さくら" }
+
+        local tokens = {
+            {
+                type = "actor",
+                actor = sakura,
+                tokens = {
+                    { type = "talk", actor = sakura, text = "あいうえおかきくけこさしすせそたちつてと" },
+                }
+            },
+        }
+        local result = BUILDER.build(tokens, {})
+        expect(result:find("\\n", 1, true)):toBeFalsy()
+    end)
+end)
+
+-- ============================================================================
+-- アクター設定（既定スポット・バルーン・改行量・ウェイトプロファイル）
+-- ============================================================================
+
+--- 1アクター1発言のactorトークンを作成
+local function actor_group(actor, text)
+    return { type = "actor", actor = actor, tokens = { { type = "talk", actor = actor, text = text } } }
+end
+
+describe("SAKURA_BUILDER - 3人以上の会話とアクター設定", function()
+    test("actor.spotを既定のスポットとして使う（数値・sakura/kero/charN）", function()
+        local BUILDER = require("pasta.shiori.sakura_builder")
+        local a = { name = "A", spot = "sakura" }
+        local b = { name = "B", spot = "kero" }
+        local c = { name = "C", spot = "char2" }
+        local d = { name = "D", spot = 3 }
+
+        local tokens = { actor_group(a, "あ"), actor_group(b, "い"), actor_group(c, "う"), actor_group(d, "え"), actor_group(a, "お") }
+        local result = BUILDER.build(tokens, { spot_newlines = 1 })
+        expect(result):toBe("\\p[0]あ\\n[100]\\p[1]い\\n[100]\\p[2]う\\n[100]\\p[3]え\\n[100]\\p[0]お\\e")
+    end)
+
+    test("actor_spots（spotトークン・前回位置）はactor.spotより優先", function()
+        local BUILDER = require("pasta.shiori.sakura_builder")
+        local a = { name = "A", spot = 0 }
+        local c = { name = "C", spot = "char2" }
+
+        local tokens = {
+            { type = "spot", actor = c, spot = 5 },
+            actor_group(a, "あ"),
+            actor_group(c, "い"),
+        }
+        local result, spots = BUILDER.build(tokens, { spot_newlines = 1 }, { A = 1 })
+        expect(result):toBe("\\p[1]あ\\n[100]\\p[5]い\\e")
+        expect(spots.C):toBe(5)
+    end)
+
+    test("actor.spot_newlinesで切り替え先アクターの改行量を上書き", function()
+        local BUILDER = require("pasta.shiori.sakura_builder")
+        local a = { name = "A", spot = 0 }
+        local b = { name = "B", spot = 1, spot_newlines = 0.5 }
+        local c = { name = "C", spot = 2 }
+
+        local tokens = { actor_group(a, "あ"), actor_group(b, "い"), actor_group(c, "う") }
+        local result = BUILDER.build(tokens, { spot_newlines = 1.5 })
+        expect(result):toBe("\\p[0]あ\\n[50]\\p[1]い\\n[150]\\p[2]う\\e")
+    end)
+
+    test("actor.balloonで\\b[ID]を出力し、同じスポットの別アクターでは既定に戻す", function()
+        local BUILDER = require("pasta.shiori.sakura_builder")
+        local a = { name = "A", spot = 0, balloon = 2 }
+        local b = { name = "B", spot = 1 }
+        local c = { name = "C", spot = 0 }
+
+        local tokens = {
+            actor_group(a, "あ"),
+            actor_group(b, "い"),
+            actor_group(a, "う"),
+            actor_group(c, "え"),
+            actor_group(c, "お"),
+        }
+        local result = BUILDER.build(tokens, { spot_newlines = 1 })
+        expect(result):toBe("\\p[0]\\b[2]あ\\n[100]\\p[1]い\\n[100]\\p[0]う\\p[0]\\b[0]えお\\e")
+    end)
+
+    test("actor.wait_profileでウェイトプロファイルを既定値にする", function()
+        local BUILDER = require("pasta.shiori.sakura_builder")
+        local profiles = { slow = { script_wait_normal = 100 } }
+        local a = { name = "A", spot = 0, wait_profile = "slow" }
+        local b = { name = "B", spot = 1, wait_profile = "slow", script_wait_normal = 150 }
+        local c = { name = "C", spot = 2, wait_profile = "unknown" }
+
+        local tokens = { actor_group(a, "あ"), actor_group(b, "い"), actor_group(c, "う") }
+        local result = BUILDER.build(tokens, { spot_newlines = 1, wait_profiles = profiles })
+        -- アクター自身のフィールド → プロファイル → [talk] の順（未知のプロファイルは無視）
+        expect(result):toBe("\\p[0]あ\\_w[50]\\n[100]\\p[1]い\\_w[100]\\n[100]\\p[2]う\\e")
+    end)
+end)
@@ -0,0 +1,81 @@
+-- shiori2_compat_test.lua
+-- SHIORI/2.x リクエストに対する EVENT.fire / RES のレスポンス形式テスト
+local describe = require("lua_test.test").describe
+local test = require("lua_test.test").test
+local expect = require("lua_test.test").expect
+
+describe("EVENT.fire - SHIORI/2.x 互換レスポンス", function()
+    local EVENT, REG, STORE, RES
+
+    local function setup()
+        -- パッケージキャッシュをクリア
+        package.loaded["pasta.shiori.event"] = nil
+        package.loaded["pasta.shiori.event.register"] = nil
+        package.loaded["pasta.store"] = nil
+        package.loaded["pasta.shiori.res"] = nil
+        package.loaded["pasta.shiori.event.boot"] = nil
+        package.loaded["pasta.shiori.event.second_change"] = nil
+
+        REG = require("pasta.shiori.event.register")
+        STORE = require("pasta.store")
+        RES = require("pasta.shiori.res")
+        STORE.reset()
+
+        -- 登録をクリア
+        for k in pairs(REG) do
+            REG[k] = nil
+        end
+
+        EVENT = require("pasta.shiori.event")
+    end
+
+    test("GET Sentence には SHIORI/2.x の Sentence ヘッダーで応答する", function()
+        setup()
+        REG.OnBoot = function(act)
+            return "\\0hello\\e"
+        end
+
+        local req = { id = "OnBoot", method = "get", version = 22, command = "Sentence" }
+        local response = EVENT.fire(req)
+
+        expect(response:sub(1, 19)):toBe("SHIORI/2.2 200 OK\r\n")
+        expect(response:find("Sentence: \\0hello\\e", 1, true)).not_:toBe(nil)
+        expect(response:find("Value:", 1, true)):toBe(nil)
+        expect(response:find("SecurityLevel:", 1, true)):toBe(nil)
+    end)
+
+    test("GET String には String ヘッダーで応答する", function()
+        setup()
+        REG.homeurl = function(act)
+            return "https://example.com/"
+        end
+
+        local req = { id = "homeurl", method = "get", version = 25, command = "String" }
+        local response = EVENT.fire(req)
+
+        expect(response:find("String: https://example.com/", 1, true)).not_:toBe(nil)
+    end)
+
+    test("ハンドラなしの 2.x リクエストは SHIORI/2.x 204 を返す", function()
+        setup()
+
+        local req = { id = "OnUnknown2x", method = "notify", version = 23, command = "OtherGhostName" }
+        local response = EVENT.fire(req)
+
+        expect(response:sub(1, 27)):toBe("SHIORI/2.3 204 No Content\r\n")
+    end)
+
+    test("続く SHIORI/3.0 リクエストでは 3.0 形式に戻る", function()
+        setup()
+        REG.OnBoot = function(act)
+            return "hello"
+        end
+
+        EVENT.fire({ id = "OnBoot", method = "get", version = 26, command = "Sentence" })
+        local response = EVENT.fire({ id = "OnBoot", method = "get", version = 30 })
+
+        expect(response:sub(1, 19)):toBe("SHIORI/3.0 200 OK\r\n")
+        expect(response:find("Value: hello", 1, true)).not_:toBe(nil)
+        expect(response:find("SecurityLevel: local", 1, true)).not_:toBe(nil)
+    end)
+end)
@@ -473,6 +473,28 @@ describe("SHIORI_ACT - E2E scenario", function()
         expect(result:sub(-2)):toBe("\\e")             -- end
     end)
 
+    test("ビルダーの出力は検証で診断を出さない", function()
+        local SHIORI_ACT = require("pasta.shiori.act")
+        local SAKURA_SCRIPT = require("@pasta_sakura_script")
+        local actors = create_mock_actors()
+        local act = SHIORI_ACT.new(actors)
+
+        act:set_spot("sakura", 0)
+        act:set_spot("kero", 1)
+        act:set_spot("char2", 2)
+
+        act:talk(actors.sakura, "こんにちは。")
+            :surface(5)
+            :wait(500)
+            :newline(2)
+            :talk(actors.kero, "やあ、元気？")
+            :talk(actors.char2, "ふむ")
+            :clear()
+
+        local diagnostics = SAKURA_SCRIPT.validate(act:build())
+        expect(#diagnostics):toBe(0)
+    end)
+
     test("multiple rounds (build auto-resets)", function()
         local SHIORI_ACT = require("pasta.shiori.act")
         local actors = create_mock_actors()
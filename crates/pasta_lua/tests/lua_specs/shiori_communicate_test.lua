@@ -0,0 +1,92 @@
+-- pasta.shiori.event.communicate テストスイート
+-- OnCommunicate シーン名の照合規則、往復回数上限のテスト
+local describe = require("lua_test.test").describe
+local test = require("lua_test.test").test
+local expect = require("lua_test.test").expect
+
+local COMMUNICATE = require("pasta.shiori.event.communicate")
+
+describe("pasta.shiori.event.communicate - match", function()
+    test("sender and message prefix has highest priority", function()
+        local priority, len = COMMUNICATE.match("OnCommunicate_まゆら_こんにちは", "まゆら", "こんにちは！")
+        expect(priority):toBe(3)
+        expect(len):toBe(#"こんにちは")
+    end)
+
+    test("sender only, message only and generic", function()
+        expect(COMMUNICATE.match("OnCommunicate_まゆら", "まゆら", "ねえ")):toBe(2)
+        expect(COMMUNICATE.match("OnCommunicate__おやすみ", "えもこ", "おやすみ～")):toBe(1)
+        expect(COMMUNICATE.match("OnCommunicate", "えもこ", "やあ")):toBe(0)
+    end)
+
+    test("returns nil for unmatched or irregular names", function()
+        expect(COMMUNICATE.match("OnCommunicate_まゆら", "えもこ", "やあ")):toBe(nil)
+        expect(COMMUNICATE.match("OnCommunicate_まゆら_こんにちは", "まゆら", "おはよう")):toBe(nil)
+        expect(COMMUNICATE.match("OnCommunicate__おやすみ", "まゆら", "やあ")):toBe(nil)
+        expect(COMMUNICATE.match("OnCommunicateX", "まゆら", "やあ")):toBe(nil)
+        expect(COMMUNICATE.match("OnBoot", "まゆら", "やあ")):toBe(nil)
+    end)
+end)
+
+describe("pasta.shiori.event.communicate - count_turn", function()
+    test("counts turns per partner and resets over the limit", function()
+        local original = COMMUNICATE.max_turns
+        COMMUNICATE.max_turns = function() return 2 end
+        COMMUNICATE.reset()
+
+        expect(COMMUNICATE.count_turn("まゆら")):toBe(true)
+        expect(COMMUNICATE.count_turn("まゆら")):toBe(true)
+        expect(COMMUNICATE.count_turn("まゆら")):toBe(false)
+        -- 上限到達でリセット済み
+        expect(COMMUNICATE.count_turn("まゆら")):toBe(true)
+
+        -- 相手が変わると数え直す
+        COMMUNICATE.count_turn("まゆら")
+        expect(COMMUNICATE.count_turn("えもこ")):toBe(true)
+        expect(COMMUNICATE.count_turn("えもこ")):toBe(true)
+        expect(COMMUNICATE.count_turn("えもこ")):toBe(false)
+
+        COMMUNICATE.max_turns = original
+        COMMUNICATE.reset()
+    end)
+
+    test("starts a new conversation after the timeout", function()
+        local original_max, original_timeout = COMMUNICATE.max_turns, COMMUNICATE.timeout_secs
+        COMMUNICATE.max_turns = function() return 2 end
+        COMMUNICATE.timeout_secs = function() return 60 end
+        COMMUNICATE.reset()
+
+        expect(COMMUNICATE.count_turn("まゆら", 1000)):toBe(true)
+        expect(COMMUNICATE.count_turn("まゆら", 1030)):toBe(true)
+        -- 60秒以上空いたら同じ相手でも数え直す
+        expect(COMMUNICATE.count_turn("まゆら", 1090)):toBe(true)
+        expect(COMMUNICATE.count_turn("まゆら", 1100)):toBe(true)
+        expect(COMMUNICATE.count_turn("まゆら", 1110)):toBe(false)
+
+        -- 0 で無効
+        COMMUNICATE.timeout_secs = function() return 0 end
+        COMMUNICATE.count_turn("まゆら", 2000)
+        COMMUNICATE.count_turn("まゆら", 5000)
+        expect(COMMUNICATE.count_turn("まゆら", 9000)):toBe(false)
+
+        COMMUNICATE.max_turns, COMMUNICATE.timeout_secs = original_max, original_timeout
+        COMMUNICATE.reset()
+    end)
+
+    test("user messages and unlimited setting are not limited", function()
+        local original = COMMUNICATE.max_turns
+        COMMUNICATE.max_turns = function() return 0 end
+        COMMUNICATE.reset()
+        for _ = 1, 20 do
+            COMMUNICATE.count_turn("まゆら")
+        end
+        expect(COMMUNICATE.count_turn("まゆら")):toBe(true)
+        COMMUNICATE.max_turns = function() return 1 end
+        COMMUNICATE.reset()
+        for _ = 1, 3 do
+            expect(COMMUNICATE.count_turn("user")):toBe(true)
+        end
+        COMMUNICATE.max_turns = original
+        COMMUNICATE.reset()
+    end)
+end)
@@ -59,6 +59,33 @@ fn test_persistence_obfuscated_roundtrip() {
     assert_eq!(loaded["inventory"][0], "sword");
 }
 
+/// Test encrypted format roundtrip and tamper detection
+#[test]
+fn test_persistence_encrypted_roundtrip() {
+    let temp_dir = TempDir::new().unwrap();
+    let file_path = temp_dir.path().join("save.dat");
+    let key = persistence::PersistenceKey::from_secret("ghost-specific-secret");
+
+    let data = serde_json::json!({"affection": 255});
+    persistence::save_to_file_encrypted(&data, &file_path, &key).unwrap();
+
+    // Not readable as gzip
+    let raw = std::fs::read(&file_path).unwrap();
+    assert_ne!(&raw[..2], &[0x1f, 0x8b]);
+
+    let loaded = persistence::load_from_file_with_key(&file_path, Some(&key)).unwrap();
+    assert_eq!(loaded["affection"], 255);
+
+    // Any modification is detected
+    let mut tampered = raw.clone();
+    tampered[20] ^= 0xff;
+    std::fs::write(&file_path, &tampered).unwrap();
+    assert!(matches!(
+        persistence::load_from_file_with_key(&file_path, Some(&key)),
+        Err(persistence::PersistenceError::TamperDetected)
+    ));
+}
+
 /// Test PersistenceConfig effective_file_path conversion
 #[test]
 fn test_persistence_config_effective_path() {
@@ -71,6 +98,7 @@ fn test_persistence_config_effective_path() {
         obfuscate: true,
         file_path: "profile/pasta/save/save.json".to_string(),
         debug_mode: false,
+        ..Default::default()
     };
     assert_eq!(config.effective_file_path(), "profile/pasta/save/save.dat");
 
@@ -79,6 +107,7 @@ fn test_persistence_config_effective_path() {
         obfuscate: true,
         file_path: "profile/pasta/save/save.dat".to_string(),
         debug_mode: false,
+        ..Default::default()
     };
     assert_eq!(config.effective_file_path(), "profile/pasta/save/save.dat");
 }
@@ -3,7 +3,7 @@
 //! Tests the full flow from Lua API calls to wait-inserted output.
 
 use mlua::Lua;
-use pasta_lua::loader::TalkConfig;
+use pasta_lua::loader::{PacingConfig, TalkConfig, WaitRuleConfig, WaitRuleMode};
 use pasta_lua::sakura_script;
 
 /// Helper to create a Lua runtime with sakura_script module registered.
@@ -641,3 +641,438 @@ fn test_consecutive_mixed_punctuation_max_selection() {
         .unwrap();
     assert_eq!(result3, r"、、、\_w[150]");
 }
+
+// ============================================
+// Parse / Validate
+// ============================================
+
+#[test]
+fn test_parse_returns_node_tables() {
+    let lua = create_test_runtime();
+
+    let (kind, pos, raw, id): (String, i64, String, String) = lua
+        .load(
+            r#"
+            local SAKURA = require "@pasta_sakura_script"
+            local nodes = SAKURA.parse("\\h\\s[5]\\q[はい,OnYes]")
+            return nodes[2].type, nodes[2].pos, nodes[2].raw, nodes[3].id
+        "#,
+        )
+        .eval()
+        .unwrap();
+    assert_eq!(kind, "surface");
+    assert_eq!(pos, 3);
+    assert_eq!(raw, r"\s[5]");
+    assert_eq!(id, "OnYes");
+}
+
+#[test]
+fn test_parse_error_returns_nil_and_message() {
+    let lua = create_test_runtime();
+
+    let (nodes, err): (mlua::Value, String) = lua
+        .load(
+            r#"
+            local SAKURA = require "@pasta_sakura_script"
+            return SAKURA.parse("\\s[0")
+        "#,
+        )
+        .eval()
+        .unwrap();
+    assert!(nodes.is_nil());
+    assert!(err.contains("unclosed bracket"), "{err}");
+}
+
+#[test]
+fn test_validate_reports_diagnostics() {
+    let lua = create_test_runtime();
+
+    let (count, kind, raw): (i64, String, String) = lua
+        .load(
+            r#"
+            local SAKURA = require "@pasta_sakura_script"
+            local d = SAKURA.validate("\\h\\q[はい]", { fragment = true })
+            return #d, d[1].kind, d[1].raw
+        "#,
+        )
+        .eval()
+        .unwrap();
+    assert_eq!(count, 1);
+    assert_eq!(kind, "malformed_arguments");
+    assert_eq!(raw, r"\q[はい]");
+
+    // スクリプト全体の検証では \e 欠落とトグルの未終了も報告する
+    let kinds: Vec<String> = lua
+        .load(
+            r#"
+            local SAKURA = require "@pasta_sakura_script"
+            local kinds = {}
+            for _, d in ipairs(SAKURA.validate("\\h\\_qあ")) do
+                table.insert(kinds, d.kind)
+            end
+            return kinds
+        "#,
+        )
+        .eval()
+        .unwrap();
+    assert_eq!(kinds, vec!["unbalanced_toggle", "missing_end"]);
+
+    let count: i64 = lua
+        .load(
+            r#"
+            local SAKURA = require "@pasta_sakura_script"
+            return #SAKURA.validate("\\h\\s[0]こんにちは\\e")
+        "#,
+        )
+        .eval()
+        .unwrap();
+    assert_eq!(count, 0);
+}
+
+// ============================================
+// Preview renderer
+// ============================================
+
+#[test]
+fn test_render_transcript_with_actor_spots() {
+    let lua = create_test_runtime();
+
+    let result: String = lua
+        .load(
+            r#"
+            local SAKURA = require "@pasta_sakura_script"
+            local script = "\\p[0]\\s[0]やあ。\\_w[950]\\n[150]\\p[1]\\s[10]どうも。\\e"
+            return SAKURA.render(script, { names = { ["さくら"] = 0, ["うにゅう"] = 1 } })
+        "#,
+        )
+        .eval()
+        .unwrap();
+    assert_eq!(
+        result,
+        "さくら：[surface 0]やあ。[wait 950ms]\nうにゅう：[surface 10]どうも。\n"
+    );
+}
+
+#[test]
+fn test_render_html_timeline() {
+    let lua = create_test_runtime();
+
+    let html: String = lua
+        .load(
+            r#"
+            local SAKURA = require "@pasta_sakura_script"
+            return SAKURA.render_html("\\0あい\\e", { names = { [0] = "さくら" }, char_wait = 100 })
+        "#,
+        )
+        .eval()
+        .unwrap();
+    assert!(html.contains(r#"data-duration="200""#), "{html}");
+    assert!(
+        html.contains(r#"<span class="speaker">さくら</span>"#),
+        "{html}"
+    );
+    assert!(
+        html.contains(r#"<span class="ch" data-t="100">い</span>"#),
+        "{html}"
+    );
+
+    let (result, err): (mlua::Value, String) = lua
+        .load(
+            r#"
+            local SAKURA = require "@pasta_sakura_script"
+            return SAKURA.render("\\s[0")
+        "#,
+        )
+        .eval()
+        .unwrap();
+    assert!(result.is_nil());
+    assert!(err.contains("unclosed bracket"), "{err}");
+}
+
+// ============================================
+// Line wrapping
+// ============================================
+
+#[test]
+fn test_talk_to_script_wraps_with_kinsoku() {
+    let config = TalkConfig {
+        script_wait_period: 50, // effective: 0
+        wrap_width: 10,
+        ..Default::default()
+    };
+    let lua = create_test_runtime_with_config(&config);
+
+    let result: String = lua
+        .load(
+            r#"
+            local SAKURA = require "@pasta_sakura_script"
+            return SAKURA.talk_to_script(nil, "あいうえお。かきくけ「こさ」")
+        "#,
+        )
+        .eval()
+        .unwrap();
+    // 「。」はぶら下げ、行末の「「」は次の行へ送る
+    assert_eq!(result, r"あいうえお。\nかきくけ\n「こさ」");
+}
+
+#[test]
+fn test_talk_to_script_column_continuation_and_actor_width() {
+    let lua = create_test_runtime();
+
+    let (script, column): (String, i64) = lua
+        .load(
+            r#"
+            local SAKURA = require "@pasta_sakura_script"
+            local actor = { wrap_width = 8 }
+            return SAKURA.talk_to_script(actor, "abあいう", 2)
+        "#,
+        )
+        .eval()
+        .unwrap();
+    assert_eq!(script, r"abあい\nう");
+    assert_eq!(column, 2);
+
+    // 桁位置を渡さない場合は戻り値は1つ
+    let count: i64 = lua
+        .load(
+            r##"
+            local SAKURA = require "@pasta_sakura_script"
+            return select("#", SAKURA.talk_to_script({ wrap_width = 8 }, "あいうえお"))
+        "##,
+        )
+        .eval()
+        .unwrap();
+    assert_eq!(count, 1);
+}
+
+// ============================================
+// Wait rules
+// ============================================
+
+#[test]
+fn test_wait_rules_from_toml() {
+    let toml_str = r#"
+script_wait_base = 40
+
+[[wait_rules]]
+pattern = "……」"
+wait = 1200
+mode = "once"
+
+[[wait_rules]]
+pattern = "[A-Za-z]"
+after = "[A-Za-z]"
+wait = 0
+"#;
+
+    let talk: TalkConfig = toml::from_str(toml_str).unwrap();
+
+    assert_eq!(talk.script_wait_base, 40);
+    assert_eq!(talk.wait_rules.len(), 2);
+    assert_eq!(talk.wait_rules[0].mode, WaitRuleMode::Once);
+    assert_eq!(talk.wait_rules[1].mode, WaitRuleMode::PerChar);
+    assert_eq!(talk.wait_rules[1].after.as_deref(), Some("[A-Za-z]"));
+}
+
+#[test]
+fn test_config_wait_rules_take_priority_over_char_classes() {
+    let config = TalkConfig {
+        script_wait_normal: 100, // effective: 50
+        wait_rules: vec![WaitRuleConfig {
+            pattern: "[A-Za-z]".into(),
+            before: None,
+            after: Some("[A-Za-z]".into()),
+            emotion: None,
+            wait: 0,
+            mode: WaitRuleMode::PerChar,
+        }],
+        ..Default::default()
+    };
+    let lua = create_test_runtime_with_config(&config);
+
+    let result: String = lua
+        .load(
+            r#"
+            local SAKURA = require "@pasta_sakura_script"
+            return SAKURA.talk_to_script(nil, "Hiあ")
+        "#,
+        )
+        .eval()
+        .unwrap();
+    // 英単語の途中はウェイトなし、単語末尾と他の文字は文字種別のウェイト
+    assert_eq!(result, r"Hi\_w[50]あ\_w[50]");
+}
+
+#[test]
+fn test_actor_wait_rules_and_emotion() {
+    let lua = create_test_runtime();
+
+    let result: String = lua
+        .load(
+            r#"
+            local SAKURA = require "@pasta_sakura_script"
+            local actor = {
+                script_wait_normal = 100,
+                wait_rules = {
+                    { pattern = "……」", wait = 1200, mode = "once" },
+                    { pattern = ".", wait = 70, emotion = "焦り" },
+                },
+            }
+            local calm = SAKURA.talk_to_script(actor, "「……」あ")
+            actor.emotion = "焦り"
+            return calm .. "|" .. SAKURA.talk_to_script(actor, "あい")
+        "#,
+        )
+        .eval()
+        .unwrap();
+    assert_eq!(result, r"「……」\_w[1150]あ\_w[50]|あ\_w[20]い\_w[20]");
+}
+
+#[test]
+fn test_script_wait_base_override() {
+    let lua = create_test_runtime();
+
+    let result: String = lua
+        .load(
+            r#"
+            local SAKURA = require "@pasta_sakura_script"
+            return SAKURA.talk_to_script({ script_wait_base = 0 }, "あ。")
+        "#,
+        )
+        .eval()
+        .unwrap();
+    assert_eq!(result, r"あ\_w[50]。\_w[1000]");
+}
+
+#[test]
+fn test_invalid_wait_rule_pattern() {
+    let config = TalkConfig {
+        wait_rules: vec![WaitRuleConfig {
+            pattern: "(".into(),
+            before: None,
+            after: None,
+            emotion: None,
+            wait: 0,
+            mode: WaitRuleMode::Once,
+        }],
+        ..Default::default()
+    };
+    let lua = Lua::new();
+    assert!(sakura_script::register(&lua, Some(&config)).is_err());
+
+    let lua = create_test_runtime();
+    let result = lua
+        .load(
+            r#"
+            local SAKURA = require "@pasta_sakura_script"
+            return SAKURA.talk_to_script({ wait_rules = { { pattern = "[", wait = 0 } } }, "あ")
+        "#,
+        )
+        .eval::<String>();
+    assert!(result.unwrap_err().to_string().contains("actor wait_rules"));
+}
+
+// ============================================
+// Pacing variance
+// ============================================
+
+#[test]
+fn test_pacing_config_from_toml() {
+    let toml_str = r#"
+[pacing]
+variance = 20
+clause_slowdown = 50
+merge_waits = true
+seed = 7
+"#;
+
+    let talk: TalkConfig = toml::from_str(toml_str).unwrap();
+
+    assert_eq!(talk.pacing.variance, 20);
+    assert_eq!(talk.pacing.burst, 0);
+    assert_eq!(talk.pacing.clause_slowdown, 50);
+    assert!(talk.pacing.merge_waits);
+    assert_eq!(talk.pacing.seed, Some(7));
+}
+
+#[test]
+fn test_pacing_variance_with_mock_selector() {
+    let config = TalkConfig {
+        script_wait_normal: 150, // effective: 100
+        pacing: PacingConfig {
+            variance: 20,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let lua = create_test_runtime_with_config(&config);
+
+    let result: String = lua
+        .load(
+            r#"
+            local SAKURA = require "@pasta_sakura_script"
+            SAKURA.set_pacing_selector(0, 20, 40)
+            return SAKURA.talk_to_script(nil, "あいう。")
+        "#,
+        )
+        .eval()
+        .unwrap();
+    // 句点の once ウェイトには揺らぎをかけない
+    assert_eq!(result, r"あ\_w[80]い\_w[100]う\_w[120]。\_w[950]");
+}
+
+#[test]
+fn test_pacing_seed_is_reproducible() {
+    let config = TalkConfig {
+        script_wait_normal: 150,
+        pacing: PacingConfig {
+            variance: 50,
+            seed: Some(42),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let script = r#"
+        local SAKURA = require "@pasta_sakura_script"
+        return SAKURA.talk_to_script(nil, "あいうえおかきくけこ")
+    "#;
+
+    let first: String = create_test_runtime_with_config(&config)
+        .load(script)
+        .eval()
+        .unwrap();
+    let second: String = create_test_runtime_with_config(&config)
+        .load(script)
+        .eval()
+        .unwrap();
+    assert_eq!(first, second);
+
+    // set_pacing_selector() で同じシードから再開する
+    let lua = create_test_runtime_with_config(&config);
+    let _: String = lua.load(script).eval().unwrap();
+    lua.load(r#"require("@pasta_sakura_script").set_pacing_selector()"#)
+        .exec()
+        .unwrap();
+    let reset: String = lua.load(script).eval().unwrap();
+    assert_eq!(first, reset);
+}
+
+#[test]
+fn test_actor_pacing_override() {
+    let lua = create_test_runtime();
+
+    let result: String = lua
+        .load(
+            r#"
+            local SAKURA = require "@pasta_sakura_script"
+            local actor = {
+                script_wait_normal = 150,
+                pacing = { burst = 50, clause_slowdown = 100, merge_waits = true },
+            }
+            return SAKURA.talk_to_script(actor, "あい、\\_w[100]う")
+        "#,
+        )
+        .eval()
+        .unwrap();
+    assert_eq!(result, r"あ\_w[50]い\_w[200]、\_w[550]う\_w[100]");
+}
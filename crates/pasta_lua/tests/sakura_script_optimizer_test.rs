@@ -0,0 +1,132 @@
+//! Snapshot tests for the sakura script optimizer.
+//!
+//! Each case records the optimized script and its transcript, and checks that
+//! every character and choice is displayed at the same time as in the original.
+
+use insta::assert_snapshot;
+use mlua::Lua;
+use pasta_lua::loader::TalkConfig;
+use pasta_lua::sakura_script;
+use pasta_lua::sakura_script::optimizer::{OptimizeOptions, optimize};
+use pasta_lua::sakura_script::render::{ItemKind, Line, RenderOptions, render_text, timeline};
+
+/// Display times of characters and choices, plus the total duration.
+fn display_times(script: &str) -> (Vec<(String, u64)>, u64) {
+    let timeline = timeline(script, &RenderOptions::default()).unwrap();
+    let mut times = Vec::new();
+    for line in &timeline.lines {
+        match line {
+            Line::Talk { scope, items, .. } => {
+                for item in items {
+                    if let ItemKind::Char(c) = item.kind {
+                        times.push((format!("{scope}:{c}"), item.t));
+                    }
+                }
+            }
+            Line::Choice { t, id, .. } => times.push((format!("choice:{id}"), *t)),
+        }
+    }
+    (times, timeline.duration)
+}
+
+/// Optimize a script, assert the timing is unchanged and format a snapshot.
+fn optimize_and_check(script: &str) -> String {
+    let optimized = optimize(script, &OptimizeOptions::default()).unwrap();
+    assert_eq!(
+        display_times(&optimized),
+        display_times(script),
+        "timing changed:\n{script}\n{optimized}"
+    );
+
+    let options = RenderOptions {
+        names: [(0, "さくら".to_string()), (1, "うにゅう".to_string())].into(),
+        ..Default::default()
+    };
+    format!(
+        "bytes: {} -> {}\n--- optimized\n{}\n--- transcript\n{}",
+        script.len(),
+        optimized.len(),
+        optimized,
+        render_text(&optimized, &options).unwrap()
+    )
+}
+
+/// Build talk text with `talk_to_script` (100ms per character).
+fn talk(text: &str) -> String {
+    let lua = Lua::new();
+    let config = TalkConfig {
+        script_wait_normal: 100,
+        ..Default::default()
+    };
+    let module = sakura_script::register(&lua, Some(&config)).unwrap();
+    let talk_to_script: mlua::Function = module.get("talk_to_script").unwrap();
+    talk_to_script.call((mlua::Value::Nil, text)).unwrap()
+}
+
+#[test]
+fn test_optimize_long_talk_snapshot() {
+    let script = format!(
+        r"\p[0]\s[0]{}\n{}\e",
+        talk("今日はいい天気だね、散歩にでも行こうか。"),
+        talk("……でも、ちょっと眠いかも。")
+    );
+    assert_snapshot!("long_talk", optimize_and_check(&script));
+}
+
+#[test]
+fn test_optimize_conversation_snapshot() {
+    // sakura_builder が出力しがちな冗長なスコープ・サーフェス切り替え
+    let script = format!(
+        r"\p[0]\s[0]{}\p[0]\s[0]{}\n[150]\p[1]\s[10]\s[11]{}\w5\w5\p[1]\p[0]\q[はい,OnYes]\q[いいえ,OnNo]\e",
+        talk("ねえねえ。"),
+        talk("聞いてる？"),
+        talk("聞いとるで。"),
+    );
+    assert_snapshot!("conversation", optimize_and_check(&script));
+}
+
+#[test]
+fn test_optimize_keeps_quick_and_absolute_waits_snapshot() {
+    let script = format!(
+        r"\0\__w[clear]{}\_q{}\_q\__w[3000]{}\e",
+        talk("せーの"),
+        talk("いっきに表示"),
+        talk("おしまい。")
+    );
+    assert_snapshot!("quick_and_absolute_waits", optimize_and_check(&script));
+}
+
+#[test]
+fn test_optimize_from_lua() {
+    let lua = Lua::new();
+    let module = sakura_script::register(&lua, None).unwrap();
+    lua.globals().set("SAKURA", module).unwrap();
+
+    let (optimized, err): (Option<String>, Option<String>) = lua
+        .load(r#"return SAKURA.optimize("\\0\\0\\s[0]\\s[0]あ\\_w[100]\\_w[200]\\e")"#)
+        .eval()
+        .unwrap();
+    assert_eq!(optimized.as_deref(), Some(r"\0\s[0]あ\_w[300]\e"));
+    assert_eq!(err, None);
+
+    // balloon_wait = false では文字ごとのウェイトを残す
+    let optimized: String = lua
+        .load(
+            r#"return SAKURA.optimize(
+                "あ\\_w[50]い\\_w[50]う\\_w[50]え\\_w[50]お\\_w[50]か\\_w[50]き\\_w[50]",
+                { balloon_wait = false })"#,
+        )
+        .eval()
+        .unwrap();
+    assert_eq!(
+        optimized,
+        r"あ\_w[50]い\_w[50]う\_w[50]え\_w[50]お\_w[50]か\_w[50]き\_w[50]"
+    );
+
+    let (optimized, err): (Option<String>, Option<String>) = lua
+        .load(r#"return SAKURA.optimize("\\s[0")"#)
+        .eval()
+        .unwrap();
+    assert_eq!(optimized, None);
+    assert!(err.is_some());
+}
@@ -0,0 +1,157 @@
+//! SAORI/1.0 client integration tests.
+//!
+//! モック SAORI ヘルパー（tests/fixtures/saori/mock_saori.sh）を使い、
+//! `@pasta_saori` / `pasta.areka.saori` と DSL の `＠関数（）` 呼び出しを検証する。
+#![cfg(unix)]
+
+mod common;
+
+use common::ghost_fixture::{self, DEBUG_PASTA_TOML};
+use pasta_lua::PastaLuaRuntime;
+use pasta_lua::loader::PastaLoader;
+use std::path::PathBuf;
+use tempfile::TempDir;
+
+/// Create a ghost directory with the mock helper in saori/ and the given main.lua / scene.
+fn create_ghost(main_lua: &str, pasta: &str) -> TempDir {
+    let temp = ghost_fixture::create_ghost("saori.pasta", pasta, DEBUG_PASTA_TOML);
+    let base_dir = temp.path();
+    let crate_root = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+
+    std::fs::create_dir_all(base_dir.join("user_scripts")).unwrap();
+    std::fs::write(base_dir.join("user_scripts/main.lua"), main_lua).unwrap();
+
+    // std::fs::copy preserves the executable bit on Unix
+    std::fs::create_dir_all(base_dir.join("saori")).unwrap();
+    std::fs::copy(
+        crate_root.join("tests/fixtures/saori/mock_saori.sh"),
+        base_dir.join("saori/mock_saori.sh"),
+    )
+    .unwrap();
+    temp
+}
+
+fn load(main_lua: &str) -> (TempDir, PastaLuaRuntime) {
+    let temp = create_ghost(main_lua, "＊テスト\n  さくら：「こんにちは」\n");
+    let runtime = PastaLoader::load(temp.path()).unwrap();
+    (temp, runtime)
+}
+
+fn eval_string(runtime: &PastaLuaRuntime, code: &str) -> String {
+    runtime.lua().load(code).eval::<String>().unwrap()
+}
+
+#[test]
+fn test_version_and_execute() {
+    let (_temp, runtime) = load("return {}");
+    let result = eval_string(
+        &runtime,
+        r#"
+        local SAORI = require("pasta.areka.saori")
+        local helper = SAORI.load("saori/mock_saori.sh")
+        local ver = assert(helper:version())
+        local add = assert(helper:execute("add", 1, 2))
+        local echo = assert(helper:execute({ "echo", "あ", "い" }))
+        return table.concat({
+            ver.version, ver.status, ver.status_text,
+            add.result, add.headers.Charset,
+            echo.result, echo.values[1], echo.values[2], #echo.values,
+        }, "|")
+    "#,
+    );
+    assert_eq!(result, "SAORI/1.0|200|OK|3|UTF-8|あ|あ|い|2");
+}
+
+#[test]
+fn test_helper_runs_in_its_own_directory() {
+    let (_temp, runtime) = load("return {}");
+    let result = eval_string(
+        &runtime,
+        r#"
+        local res = assert(require("pasta.areka.saori").execute("saori/mock_saori.sh", "cwd"))
+        return res.result
+    "#,
+    );
+    assert_eq!(result, "saori");
+}
+
+#[test]
+fn test_errors_return_nil_and_message() {
+    let (_temp, runtime) = load("return {}");
+    let result = eval_string(
+        &runtime,
+        r#"
+        local RAW = require("@pasta_saori")
+        local helper = RAW.load("saori/mock_saori.sh", { timeout = 0.2 })
+        local _, timeout_err = helper:execute("sleep")
+        local _, garbage_err = helper:execute("garbage")
+        local bad = assert(helper:execute("unknown"))
+        local _, spawn_err = RAW.load("saori/missing"):version()
+        return table.concat({ timeout_err, garbage_err, bad.status, spawn_err }, "\n")
+    "#,
+    );
+    let lines: Vec<&str> = result.lines().collect();
+    assert!(lines[0].contains("timed out"), "{result}");
+    assert!(lines[1].contains("invalid SAORI response"), "{result}");
+    assert_eq!(lines[2], "400");
+    assert!(
+        lines[3].contains("failed to start SAORI helper"),
+        "{result}"
+    );
+}
+
+#[test]
+fn test_timeout_kills_helper_promptly() {
+    let (_temp, runtime) = load("return {}");
+    let start = std::time::Instant::now();
+    runtime
+        .exec(
+            r#"
+        local helper = require("@pasta_saori").load("saori/mock_saori.sh", { timeout = 0.2 })
+        assert(helper:execute("sleep") == nil)
+    "#,
+        )
+        .unwrap();
+    assert!(start.elapsed() < std::time::Duration::from_secs(5));
+}
+
+#[test]
+fn test_dsl_function_call_reaches_saori() {
+    let temp = create_ghost(
+        r#"
+        local SAORI = require("pasta.areka.saori")
+        SAORI.register("計算", "saori/mock_saori.sh")
+        return {}
+        "#,
+        "％さくら\n\n＊OnSaori\n  さくら：答えは＠計算（「add」、１、２）です\n",
+    );
+    let runtime = PastaLoader::load(temp.path()).unwrap();
+    let response = eval_string(
+        &runtime,
+        r#"
+        local EVENT = require("pasta.shiori.event")
+        return EVENT.fire({ id = "OnSaori", method = "get", version = 30 })
+    "#,
+    );
+    assert!(response.contains("答えは3です"), "{response}");
+}
+
+#[test]
+fn test_registered_function_failure_returns_empty_string() {
+    let (_temp, runtime) = load(
+        r#"
+        require("pasta.areka.saori").register("壊れ", "saori/mock_saori.sh")
+        return {}
+        "#,
+    );
+    let result = eval_string(
+        &runtime,
+        r#"
+        local ACT = require("pasta.act")
+        local act = ACT.new({})
+        local value = act:call_fn(nil, "壊れ", "unknown")
+        return value .. "|" .. tostring(act.var.saori.status)
+    "#,
+    );
+    assert_eq!(result, "|400");
+}
@@ -0,0 +1,99 @@
+//! Embedding API (PastaLuaRuntime::run_scene) integration tests.
+//!
+//! SHIORI リクエストを介さずにシーンを実行し、yield ごとの ScriptEvent、
+//! 変数・永続変数へのアクセスを検証する。
+
+mod common;
+
+use common::ghost_fixture::{DEBUG_PASTA_TOML, load_ghost};
+use pasta_lua::{PastaLuaRuntime, ScriptEvent};
+use tempfile::TempDir;
+
+const SCENES: &str = r#"％さくら
+
+＊メイン
+  さくら：こんにちは、＄名前　さん。
+  ＞チェイントーク
+  さくら：またね。
+  ＄＊回数＝1
+
+＊空
+  ＞チェイントーク
+
+＊エラー
+  さくら：＠存在しない関数（）
+"#;
+
+fn load() -> (TempDir, PastaLuaRuntime) {
+    load_ghost("scene.pasta", SCENES, DEBUG_PASTA_TOML)
+}
+
+fn talk(text: &str) -> ScriptEvent {
+    ScriptEvent::Talk {
+        actor: Some("さくら".into()),
+        text: text.into(),
+    }
+}
+
+#[test]
+fn test_run_scene_yields_events_per_chain_talk() {
+    let (_temp, runtime) = load();
+
+    let mut run = runtime.run_scene("メイン", ()).unwrap().unwrap();
+    assert_eq!(run.global_name(), "メイン1");
+    assert_eq!(run.local_name(), "__start__");
+    run.set_var("名前", "ユーザー").unwrap();
+
+    let first = run.next_events().unwrap().unwrap();
+    assert_eq!(
+        first,
+        vec![
+            ScriptEvent::Actor {
+                name: "さくら".into(),
+                spot: 0,
+                balloon: None,
+            },
+            talk("こんにちは、ユーザーさん。"),
+        ]
+    );
+    assert!(!run.is_finished());
+
+    // チェイントークの続き（出力ごとに話者から始まる）
+    let second = run.next_events().unwrap().unwrap();
+    assert_eq!(second[1..], [talk("またね。")]);
+    assert!(run.is_finished());
+    assert_eq!(run.next_events().unwrap(), None);
+
+    // 永続変数は act.save と pasta.save で共有される
+    let count: i64 = run.save().unwrap().get("回数").unwrap();
+    assert_eq!(count, 1);
+    let count: i64 = runtime.save_table().unwrap().get("回数").unwrap();
+    assert_eq!(count, 1);
+}
+
+#[test]
+fn test_run_scene_as_iterator_skips_empty_yields() {
+    let (_temp, runtime) = load();
+
+    let run = runtime.run_scene("空", ()).unwrap().unwrap();
+    let outputs: Vec<_> = run.collect::<Result<_, _>>().unwrap();
+    assert!(outputs.is_empty(), "{outputs:?}");
+}
+
+#[test]
+fn test_run_scene_not_found() {
+    let (_temp, runtime) = load();
+
+    assert!(runtime.run_scene("存在しない", ()).unwrap().is_none());
+}
+
+#[test]
+fn test_run_scene_error_finishes_run() {
+    let (_temp, runtime) = load();
+
+    let mut run = runtime.run_scene("エラー", ()).unwrap().unwrap();
+    let err = run.next_events().unwrap_err();
+    assert!(err.to_string().contains("function not found"), "{err}");
+    assert!(run.is_finished());
+    assert_eq!(run.next_events().unwrap(), None);
+}
@@ -0,0 +1,69 @@
+//! JSON event stream output integration tests.
+//!
+//! `[talk] output = "json"` のとき、EVENT.fire の応答 Value が
+//! ScriptEventStream として読めることを検証する。
+
+mod common;
+
+use common::ghost_fixture::load_ghost;
+use pasta_lua::{PastaLuaRuntime, SCRIPT_EVENT_VERSION, ScriptEvent, ScriptEventStream};
+use tempfile::TempDir;
+
+const SCENES: &str = r#"％さくら
+
+＊OnTest
+  さくら：こんにちは。
+"#;
+
+fn load(pasta_toml: &str) -> (TempDir, PastaLuaRuntime) {
+    load_ghost("event.pasta", SCENES, pasta_toml)
+}
+
+/// OnTest を発火し、応答の Value ヘッダーを返す
+fn fire_value(runtime: &PastaLuaRuntime) -> String {
+    let res = runtime
+        .lua()
+        .load(
+            r#"
+            local EVENT = require("pasta.shiori.event")
+            return EVENT.fire({ id = "OnTest", method = "get", version = 30, reference = {} })
+            "#,
+        )
+        .eval::<String>()
+        .unwrap();
+    res.lines()
+        .find_map(|line| line.strip_prefix("Value: "))
+        .unwrap_or_else(|| panic!("no Value header: {res}"))
+        .to_string()
+}
+
+#[test]
+fn test_json_output_from_config() {
+    let (_temp, runtime) = load("[loader]\ndebug_mode = true\n\n[talk]\noutput = \"json\"\n");
+
+    let stream = ScriptEventStream::from_json(&fire_value(&runtime)).unwrap();
+    assert_eq!(stream.version, SCRIPT_EVENT_VERSION);
+    assert_eq!(
+        stream.events,
+        vec![
+            ScriptEvent::Actor {
+                name: "さくら".into(),
+                spot: 0,
+                balloon: None,
+            },
+            ScriptEvent::Talk {
+                actor: Some("さくら".into()),
+                text: "こんにちは。".into(),
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_sakura_output_by_default() {
+    let (_temp, runtime) = load("[loader]\ndebug_mode = true\n");
+
+    let value = fire_value(&runtime);
+    assert!(value.contains("こんにちは"), "{value}");
+    assert!(value.ends_with(r"\e"), "{value}");
+}
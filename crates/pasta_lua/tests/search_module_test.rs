@@ -324,3 +324,57 @@ fn test_search_scene_local_returns_transpiler_format() {
         "Expected global_name='メイン_1' and local_name='__選択肢_1__'"
     );
 }
+
+#[test]
+fn test_scene_candidates_returns_all_prefix_matches() {
+    let ctx = create_test_context();
+    let runtime = PastaLuaRuntime::new(ctx).unwrap();
+
+    // Test that scene_candidates returns global scenes matching the prefix
+    // (local scenes are excluded, unmatched prefix returns an empty array)
+    let result = runtime.exec(
+        r#"
+        local SEARCH = require "@pasta_search"
+        local one = SEARCH:scene_candidates("挨")
+        local main = SEARCH:scene_candidates("メイン")
+        local none = SEARCH:scene_candidates("存在しないシーン")
+        return #one == 1 and one[1] == "挨拶_1"
+            and #main == 1 and main[1] == "メイン_1"
+            and #none == 0
+    "#,
+    );
+
+    assert!(result.is_ok(), "Expected Ok, got Err: {:?}", result);
+    let value = result.unwrap();
+    assert!(
+        value.as_boolean().unwrap_or(false),
+        "Expected ['挨拶_1'] for '挨', ['メイン_1'] for 'メイン' and [] for unknown prefix"
+    );
+}
+
+#[test]
+fn test_select_scene_uses_scene_selector() {
+    let ctx = create_test_context();
+    let runtime = PastaLuaRuntime::new(ctx).unwrap();
+
+    // Test that select_scene picks with the scene selector, and that changing
+    // the selector keeps the registered scenes
+    let result = runtime.exec(
+        r#"
+        local SEARCH = require "@pasta_search"
+        SEARCH:set_scene_selector(2, 0)
+        local first = SEARCH:select_scene({ "a", "b", "c" })
+        local second = SEARCH:select_scene({ "a", "b", "c" })
+        local empty = SEARCH:select_scene({})
+        local scenes = SEARCH:scene_candidates("挨")
+        return first == "c" and second == "a" and empty == nil and #scenes == 1
+    "#,
+    );
+
+    assert!(result.is_ok(), "Expected Ok, got Err: {:?}", result);
+    let value = result.unwrap();
+    assert!(
+        value.as_boolean().unwrap_or(false),
+        "Expected 'c' then 'a' from the mock selector, nil for no candidates"
+    );
+}
@@ -1035,3 +1035,111 @@ fn test_nil_reference_access() {
     );
     assert!(result.unwrap().as_boolean().unwrap_or(false));
 }
+
+#[test]
+fn test_event_fire_emits_act_response_headers() {
+    let runtime = create_runtime_with_pasta_path();
+
+    let result = runtime.exec(
+        r#"
+        local REG = require "pasta.shiori.event.register"
+        local EVENT = require "pasta.shiori.event"
+
+        REG.OnCommunicate = function(act)
+            act:set_reference(0, act.req.reference[0])
+            act:set_marker("返信中")
+            act:set_balloon_offset(5, -10, 1)
+            act:add_error("warning", "first")
+            act:add_error("info", "second")
+            return "\\0reply\\e"
+        end
+
+        local req = {
+            id = "OnCommunicate",
+            method = "get",
+            version = 30,
+            reference = { [0] = "user" },
+        }
+        return EVENT.fire(req)
+    "#,
+    );
+
+    let response = result.unwrap();
+    let response = response.as_string().unwrap().to_str().unwrap().to_string();
+    assert_eq!(
+        response,
+        "SHIORI/3.0 200 OK\r\n\
+         Charset: UTF-8\r\n\
+         Sender: Pasta\r\n\
+         SecurityLevel: local\r\n\
+         Value: \\0reply\\e\r\n\
+         Reference0: user\r\n\
+         Marker: 返信中\r\n\
+         BalloonOffset: 0,0\u{1}5,-10\r\n\
+         ErrorLevel: warning\u{1}info\r\n\
+         ErrorDescription: first\u{1}second\r\n\
+         \r\n"
+    );
+}
+
+#[test]
+fn test_event_fire_notify_with_value_notify() {
+    let runtime = create_runtime_with_pasta_path();
+
+    let result = runtime.exec(
+        r#"
+        local REG = require "pasta.shiori.event.register"
+        local EVENT = require "pasta.shiori.event"
+
+        REG.OnNotifyTest = function(act)
+            act:set_value_notify("\\0notified\\e")
+            return nil
+        end
+
+        return EVENT.fire({ id = "OnNotifyTest", method = "notify", version = 30 })
+    "#,
+    );
+
+    let response = result.unwrap();
+    let response = response.as_string().unwrap().to_str().unwrap().to_string();
+    assert_eq!(
+        response,
+        "SHIORI/3.0 204 No Content\r\n\
+         Charset: UTF-8\r\n\
+         Sender: Pasta\r\n\
+         SecurityLevel: local\r\n\
+         ValueNotify: \\0notified\\e\r\n\
+         \r\n"
+    );
+}
+
+#[test]
+fn test_act_response_header_setters_validate_arguments() {
+    let runtime = create_runtime_with_pasta_path();
+
+    let result = runtime.exec(
+        r#"
+        local SHIORI_ACT = require "pasta.shiori.act"
+        local act = SHIORI_ACT.new({}, { id = "OnTest" })
+
+        local function fails(f, pattern)
+            local ok, err = pcall(f)
+            assert(not ok, "expected failure: " .. pattern)
+            assert(tostring(err):find(pattern, 1, true), tostring(err))
+        end
+
+        fails(function() act:set_reference(-1, "x") end, "non-negative integer")
+        fails(function() act:set_reference(1.5, "x") end, "non-negative integer")
+        fails(function() act:set_marker("a\nb") end, "CR/LF")
+        fails(function() act:set_marker({}) end, "string expected")
+        fails(function() act:add_error("fatal", "x") end, "unknown level")
+        fails(function() act:set_balloon_offset(1.5, 0) end, "integer x, y")
+
+        -- 失敗した呼び出しはヘッダーを残さない
+        assert(next(act:response_headers()) == nil, "no headers expected")
+        return true
+    "#,
+    );
+
+    assert!(result.is_ok(), "setter validation failed: {:?}", result);
+}
@@ -370,3 +370,157 @@ fn test_defensive_nil_handling() {
         result
     );
 }
+
+#[test]
+fn test_res_set_protocol_shiori2_sentence() {
+    let runtime = create_runtime_with_pasta_path();
+
+    let result = runtime.exec(
+        r#"
+        local RES = require "pasta.shiori.res"
+        RES.set_protocol({ version = 26, command = "Sentence" })
+        local response = RES.ok("\\0hello\\e")
+        RES.set_protocol(nil)
+        return response
+    "#,
+    );
+
+    let response = result.unwrap();
+    let response = response.as_string().unwrap().to_str().unwrap().to_string();
+    assert_eq!(
+        response,
+        "SHIORI/2.6 200 OK\r\n\
+         Charset: UTF-8\r\n\
+         Sender: Pasta\r\n\
+         Sentence: \\0hello\\e\r\n\
+         \r\n"
+    );
+}
+
+#[test]
+fn test_res_set_protocol_resets_to_shiori3() {
+    let runtime = create_runtime_with_pasta_path();
+
+    let result = runtime.exec(
+        r#"
+        local RES = require "pasta.shiori.res"
+        RES.set_protocol({ version = 24, command = "String" })
+        assert(RES.protocol.value_key == "String", "2.x value key")
+        RES.set_protocol({ version = 30 })
+        assert(RES.protocol.version == 30, "reset version")
+        assert(RES.protocol.value_key == "Value", "reset value key")
+        local response = RES.ok("v")
+        assert(response:sub(1, 19) == "SHIORI/3.0 200 OK\r\n", "3.0 status line")
+        assert(response:find("SecurityLevel: local", 1, true), "3.0 SecurityLevel")
+        return true
+    "#,
+    );
+
+    assert!(result.is_ok(), "set_protocol reset failed: {:?}", result);
+}
+
+#[test]
+fn test_res_set_protocol_echoes_request_charset() {
+    let runtime = create_runtime_with_pasta_path();
+
+    let result = runtime.exec(
+        r#"
+        local RES = require "pasta.shiori.res"
+        RES.set_protocol({ version = 30, charset = "Shift_JIS" })
+        local sjis = RES.no_content()
+        RES.set_protocol({ version = 30 })
+        local default = RES.no_content()
+        assert(sjis:find("Charset: Shift_JIS\r\n", 1, true), "request charset echoed")
+        assert(default:find("Charset: UTF-8\r\n", 1, true), "falls back to RES.env.charset")
+        return true
+    "#,
+    );
+
+    assert!(result.is_ok(), "charset echo failed: {:?}", result);
+}
+
+#[test]
+fn test_res_build_orders_headers_deterministically() {
+    let runtime = create_runtime_with_pasta_path();
+
+    let result = runtime.exec(
+        r#"
+        local RES = require "pasta.shiori.res"
+        return RES.ok("\\0hi\\e", {
+            ["X-Extra"] = "x",
+            ErrorDescription = "desc",
+            Reference10 = "r10",
+            Marker = "marker",
+            Reference2 = "r2",
+            ValueNotify = "\\0notify\\e",
+            ErrorLevel = "warning",
+            Reference0 = "r0",
+            BalloonOffset = "10,20",
+            Age = 3,
+        })
+    "#,
+    );
+
+    let response = result.unwrap();
+    let response = response.as_string().unwrap().to_str().unwrap().to_string();
+    assert_eq!(
+        response,
+        "SHIORI/3.0 200 OK\r\n\
+         Charset: UTF-8\r\n\
+         Sender: Pasta\r\n\
+         SecurityLevel: local\r\n\
+         Value: \\0hi\\e\r\n\
+         ValueNotify: \\0notify\\e\r\n\
+         Reference0: r0\r\n\
+         Reference2: r2\r\n\
+         Reference10: r10\r\n\
+         Marker: marker\r\n\
+         BalloonOffset: 10,20\r\n\
+         ErrorLevel: warning\r\n\
+         ErrorDescription: desc\r\n\
+         Age: 3\r\n\
+         X-Extra: x\r\n\
+         \r\n"
+    );
+}
+
+#[test]
+fn test_res_build_rejects_invalid_headers() {
+    let runtime = create_runtime_with_pasta_path();
+
+    let result = runtime.exec(
+        r#"
+        local RES = require "pasta.shiori.res"
+        local ok1, err1 = pcall(RES.ok, "v", { Marker = "a\r\nInjected: 1" })
+        local ok2, err2 = pcall(RES.ok, "line1\nline2")
+        local ok3, err3 = pcall(RES.ok, "v", { ["Bad Name"] = "x" })
+        local ok4, err4 = pcall(RES.ok, "v", { Marker = {} })
+        assert(not ok1 and err1:find("CR/LF", 1, true), tostring(err1))
+        assert(not ok2 and err2:find("CR/LF", 1, true), tostring(err2))
+        assert(not ok3 and err3:find("invalid header name", 1, true), tostring(err3))
+        assert(not ok4 and err4:find("must be string", 1, true), tostring(err4))
+        return true
+    "#,
+    );
+
+    assert!(result.is_ok(), "header validation failed: {:?}", result);
+}
+
+#[test]
+fn test_res_err_flattens_multiline_reason() {
+    let runtime = create_runtime_with_pasta_path();
+
+    let result = runtime.exec(
+        r#"
+        local RES = require "pasta.shiori.res"
+        return RES.err("first\nsecond")
+    "#,
+    );
+
+    let response = result.unwrap();
+    let response = response.as_string().unwrap().to_str().unwrap().to_string();
+    assert!(
+        response.contains("X-Error-Reason: first second\r\n"),
+        "{response}"
+    );
+}
@@ -0,0 +1,225 @@
+//! SSTP client integration tests.
+//!
+//! プロセス内のスタンドイン SSTP サーバー（TcpListener）に接続し、
+//! `SstpClient` と `@pasta_sstp` のリクエスト組み立て・文字コード・
+//! ステータス解析・エラー処理を検証する。
+
+mod common;
+
+use common::ghost_fixture::load_ghost;
+use pasta_lua::runtime::sstp::{SstpClient, SstpError, SstpRequest};
+use pasta_lua::{PastaLuaRuntime, TranspileContext};
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpListener;
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Stand-in SSTP server.
+///
+/// Accepts `replies.len()` connections, records each request (raw bytes up to the
+/// blank line) and answers with the corresponding reply. `None` keeps the
+/// connection open without answering.
+fn spawn_server(replies: Vec<Option<&'static [u8]>>) -> (u16, Receiver<Vec<u8>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        for reply in replies {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream);
+            let mut request = Vec::new();
+            loop {
+                let mut line = Vec::new();
+                if reader.read_until(b'\n', &mut line).unwrap() == 0 {
+                    break;
+                }
+                request.extend_from_slice(&line);
+                if line == b"\r\n" {
+                    break;
+                }
+            }
+            tx.send(request).unwrap();
+            let mut stream = reader.into_inner();
+            match reply {
+                Some(reply) => stream.write_all(reply).unwrap(),
+                None => {
+                    thread::spawn(move || {
+                        thread::sleep(Duration::from_secs(2));
+                        drop(stream);
+                    });
+                }
+            }
+        }
+    });
+    (port, rx)
+}
+
+fn received(rx: &Receiver<Vec<u8>>) -> String {
+    let bytes = rx.recv_timeout(Duration::from_secs(5)).unwrap();
+    String::from_utf8(bytes).unwrap()
+}
+
+fn new_runtime() -> PastaLuaRuntime {
+    PastaLuaRuntime::new(TranspileContext::new()).unwrap()
+}
+
+#[test]
+fn test_send_request_and_status() {
+    let (port, rx) = spawn_server(vec![Some(b"SSTP/1.4 200 OK\r\n\r\n")]);
+    let client = SstpClient::new("127.0.0.1", port).with_sender("テスト");
+
+    let res = client
+        .request(&SstpRequest::send("\\h\\s[0]こんにちは\\e").header("Option", "nodescript"))
+        .unwrap();
+    assert_eq!(res.version, "SSTP/1.4");
+    assert_eq!(res.status, 200);
+    assert_eq!(res.status_text, "OK");
+    assert!(res.is_success());
+
+    assert_eq!(
+        received(&rx),
+        "SEND SSTP/1.4\r\nCharset: UTF-8\r\nSender: テスト\r\n\
+         Script: \\h\\s[0]こんにちは\\e\r\nOption: nodescript\r\n\r\n"
+    );
+}
+
+#[test]
+fn test_shift_jis_charset() {
+    let (port, rx) = spawn_server(vec![Some(
+        b"SSTP/1.2 200 OK\r\n\x82\xb3\x82\xad\x82\xe7\r\n\r\n", // "さくら"
+    )]);
+    let client = SstpClient::new("127.0.0.1", port)
+        .with_charset("Shift_JIS")
+        .unwrap();
+
+    let res = client.request(&SstpRequest::execute("GetName")).unwrap();
+    assert_eq!(res.data, vec!["さくら"]);
+
+    let request = rx.recv_timeout(Duration::from_secs(5)).unwrap();
+    let (text, _, had_errors) = encoding_rs::SHIFT_JIS.decode(&request);
+    assert!(!had_errors);
+    assert!(text.starts_with("EXECUTE SSTP/1.2\r\nCharset: Shift_JIS\r\n"));
+}
+
+#[test]
+fn test_unencodable_request() {
+    let client = SstpClient::new("127.0.0.1", 9)
+        .with_charset("Shift_JIS")
+        .unwrap();
+    let err = client.request(&SstpRequest::send("😀")).unwrap_err();
+    assert!(matches!(err, SstpError::Encoding("Shift_JIS")));
+}
+
+#[test]
+fn test_errors() {
+    // Nothing listening on the port
+    let port = {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        listener.local_addr().unwrap().port()
+    };
+    let err = SstpClient::new("127.0.0.1", port)
+        .request(&SstpRequest::notify("OnTest"))
+        .unwrap_err();
+    assert!(matches!(err, SstpError::Connect { .. }), "{err}");
+
+    let (port, _rx) = spawn_server(vec![None, Some(b"HTTP/1.1 200 OK\r\n\r\n")]);
+    let client = SstpClient::new("127.0.0.1", port).with_timeout(Duration::from_millis(200));
+
+    // Server accepts but never answers
+    let start = Instant::now();
+    let err = client.request(&SstpRequest::notify("OnTest")).unwrap_err();
+    assert!(matches!(err, SstpError::Timeout(_)), "{err}");
+    assert!(start.elapsed() < Duration::from_secs(2));
+
+    // Not an SSTP response
+    let err = client.request(&SstpRequest::notify("OnTest")).unwrap_err();
+    assert!(matches!(err, SstpError::InvalidResponse(_)), "{err}");
+}
+
+#[test]
+fn test_lua_client_requests() {
+    let (port, rx) = spawn_server(vec![
+        Some(b"SSTP/1.1 204 No Content\r\n\r\n"),
+        Some(b"SSTP/1.2 200 OK\r\nSakura\r\nKero\r\n\r\n"),
+    ]);
+    let runtime = new_runtime();
+
+    let code = format!(
+        r#"
+        local SSTP = require "@pasta_sstp"
+        local client = SSTP.client({{ port = {port}, sender = "LuaGhost" }})
+        local res1 = assert(client:notify({{
+            event = "OnTimerTalk",
+            references = {{ "10", "a" }},
+            headers = {{ IfGhost = "さくら" }},
+        }}))
+        local res2 = assert(client:execute("GetNames"))
+        return res1.status .. "|" .. res2.status .. "|" .. table.concat(res2.data, ",")
+        "#
+    );
+    let result: String = runtime.lua().load(&code).eval().unwrap();
+    assert_eq!(result, "204|200|Sakura,Kero");
+
+    assert_eq!(
+        received(&rx),
+        "NOTIFY SSTP/1.1\r\nCharset: UTF-8\r\nSender: LuaGhost\r\nEvent: OnTimerTalk\r\n\
+         Reference0: 10\r\nReference1: a\r\nIfGhost: さくら\r\n\r\n"
+    );
+    assert_eq!(
+        received(&rx),
+        "EXECUTE SSTP/1.2\r\nCharset: UTF-8\r\nSender: LuaGhost\r\nCommand: GetNames\r\n\r\n"
+    );
+}
+
+#[test]
+fn test_lua_errors() {
+    let runtime = new_runtime();
+    let result: String = runtime
+        .lua()
+        .load(
+            r#"
+            local SSTP = require "@pasta_sstp"
+            local client = SSTP.client({ port = 1, timeout = 0.5 })
+            local res, err = client:send("\\0\\e")
+            assert(res == nil)
+            local ok, msg = pcall(client.notify, client, { script = "\\0\\e" })
+            assert(not ok)
+            local res2, name_err = client:send({ script = "\\0\\e", headers = { ["X\r\nEvent"] = "x" } })
+            assert(res2 == nil)
+            return err .. "|" .. tostring(msg) .. "|" .. name_err
+            "#,
+        )
+        .eval()
+        .unwrap();
+    let mut parts = result.split('|');
+    let (err, msg, name_err) = (
+        parts.next().unwrap(),
+        parts.next().unwrap(),
+        parts.next().unwrap(),
+    );
+    assert!(err.contains("SSTP"), "{err}");
+    assert!(msg.contains("'event' is required"), "{msg}");
+    assert!(name_err.contains("invalid SSTP header name"), "{name_err}");
+}
+
+#[test]
+fn test_endpoint_from_pasta_toml() {
+    let (port, rx) = spawn_server(vec![Some(b"SSTP/1.4 200 OK\r\n\r\n")]);
+
+    let (_temp, runtime) = load_ghost(
+        "sstp.pasta",
+        "＊テスト\n  さくら：「こんにちは」\n",
+        &format!(
+            "[loader]\ndebug_mode = true\n\n[sstp]\nport = {port}\nsender = \"設定ゴースト\"\n"
+        ),
+    );
+
+    let status: u16 = runtime
+        .lua()
+        .load(r#"return require("@pasta_sstp").send("\\0\\e").status"#)
+        .eval()
+        .unwrap();
+    assert_eq!(status, 200);
+    assert!(received(&rx).contains("Sender: 設定ゴースト\r\n"));
+}
@@ -1260,3 +1260,34 @@ fn test_tail_call_optimization_fixture() {
         "No call scene should have no return act:call. Section:\n{no_call_code}"
     );
 }
+
+#[test]
+fn test_transpile_collects_sakura_script_warnings() {
+    let source = r#"＊メイン
+  さくら：\s[0]こんにちは\q[はい]\k
+"#;
+    let file = parse_str(source, "test.pasta").unwrap();
+    let transpiler = LuaTranspiler::new(TranspilerConfig::default());
+    let mut output = Vec::new();
+    let context = transpiler.transpile(&file, &mut output).unwrap();
+
+    let kinds: Vec<_> = context
+        .sakura_warnings
+        .iter()
+        .map(|w| w.diagnostic.kind.as_str())
+        .collect();
+    assert_eq!(kinds, vec!["malformed_arguments", "unknown_tag"]);
+    assert!(
+        context
+            .sakura_warnings
+            .iter()
+            .all(|w| w.span.start_line == 2)
+    );
+
+    // 警告があっても出力は生成される
+    let lua_code = String::from_utf8(output).unwrap();
+    assert!(
+        lua_code.contains(r"act:sakura_script([=[\q[はい]]=])"),
+        "{lua_code}"
+    );
+}
@@ -0,0 +1,46 @@
+/* pasta_shiori portable C ABI. Generated by cbindgen; do not edit. */
+
+#ifndef PASTA_H
+#define PASTA_H
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// Load the SHIORI from `dir` (`len` bytes, not NUL-terminated).
+//
+// Returns `true` when the ghost was loaded successfully.
+//
+// # Safety
+// `dir` must point to `len` readable bytes.
+bool pasta_load(const uint8_t *dir, size_t len);
+
+// Process a SHIORI request of `len` bytes.
+//
+// Returns a newly allocated response buffer and stores its length in
+// `out_len`. The buffer must be released with `pasta_free`.
+// Returns NULL (and `*out_len = 0`) when `buf` or `out_len` is NULL.
+//
+// # Safety
+// `buf` must point to `len` readable bytes and `out_len` must be writable.
+uint8_t *pasta_request(const uint8_t *buf, size_t len, size_t *out_len);
+
+// Release a buffer returned by `pasta_request`.
+//
+// # Safety
+// `buf` and `len` must be exactly the pointer and length returned by
+// `pasta_request`, and the buffer must not be freed twice.
+void pasta_free(uint8_t *buf, size_t len);
+
+// Unload the SHIORI. Safe to call when nothing is loaded.
+bool pasta_unload(void);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* PASTA_H */
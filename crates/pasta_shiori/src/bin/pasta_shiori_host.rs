@@ -0,0 +1,79 @@
+//! pasta_shiori_host - SHIORI over stdio / Unix domain socket
+//!
+//! Windows 以外のベースウェアやテストハーネスから pasta を SHIORI として駆動します。
+//! フレーミングと LOAD / UNLOAD 制御フレームは `pasta::host` を参照。
+//!
+//! # 使い方
+//!
+//! ```bash
+//! # stdin/stdout（LOAD フレームでロードディレクトリを指定）
+//! pasta_shiori_host
+//!
+//! # 起動時にロード
+//! pasta_shiori_host --load-dir /path/to/ghost/master
+//!
+//! # Unix ドメインソケットで待ち受け（接続ごとに1セッション）
+//! pasta_shiori_host --socket /tmp/pasta.sock --load-dir /path/to/ghost/master
+//! ```
+//!
+//! # 注意
+//!
+//! stdio モードでは stdout がプロトコル用のため、Lua の print は stderr に出力されます。
+
+use pasta::PastaShiori;
+use pasta::host::{ShioriHost, redirect_print_to_stderr};
+use std::env;
+use std::io;
+use std::path::PathBuf;
+
+const USAGE: &str = "usage: pasta_shiori_host [--load-dir DIR] [--socket PATH]";
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let mut load_dir: Option<PathBuf> = None;
+    let mut socket: Option<PathBuf> = None;
+
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--load-dir" => load_dir = Some(args.next().ok_or(USAGE)?.into()),
+            "--socket" => socket = Some(args.next().ok_or(USAGE)?.into()),
+            "-h" | "--help" => {
+                println!("{USAGE}");
+                return Ok(());
+            }
+            _ => return Err(format!("unknown argument '{arg}'\n{USAGE}").into()),
+        }
+    }
+
+    match socket {
+        Some(socket) => serve_socket(socket, load_dir),
+        None => {
+            let mut host =
+                ShioriHost::<PastaShiori>::new().with_after_load(redirect_print_to_stderr);
+            if let Some(dir) = &load_dir
+                && !host.load(dir)?
+            {
+                return Err(format!("failed to load '{}'", dir.display()).into());
+            }
+            host.serve(&mut io::stdin().lock(), &mut io::stdout().lock())?;
+            Ok(())
+        }
+    }
+}
+
+#[cfg(unix)]
+fn serve_socket(
+    socket: PathBuf,
+    load_dir: Option<PathBuf>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    pasta::host::serve_unix_socket(&socket, load_dir.as_deref(), ShioriHost::<PastaShiori>::new)?;
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn serve_socket(
+    _socket: PathBuf,
+    _load_dir: Option<PathBuf>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    Err("--socket is only supported on Unix".into())
+}
@@ -0,0 +1,97 @@
+//! Portable C ABI
+//!
+//! HGLOBAL を使わないプラットフォーム非依存の SHIORI エントリポイント。
+//! Linux / macOS のホストから `libpasta.so` / `libpasta.dylib` をリンク
+//! （または dlopen）して利用する。C ヘッダーは `include/pasta.h`
+//! （cbindgen で生成、`tests/capi_header_test.rs` で同期を検証）。
+//!
+//! - `pasta_load(dir, len)`: UTF-8（Unix では任意のバイト列）のロードディレクトリで load
+//! - `pasta_request(buf, len, out_len)`: リクエストを処理し、レスポンスバッファを返す
+//! - `pasta_free(buf, len)`: `pasta_request` が返したバッファを解放
+//! - `pasta_unload()`: unload
+//!
+//! インスタンスは Windows の `load`/`request`/`unload` と共有する。
+
+use crate::raw::*;
+use std::ffi::OsStr;
+use std::ptr;
+use std::slice;
+
+fn shiori() -> &'static RawShiori<crate::shiori::PastaShiori> {
+    SHIORI.get_or_init(|| RawShiori::new(0))
+}
+
+/// Load the SHIORI from `dir` (`len` bytes, not NUL-terminated).
+///
+/// Returns `true` when the ghost was loaded successfully.
+///
+/// # Safety
+/// `dir` must point to `len` readable bytes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn pasta_load(dir: *const u8, len: usize) -> bool {
+    if dir.is_null() {
+        return false;
+    }
+    // SAFETY: 呼び出し側が dir から len バイトの読み出しを保証する
+    let bytes = unsafe { slice::from_raw_parts(dir, len) };
+    #[cfg(unix)]
+    let dir = {
+        use std::os::unix::ffi::OsStrExt;
+        OsStr::from_bytes(bytes)
+    };
+    #[cfg(not(unix))]
+    let dir = match std::str::from_utf8(bytes) {
+        Ok(dir) => OsStr::new(dir),
+        Err(e) => {
+            tracing::error!("[pasta_shiori::pasta_load] {e}");
+            return false;
+        }
+    };
+    shiori().load(dir)
+}
+
+/// Process a SHIORI request of `len` bytes.
+///
+/// Returns a newly allocated response buffer and stores its length in
+/// `out_len`. The buffer must be released with `pasta_free`.
+/// Returns NULL (and `*out_len = 0`) when `buf` or `out_len` is NULL.
+///
+/// # Safety
+/// `buf` must point to `len` readable bytes and `out_len` must be writable.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn pasta_request(buf: *const u8, len: usize, out_len: *mut usize) -> *mut u8 {
+    if out_len.is_null() {
+        return ptr::null_mut();
+    }
+    if buf.is_null() {
+        // SAFETY: out_len は null チェック済み
+        unsafe { *out_len = 0 };
+        return ptr::null_mut();
+    }
+    // SAFETY: 呼び出し側が buf から len バイトの読み出しを保証する
+    let req = unsafe { slice::from_raw_parts(buf, len) };
+    let res = shiori().request(req).into_boxed_slice();
+    // SAFETY: out_len は null チェック済み
+    unsafe { *out_len = res.len() };
+    Box::into_raw(res).cast::<u8>()
+}
+
+/// Release a buffer returned by `pasta_request`.
+///
+/// # Safety
+/// `buf` and `len` must be exactly the pointer and length returned by
+/// `pasta_request`, and the buffer must not be freed twice.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn pasta_free(buf: *mut u8, len: usize) {
+    if buf.is_null() {
+        return;
+    }
+    // SAFETY: pasta_request が Box<[u8]> から作ったポインタと長さ
+    drop(unsafe { Box::from_raw(ptr::slice_from_raw_parts_mut(buf, len)) });
+}
+
+/// Unload the SHIORI. Safe to call when nothing is loaded.
+#[unsafe(no_mangle)]
+pub extern "C" fn pasta_unload() -> bool {
+    shiori().unload()
+}
@@ -5,7 +5,7 @@ use thiserror::Error;
 
 pub type MyResult<T> = Result<T, MyError>;
 
-#[derive(Clone, Eq, PartialEq, Debug, Error)]
+#[derive(Debug, Error)]
 pub enum MyError {
     #[error("others error")]
     #[allow(dead_code)]
@@ -30,6 +30,12 @@ pub enum MyError {
 
     #[error("script error: {}", message)]
     Script { message: String },
+
+    #[error("invalid SHIORI response: {0}")]
+    InvalidResponse(String),
+
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
 }
 
 impl From<parsers::req::ParseError> for MyError {
@@ -43,6 +49,7 @@ impl<G> From<PoisonError<G>> for MyError {
         MyError::Poison
     }
 }
+
 impl From<Utf8Error> for MyError {
     fn from(error: Utf8Error) -> MyError {
         MyError::EncodeUtf8(error)
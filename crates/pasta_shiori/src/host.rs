@@ -0,0 +1,482 @@
+//! SHIORI host mode over byte streams (stdio / Unix domain socket).
+//!
+//! Windows 以外のベースウェア（ninix 系）や他言語のテストハーネスから
+//! [`Shiori`] 実装を駆動するためのホスト。SHIORI のリクエスト／レスポンスを
+//! そのままストリームに流す。
+//!
+//! # フレーミング
+//!
+//! 1フレーム = 空行（`\r\n\r\n` または `\n\n`）で終わるヘッダーブロック。
+//! 先頭行のメソッドで振り分ける。
+//!
+//! - `LOAD SHIORI/3.0` + `Directory: <path>`: load（ロード済みなら unload して再ロード）
+//! - `UNLOAD SHIORI/3.0`: unload してセッション終了
+//! - `GET` / `NOTIFY` / `TEACH`（SHIORI/2.x, 3.0）: request
+//!
+//! LOAD / UNLOAD には `SHIORI/3.0 200 OK`（失敗時は 500）で応答する。
+//! 入力が EOF に達した場合も unload してからセッションを終了する。
+
+use crate::error::*;
+use crate::shiori::{PastaShiori, Shiori};
+use std::io::{self, BufRead, Write};
+use std::path::{Path, PathBuf};
+use tracing::{debug, info, warn};
+
+/// ロード直後に呼ばれるフック
+pub type AfterLoad<S> = Box<dyn FnMut(&mut S)>;
+
+/// ストリーム上で [`Shiori`] を駆動するホスト
+pub struct ShioriHost<S: Shiori + Default> {
+    shiori: Option<S>,
+    after_load: Option<AfterLoad<S>>,
+}
+
+impl<S: Shiori + Default> Default for ShioriHost<S> {
+    fn default() -> Self {
+        ShioriHost {
+            shiori: None,
+            after_load: None,
+        }
+    }
+}
+
+impl<S: Shiori + Default> ShioriHost<S> {
+    /// 未ロード状態のホストを作成します。
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// ロード成功直後に呼ばれるフックを設定します。
+    /// stdio モードで Lua の print を stdout から逃がす用途などに使います。
+    pub fn with_after_load<F: FnMut(&mut S) + 'static>(mut self, f: F) -> Self {
+        self.after_load = Some(Box::new(f));
+        self
+    }
+
+    /// ロード済みかどうか
+    pub fn is_loaded(&self) -> bool {
+        self.shiori.is_some()
+    }
+
+    /// ロード済みの SHIORI への参照
+    pub fn shiori(&self) -> Option<&S> {
+        self.shiori.as_ref()
+    }
+
+    /// 指定ディレクトリで SHIORI をロードします。既存のインスタンスは先に unload します。
+    pub fn load<P: AsRef<Path>>(&mut self, load_dir: P) -> MyResult<bool> {
+        self.unload();
+        let mut shiori = S::default();
+        let loaded = shiori.load(0, load_dir.as_ref().as_os_str())?;
+        if loaded {
+            if let Some(after_load) = self.after_load.as_mut() {
+                after_load(&mut shiori);
+            }
+            self.shiori = Some(shiori);
+        }
+        Ok(loaded)
+    }
+
+    /// SHIORI を unload します（インスタンスの Drop で SHIORI.unload が呼ばれる）。
+    pub fn unload(&mut self) {
+        if self.shiori.take().is_some() {
+            info!("SHIORI host unloaded");
+        }
+    }
+
+    /// EOF または UNLOAD フレームまでリクエストを処理します。
+    /// 終了時は必ず unload します。
+    pub fn serve<R: BufRead, W: Write>(&mut self, reader: &mut R, writer: &mut W) -> MyResult<()> {
+        let result = self.serve_frames(reader, writer);
+        self.unload();
+        result
+    }
+
+    fn serve_frames<R: BufRead, W: Write>(
+        &mut self,
+        reader: &mut R,
+        writer: &mut W,
+    ) -> MyResult<()> {
+        while let Some(frame) = read_frame(reader)? {
+            let (response, end) = self.handle_frame(&frame);
+            writer.write_all(&response)?;
+            writer.flush()?;
+            if end {
+                break;
+            }
+        }
+        debug!("SHIORI host session finished");
+        Ok(())
+    }
+
+    /// 1フレームを処理し、(応答, セッション終了フラグ) を返します。
+    pub fn handle_frame(&mut self, frame: &[u8]) -> (Vec<u8>, bool) {
+        let first_line = frame
+            .split(|&b| b == b'\n')
+            .next()
+            .map(|line| line.strip_suffix(b"\r").unwrap_or(line))
+            .unwrap_or_default();
+
+        if first_line.starts_with(b"LOAD ") {
+            let response = match frame_header(frame, "Directory") {
+                Some(dir) => match self.load(PathBuf::from(dir)) {
+                    Ok(true) => control_response(None),
+                    Ok(false) => control_response(Some("load failed")),
+                    Err(e) => control_response(Some(&e.to_string())),
+                },
+                None => control_response(Some("Directory header is required")),
+            };
+            (response, false)
+        } else if first_line.starts_with(b"UNLOAD ") {
+            self.unload();
+            (control_response(None), true)
+        } else {
+            let result = match self.shiori.as_mut() {
+                Some(shiori) => shiori.request_bytes(frame),
+                None => Err(MyError::NotInitialized),
+            };
+            let response = result.unwrap_or_else(|e| {
+                warn!(error = %e, "SHIORI host request failed");
+                e.to_shiori_response().into_bytes()
+            });
+            (response, false)
+        }
+    }
+}
+
+/// 空行で終わる1フレームを読み込みます。
+/// EOF なら None を返します（途中で切れたフレームは破棄）。
+pub fn read_frame<R: BufRead>(reader: &mut R) -> io::Result<Option<Vec<u8>>> {
+    let mut frame = Vec::new();
+    let mut line = Vec::new();
+    loop {
+        line.clear();
+        if reader.read_until(b'\n', &mut line)? == 0 {
+            if !frame.is_empty() {
+                warn!("SHIORI host input ended in the middle of a frame");
+            }
+            return Ok(None);
+        }
+        // フレーム間の余分な空行は読み飛ばす
+        if frame.is_empty() && (line == b"\r\n" || line == b"\n") {
+            continue;
+        }
+        frame.extend_from_slice(&line);
+        if line == b"\r\n" || line == b"\n" {
+            return Ok(Some(frame));
+        }
+    }
+}
+
+/// フレームからヘッダー値を取り出します（制御フレーム用、UTF-8 とみなす）。
+fn frame_header(frame: &[u8], name: &str) -> Option<String> {
+    let text = String::from_utf8_lossy(frame);
+    text.lines().skip(1).find_map(|line| {
+        let (key, value) = line.split_once(": ")?;
+        (key == name).then(|| value.to_string())
+    })
+}
+
+/// LOAD / UNLOAD への応答
+fn control_response(error: Option<&str>) -> Vec<u8> {
+    match error {
+        None => b"SHIORI/3.0 200 OK\r\nCharset: UTF-8\r\n\r\n".to_vec(),
+        Some(reason) => format!(
+            "SHIORI/3.0 500 Internal Server Error\r\n\
+             Charset: UTF-8\r\n\
+             X-ERROR-REASON: {}\r\n\
+             \r\n",
+            reason.replace(['\r', '\n'], " ")
+        )
+        .into_bytes(),
+    }
+}
+
+/// Unix ドメインソケットで待ち受け、接続ごとに1セッションを処理します。
+/// 接続は順番に処理し、各セッションは新しい [`ShioriHost`] で開始します
+/// （`load_dir` 指定時は接続直後にロード済みの状態で開始）。
+/// `socket_path` に残っている古いソケットは削除し、ソケット以外のファイルがあればエラーにします。
+#[cfg(unix)]
+pub fn serve_unix_socket<S, F>(
+    socket_path: &Path,
+    load_dir: Option<&Path>,
+    mut make_host: F,
+) -> MyResult<()>
+where
+    S: Shiori + Default,
+    F: FnMut() -> ShioriHost<S>,
+{
+    use std::io::BufReader;
+    use std::os::unix::fs::FileTypeExt;
+    use std::os::unix::net::UnixListener;
+
+    // 前回の起動が残したソケットだけを削除する（指定ミスで通常ファイルを消さない）
+    match std::fs::symlink_metadata(socket_path) {
+        Ok(meta) if meta.file_type().is_socket() => std::fs::remove_file(socket_path)?,
+        Ok(_) => {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::AlreadyExists,
+                format!("{} exists and is not a socket", socket_path.display()),
+            )
+            .into());
+        }
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+        Err(e) => return Err(e.into()),
+    }
+    let listener = UnixListener::bind(socket_path)?;
+    info!(path = %socket_path.display(), "SHIORI host listening");
+    for stream in listener.incoming() {
+        // 1接続の失敗でサーバーを止めない
+        let stream = match stream {
+            Ok(stream) => stream,
+            Err(e) => {
+                warn!(error = %e, "SHIORI host failed to accept connection");
+                continue;
+            }
+        };
+        let mut host = make_host();
+        if let Some(dir) = load_dir {
+            match host.load(dir) {
+                Ok(true) => {}
+                Ok(false) => warn!(load_dir = %dir.display(), "SHIORI host failed to load"),
+                Err(e) => {
+                    warn!(error = %e, load_dir = %dir.display(), "SHIORI host failed to load")
+                }
+            }
+        }
+        let reader_stream = match stream.try_clone() {
+            Ok(reader_stream) => reader_stream,
+            Err(e) => {
+                warn!(error = %e, "SHIORI host failed to set up connection");
+                continue;
+            }
+        };
+        let mut reader = BufReader::new(reader_stream);
+        let mut writer = stream;
+        if let Err(e) = host.serve(&mut reader, &mut writer) {
+            warn!(error = %e, "SHIORI host session aborted");
+        }
+    }
+    Ok(())
+}
+
+/// Lua の `print` を stderr に向け直します。
+/// stdio モードでは stdout がプロトコル用のため、スクリプトの出力で応答が壊れないようにします。
+pub fn redirect_print_to_stderr(shiori: &mut PastaShiori) {
+    let Some(runtime) = shiori.runtime() else {
+        return;
+    };
+    let lua = runtime.lua();
+    let print = lua.create_function(|_, args: pasta_lua::mlua::MultiValue| {
+        let texts = args
+            .iter()
+            .map(|v| v.to_string())
+            .collect::<pasta_lua::mlua::Result<Vec<_>>>()?;
+        eprintln!("{}", texts.join("\t"));
+        Ok(())
+    });
+    if let Err(e) = print.and_then(|f| lua.globals().set("print", f)) {
+        warn!(error = %e, "Failed to redirect Lua print to stderr");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::ffi::OsStr;
+    use std::io::Cursor;
+
+    thread_local! {
+        static EVENTS: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
+    }
+
+    fn events() -> Vec<String> {
+        EVENTS.with(|e| e.borrow().clone())
+    }
+
+    fn record(event: String) {
+        EVENTS.with(|e| e.borrow_mut().push(event));
+    }
+
+    /// イベントを記録するだけのテスト用 SHIORI
+    #[derive(Default)]
+    struct EchoShiori;
+
+    impl Shiori for EchoShiori {
+        fn load<S: AsRef<OsStr>>(&mut self, _hinst: isize, load_dir: S) -> MyResult<bool> {
+            let dir = load_dir.as_ref().to_string_lossy().to_string();
+            record(format!("load {dir}"));
+            if dir == "broken" {
+                return Err(MyError::Load(dir));
+            }
+            Ok(dir != "missing")
+        }
+
+        fn request<S: AsRef<str>>(&mut self, request: S) -> MyResult<String> {
+            let first = request
+                .as_ref()
+                .lines()
+                .next()
+                .unwrap_or_default()
+                .to_string();
+            record(format!("request {first}"));
+            Ok(format!("SHIORI/3.0 200 OK\r\nValue: {first}\r\n\r\n"))
+        }
+    }
+
+    impl Drop for EchoShiori {
+        fn drop(&mut self) {
+            record("unload".into());
+        }
+    }
+
+    fn run(input: &str) -> String {
+        let mut host = ShioriHost::<EchoShiori>::new();
+        let mut reader = Cursor::new(input.as_bytes().to_vec());
+        let mut output = Vec::new();
+        host.serve(&mut reader, &mut output).unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn read_frame_splits_on_blank_lines() {
+        let mut reader =
+            Cursor::new(b"\r\nGET SHIORI/3.0\r\nID: a\r\n\r\nNOTIFY SHIORI/3.0\n\nGET".to_vec());
+        assert_eq!(
+            read_frame(&mut reader).unwrap().unwrap(),
+            b"GET SHIORI/3.0\r\nID: a\r\n\r\n"
+        );
+        assert_eq!(
+            read_frame(&mut reader).unwrap().unwrap(),
+            b"NOTIFY SHIORI/3.0\n\n"
+        );
+        assert_eq!(read_frame(&mut reader).unwrap(), None);
+    }
+
+    #[test]
+    fn load_request_and_unload_on_eof() {
+        let output = run("LOAD SHIORI/3.0\r\nDirectory: ghost\r\n\r\n\
+             GET SHIORI/3.0\r\nID: OnBoot\r\n\r\n");
+        assert_eq!(
+            output,
+            "SHIORI/3.0 200 OK\r\nCharset: UTF-8\r\n\r\n\
+             SHIORI/3.0 200 OK\r\nValue: GET SHIORI/3.0\r\n\r\n"
+        );
+        assert_eq!(events(), ["load ghost", "request GET SHIORI/3.0", "unload"]);
+    }
+
+    #[test]
+    fn unload_frame_ends_session() {
+        let output = run("LOAD SHIORI/3.0\r\nDirectory: ghost\r\n\r\n\
+             UNLOAD SHIORI/3.0\r\n\r\n\
+             GET SHIORI/3.0\r\nID: OnBoot\r\n\r\n");
+        assert_eq!(output.matches("200 OK").count(), 2);
+        assert_eq!(events(), ["load ghost", "unload"]);
+    }
+
+    #[test]
+    fn request_before_load_returns_error_response() {
+        let output = run("GET SHIORI/3.0\r\nID: OnBoot\r\n\r\n");
+        assert!(
+            output.starts_with("SHIORI/3.0 500 Internal Server Error\r\n"),
+            "{output}"
+        );
+        assert!(events().is_empty());
+    }
+
+    #[test]
+    fn load_failures_are_reported() {
+        let output = run("LOAD SHIORI/3.0\r\nDirectory: missing\r\n\r\n\
+             LOAD SHIORI/3.0\r\n\r\n");
+        assert_eq!(
+            output.matches("500 Internal Server Error").count(),
+            2,
+            "{output}"
+        );
+        assert!(output.contains("X-ERROR-REASON: load failed\r\n"));
+        assert!(output.contains("X-ERROR-REASON: Directory header is required\r\n"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn unix_socket_server_survives_load_errors() {
+        use std::io::Read;
+        use std::os::unix::net::UnixStream;
+
+        let temp = tempfile::TempDir::new().unwrap();
+        let socket_path = temp.path().join("shiori.sock");
+        let server_path = socket_path.clone();
+        std::thread::spawn(move || {
+            serve_unix_socket(
+                &server_path,
+                Some(Path::new("broken")),
+                ShioriHost::<EchoShiori>::new,
+            )
+        });
+
+        // ロードに失敗しても接続ごとに応答を返し続ける
+        for _ in 0..2 {
+            let mut stream = (0..200)
+                .find_map(|_| {
+                    UnixStream::connect(&socket_path)
+                        .inspect_err(|_| std::thread::sleep(std::time::Duration::from_millis(10)))
+                        .ok()
+                })
+                .expect("server should accept connections");
+            stream
+                .write_all(b"GET SHIORI/3.0\r\nID: OnBoot\r\n\r\n")
+                .unwrap();
+            stream.shutdown(std::net::Shutdown::Write).unwrap();
+            let mut output = String::new();
+            stream.read_to_string(&mut output).unwrap();
+            assert!(
+                output.starts_with("SHIORI/3.0 500 Internal Server Error\r\n"),
+                "{output}"
+            );
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn unix_socket_server_keeps_non_socket_files() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let path = temp.path().join("pasta.toml");
+        std::fs::write(&path, "keep").unwrap();
+
+        let err = serve_unix_socket(&path, None, ShioriHost::<EchoShiori>::new).unwrap_err();
+        assert!(
+            matches!(err, MyError::Io(ref e) if e.kind() == std::io::ErrorKind::AlreadyExists),
+            "{err}"
+        );
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "keep");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn unix_socket_server_replaces_stale_socket() {
+        use std::os::unix::net::{UnixListener, UnixStream};
+
+        let temp = tempfile::TempDir::new().unwrap();
+        let socket_path = temp.path().join("shiori.sock");
+        // 前回の起動が残したソケット
+        drop(UnixListener::bind(&socket_path).unwrap());
+
+        let server_path = socket_path.clone();
+        std::thread::spawn(move || {
+            serve_unix_socket(&server_path, None, ShioriHost::<EchoShiori>::new)
+        });
+        let connected = (0..200).any(|_| {
+            UnixStream::connect(&socket_path)
+                .inspect_err(|_| std::thread::sleep(std::time::Duration::from_millis(10)))
+                .is_ok()
+        });
+        assert!(connected);
+    }
+
+    #[test]
+    fn reload_unloads_previous_instance() {
+        run("LOAD SHIORI/3.0\r\nDirectory: a\r\n\r\nLOAD SHIORI/3.0\r\nDirectory: b\r\n\r\n");
+        assert_eq!(events(), ["load a", "unload", "load b", "unload"]);
+    }
+}
@@ -1,9 +1,14 @@
 //! pasta_shiori - SHIORI DLL interface for pasta script engine
 //!
-//! This crate provides the SHIORI protocol interface as a Windows DLL.
+//! This crate provides the SHIORI protocol interface as a Windows DLL,
+//! a portable C ABI (`pasta_load` / `pasta_request` / `pasta_free` / `pasta_unload`),
+//! and a stream host (`host`) for non-Windows baseware and test harnesses.
 
+mod capi;
 mod error;
+pub mod host;
 pub mod lua_request;
+mod raw;
 mod shiori;
 mod util;
 
@@ -12,3 +17,4 @@ mod windows;
 
 // Re-export for integration tests
 pub use shiori::{PastaShiori, Shiori};
+pub use util::res::ShioriResponse;
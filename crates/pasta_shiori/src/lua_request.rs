@@ -37,10 +37,11 @@ pub fn lua_date(lua: &Lua) -> MyResult<Table> {
 }
 
 /// SHIORI REQUESTを解析し、luaオブジェクトに展開します。
-/// * req.method: get / notify
-/// * req.version: 30であること
+/// * req.method: get / notify / teach（teachはSHIORI/2.xのみ）
+/// * req.version: SHIORI/3.0は30、SHIORI/2.xは20～29
 /// * req.charset: utf-8であること
-/// * req.id: event id
+/// * req.id: event id（SHIORI/2.xはイベント名に読み替え済み）
+/// * req.command: SHIORI/2.xのリクエスト種別（"Sentence", "String" 等）
 /// * req.base_id:
 /// * req.status:
 /// * req.security_level:
@@ -52,11 +53,80 @@ pub fn parse_request(lua: &Lua, text: &str) -> MyResult<Table> {
     t.set("reference", lua.create_table()?)?;
     t.set("dic", lua.create_table()?)?;
     t.set("date", lua_date(lua)?)?;
-    let it = Parser::parse(Rule::req, text)?.flatten();
-    parse1(&t, it)?;
+    let pairs = Parser::parse(Rule::req, text)?;
+    parse1(&t, pairs.clone().flatten())?;
+    let version: i32 = t.get("version")?;
+    if version < 30 {
+        map_shiori2(&t, pairs.flatten())?;
+    }
     Ok(t)
 }
 
+/// SHIORI/2.xのリクエストをSHIORI/3.0相当のイベントに読み替えます。
+/// 元のリクエスト種別は req.command に残します。
+/// * GET Version → "version"
+/// * GET Sentence（Eventヘッダーあり）→ Eventの値
+/// * GET Sentence（Sentenceヘッダーあり）→ "OnCommunicate"（reference0=Sender, reference1=Sentence）
+/// * GET Sentence（上記以外）→ "OnTalk"
+/// * GET String → IDヘッダーの値
+/// * NOTIFY OwnerGhostName / OtherGhostName → "ownerghostname" / "otherghostname"（Ghostヘッダーを順にreferenceへ）
+/// * TEACH → "OnTeach"（reference0=Word）
+fn map_shiori2(table: &Table, it: FlatPairs<'_, Rule>) -> MyResult<()> {
+    let command: Option<String> = table.get("command")?;
+    let dic: Table = table.get("dic")?;
+    let reference: Table = table.get("reference")?;
+    let method: String = table.get("method")?;
+
+    let id = match (method.as_str(), command.as_deref()) {
+        ("teach", _) => {
+            if let Some(word) = dic.get::<Option<String>>("Word")? {
+                set_reference_if_absent(&reference, 0, &word)?;
+            }
+            Some("OnTeach".to_string())
+        }
+        ("get", Some("Version")) => Some("version".to_string()),
+        ("get", Some("Sentence")) => {
+            if let Some(event) = dic.get::<Option<String>>("Event")? {
+                Some(event)
+            } else if let Some(sentence) = dic.get::<Option<String>>("Sentence")? {
+                if let Some(sender) = table.get::<Option<String>>("sender")? {
+                    set_reference_if_absent(&reference, 0, &sender)?;
+                }
+                set_reference_if_absent(&reference, 1, &sentence)?;
+                Some("OnCommunicate".to_string())
+            } else {
+                Some("OnTalk".to_string())
+            }
+        }
+        ("notify", Some(name @ ("OwnerGhostName" | "OtherGhostName"))) => {
+            let ghosts = it
+                .filter(|pair| pair.as_rule() == Rule::key_value)
+                .filter_map(|pair| {
+                    let mut inner = pair.into_inner();
+                    let key = inner.next()?;
+                    let value = inner.next()?;
+                    (key.as_str() == "Ghost").then(|| value.as_str())
+                });
+            for (i, ghost) in ghosts.enumerate() {
+                set_reference_if_absent(&reference, i as i32, ghost)?;
+            }
+            Some(name.to_lowercase())
+        }
+        _ => None,
+    };
+    if let Some(id) = id {
+        table.set("id", id)?;
+    }
+    Ok(())
+}
+
+fn set_reference_if_absent(reference: &Table, index: i32, value: &str) -> MyResult<()> {
+    if !reference.contains_key(index)? {
+        reference.set(index, value)?;
+    }
+    Ok(())
+}
+
 fn parse1(table: &Table, mut it: FlatPairs<'_, Rule>) -> MyResult<()> {
     let pair = match it.next() {
         Some(a) => a,
@@ -67,8 +137,12 @@ fn parse1(table: &Table, mut it: FlatPairs<'_, Rule>) -> MyResult<()> {
         Rule::key_value => parse_key_value(table, &mut it)?,
         Rule::get => table.set("method", "get")?,
         Rule::notify => table.set("method", "notify")?,
+        Rule::teach => table.set("method", "teach")?,
         Rule::header3 => table.set("version", 30)?,
-        Rule::shiori2_id => table.set("id", pair.as_str())?,
+        Rule::shiori2_id => {
+            table.set("id", pair.as_str())?;
+            table.set("command", pair.as_str())?
+        }
         Rule::shiori2_ver => {
             let version = {
                 let nums: i32 = pair.as_str().parse().unwrap();
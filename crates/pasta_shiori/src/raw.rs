@@ -0,0 +1,80 @@
+//! Platform-neutral SHIORI instance holder
+//!
+//! Windows DLL エクスポート（`windows.rs`）と C ABI（`capi.rs`）が共有する
+//! プロセス単位の SHIORI インスタンス。入出力はバイト列で扱い、
+//! HGLOBAL などのプラットフォーム固有の変換は各エクスポート側で行う。
+
+use crate::error::*;
+use crate::shiori::*;
+use std::ffi::OsStr;
+use std::sync::*;
+use tracing::*;
+
+/// プロセス内で共有する SHIORI インスタンス
+pub(crate) static SHIORI: OnceLock<RawShiori<PastaShiori>> = OnceLock::new();
+
+pub(crate) struct RawShiori<T: Shiori + Default + Sized>(isize, Arc<Mutex<Option<T>>>);
+
+impl<T: Shiori + Default + Sized> RawShiori<T> {
+    pub(crate) fn new(hinst: isize) -> Self {
+        // Note: tracing subscriber is NOT initialized here.
+        // It is deferred to PastaShiori::load() after pasta.toml is read,
+        // so that [logging] configuration can be applied (Requirement 6).
+        RawShiori(hinst, Arc::new(Mutex::new(None)))
+    }
+
+    pub(crate) fn unload(&self) -> bool {
+        if let Err(e) = self.unload_impl() {
+            error!("[pasta_shiori::unload] {e}");
+        }
+        true
+    }
+
+    pub(crate) fn load(&self, dir: &OsStr) -> bool {
+        match self.load_impl(dir) {
+            Ok(ret) => ret,
+            Err(e) => {
+                error!("[pasta_shiori::load] {e}");
+                false
+            }
+        }
+    }
+
+    /// リクエストを処理し、レスポンスのバイト列を返します。
+    /// エラー時は 500 レスポンスを返します。
+    pub(crate) fn request(&self, req: &[u8]) -> Vec<u8> {
+        match self.request_impl(req) {
+            Ok(res) => res,
+            Err(e) => {
+                error!("[pasta_shiori::request] {e}");
+                e.to_shiori_response().into_bytes()
+            }
+        }
+    }
+}
+
+impl<T: Shiori + Default + Sized> RawShiori<T> {
+    fn unload_impl(&self) -> MyResult<bool> {
+        let mut guard = self.1.lock()?;
+        *guard = None;
+        Ok(true)
+    }
+
+    fn load_impl(&self, dir: &OsStr) -> MyResult<bool> {
+        let hinst = self.0;
+        let mut guard = self.1.lock()?;
+        *guard = None;
+        let mut shiori = T::default();
+        let rc = shiori.load(hinst, dir)?;
+        *guard = Some(shiori);
+        Ok(rc)
+    }
+
+    fn request_impl(&self, req: &[u8]) -> MyResult<Vec<u8>> {
+        let mut guard = self.1.lock()?;
+        match *guard {
+            None => Err(MyError::NotInitialized),
+            Some(ref mut shiori) => shiori.request_bytes(req),
+        }
+    }
+}
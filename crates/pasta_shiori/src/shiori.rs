@@ -1,7 +1,9 @@
 use crate::error::*;
 use crate::lua_request;
+use crate::util::charset::Charset;
+use crate::util::res::ShioriResponse;
 use pasta_lua::loader::LoggingConfig;
-use pasta_lua::mlua::{Function, Table};
+use pasta_lua::mlua::{Function, Lua, Table, Value};
 use pasta_lua::{GlobalLoggerRegistry, LoadDirGuard, PastaLoader, PastaLuaRuntime};
 use std::{ffi::*, path::*};
 use tracing::{debug, error, info, trace, warn};
@@ -49,6 +51,17 @@ pub fn init_tracing_with_config(config: &LoggingConfig) {
 pub trait Shiori {
     fn load<S: AsRef<OsStr>>(&mut self, hinst: isize, load_dir: S) -> MyResult<bool>;
     fn request<S: AsRef<str>>(&mut self, request: S) -> MyResult<String>;
+
+    /// Process a raw request in the charset declared by its `Charset` header.
+    ///
+    /// Supports UTF-8, Shift_JIS/Windows-31J and EUC-JP; the response is
+    /// encoded back into the same charset. Unknown charsets fall back to UTF-8.
+    fn request_bytes(&mut self, request: &[u8]) -> MyResult<Vec<u8>> {
+        let charset = Charset::sniff(request);
+        let request = charset.decode(request);
+        let response = self.request(request)?;
+        Ok(charset.encode(&response))
+    }
 }
 
 /// PastaShiori - SHIORI implementation using pasta_lua engine.
@@ -308,6 +321,9 @@ impl PastaShiori {
     /// Parses request text and passes parsed table to Lua.
     /// Returns 204 response if function doesn't exist.
     /// Returns 400 Bad Request if request parsing fails.
+    ///
+    /// SHIORI.request may return either a raw response string (passed through
+    /// as-is) or a table that is validated and serialized via [`ShioriResponse`].
     fn call_lua_request(&self, request: &str) -> MyResult<String> {
         // Use cached request_fn directly
         let request_fn = match &self.request_fn {
@@ -331,11 +347,23 @@ impl PastaShiori {
             }
         };
 
+        // Unsupported charsets are answered in UTF-8 (see Shiori::request_bytes)
+        if let Ok(Some(label)) = req_table.get::<Option<String>>("charset")
+            && Charset::from_label(&label).is_none()
+        {
+            let _ = req_table.set("charset", Charset::Utf8.name());
+        }
+
         // Call SHIORI.request(req) with parsed table
-        match request_fn.call::<String>(req_table) {
-            Ok(response) => {
+        match request_fn.call::<Value>(req_table.clone()) {
+            Ok(value) => {
+                let response =
+                    Self::response_from_lua(lua, value, &req_table).inspect_err(|e| {
+                        error!(error = %e, "SHIORI.request returned an invalid response");
+                    })?;
                 // Log request/response at DEBUG level for non-204 responses
-                if !response.starts_with("SHIORI/3.0 204 No Content") {
+                let status_line = response.lines().next().unwrap_or_default();
+                if !status_line.ends_with(" 204 No Content") {
                     debug!(request = %request, "### SHIORI request ###\n");
                     debug!(response = %response, "### SHIORI response###\n");
                 }
@@ -349,6 +377,57 @@ impl PastaShiori {
         }
     }
 
+    /// Convert the value returned by SHIORI.request into response text.
+    ///
+    /// Table responses get the same leading standard headers as `RES.build`
+    /// (Charset, Sender and, for SHIORI/3.0, SecurityLevel).
+    fn response_from_lua(lua: &Lua, value: Value, req_table: &Table) -> MyResult<String> {
+        match value {
+            Value::String(s) => {
+                let response = s.to_str()?.to_string();
+                if !response.ends_with("\r\n\r\n") {
+                    warn!("SHIORI.request returned a response without a terminating blank line");
+                }
+                Ok(response)
+            }
+            Value::Table(table) => {
+                let version = req_table.get::<Option<i32>>("version")?.unwrap_or(30);
+                let mut response = ShioriResponse::from_table(&table, version)?;
+                let charset = req_table
+                    .get::<Option<String>>("charset")?
+                    .unwrap_or_else(|| Charset::Utf8.name().to_string());
+                // RES.env（pasta.shiori.res 未ロード時は同じ既定値）
+                let env = lua
+                    .globals()
+                    .get::<Table>("package")?
+                    .get::<Table>("loaded")?
+                    .get::<Option<Table>>("pasta.shiori.res")?
+                    .map(|res| res.get::<Option<Table>>("env"))
+                    .transpose()?
+                    .flatten();
+                let env_text = |key: &str, default: &str| -> MyResult<String> {
+                    Ok(match &env {
+                        Some(env) => env.get::<Option<String>>(key)?,
+                        None => None,
+                    }
+                    .unwrap_or_else(|| default.to_string()))
+                };
+                let sender = env_text("sender", "Pasta")?;
+                let security_level = env_text("security_level", "local")?;
+                let mut standard = vec![("Charset", charset.as_str()), ("Sender", sender.as_str())];
+                if response.version >= 30 {
+                    standard.push(("SecurityLevel", security_level.as_str()));
+                }
+                response.set_leading_headers(&standard)?;
+                Ok(response.to_string())
+            }
+            other => Err(MyError::InvalidResponse(format!(
+                "SHIORI.request must return a string or table, got {}",
+                other.type_name()
+            ))),
+        }
+    }
+
     /// Call SHIORI.unload function using cached function.
     /// Logs warning on error but does not propagate (safe for Drop).
     fn call_lua_unload(&self) {
@@ -1149,6 +1228,218 @@ end
         );
     }
 
+    /// Override entry.lua with a handler that answers through pasta.shiori.res.
+    fn write_res_echo_entry(temp: &TempDir) {
+        let entry_lua_path = temp.path().join("scripts/pasta/shiori/entry.lua");
+        std::fs::write(
+            &entry_lua_path,
+            r#"
+local RES = require "pasta.shiori.res"
+SHIORI = {}
+
+function SHIORI.load(hinst, load_dir)
+    return true
+end
+
+function SHIORI.request(req)
+    RES.set_protocol(req)
+    return RES.ok("こんにちは、" .. tostring(req.reference[0]))
+end
+
+function SHIORI.unload()
+end
+"#,
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_request_bytes_answers_in_request_charset() {
+        let temp = copy_shiori_lifecycle_fixture();
+        write_res_echo_entry(&temp);
+
+        let mut shiori = PastaShiori::default();
+        assert!(shiori.load(0, temp.path().as_os_str()).unwrap());
+
+        for label in ["Shift_JIS", "Windows-31J", "EUC-JP"] {
+            let charset = Charset::from_label(label).unwrap();
+            let request = format!(
+                "GET SHIORI/3.0\r\n\
+                 Charset: {label}\r\n\
+                 ID: OnBoot\r\n\
+                 Reference0: 世界\r\n\
+                 \r\n"
+            );
+            let response = shiori.request_bytes(&charset.encode(&request)).unwrap();
+
+            assert!(
+                std::str::from_utf8(&response).is_err(),
+                "{label} response should not be UTF-8"
+            );
+            let response = charset.decode(&response);
+            assert!(response.starts_with("SHIORI/3.0 200 OK\r\n"), "{response}");
+            assert!(
+                response.contains(&format!("Charset: {label}\r\n")),
+                "{response}"
+            );
+            assert!(
+                response.contains("Value: こんにちは、世界\r\n"),
+                "{response}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_request_bytes_unsupported_charset_falls_back_to_utf8() {
+        let temp = copy_shiori_lifecycle_fixture();
+        write_res_echo_entry(&temp);
+
+        let mut shiori = PastaShiori::default();
+        assert!(shiori.load(0, temp.path().as_os_str()).unwrap());
+
+        let request = "GET SHIORI/3.0\r\n\
+            Charset: ISO-2022-JP\r\n\
+            ID: OnBoot\r\n\
+            Reference0: 世界\r\n\
+            \r\n";
+        let response = shiori.request_bytes(request.as_bytes()).unwrap();
+        let response = String::from_utf8(response).unwrap();
+
+        assert!(response.contains("Charset: UTF-8\r\n"), "{response}");
+        assert!(
+            response.contains("Value: こんにちは、世界\r\n"),
+            "{response}"
+        );
+    }
+
+    /// Override entry.lua so that SHIORI.request returns the given Lua expression.
+    fn write_table_response_entry(temp: &TempDir, response_expr: &str) {
+        let entry_lua_path = temp.path().join("scripts/pasta/shiori/entry.lua");
+        std::fs::write(
+            &entry_lua_path,
+            format!(
+                r#"
+SHIORI = {{}}
+
+function SHIORI.load(hinst, load_dir)
+    return true
+end
+
+function SHIORI.request(req)
+    return {response_expr}
+end
+
+function SHIORI.unload()
+end
+"#
+            ),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_request_serializes_table_response() {
+        let temp = copy_shiori_lifecycle_fixture();
+        write_table_response_entry(
+            &temp,
+            r#"{ status = 200, value = "\\0" .. req.reference[0] .. "\\e", headers = { { "Reference0", "user" } } }"#,
+        );
+
+        let mut shiori = PastaShiori::default();
+        assert!(shiori.load(0, temp.path().as_os_str()).unwrap());
+
+        let request = "GET SHIORI/3.0\r\n\
+            Charset: Shift_JIS\r\n\
+            ID: OnCommunicate\r\n\
+            Reference0: こんにちは\r\n\
+            \r\n";
+        let response = shiori.request(request).unwrap();
+        // 標準ヘッダーは RES.build と同じく先頭に同じ順で出力する
+        assert_eq!(
+            response,
+            "SHIORI/3.0 200 OK\r\n\
+             Charset: Shift_JIS\r\n\
+             Sender: Pasta\r\n\
+             SecurityLevel: local\r\n\
+             Value: \\0こんにちは\\e\r\n\
+             Reference0: user\r\n\
+             \r\n"
+        );
+    }
+
+    #[test]
+    fn test_request_table_response_follows_shiori2_version() {
+        let temp = copy_shiori_lifecycle_fixture();
+        write_table_response_entry(&temp, r#"{ status = 200, value = "hi" }"#);
+
+        let mut shiori = PastaShiori::default();
+        assert!(shiori.load(0, temp.path().as_os_str()).unwrap());
+
+        let request = "GET Sentence SHIORI/2.2\r\n\
+            Charset: UTF-8\r\n\
+            \r\n";
+        let response = shiori.request(request).unwrap();
+        assert_eq!(
+            response,
+            "SHIORI/2.2 200 OK\r\nCharset: UTF-8\r\nSender: Pasta\r\nSentence: hi\r\n\r\n"
+        );
+    }
+
+    #[test]
+    fn test_request_table_response_uses_res_env_sender() {
+        let temp = copy_shiori_lifecycle_fixture();
+        write_table_response_entry(
+            &temp,
+            r#"(function()
+        require("pasta.shiori.res").env.sender = "MyGhost"
+        return { status = 204, headers = { Marker = "m", Charset = "UTF-8" } }
+    end)()"#,
+        );
+
+        let mut shiori = PastaShiori::default();
+        assert!(shiori.load(0, temp.path().as_os_str()).unwrap());
+
+        let request = "GET SHIORI/3.0\r\nCharset: Shift_JIS\r\nID: OnBoot\r\n\r\n";
+        let response = shiori.request(request).unwrap();
+        // テーブルで指定した Charset もそのまま先頭に移す
+        assert_eq!(
+            response,
+            "SHIORI/3.0 204 No Content\r\n\
+             Charset: UTF-8\r\n\
+             Sender: MyGhost\r\n\
+             SecurityLevel: local\r\n\
+             Marker: m\r\n\
+             \r\n"
+        );
+    }
+
+    #[test]
+    fn test_request_rejects_header_injection_in_table_response() {
+        let temp = copy_shiori_lifecycle_fixture();
+        write_table_response_entry(&temp, r#"{ status = 200, value = "hi\r\nX-Injected: 1" }"#);
+
+        let mut shiori = PastaShiori::default();
+        assert!(shiori.load(0, temp.path().as_os_str()).unwrap());
+
+        let request = "GET SHIORI/3.0\r\nCharset: UTF-8\r\nID: OnBoot\r\n\r\n";
+        let err = shiori.request(request).unwrap_err();
+        assert!(matches!(err, MyError::InvalidResponse(_)), "{err}");
+        assert!(err.to_shiori_response().starts_with("SHIORI/3.0 500"));
+    }
+
+    #[test]
+    fn test_request_rejects_non_string_non_table_response() {
+        let temp = copy_shiori_lifecycle_fixture();
+        write_table_response_entry(&temp, "42");
+
+        let mut shiori = PastaShiori::default();
+        assert!(shiori.load(0, temp.path().as_os_str()).unwrap());
+
+        let request = "GET SHIORI/3.0\r\nCharset: UTF-8\r\nID: OnBoot\r\n\r\n";
+        let err = shiori.request(request).unwrap_err();
+        assert!(matches!(err, MyError::InvalidResponse(_)), "{err}");
+    }
+
     /// Copy shiori_lifecycle fixture to a temporary directory.
     fn copy_shiori_lifecycle_fixture() -> TempDir {
         let src = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/shiori_lifecycle");
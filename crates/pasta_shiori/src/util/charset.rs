@@ -0,0 +1,148 @@
+//! SHIORI request/response charset handling.
+//!
+//! ベースウェアは `Charset` ヘッダーで宣言した文字コードでリクエストを送り、
+//! 同じ文字コードでのレスポンスを期待する。
+//! Windows API に依存せず encoding_rs で変換するため、全プラットフォームで動作する。
+
+use tracing::warn;
+
+/// SHIORI で扱う文字コード
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum Charset {
+    /// UTF-8（既定値）
+    #[default]
+    Utf8,
+    /// Shift_JIS / Windows-31J（CP932 として扱う）
+    ShiftJis,
+    /// EUC-JP
+    EucJp,
+}
+
+impl Charset {
+    /// Charset ヘッダーの値から文字コードを判定します。
+    /// 大文字小文字は区別しません。未対応のラベルは None を返します。
+    pub fn from_label(label: &str) -> Option<Charset> {
+        match label.trim().to_ascii_lowercase().as_str() {
+            "utf-8" | "utf8" => Some(Charset::Utf8),
+            "shift_jis" | "shift-jis" | "sjis" | "x-sjis" | "windows-31j" | "cp932" | "ms932"
+            | "ms_kanji" => Some(Charset::ShiftJis),
+            "euc-jp" | "euc_jp" | "x-euc-jp" => Some(Charset::EucJp),
+            _ => None,
+        }
+    }
+
+    /// リクエストの生バイト列から Charset ヘッダーを探して文字コードを判定します。
+    /// ヘッダーがない、または未対応の場合は UTF-8 とみなします。
+    ///
+    /// 対応する文字コードはいずれも CR/LF と ASCII 部分が共通のため、
+    /// デコード前の行単位走査でヘッダーを安全に取り出せます。
+    pub fn sniff(request: &[u8]) -> Charset {
+        const KEY: &[u8] = b"Charset: ";
+        let label = request
+            .split(|&b| b == b'\n')
+            .map(|line| line.strip_suffix(b"\r").unwrap_or(line))
+            .take_while(|line| !line.is_empty())
+            .find_map(|line| line.strip_prefix(KEY));
+        let Some(label) = label else {
+            return Charset::Utf8;
+        };
+        let label = String::from_utf8_lossy(label);
+        Charset::from_label(&label).unwrap_or_else(|| {
+            warn!(charset = %label, "Unsupported request charset, falling back to UTF-8");
+            Charset::Utf8
+        })
+    }
+
+    /// レスポンスの Charset ヘッダーに出力する正規名を返します。
+    pub fn name(&self) -> &'static str {
+        match self {
+            Charset::Utf8 => "UTF-8",
+            Charset::ShiftJis => "Shift_JIS",
+            Charset::EucJp => "EUC-JP",
+        }
+    }
+
+    fn encoding(&self) -> &'static encoding_rs::Encoding {
+        match self {
+            Charset::Utf8 => encoding_rs::UTF_8,
+            Charset::ShiftJis => encoding_rs::SHIFT_JIS,
+            Charset::EucJp => encoding_rs::EUC_JP,
+        }
+    }
+
+    /// バイト列を文字列にデコードします。
+    /// 不正なバイト列は置換文字に変換し、警告を記録します。
+    pub fn decode(&self, bytes: &[u8]) -> String {
+        let (text, had_errors) = self.encoding().decode_without_bom_handling(bytes);
+        if had_errors {
+            warn!(charset = self.name(), "Malformed bytes in SHIORI request");
+        }
+        text.into_owned()
+    }
+
+    /// 文字列をバイト列にエンコードします。
+    /// 表現できない文字は数値文字参照に変換し、警告を記録します。
+    pub fn encode(&self, text: &str) -> Vec<u8> {
+        let (bytes, _, had_errors) = self.encoding().encode(text);
+        if had_errors {
+            warn!(
+                charset = self.name(),
+                "Unmappable characters in SHIORI response"
+            );
+        }
+        bytes.into_owned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_label_accepts_aliases() {
+        assert_eq!(Charset::from_label("UTF-8"), Some(Charset::Utf8));
+        assert_eq!(Charset::from_label("Shift_JIS"), Some(Charset::ShiftJis));
+        assert_eq!(Charset::from_label("windows-31j"), Some(Charset::ShiftJis));
+        assert_eq!(Charset::from_label("CP932"), Some(Charset::ShiftJis));
+        assert_eq!(Charset::from_label("EUC-JP"), Some(Charset::EucJp));
+        assert_eq!(Charset::from_label("ISO-2022-JP"), None);
+    }
+
+    #[test]
+    fn sniff_finds_charset_header() {
+        let req = b"GET SHIORI/3.0\r\nCharset: Shift_JIS\r\nID: OnBoot\r\n\r\n";
+        assert_eq!(Charset::sniff(req), Charset::ShiftJis);
+    }
+
+    #[test]
+    fn sniff_defaults_to_utf8() {
+        assert_eq!(
+            Charset::sniff(b"GET SHIORI/3.0\r\nID: OnBoot\r\n\r\n"),
+            Charset::Utf8
+        );
+        assert_eq!(
+            Charset::sniff(b"GET SHIORI/3.0\r\nCharset: ISO-2022-JP\r\n\r\n"),
+            Charset::Utf8
+        );
+    }
+
+    #[test]
+    fn sniff_ignores_multibyte_values() {
+        // "表" の Shift_JIS 表現は 0x95 0x5C（2バイト目が '\'）
+        let mut req = b"GET SHIORI/3.0\r\nReference0: ".to_vec();
+        req.extend_from_slice(&[0x95, 0x5C]);
+        req.extend_from_slice(b"\r\nCharset: Windows-31J\r\n\r\n");
+        assert_eq!(Charset::sniff(&req), Charset::ShiftJis);
+        assert!(Charset::ShiftJis.decode(&req).contains("Reference0: 表"));
+    }
+
+    #[test]
+    fn roundtrip_japanese_text() {
+        let text = "こんにちは、世界";
+        for charset in [Charset::Utf8, Charset::ShiftJis, Charset::EucJp] {
+            let bytes = charset.encode(text);
+            assert_eq!(charset.decode(&bytes), text, "{:?}", charset);
+        }
+        assert_ne!(Charset::ShiftJis.encode(text), text.as_bytes());
+    }
+}
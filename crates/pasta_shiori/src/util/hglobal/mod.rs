@@ -61,7 +61,6 @@ impl ShioriString {
     /// HGLOBALを新たに作成し、&[u8]をShioriStringにクローンします。
     /// drop時にHGLOBALを開放しません。
     /// shiori応答の作成に利用してください。
-    #[allow(dead_code)]
     pub fn clone_from_slice_nofree(bytes: &[u8]) -> ShioriString {
         ShioriString::clone_from_slice_impl(bytes, false)
     }
@@ -129,7 +128,8 @@ impl ShioriString {
     }
 
     /// 格納データを「UTF-8」とみなして、strに変換する。
-    /// SHIORI::request()文字列の取り出しに利用する。
+    /// SHIORI::request()はCharsetヘッダーに従ってas_bytes()から変換するため、現在はテスト用。
+    #[allow(dead_code)]
     pub fn to_utf8_str(&self) -> MyResult<&str> {
         let bytes = self.as_bytes();
         Ok(str::from_utf8(bytes)?)
@@ -1,5 +1,6 @@
 //! Utility modules for pasta_shiori
 
+pub mod charset;
 pub mod parsers;
 pub mod res;
 
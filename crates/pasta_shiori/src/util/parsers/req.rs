@@ -63,6 +63,7 @@ impl<'a> ShioriRequest<'a> {
             Rule::key_value => self.parse_key_value(&mut it)?,
             Rule::get => self.method = rule,
             Rule::notify => self.method = rule,
+            Rule::teach => self.method = rule,
             Rule::header3 => self.version = 30,
             Rule::shiori2_id => self.id = Some(pair.as_str()),
             Rule::shiori2_ver => {
@@ -200,4 +201,36 @@ mod tests {
         assert_eq!(req.key_values.len(), 0);
         assert_eq!(req.reference.len(), 0);
     }
+
+    #[test]
+    fn req_4() {
+        let src = include_str!("test_data/shiori2-2.txt")
+            .replace("\r\n", "\n")
+            .replace("\r", "\n")
+            .replace("\n", "\r\n");
+        let grammar = src.as_str();
+
+        let req = ShioriRequest::parse(grammar).unwrap_or_else(|e| panic!("{}", e));
+
+        assert_eq!(req.version, 23);
+        assert_eq!(req.method, Rule::notify);
+        assert_eq!(req.id.unwrap(), "OtherGhostName");
+        assert_eq!(req.sender.unwrap(), "SSP");
+
+        // 同名ヘッダーは最初の値を保持する
+        assert_eq!(req.dic.len(), 3);
+        assert_eq!(req.dic["Ghost"], "双葉\u{1}0\u{1}10");
+        assert_eq!(req.reference.len(), 0);
+    }
+
+    #[test]
+    fn req_5() {
+        let req = ShioriRequest::parse("TEACH SHIORI/2.4\r\nWord: りんご\r\n\r\n")
+            .unwrap_or_else(|e| panic!("{}", e));
+
+        assert_eq!(req.version, 24);
+        assert_eq!(req.method, Rule::teach);
+        assert_eq!(req.id, None);
+        assert_eq!(req.dic["Word"], "りんご");
+    }
 }
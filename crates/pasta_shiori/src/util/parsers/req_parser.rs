@@ -190,6 +190,45 @@ mod tests {
         }
     }
 
+    #[test]
+    fn method_3() {
+        let items = ShioriRequestParser::parse(Rule::method, "TEACH")
+            .unwrap_or_else(|e| panic!("{}", e))
+            .collect::<Vec<_>>();
+        assert_eq!(items.len(), 1);
+        {
+            let pair = &items[0];
+            assert_eq!(pair.as_rule(), Rule::method);
+            let items = pair.clone().into_inner().collect::<Vec<_>>();
+            assert_eq!(items.len(), 1);
+            let pair = &items[0];
+            assert_eq!(pair.as_rule(), Rule::teach);
+        }
+    }
+
+    #[test]
+    fn header_3() {
+        let items = ShioriRequestParser::parse(Rule::header, "TEACH SHIORI/2.4\r\n")
+            .unwrap_or_else(|e| panic!("{}", e))
+            .collect::<Vec<_>>();
+        assert_eq!(items.len(), 1);
+        {
+            let pair = &items[0];
+            assert_eq!(pair.as_rule(), Rule::header);
+            let items = pair.clone().into_inner().collect::<Vec<_>>();
+            assert_eq!(items.len(), 2);
+            let pair = &items[1];
+            assert_eq!(pair.as_rule(), Rule::header2);
+            {
+                let items = pair.clone().into_inner().collect::<Vec<_>>();
+                assert_eq!(items.len(), 1);
+                let pair = &items[0];
+                assert_eq!(pair.as_rule(), Rule::shiori2_ver);
+                assert_eq!(pair.as_str(), "4");
+            }
+        }
+    }
+
     #[test]
     fn key_1() {
         let items = ShioriRequestParser::parse(Rule::key, "感じの良いID")
@@ -1 +1,358 @@
+//! Structured SHIORI response.
+//!
+//! Lua の `SHIORI.request` はレスポンス文字列の代わりに次の形式のテーブルを返せる。
+//!
+//! ```lua
+//! return {
+//!     status = 200,                 -- 数値、または "200 OK" 形式の文字列
+//!     value = "\\0hello\\e",        -- 省略可。Value ヘッダーの短縮形
+//!     headers = {                   -- 省略可。配列なら記述順、辞書ならキー名の昇順で出力
+//!         { "Reference0", "user" },
+//!         { "Marker", "返信中" },
+//!     },
+//!     version = 30,                 -- 省略時はリクエストのバージョン
+//!     value_key = "Value",          -- 省略時は 3.0 で "Value"、2.x で "Sentence"
+//! }
+//! ```
+//!
+//! ヘッダー名・値はシリアライズ前に検証し、改行によるヘッダーインジェクションを拒否する。
 
+use crate::error::*;
+use pasta_lua::mlua::{Table, Value};
+use std::fmt;
+
+/// SHIORI レスポンス（ステータス行とヘッダー列）
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ShioriResponse {
+    /// プロトコルバージョン（30: SHIORI/3.0、20～29: SHIORI/2.x）
+    pub version: i32,
+    /// ステータスコード
+    pub status: u16,
+    /// ヘッダー（出力順）
+    headers: Vec<(String, String)>,
+}
+
+impl ShioriResponse {
+    /// 空のレスポンスを作成します。
+    pub fn new(version: i32, status: u16) -> MyResult<ShioriResponse> {
+        if version != 30 && !(20..=29).contains(&version) {
+            return Err(invalid(format!("unsupported version {version}")));
+        }
+        if reason_phrase(status).is_none() {
+            return Err(invalid(format!("unknown status code {status}")));
+        }
+        Ok(ShioriResponse {
+            version,
+            status,
+            headers: Vec::new(),
+        })
+    }
+
+    /// Lua テーブルからレスポンスを組み立てます。
+    /// `version` 未指定時は `default_version` を使います。
+    pub fn from_table(table: &Table, default_version: i32) -> MyResult<ShioriResponse> {
+        let version = match table.get::<Value>("version")? {
+            Value::Nil => default_version,
+            Value::Integer(v) => v as i32,
+            other => {
+                return Err(invalid(format!(
+                    "version: integer expected, got {}",
+                    other.type_name()
+                )));
+            }
+        };
+        let status = match table.get::<Value>("status")? {
+            Value::Integer(code) => {
+                u16::try_from(code).map_err(|_| invalid(format!("unknown status code {code}")))?
+            }
+            Value::String(s) => parse_status(&s.to_str()?)?,
+            Value::Nil => return Err(invalid("status is required".into())),
+            other => {
+                return Err(invalid(format!(
+                    "status: integer or string expected, got {}",
+                    other.type_name()
+                )));
+            }
+        };
+        let mut response = ShioriResponse::new(version, status)?;
+
+        if let Some(value) = lua_text("value", table.get::<Value>("value")?)? {
+            let value_key = match table.get::<Option<String>>("value_key")? {
+                Some(key) => key,
+                None if version >= 30 => "Value".to_string(),
+                None => "Sentence".to_string(),
+            };
+            response.push_header(value_key, value)?;
+        }
+
+        match table.get::<Value>("headers")? {
+            Value::Nil => {}
+            Value::Table(headers) if headers.raw_len() > 0 => {
+                for pair in headers.sequence_values::<Table>() {
+                    let pair =
+                        pair.map_err(|_| invalid("headers: {name, value} pairs expected".into()))?;
+                    let name: String = pair.get(1)?;
+                    let value = lua_text(&name, pair.get::<Value>(2)?)?
+                        .ok_or_else(|| invalid(format!("header '{name}': value is required")))?;
+                    response.push_header(name, value)?;
+                }
+            }
+            Value::Table(headers) => {
+                let mut entries = Vec::new();
+                for pair in headers.pairs::<String, Value>() {
+                    let (name, value) = pair?;
+                    let value = lua_text(&name, value)?
+                        .ok_or_else(|| invalid(format!("header '{name}': value is required")))?;
+                    entries.push((name, value));
+                }
+                entries.sort();
+                for (name, value) in entries {
+                    response.push_header(name, value)?;
+                }
+            }
+            other => {
+                return Err(invalid(format!(
+                    "headers: table expected, got {}",
+                    other.type_name()
+                )));
+            }
+        }
+        Ok(response)
+    }
+
+    /// ヘッダーを検証して末尾に追加します。
+    /// 名前は英数字と "-", ".", "_" のみ、値は CR/LF を含まないこと。同名ヘッダーは拒否します。
+    pub fn push_header<K: Into<String>, V: Into<String>>(
+        &mut self,
+        name: K,
+        value: V,
+    ) -> MyResult<()> {
+        let name = name.into();
+        let value = value.into();
+        check_header(&name, &value)?;
+        if self.header(&name).is_some() {
+            return Err(invalid(format!("duplicate header '{name}'")));
+        }
+        self.headers.push((name, value));
+        Ok(())
+    }
+
+    /// 標準ヘッダー（Charset, Sender 等）を指定順で先頭に配置します。
+    /// 同名のヘッダーが既にあればその値のまま先頭へ移し、なければ既定値を追加します。
+    pub fn set_leading_headers(&mut self, defaults: &[(&str, &str)]) -> MyResult<()> {
+        let mut leading = Vec::with_capacity(defaults.len());
+        for &(name, default) in defaults {
+            let header = match self
+                .headers
+                .iter()
+                .position(|(k, _)| k.eq_ignore_ascii_case(name))
+            {
+                Some(index) => self.headers.remove(index),
+                None => {
+                    check_header(name, default)?;
+                    (name.to_string(), default.to_string())
+                }
+            };
+            leading.push(header);
+        }
+        leading.append(&mut self.headers);
+        self.headers = leading;
+        Ok(())
+    }
+
+    /// ヘッダー値を取得します（名前は大文字小文字を区別しない）。
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// ヘッダー一覧（出力順）
+    pub fn headers(&self) -> &[(String, String)] {
+        &self.headers
+    }
+}
+
+/// SHIORI ワイヤーフォーマットへのシリアライズ
+impl fmt::Display for ShioriResponse {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let reason = reason_phrase(self.status).unwrap_or_default();
+        if self.version >= 30 {
+            write!(f, "SHIORI/3.0 {} {}\r\n", self.status, reason)?;
+        } else {
+            write!(
+                f,
+                "SHIORI/2.{} {} {}\r\n",
+                self.version - 20,
+                self.status,
+                reason
+            )?;
+        }
+        for (name, value) in &self.headers {
+            write!(f, "{name}: {value}\r\n")?;
+        }
+        f.write_str("\r\n")
+    }
+}
+
+/// ステータスコードの理由句
+fn reason_phrase(status: u16) -> Option<&'static str> {
+    Some(match status {
+        200 => "OK",
+        204 => "No Content",
+        310 => "Communicate",
+        311 => "Not Enough",
+        312 => "Advice",
+        400 => "Bad Request",
+        500 => "Internal Server Error",
+        _ => return None,
+    })
+}
+
+/// ヘッダーを検証します。
+/// 名前は英数字と "-", ".", "_" のみ（先頭は英数字）、値は CR/LF を含まないこと。
+fn check_header(name: &str, value: &str) -> MyResult<()> {
+    let valid_name = name
+        .chars()
+        .next()
+        .is_some_and(|c| c.is_ascii_alphanumeric())
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '.' | '_'));
+    if !valid_name {
+        return Err(invalid(format!("invalid header name '{name}'")));
+    }
+    if value.contains(['\r', '\n']) {
+        return Err(invalid(format!("header '{name}' must not contain CR/LF")));
+    }
+    Ok(())
+}
+
+/// "200" / "200 OK" 形式のステータスを解釈します。理由句はコードと一致すること。
+fn parse_status(text: &str) -> MyResult<u16> {
+    let (code, reason) = text.split_once(' ').unwrap_or((text, ""));
+    let status: u16 = code
+        .parse()
+        .map_err(|_| invalid(format!("invalid status '{text}'")))?;
+    match reason_phrase(status) {
+        Some(expected) if reason.is_empty() || reason == expected => Ok(status),
+        _ => Err(invalid(format!("invalid status '{text}'"))),
+    }
+}
+
+/// ヘッダー値として使える Lua 値（文字列・数値）を文字列化します。
+fn lua_text(name: &str, value: Value) -> MyResult<Option<String>> {
+    match value {
+        Value::Nil => Ok(None),
+        Value::String(s) => Ok(Some(s.to_str()?.to_string())),
+        Value::Integer(i) => Ok(Some(i.to_string())),
+        Value::Number(n) => Ok(Some(n.to_string())),
+        other => Err(invalid(format!(
+            "'{name}': string expected, got {}",
+            other.type_name()
+        ))),
+    }
+}
+
+fn invalid(message: String) -> MyError {
+    MyError::InvalidResponse(message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pasta_lua::mlua::Lua;
+
+    fn from_lua(code: &str, default_version: i32) -> MyResult<ShioriResponse> {
+        let lua = Lua::new();
+        let table: Table = lua.load(code).eval().unwrap();
+        ShioriResponse::from_table(&table, default_version)
+    }
+
+    #[test]
+    fn serialize_ok_with_ordered_headers() {
+        let res = from_lua(
+            r#"return {
+                status = 200,
+                value = "\\0hi\\e",
+                headers = { { "Charset", "UTF-8" }, { "Reference0", "user" }, { "Age", 3 } },
+            }"#,
+            30,
+        )
+        .unwrap();
+        assert_eq!(
+            res.to_string(),
+            "SHIORI/3.0 200 OK\r\n\
+             Value: \\0hi\\e\r\n\
+             Charset: UTF-8\r\n\
+             Reference0: user\r\n\
+             Age: 3\r\n\
+             \r\n"
+        );
+    }
+
+    #[test]
+    fn dictionary_headers_are_sorted() {
+        let res = from_lua(
+            r#"return { status = "204 No Content", headers = { Sender = "Pasta", Charset = "UTF-8" } }"#,
+            30,
+        )
+        .unwrap();
+        assert_eq!(
+            res.to_string(),
+            "SHIORI/3.0 204 No Content\r\nCharset: UTF-8\r\nSender: Pasta\r\n\r\n"
+        );
+    }
+
+    #[test]
+    fn shiori2_uses_sentence_by_default() {
+        let res = from_lua(r#"return { status = 200, value = "x" }"#, 25).unwrap();
+        assert_eq!(res.to_string(), "SHIORI/2.5 200 OK\r\nSentence: x\r\n\r\n");
+
+        let res = from_lua(
+            r#"return { status = 200, value = "x", value_key = "String" }"#,
+            25,
+        )
+        .unwrap();
+        assert_eq!(res.header("String"), Some("x"));
+    }
+
+    #[test]
+    fn rejects_header_injection() {
+        let err = from_lua(
+            r#"return { status = 200, value = "a\r\nX-Injected: 1" }"#,
+            30,
+        )
+        .unwrap_err();
+        assert!(
+            matches!(err, MyError::InvalidResponse(ref m) if m.contains("CR/LF")),
+            "{err}"
+        );
+
+        let err = from_lua(
+            r#"return { status = 200, headers = { { "Bad: Name", "x" } } }"#,
+            30,
+        )
+        .unwrap_err();
+        assert!(
+            matches!(err, MyError::InvalidResponse(ref m) if m.contains("invalid header name")),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_status_and_duplicates() {
+        assert!(from_lua(r#"return { status = 201 }"#, 30).is_err());
+        assert!(from_lua(r#"return { status = "200 Fine" }"#, 30).is_err());
+        assert!(from_lua(r#"return { value = "x" }"#, 30).is_err());
+        assert!(from_lua(r#"return { status = 200, version = 31 }"#, 30).is_err());
+        assert!(
+            from_lua(
+                r#"return { status = 200, value = "x", headers = { { "value", "y" } } }"#,
+                30
+            )
+            .is_err()
+        );
+    }
+}
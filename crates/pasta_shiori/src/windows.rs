@@ -3,15 +3,12 @@
 //! Provides SHIORI protocol entry points for Windows DLL.
 
 use crate::error::*;
-use crate::shiori::*;
+use crate::raw::*;
 use crate::util::hglobal::*;
 use std::ptr;
-use std::sync::*;
 use tracing::*;
 use windows_sys::Win32::Foundation::*;
 
-static SHIORI: OnceLock<RawShiori<PastaShiori>> = OnceLock::new();
-
 /// Windows DLL entry point
 /// Initializes SHIORI at DLL load/unload time.
 ///
@@ -50,7 +47,13 @@ extern "system" fn DllMain(
 pub extern "C" fn load(hdir: HGLOBAL, len: usize) -> bool {
     // SHIORI is already initialized in DllMain
     match SHIORI.get() {
-        Some(raw) => raw.load(hdir, len),
+        Some(raw) => match capture_load_dir(hdir, len) {
+            Ok(dir) => raw.load(&dir),
+            Err(e) => {
+                error!("[pasta_shiori::load] {e}");
+                false
+            }
+        },
         None => false,
     }
 }
@@ -75,7 +78,13 @@ pub extern "C" fn unload() -> bool {
 #[unsafe(no_mangle)]
 pub extern "C" fn request(req: HGLOBAL, len: &mut usize) -> HGLOBAL {
     match SHIORI.get() {
-        Some(raw) => raw.request(req, len),
+        Some(raw) => {
+            let hreq = ShioriString::capture(req, *len);
+            let res = raw.request(hreq.as_bytes());
+            let (hres, res_len) = ShioriString::clone_from_slice_nofree(&res).value();
+            *len = res_len;
+            hres
+        }
         None => {
             *len = 0;
             ptr::null_mut()
@@ -83,89 +92,7 @@ pub extern "C" fn request(req: HGLOBAL, len: &mut usize) -> HGLOBAL {
     }
 }
 
-struct RawShiori<T: Shiori + Default + Sized>(isize, Arc<Mutex<Option<T>>>);
-
-impl<T: Shiori + Default + Sized> RawShiori<T> {
-    fn new(hinst: isize) -> Self {
-        // Note: tracing subscriber is NOT initialized here.
-        // It is deferred to PastaShiori::load() after pasta.toml is read,
-        // so that [logging] configuration can be applied (Requirement 6).
-        RawShiori(hinst, Arc::new(Mutex::new(None)))
-    }
-
-    fn unload(&self) -> bool {
-        match self.unload_impl() {
-            Ok(_) => (),
-            Err(e) => {
-                error!("[pasta_shiori::unload] {e}");
-                ()
-            }
-        };
-        true
-    }
-
-    fn load(&self, hdir: HGLOBAL, len: usize) -> bool {
-        match self.load_impl(hdir, len) {
-            Ok(ret) => ret,
-            Err(e) => {
-                error!("[pasta_shiori::load] {e}");
-                false
-            }
-        }
-    }
-
-    fn request(&self, req: HGLOBAL, len: &mut usize) -> HGLOBAL {
-        match self.request_impl(req, *len) {
-            Ok((res, res_len)) => {
-                *len = res_len;
-                res
-            }
-            Err(e) => {
-                error!("[pasta_shiori::request] {e}");
-                let (res, res_len) = Self::error_response(e);
-                *len = res_len;
-                res
-            }
-        }
-    }
-}
-
-impl<T: Shiori + Default + Sized> RawShiori<T> {
-    fn unload_impl(&self) -> MyResult<bool> {
-        let mut guard = self.1.lock()?;
-        *guard = None;
-        Ok(true)
-    }
-
-    fn load_impl(&self, hdir: HGLOBAL, len: usize) -> MyResult<bool> {
-        let hinst = self.0;
-        let mut guard = self.1.lock()?;
-        *guard = None;
-        let mut shiori = T::default();
-        let hdir = ShioriString::capture(hdir, len);
-        let dir = hdir.to_ansi_str()?;
-        let rc = shiori.load(hinst, dir)?;
-        *guard = Some(shiori);
-        Ok(rc)
-    }
-
-    fn request_impl(&self, hreq: HGLOBAL, len: usize) -> MyResult<(HGLOBAL, usize)> {
-        let mut guard = self.1.lock()?;
-        match *guard {
-            None => return Err(MyError::NotInitialized),
-            Some(ref mut shiori) => {
-                let hreq = ShioriString::capture(hreq, len);
-                let req = hreq.to_utf8_str()?;
-                let res = shiori.request(req)?;
-                let hres = ShioriString::clone_from_str_nofree(res);
-                Ok(hres.value())
-            }
-        }
-    }
-
-    fn error_response(e: MyError) -> (HGLOBAL, usize) {
-        let res = e.to_shiori_response();
-        let hres = ShioriString::clone_from_str_nofree(res);
-        hres.value()
-    }
+/// load_dir は ANSI 文字列（JP 環境では SJIS）で渡される。
+fn capture_load_dir(hdir: HGLOBAL, len: usize) -> MyResult<std::ffi::OsString> {
+    ShioriString::capture(hdir, len).to_ansi_str()
 }
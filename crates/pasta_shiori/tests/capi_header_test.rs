@@ -0,0 +1,34 @@
+//! Portable C ABI header test.
+//!
+//! `include/pasta.h` が `src/capi.rs` から cbindgen で生成した内容と
+//! 一致することを検証する。`PASTA_UPDATE_HEADER=1` で再生成する。
+
+use std::path::PathBuf;
+
+#[test]
+fn test_c_header_is_up_to_date() {
+    let crate_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    let config = cbindgen::Config::from_file(crate_dir.join("cbindgen.toml")).unwrap();
+    let mut generated = Vec::new();
+    cbindgen::Builder::new()
+        .with_crate(&crate_dir)
+        .with_config(config)
+        .generate()
+        .expect("cbindgen should parse pasta_shiori")
+        .write(&mut generated);
+    let generated = String::from_utf8(generated).unwrap();
+
+    let header_path = crate_dir.join("include/pasta.h");
+    if std::env::var_os("PASTA_UPDATE_HEADER").is_some() {
+        std::fs::write(&header_path, &generated).unwrap();
+    }
+    let committed = std::fs::read_to_string(&header_path).unwrap_or_default();
+    assert_eq!(
+        committed.replace("\r\n", "\n"),
+        generated,
+        "include/pasta.h is stale; regenerate with PASTA_UPDATE_HEADER=1"
+    );
+    for name in ["pasta_load", "pasta_request", "pasta_free", "pasta_unload"] {
+        assert!(generated.contains(name), "{name} should be exported");
+    }
+}
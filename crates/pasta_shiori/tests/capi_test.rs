@@ -0,0 +1,116 @@
+//! Portable C ABI integration tests.
+//!
+//! ビルドされた cdylib（`libpasta.so`）を dlopen し、
+//! `pasta_load` → `pasta_request` → `pasta_free` → `pasta_unload` を C ABI 経由で呼び出す。
+#![cfg(target_os = "linux")]
+
+mod common;
+
+use common::copy_fixture_to_temp;
+use libloading::{Library, Symbol};
+use std::path::PathBuf;
+use std::ptr;
+
+type LoadFn = unsafe extern "C" fn(*const u8, usize) -> bool;
+type RequestFn = unsafe extern "C" fn(*const u8, usize, *mut usize) -> *mut u8;
+type FreeFn = unsafe extern "C" fn(*mut u8, usize);
+type UnloadFn = unsafe extern "C" fn() -> bool;
+
+/// テストバイナリと同じ deps ディレクトリにある cdylib を開く。
+/// TLS デストラクタとの競合を避けるため dlclose はしない。
+fn open_library() -> &'static Library {
+    let exe = std::env::current_exe().unwrap();
+    let name = format!(
+        "{}pasta{}",
+        std::env::consts::DLL_PREFIX,
+        std::env::consts::DLL_SUFFIX
+    );
+    let path = exe
+        .ancestors()
+        .skip(1)
+        .map(|dir| dir.join(&name))
+        .find(|path| path.exists())
+        .unwrap_or_else(|| PathBuf::from(&name));
+    let lib = unsafe { Library::new(&path) }
+        .unwrap_or_else(|e| panic!("failed to dlopen {}: {e}", path.display()));
+    Box::leak(Box::new(lib))
+}
+
+struct Api {
+    load: Symbol<'static, LoadFn>,
+    request: Symbol<'static, RequestFn>,
+    free: Symbol<'static, FreeFn>,
+    unload: Symbol<'static, UnloadFn>,
+}
+
+impl Api {
+    fn new() -> Api {
+        let lib = open_library();
+        unsafe {
+            Api {
+                load: lib.get(b"pasta_load").unwrap(),
+                request: lib.get(b"pasta_request").unwrap(),
+                free: lib.get(b"pasta_free").unwrap(),
+                unload: lib.get(b"pasta_unload").unwrap(),
+            }
+        }
+    }
+
+    fn load(&self, dir: &str) -> bool {
+        unsafe { (self.load)(dir.as_ptr(), dir.len()) }
+    }
+
+    fn request(&self, req: &str) -> String {
+        let mut len = 0usize;
+        let res = unsafe { (self.request)(req.as_ptr(), req.len(), &mut len) };
+        assert!(!res.is_null());
+        let text = String::from_utf8(unsafe { std::slice::from_raw_parts(res, len) }.to_vec());
+        unsafe { (self.free)(res, len) };
+        text.unwrap()
+    }
+}
+
+/// 共有インスタンスを使うため、ライフサイクル全体を1テストで検証する。
+#[test]
+fn test_c_abi_lifecycle() {
+    let api = Api::new();
+    let temp = copy_fixture_to_temp("shiori_lifecycle");
+    let dir = temp.path().to_str().unwrap();
+    let get = "GET SHIORI/3.0\r\nCharset: UTF-8\r\nID: OnBoot\r\n\r\n";
+
+    // load 前のリクエストはエラーレスポンス
+    assert!(
+        api.request(get)
+            .starts_with("SHIORI/3.0 500 Internal Server Error\r\n")
+    );
+
+    // 存在しないディレクトリ
+    assert!(!api.load("/nonexistent/pasta/ghost"));
+
+    assert!(api.load(dir), "pasta_load should succeed");
+    let res = api.request(get);
+    assert!(res.starts_with("SHIORI/3.0 "), "{res}");
+    assert!(res.ends_with("\r\n\r\n"), "{res}");
+
+    assert!(unsafe { (api.unload)() });
+    assert!(
+        temp.path().join("unload_called.marker").exists(),
+        "pasta_unload should run SHIORI.unload"
+    );
+    assert!(api.request(get).contains(" 500 "));
+
+    // unload の重複呼び出しは安全
+    assert!(unsafe { (api.unload)() });
+}
+
+#[test]
+fn test_c_abi_null_arguments() {
+    let api = Api::new();
+    let mut len = 123usize;
+    let res = unsafe { (api.request)(ptr::null(), 0, &mut len) };
+    assert!(res.is_null());
+    assert_eq!(len, 0);
+    assert!(unsafe { (api.request)(b"x".as_ptr(), 1, ptr::null_mut()) }.is_null());
+    assert!(!unsafe { (api.load)(ptr::null(), 0) });
+    unsafe { (api.free)(ptr::null_mut(), 0) };
+}
@@ -1,5 +1,8 @@
 //! Common test utilities for pasta_shiori integration tests.
 
+// テストバイナリごとに使う関数が異なるため
+#![allow(dead_code)]
+
 use std::path::{Path, PathBuf};
 use tempfile::TempDir;
 
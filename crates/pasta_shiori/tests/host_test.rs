@@ -0,0 +1,110 @@
+//! SHIORI host mode integration tests.
+//!
+//! stdio 相当のバイトストリームと Unix ドメインソケットで
+//! PastaShiori を LOAD → request → unload まで駆動する。
+
+mod common;
+
+use common::copy_fixture_to_temp;
+use pasta::PastaShiori;
+use pasta::host::ShioriHost;
+use std::io::Cursor;
+
+fn load_frame(dir: &std::path::Path) -> String {
+    format!("LOAD SHIORI/3.0\r\nDirectory: {}\r\n\r\n", dir.display())
+}
+
+#[test]
+fn test_stream_session_load_request_and_unload_on_eof() {
+    let temp = copy_fixture_to_temp("shiori_lifecycle");
+    let input = format!(
+        "{}GET SHIORI/3.0\r\nCharset: UTF-8\r\nID: OnBoot\r\n\r\n",
+        load_frame(temp.path())
+    );
+
+    let mut host = ShioriHost::<PastaShiori>::new();
+    let mut output = Vec::new();
+    host.serve(&mut Cursor::new(input.into_bytes()), &mut output)
+        .unwrap();
+    let output = String::from_utf8(output).unwrap();
+
+    let (load_res, request_res) = output.split_once("\r\n\r\n").unwrap();
+    assert_eq!(load_res, "SHIORI/3.0 200 OK\r\nCharset: UTF-8");
+    assert!(
+        request_res.starts_with("SHIORI/3.0 "),
+        "request should be answered: {request_res}"
+    );
+    assert!(!host.is_loaded());
+    assert!(
+        temp.path().join("unload_called.marker").exists(),
+        "EOF should unload SHIORI"
+    );
+}
+
+#[test]
+fn test_stream_session_unload_frame() {
+    let temp = copy_fixture_to_temp("shiori_lifecycle");
+    let input = format!(
+        "{}UNLOAD SHIORI/3.0\r\n\r\nGET SHIORI/3.0\r\nID: OnBoot\r\n\r\n",
+        load_frame(temp.path())
+    );
+
+    let mut host = ShioriHost::<PastaShiori>::new();
+    let mut output = Vec::new();
+    host.serve(&mut Cursor::new(input.into_bytes()), &mut output)
+        .unwrap();
+    let output = String::from_utf8(output).unwrap();
+
+    assert_eq!(
+        output,
+        "SHIORI/3.0 200 OK\r\nCharset: UTF-8\r\n\r\n".repeat(2),
+        "frames after UNLOAD should not be processed"
+    );
+    assert!(temp.path().join("unload_called.marker").exists());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_unix_socket_session() {
+    use std::io::{BufReader, Write};
+    use std::os::unix::net::UnixStream;
+    use std::time::Duration;
+
+    let temp = copy_fixture_to_temp("shiori_lifecycle");
+    let socket_dir = tempfile::TempDir::new().unwrap();
+    let socket = socket_dir.path().join("pasta.sock");
+
+    let server_socket = socket.clone();
+    let load_dir = temp.path().to_path_buf();
+    std::thread::spawn(move || {
+        pasta::host::serve_unix_socket(
+            &server_socket,
+            Some(&load_dir),
+            ShioriHost::<PastaShiori>::new,
+        )
+    });
+
+    let mut stream = (0..100)
+        .find_map(|_| {
+            UnixStream::connect(&socket).ok().or_else(|| {
+                std::thread::sleep(Duration::from_millis(50));
+                None
+            })
+        })
+        .expect("host should listen on the socket");
+    stream
+        .write_all(b"GET SHIORI/3.0\r\nCharset: UTF-8\r\nID: OnBoot\r\n\r\n")
+        .unwrap();
+    let mut reader = BufReader::new(stream.try_clone().unwrap());
+    let response = pasta::host::read_frame(&mut reader).unwrap().unwrap();
+    assert!(
+        String::from_utf8(response)
+            .unwrap()
+            .starts_with("SHIORI/3.0 ")
+    );
+
+    stream.write_all(b"UNLOAD SHIORI/3.0\r\n\r\n").unwrap();
+    let response = pasta::host::read_frame(&mut reader).unwrap().unwrap();
+    assert_eq!(response, b"SHIORI/3.0 200 OK\r\nCharset: UTF-8\r\n\r\n");
+    assert!(temp.path().join("unload_called.marker").exists());
+}
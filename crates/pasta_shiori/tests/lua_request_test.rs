@@ -380,6 +380,154 @@ mod shiori2_tests {
             version
         );
     }
+
+    #[test]
+    fn test_parse_shiori2_version_maps_to_version_event() {
+        let lua = create_test_lua();
+        let request = "GET Version SHIORI/2.6\r\n\
+            Charset: UTF-8\r\n\
+            Sender: SSP\r\n\
+            \r\n";
+
+        let table = parse_request(&lua, request).expect("parse_request should succeed");
+
+        let id: String = table.get("id").unwrap();
+        let command: String = table.get("command").unwrap();
+        let version: i32 = table.get("version").unwrap();
+        assert_eq!(id, "version");
+        assert_eq!(command, "Version");
+        assert_eq!(version, 26);
+    }
+
+    #[test]
+    fn test_parse_shiori2_sentence_with_event_header() {
+        let lua = create_test_lua();
+        let request = "GET Sentence SHIORI/2.2\r\n\
+            Charset: UTF-8\r\n\
+            Sender: SSP\r\n\
+            Event: OnBoot\r\n\
+            Reference0: master\r\n\
+            \r\n";
+
+        let table = parse_request(&lua, request).expect("parse_request should succeed");
+
+        let id: String = table.get("id").unwrap();
+        let command: String = table.get("command").unwrap();
+        let reference: pasta_lua::mlua::Table = table.get("reference").unwrap();
+        let ref0: String = reference.get(0).unwrap();
+        assert_eq!(id, "OnBoot");
+        assert_eq!(command, "Sentence");
+        assert_eq!(ref0, "master");
+    }
+
+    #[test]
+    fn test_parse_shiori2_sentence_without_event_maps_to_ontalk() {
+        let lua = create_test_lua();
+        let request = "GET Sentence SHIORI/2.2\r\n\
+            Charset: UTF-8\r\n\
+            Sender: SSP\r\n\
+            \r\n";
+
+        let table = parse_request(&lua, request).expect("parse_request should succeed");
+
+        let id: String = table.get("id").unwrap();
+        assert_eq!(id, "OnTalk");
+    }
+
+    #[test]
+    fn test_parse_shiori2_communicate_maps_to_oncommunicate() {
+        let lua = create_test_lua();
+        let request = "GET Sentence SHIORI/2.3\r\n\
+            Charset: UTF-8\r\n\
+            Sender: 双葉\r\n\
+            Sentence: \\0こんにちは\\e\r\n\
+            \r\n";
+
+        let table = parse_request(&lua, request).expect("parse_request should succeed");
+
+        let id: String = table.get("id").unwrap();
+        let reference: pasta_lua::mlua::Table = table.get("reference").unwrap();
+        let ref0: String = reference.get(0).unwrap();
+        let ref1: String = reference.get(1).unwrap();
+        assert_eq!(id, "OnCommunicate");
+        assert_eq!(ref0, "双葉");
+        assert_eq!(ref1, "\\0こんにちは\\e");
+    }
+
+    #[test]
+    fn test_parse_shiori2_string_uses_id_header() {
+        let lua = create_test_lua();
+        let request = "GET String SHIORI/2.5\r\n\
+            Charset: UTF-8\r\n\
+            Sender: SSP\r\n\
+            ID: homeurl\r\n\
+            \r\n";
+
+        let table = parse_request(&lua, request).expect("parse_request should succeed");
+
+        let id: String = table.get("id").unwrap();
+        let command: String = table.get("command").unwrap();
+        assert_eq!(id, "homeurl");
+        assert_eq!(command, "String");
+    }
+
+    #[test]
+    fn test_parse_shiori2_other_ghost_name_collects_ghost_headers() {
+        let lua = create_test_lua();
+        let request = "NOTIFY OtherGhostName SHIORI/2.3\r\n\
+            Charset: UTF-8\r\n\
+            Sender: SSP\r\n\
+            Ghost: 双葉\u{1}0\u{1}10\r\n\
+            Ghost: 理夢\u{1}5\u{1}10\r\n\
+            \r\n";
+
+        let table = parse_request(&lua, request).expect("parse_request should succeed");
+
+        let method: String = table.get("method").unwrap();
+        let id: String = table.get("id").unwrap();
+        let reference: pasta_lua::mlua::Table = table.get("reference").unwrap();
+        let ref0: String = reference.get(0).unwrap();
+        let ref1: String = reference.get(1).unwrap();
+        assert_eq!(method, "notify");
+        assert_eq!(id, "otherghostname");
+        assert_eq!(ref0, "双葉\u{1}0\u{1}10");
+        assert_eq!(ref1, "理夢\u{1}5\u{1}10");
+    }
+
+    #[test]
+    fn test_parse_shiori2_teach_maps_to_onteach() {
+        let lua = create_test_lua();
+        let request = "TEACH SHIORI/2.4\r\n\
+            Charset: UTF-8\r\n\
+            Sender: SSP\r\n\
+            Word: りんご\r\n\
+            \r\n";
+
+        let table = parse_request(&lua, request).expect("parse_request should succeed");
+
+        let method: String = table.get("method").unwrap();
+        let id: String = table.get("id").unwrap();
+        let command: Option<String> = table.get("command").unwrap();
+        let reference: pasta_lua::mlua::Table = table.get("reference").unwrap();
+        let ref0: String = reference.get(0).unwrap();
+        assert_eq!(method, "teach");
+        assert_eq!(id, "OnTeach");
+        assert_eq!(command, None);
+        assert_eq!(ref0, "りんご");
+    }
+
+    #[test]
+    fn test_parse_shiori3_has_no_command() {
+        let lua = create_test_lua();
+        let request = "GET SHIORI/3.0\r\n\
+            ID: OnBoot\r\n\
+            \r\n";
+
+        let table = parse_request(&lua, request).expect("parse_request should succeed");
+
+        let command: Option<String> = table.get("command").unwrap();
+        assert_eq!(command, None);
+    }
 }
 
 // ============================================================================